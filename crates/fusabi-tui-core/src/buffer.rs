@@ -1,18 +1,116 @@
 //! Terminal buffer management for rendering TUI elements.
 //!
 //! This module provides `Cell` and `Buffer` types for managing terminal content
-//! with styling and efficient diff computation.
+//! with styling and efficient diff computation, and the [`Hyperlink`] targets
+//! that cells can link to.
+
+use std::fmt::Write as _;
+use std::sync::Arc;
 
 use crate::layout::Rect;
-use crate::style::{Color, Modifier, Style};
+use crate::style::{Color, Modifier, Style, UnderlineStyle};
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+/// The target of a terminal hyperlink (OSC 8).
+///
+/// Cells that belong to the same link share its [`id`](Self::id), which lets
+/// terminals treat them as one link even when they are drawn in separate
+/// frames or split across lines. [`Hyperlink::new`] derives the id from the
+/// URI, so the same URI always gets the same id; use [`Hyperlink::with_id`] to
+/// tell apart separate links to the same URI.
+///
+/// # Examples
+///
+/// ```
+/// use fusabi_tui_core::buffer::{Buffer, Hyperlink};
+/// use fusabi_tui_core::layout::Rect;
+/// use fusabi_tui_core::style::Style;
+///
+/// let link = Hyperlink::new("https://example.com/tickets/42");
+/// let mut buffer = Buffer::new(Rect::new(0, 0, 10, 1));
+/// let width = buffer.set_string(0, 0, "#42", Style::default());
+/// buffer.set_hyperlink(Rect::new(0, 0, width as u16, 1), Some(&link));
+///
+/// assert_eq!(buffer.get(1, 0).unwrap().hyperlink.as_ref(), Some(&link));
+/// assert_eq!(buffer.hyperlinks(), vec![(Rect::new(0, 0, 3, 1), &link)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperlink {
+    uri: Arc<str>,
+    id: Arc<str>,
+}
+
+impl Hyperlink {
+    /// Creates a link to `uri`, with an id derived from the URI.
+    ///
+    /// Spaces, control characters and non-ASCII bytes in the URI are
+    /// percent-encoded, so the URI cannot end the escape sequence it is
+    /// written in.
+    pub fn new(uri: impl AsRef<str>) -> Self {
+        let mut encoded = String::with_capacity(uri.as_ref().len());
+        for byte in uri.as_ref().bytes() {
+            if byte.is_ascii_graphic() {
+                encoded.push(char::from(byte));
+            } else {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+
+        // FNV-1a, which is stable across runs and platforms
+        let hash = encoded.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self {
+            uri: encoded.into(),
+            id: format!("{:016x}", hash).into(),
+        }
+    }
+
+    /// Sets the id of the link.
+    ///
+    /// Characters other than ASCII letters, digits, `-`, `_` and `.` are
+    /// replaced with `_`.
+    #[must_use]
+    pub fn with_id(mut self, id: impl AsRef<str>) -> Self {
+        self.id = id
+            .as_ref()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>()
+            .into();
+        self
+    }
+
+    /// Returns the URI the link opens, with bytes outside printable ASCII
+    /// percent-encoded.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the id that identifies the cells of one link.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
 /// A single cell in the terminal buffer.
 ///
 /// Contains the character to display and its styling information.
+///
+/// A double-width glyph occupies its own cell plus the cell to its right. That
+/// second cell is a *continuation* cell: it has an empty symbol, is never drawn
+/// by itself, and exists only so that the grid stays aligned with the terminal
+/// columns.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Cell {
-    /// The character(s) to display in this cell
+    /// The grapheme cluster to display in this cell
     pub symbol: String,
     /// Foreground color
     pub fg: Color,
@@ -20,6 +118,14 @@ pub struct Cell {
     pub bg: Color,
     /// Text modifiers
     pub modifier: Modifier,
+    /// Underline shape, drawn when `modifier` contains [`Modifier::UNDERLINED`]
+    pub underline_style: UnderlineStyle,
+    /// Underline color, where `Reset` uses the foreground color
+    pub underline_color: Color,
+    /// The link this cell belongs to
+    pub hyperlink: Option<Hyperlink>,
+    /// Whether this cell is covered by the wide glyph in the cell to its left
+    pub continuation: bool,
 }
 
 impl Default for Cell {
@@ -29,6 +135,10 @@ impl Default for Cell {
             fg: Color::Reset,
             bg: Color::Reset,
             modifier: Modifier::EMPTY,
+            underline_style: UnderlineStyle::Single,
+            underline_color: Color::Reset,
+            hyperlink: None,
+            continuation: false,
         }
     }
 }
@@ -63,6 +173,44 @@ impl Cell {
         self
     }
 
+    /// Sets the underline shape.
+    #[inline]
+    pub fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.underline_style = style;
+        self
+    }
+
+    /// Sets the underline color.
+    #[inline]
+    pub fn underline_color(mut self, color: Color) -> Self {
+        self.underline_color = color;
+        self
+    }
+
+    /// Sets the link this cell belongs to.
+    #[inline]
+    pub fn hyperlink(mut self, hyperlink: Hyperlink) -> Self {
+        self.hyperlink = Some(hyperlink);
+        self
+    }
+
+    /// Turns this cell into the continuation of a wide glyph to its left.
+    pub fn set_continuation(&mut self) {
+        self.symbol.clear();
+        self.continuation = true;
+    }
+
+    /// Returns the number of columns this cell's symbol occupies.
+    ///
+    /// Continuation cells have a width of zero.
+    pub fn width(&self) -> usize {
+        if self.continuation {
+            0
+        } else {
+            self.symbol.width()
+        }
+    }
+
     /// Applies a style to this cell.
     pub fn set_style(&mut self, style: Style) {
         if let Some(fg) = style.fg {
@@ -72,6 +220,12 @@ impl Cell {
             self.bg = bg;
         }
         self.modifier = self.modifier.insert(style.modifiers);
+        if let Some(underline_style) = style.underline_style {
+            self.underline_style = underline_style;
+        }
+        if let Some(underline_color) = style.underline_color {
+            self.underline_color = underline_color;
+        }
     }
 
     /// Resets this cell to default values.
@@ -80,6 +234,10 @@ impl Cell {
         self.fg = Color::Reset;
         self.bg = Color::Reset;
         self.modifier = Modifier::EMPTY;
+        self.underline_style = UnderlineStyle::Single;
+        self.underline_color = Color::Reset;
+        self.hyperlink = None;
+        self.continuation = false;
     }
 }
 
@@ -147,37 +305,83 @@ impl Buffer {
 
     /// Sets the string at the given coordinates with the given style.
     ///
-    /// Returns the number of cells written.
+    /// The string is split into extended grapheme clusters, so combining
+    /// accents, flags and ZWJ emoji sequences each land in a single cell. Wide
+    /// clusters also claim the continuation cells to their right. Zero-width
+    /// clusters are skipped, and a cluster that would not fit in the remaining
+    /// width is not written at all.
+    ///
+    /// The written cells lose their hyperlink; link them again with
+    /// [`Buffer::set_hyperlink`].
+    ///
+    /// Returns the number of cells written, including continuation cells.
     pub fn set_string(&mut self, x: u16, y: u16, string: &str, style: Style) -> usize {
-        let mut written = 0;
-        let mut current_x = x;
+        if y >= self.area.height {
+            return 0;
+        }
 
-        for grapheme in string.chars() {
-            if current_x >= self.area.width {
-                break;
+        let mut current_x = x;
+        for grapheme in string.graphemes(true) {
+            let width = grapheme.width();
+            if width == 0 {
+                continue;
             }
+            let Some(end) = u16::try_from(width)
+                .ok()
+                .and_then(|w| current_x.checked_add(w))
+                .filter(|&end| end <= self.area.width)
+            else {
+                break;
+            };
 
-            let width = UnicodeWidthStr::width(grapheme.to_string().as_str()).max(1);
-
+            self.clear_wide_remnants(current_x, end, y);
             if let Some(cell) = self.get_mut(current_x, y) {
-                cell.symbol = grapheme.to_string();
+                cell.symbol.clear();
+                cell.symbol.push_str(grapheme);
+                cell.continuation = false;
+                cell.hyperlink = None;
                 cell.set_style(style);
-                written += 1;
             }
+            for cx in current_x + 1..end {
+                if let Some(cell) = self.get_mut(cx, y) {
+                    cell.set_continuation();
+                    cell.hyperlink = None;
+                    cell.set_style(style);
+                }
+            }
+            current_x = end;
+        }
 
-            current_x += width as u16;
-
-            // Clear cells covered by wide characters
-            for i in 1..width {
-                if let Some(cell) = self.get_mut(current_x - width as u16 + i as u16, y) {
-                    if i > 0 {
-                        cell.symbol = String::new();
-                    }
+        usize::from(current_x.saturating_sub(x))
+    }
+
+    /// Blanks out the parts of wide glyphs that the columns `start..end` are
+    /// about to cut in half.
+    fn clear_wide_remnants(&mut self, start: u16, end: u16, y: u16) {
+        // A continuation at `start` belongs to a glyph that begins further left.
+        if self.get(start, y).is_some_and(|cell| cell.continuation) {
+            let mut cx = start;
+            while cx > 0 {
+                cx -= 1;
+                let Some(cell) = self.get_mut(cx, y) else {
+                    break;
+                };
+                let was_continuation = cell.continuation;
+                cell.symbol = " ".to_string();
+                cell.continuation = false;
+                if !was_continuation {
+                    break;
                 }
             }
         }
 
-        written
+        // Continuations at `end` and beyond lose the glyph they belonged to.
+        let mut cx = end;
+        while let Some(cell) = self.get_mut(cx, y).filter(|cell| cell.continuation) {
+            cell.symbol = " ".to_string();
+            cell.continuation = false;
+            cx += 1;
+        }
     }
 
     /// Sets the style for all cells in the given area.
@@ -191,6 +395,40 @@ impl Buffer {
         }
     }
 
+    /// Sets the hyperlink of all cells in the given area, or removes it with
+    /// `None`.
+    pub fn set_hyperlink(&mut self, area: Rect, hyperlink: Option<&Hyperlink>) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                if let Some(cell) = self.get_mut(x, y) {
+                    cell.hyperlink = hyperlink.cloned();
+                }
+            }
+        }
+    }
+
+    /// Returns the linked runs of cells, row by row.
+    ///
+    /// Each run is one row high and covers adjacent cells with the same
+    /// hyperlink, including the continuation cells of wide glyphs.
+    pub fn hyperlinks(&self) -> Vec<(Rect, &Hyperlink)> {
+        let mut runs: Vec<(Rect, &Hyperlink)> = Vec::new();
+        for y in 0..self.area.height {
+            for x in 0..self.area.width {
+                let Some(link) = self.get(x, y).and_then(|cell| cell.hyperlink.as_ref()) else {
+                    continue;
+                };
+                match runs.last_mut() {
+                    Some((area, last)) if *last == link && area.y == y && area.right() == x => {
+                        area.width += 1;
+                    }
+                    _ => runs.push((Rect::new(x, y, 1, 1), link)),
+                }
+            }
+        }
+        runs
+    }
+
     /// Clears the entire buffer.
     pub fn clear(&mut self) {
         for cell in &mut self.content {
@@ -224,6 +462,8 @@ impl Buffer {
     /// Computes the difference between this buffer and another buffer.
     ///
     /// Returns a vector of (x, y, cell) tuples representing cells that differ.
+    /// Continuation cells are never included, since drawing the wide glyph to
+    /// their left already covers them.
     pub fn diff<'a>(&self, other: &'a Buffer) -> Vec<(u16, u16, &'a Cell)> {
         let mut updates = Vec::new();
 
@@ -231,7 +471,7 @@ impl Buffer {
             // If areas differ, return all cells from the other buffer
             for y in 0..other.area.height {
                 for x in 0..other.area.width {
-                    if let Some(cell) = other.get(x, y) {
+                    if let Some(cell) = other.get(x, y).filter(|cell| !cell.continuation) {
                         updates.push((x, y, cell));
                     }
                 }
@@ -246,7 +486,7 @@ impl Buffer {
                 let other_cell = other.get(x, y);
 
                 if self_cell != other_cell {
-                    if let Some(cell) = other_cell {
+                    if let Some(cell) = other_cell.filter(|cell| !cell.continuation) {
                         updates.push((x, y, cell));
                     }
                 }
@@ -321,6 +561,62 @@ mod tests {
         assert!(cell.modifier.contains(Modifier::ITALIC));
     }
 
+    #[test]
+    fn test_cell_set_underline_style() {
+        let mut cell = Cell::new("a").underline_color(Color::Blue);
+        cell.set_style(Style::new().underline_style(UnderlineStyle::Curly));
+        assert!(cell.modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(cell.underline_style, UnderlineStyle::Curly);
+        assert_eq!(cell.underline_color, Color::Blue);
+
+        cell.reset();
+        assert_eq!(cell, Cell::default());
+    }
+
+    #[test]
+    fn test_hyperlink() {
+        let link = Hyperlink::new("file:///tmp/my notes.txt");
+        assert_eq!(link.uri(), "file:///tmp/my%20notes.txt");
+        assert_eq!(link.id(), Hyperlink::new("file:///tmp/my notes.txt").id());
+        assert_ne!(link.id(), Hyperlink::new("file:///tmp/other.txt").id());
+        assert_eq!(Hyperlink::new("https://a.b/\x1b]").uri(), "https://a.b/%1B]");
+
+        let link = link.with_id("pane 1;x");
+        assert_eq!(link.id(), "pane_1_x");
+    }
+
+    #[test]
+    fn test_buffer_hyperlinks() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 6, 2));
+        let docs = Hyperlink::new("https://docs.example");
+        let other = Hyperlink::new("https://other.example");
+        buffer.set_string(0, 0, "界ab", Style::default());
+        buffer.set_hyperlink(Rect::new(0, 0, 3, 1), Some(&docs));
+        buffer.set_hyperlink(Rect::new(3, 0, 1, 1), Some(&other));
+        buffer.set_hyperlink(Rect::new(4, 0, 2, 2), Some(&docs));
+        assert_eq!(
+            buffer.hyperlinks(),
+            vec![
+                (Rect::new(0, 0, 3, 1), &docs),
+                (Rect::new(3, 0, 1, 1), &other),
+                (Rect::new(4, 0, 2, 1), &docs),
+                (Rect::new(4, 1, 2, 1), &docs),
+            ]
+        );
+
+        // Writing text removes the links of the cells written
+        buffer.set_string(0, 0, "xy", Style::default());
+        assert_eq!(buffer.get(1, 0).unwrap().hyperlink, None);
+        assert_eq!(buffer.get(2, 0).unwrap().hyperlink.as_ref(), Some(&docs));
+
+        // A link change alone shows up in the diff
+        let mut unlinked = buffer.clone();
+        unlinked.set_hyperlink(Rect::new(4, 1, 1, 1), None);
+        let diff = buffer.diff(&unlinked);
+        assert_eq!(diff.len(), 1);
+        assert_eq!((diff[0].0, diff[0].1), (4, 1));
+    }
+
     #[test]
     fn test_cell_reset() {
         let mut cell = Cell::new("X")
@@ -389,6 +685,68 @@ mod tests {
         assert_eq!(buffer.get(0, 0).unwrap().fg, Color::Green);
     }
 
+    #[test]
+    fn test_buffer_set_string_graphemes() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 10, 1));
+
+        // "e" + combining acute accent, then a flag made of two regional indicators
+        let written = buffer.set_string(0, 0, "e\u{301}x\u{1F1EF}\u{1F1F5}", Style::default());
+        assert_eq!(written, 4);
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, "x");
+        assert_eq!(buffer.get(2, 0).unwrap().symbol, "\u{1F1EF}\u{1F1F5}");
+        assert!(buffer.get(3, 0).unwrap().continuation);
+        assert_eq!(buffer.get(4, 0).unwrap().symbol, " ");
+    }
+
+    #[test]
+    fn test_buffer_set_string_zwj_sequence() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 10, 1));
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+        let written = buffer.set_string(0, 0, family, Style::default());
+        assert_eq!(written, 2);
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, family);
+        assert!(buffer.get(1, 0).unwrap().continuation);
+        assert!(!buffer.get(2, 0).unwrap().continuation);
+    }
+
+    #[test]
+    fn test_buffer_set_string_wide_does_not_split() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 3, 1));
+
+        let written = buffer.set_string(0, 0, "a世界", Style::default());
+        assert_eq!(written, 3);
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, "世");
+        assert!(buffer.get(2, 0).unwrap().continuation);
+    }
+
+    #[test]
+    fn test_buffer_set_string_overwrites_half_of_wide() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "世界", Style::default());
+
+        // Writing into the continuation of 世 blanks its lead cell, and the
+        // continuation of 界 left behind becomes a plain space.
+        buffer.set_string(1, 0, "ab", Style::default());
+        let symbols: Vec<_> = (0..4)
+            .map(|x| buffer.get(x, 0).unwrap().symbol.as_str())
+            .collect();
+        assert_eq!(symbols, vec![" ", "a", "b", " "]);
+        assert!((0..4).all(|x| !buffer.get(x, 0).unwrap().continuation));
+    }
+
+    #[test]
+    fn test_cell_width() {
+        let mut cell = Cell::new("世");
+        assert_eq!(cell.width(), 2);
+        cell.set_continuation();
+        assert_eq!(cell.width(), 0);
+        assert!(cell.symbol.is_empty());
+        cell.reset();
+        assert!(!cell.continuation);
+    }
+
     #[test]
     fn test_buffer_set_style() {
         let area = Rect::new(0, 0, 5, 5);
@@ -452,6 +810,25 @@ mod tests {
         assert_eq!(diff[0].2.symbol, "X");
     }
 
+    #[test]
+    fn test_buffer_diff_skips_continuation() {
+        let area = Rect::new(0, 0, 4, 1);
+        let buffer1 = Buffer::new(area);
+        let mut buffer2 = Buffer::new(area);
+        buffer2.set_string(0, 0, "世", Style::default());
+
+        let diff = buffer1.diff(&buffer2);
+        assert_eq!(diff.len(), 1);
+        assert_eq!((diff[0].0, diff[0].2.symbol.as_str()), (0, "世"));
+
+        // Replacing the wide glyph with narrow ones redraws both columns.
+        let mut buffer3 = Buffer::new(area);
+        buffer3.set_string(0, 0, "ab", Style::default());
+        let diff = buffer2.diff(&buffer3);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[1].2.symbol, "b");
+    }
+
     #[test]
     fn test_buffer_merge() {
         let area1 = Rect::new(0, 0, 5, 5);
@@ -0,0 +1,563 @@
+//! Color spaces and color math.
+//!
+//! Every [`Color`] except [`Color::Reset`] has an RGB value: RGB colors
+//! directly, and the named and indexed colors through the standard xterm
+//! 256-color palette. On top of that, this module provides conversions to
+//! [`Hsl`], [`Oklab`] and [`Oklch`], and the operations dashboards need for
+//! derived colors:
+//!
+//! - [`Color::lighten`] and [`Color::darken`] change the perceived lightness
+//! - [`Color::mix`] interpolates between two colors in OKLab, so the halfway
+//!   point looks halfway
+//! - [`Color::blend`] lays a translucent color over another, like light does
+//! - [`Color::contrast_ratio`] computes the WCAG contrast ratio
+//! - [`Gradient`] interpolates between several colors, for heatmaps
+//!
+//! Derived colors are always [`Color::Rgb`]. Renderers downsample them for
+//! terminals without 24-bit color. [`Color::Reset`] stands for the
+//! terminal's default color, which is unknown, so the operations return it
+//! unchanged.
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_core::color::Gradient;
+//! use fusabi_tui_core::style::Color;
+//!
+//! let border = Color::Cyan.darken(0.2);
+//! let hover = Color::Rgb(40, 40, 40).lighten(0.1);
+//! assert!(Color::White.contrast_ratio(Color::Black).unwrap() > 15.0);
+//!
+//! let heat = Gradient::new([Color::Blue, Color::Yellow, Color::Red]);
+//! assert_eq!(heat.at(0.5), Color::Yellow);
+//! assert_eq!(heat.steps(5).len(), 5);
+//! # let _ = (border, hover);
+//! ```
+
+use crate::style::Color;
+
+/// A color in the HSL color space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    /// Hue in degrees, from 0 to 360
+    pub h: f32,
+    /// Saturation, from 0 to 1
+    pub s: f32,
+    /// Lightness, from 0 to 1
+    pub l: f32,
+}
+
+/// A color in the OKLab color space, where distances match perceived
+/// differences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    /// Perceived lightness, from 0 to 1
+    pub l: f32,
+    /// Green-red axis
+    pub a: f32,
+    /// Blue-yellow axis
+    pub b: f32,
+}
+
+/// A color in the OKLCH color space, the polar form of [`Oklab`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    /// Perceived lightness, from 0 to 1
+    pub l: f32,
+    /// Chroma, from 0 to about 0.4
+    pub c: f32,
+    /// Hue in degrees, from 0 to 360
+    pub h: f32,
+}
+
+impl Oklab {
+    /// Get the perceptual distance to another color.
+    pub fn distance(self, other: Oklab) -> f32 {
+        let (dl, da, db) = (self.l - other.l, self.a - other.a, self.b - other.b);
+        (dl * dl + da * da + db * db).sqrt()
+    }
+
+    /// Convert to OKLCH.
+    pub fn to_oklch(self) -> Oklch {
+        Oklch {
+            l: self.l,
+            c: self.a.hypot(self.b),
+            h: self.b.atan2(self.a).to_degrees().rem_euclid(360.0),
+        }
+    }
+}
+
+impl Oklch {
+    /// Convert to OKLab.
+    pub fn to_oklab(self) -> Oklab {
+        let (sin, cos) = self.h.to_radians().sin_cos();
+        Oklab {
+            l: self.l,
+            a: self.c * cos,
+            b: self.c * sin,
+        }
+    }
+}
+
+impl Color {
+    /// Get the RGB value of the color.
+    ///
+    /// Named and indexed colors use the standard xterm palette. Returns
+    /// `None` for [`Color::Reset`].
+    pub fn to_rgb(self) -> Option<(u8, u8, u8)> {
+        let index = match self {
+            Color::Rgb(r, g, b) => return Some((r, g, b)),
+            Color::Reset => return None,
+            Color::Indexed(i) => i,
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::DarkGray => 8,
+            Color::LightRed => 9,
+            Color::LightGreen => 10,
+            Color::LightYellow => 11,
+            Color::LightBlue => 12,
+            Color::LightMagenta => 13,
+            Color::LightCyan => 14,
+            Color::LightWhite => 15,
+        };
+        Some(xterm_rgb(index))
+    }
+
+    /// Create a color from HSL.
+    pub fn from_hsl(hsl: Hsl) -> Self {
+        let s = hsl.s.clamp(0.0, 1.0);
+        let l = hsl.l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let h = hsl.h.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h {
+            h if h < 1.0 => (chroma, x, 0.0),
+            h if h < 2.0 => (x, chroma, 0.0),
+            h if h < 3.0 => (0.0, chroma, x),
+            h if h < 4.0 => (0.0, x, chroma),
+            h if h < 5.0 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = l - chroma / 2.0;
+        Color::Rgb(channel(r + m), channel(g + m), channel(b + m))
+    }
+
+    /// Get the color in HSL. Returns `None` for [`Color::Reset`].
+    pub fn to_hsl(self) -> Option<Hsl> {
+        let (r, g, b) = self.to_rgb()?;
+        let (r, g, b) = (
+            f32::from(r) / 255.0,
+            f32::from(g) / 255.0,
+            f32::from(b) / 255.0,
+        );
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+        if delta == 0.0 {
+            return Some(Hsl { h: 0.0, s: 0.0, l });
+        }
+
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        Some(Hsl { h: h * 60.0, s, l })
+    }
+
+    /// Create a color from OKLab, clipped to the RGB gamut.
+    pub fn from_oklab(lab: Oklab) -> Self {
+        let l = (lab.l + 0.396_337_78 * lab.a + 0.215_803_76 * lab.b).powi(3);
+        let m = (lab.l - 0.105_561_346 * lab.a - 0.063_854_17 * lab.b).powi(3);
+        let s = (lab.l - 0.089_484_18 * lab.a - 1.291_485_5 * lab.b).powi(3);
+
+        let r = 4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s;
+        let g = -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s;
+        let b = -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s;
+        Color::Rgb(from_linear(r), from_linear(g), from_linear(b))
+    }
+
+    /// Get the color in OKLab. Returns `None` for [`Color::Reset`].
+    pub fn to_oklab(self) -> Option<Oklab> {
+        let (r, g, b) = self.to_rgb()?;
+        let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        Some(Oklab {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        })
+    }
+
+    /// Create a color from OKLCH, clipped to the RGB gamut.
+    pub fn from_oklch(lch: Oklch) -> Self {
+        Self::from_oklab(lch.to_oklab())
+    }
+
+    /// Get the color in OKLCH. Returns `None` for [`Color::Reset`].
+    pub fn to_oklch(self) -> Option<Oklch> {
+        self.to_oklab().map(Oklab::to_oklch)
+    }
+
+    /// Make the color lighter by `amount`, from 0 to 1, of perceived
+    /// lightness.
+    pub fn lighten(self, amount: f32) -> Self {
+        match self.to_oklch() {
+            Some(lch) => Self::from_oklch(Oklch {
+                l: (lch.l + amount).clamp(0.0, 1.0),
+                ..lch
+            }),
+            None => self,
+        }
+    }
+
+    /// Make the color darker by `amount`, from 0 to 1, of perceived
+    /// lightness.
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Interpolate between this color, at `t = 0`, and `other`, at `t = 1`,
+    /// in OKLab.
+    ///
+    /// At `t = 0` and `t = 1` the colors themselves are returned. If either
+    /// color is [`Color::Reset`], the nearer of the two is returned.
+    pub fn mix(self, other: Color, t: f32) -> Self {
+        if t <= 0.0 {
+            return self;
+        }
+        if t >= 1.0 {
+            return other;
+        }
+        match (self.to_oklab(), other.to_oklab()) {
+            (Some(a), Some(b)) => Self::from_oklab(Oklab {
+                l: lerp(a.l, b.l, t),
+                a: lerp(a.a, b.a, t),
+                b: lerp(a.b, b.b, t),
+            }),
+            _ if t < 0.5 => self,
+            _ => other,
+        }
+    }
+
+    /// Lay `other` over this color with opacity `alpha`, from 0 to 1.
+    ///
+    /// The colors are blended in linear RGB, the way light mixes. If either
+    /// color is [`Color::Reset`], the more opaque of the two is returned.
+    pub fn blend(self, other: Color, alpha: f32) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        match (self.to_rgb(), other.to_rgb()) {
+            (Some((r1, g1, b1)), Some((r2, g2, b2))) => {
+                let blend = |a: u8, b: u8| from_linear(lerp(to_linear(a), to_linear(b), alpha));
+                Color::Rgb(blend(r1, r2), blend(g1, g2), blend(b1, b2))
+            }
+            _ if alpha < 0.5 => self,
+            _ => other,
+        }
+    }
+
+    /// Get the relative luminance of the color as defined by WCAG, from 0
+    /// for black to 1 for white. Returns `None` for [`Color::Reset`].
+    pub fn relative_luminance(self) -> Option<f32> {
+        let (r, g, b) = self.to_rgb()?;
+        Some(0.2126 * to_linear(r) + 0.7152 * to_linear(g) + 0.0722 * to_linear(b))
+    }
+
+    /// Get the WCAG contrast ratio between two colors, from 1 for equal
+    /// colors to 21 for black and white.
+    ///
+    /// WCAG asks for at least 4.5 for text. Returns `None` if either color is
+    /// [`Color::Reset`].
+    pub fn contrast_ratio(self, other: Color) -> Option<f32> {
+        let a = self.relative_luminance()?;
+        let b = other.relative_luminance()?;
+        Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+    }
+}
+
+/// Colors spread over the range from 0 to 1, interpolated in OKLab.
+///
+/// # Example
+///
+/// ```
+/// use fusabi_tui_core::color::Gradient;
+/// use fusabi_tui_core::style::Color;
+///
+/// let gradient = Gradient::with_stops([(0.0, Color::Green), (0.8, Color::Red)]);
+/// assert_eq!(gradient.at(0.0), Color::Green);
+/// assert_eq!(gradient.at(1.0), Color::Red);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    /// Positions and colors, sorted by position
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    /// Create a gradient of colors spaced evenly from 0 to 1.
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
+        let colors: Vec<Color> = colors.into_iter().collect();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        Self {
+            stops: colors
+                .into_iter()
+                .enumerate()
+                .map(|(i, color)| (i as f32 / last, color))
+                .collect(),
+        }
+    }
+
+    /// Create a gradient with colors at the given positions, from 0 to 1.
+    ///
+    /// Before the first and after the last position, the gradient has the
+    /// color of the nearest stop.
+    pub fn with_stops(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        let mut stops: Vec<(f32, Color)> = stops
+            .into_iter()
+            .map(|(position, color)| (position.clamp(0.0, 1.0), color))
+            .collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Get the color at position `t`, from 0 to 1.
+    ///
+    /// An empty gradient is [`Color::Reset`] everywhere.
+    pub fn at(&self, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let next = self.stops.partition_point(|(position, _)| *position < t);
+        match (next.checked_sub(1).map(|i| self.stops[i]), self.stops.get(next)) {
+            (Some((p1, c1)), Some(&(p2, c2))) if p2 > p1 => c1.mix(c2, (t - p1) / (p2 - p1)),
+            (_, Some(&(_, color))) | (Some((_, color)), None) => color,
+            (None, None) => Color::Reset,
+        }
+    }
+
+    /// Get `count` colors evenly spaced along the gradient, from 0 to 1.
+    pub fn steps(&self, count: usize) -> Vec<Color> {
+        let last = count.saturating_sub(1).max(1) as f32;
+        (0..count).map(|i| self.at(i as f32 / last)).collect()
+    }
+}
+
+/// Get the RGB value of a color in the xterm 256-color palette.
+fn xterm_rgb(index: u8) -> (u8, u8, u8) {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match index {
+        0..=15 => ANSI[usize::from(index)],
+        16..=231 => {
+            let i = index - 16;
+            (
+                LEVELS[usize::from(i / 36)],
+                LEVELS[usize::from(i / 6 % 6)],
+                LEVELS[usize::from(i % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Convert an sRGB channel to linear light.
+fn to_linear(c: u8) -> f32 {
+    let c = f32::from(c) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert linear light to an sRGB channel.
+fn from_linear(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    channel(if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    })
+}
+
+/// Convert a channel from 0 to 1 to a byte.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn channel(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn test_to_rgb() {
+        assert_eq!(Color::Rgb(1, 2, 3).to_rgb(), Some((1, 2, 3)));
+        assert_eq!(Color::Red.to_rgb(), Some((205, 0, 0)));
+        assert_eq!(Color::LightWhite.to_rgb(), Some((255, 255, 255)));
+        assert_eq!(Color::Indexed(9).to_rgb(), Some((255, 0, 0)));
+        assert_eq!(Color::Indexed(67).to_rgb(), Some((95, 135, 175)));
+        assert_eq!(Color::Indexed(231).to_rgb(), Some((255, 255, 255)));
+        assert_eq!(Color::Indexed(232).to_rgb(), Some((8, 8, 8)));
+        assert_eq!(Color::Indexed(255).to_rgb(), Some((238, 238, 238)));
+        assert_eq!(Color::Reset.to_rgb(), None);
+    }
+
+    #[test]
+    fn test_hsl() {
+        let hsl = Color::Rgb(255, 0, 0).to_hsl().unwrap();
+        assert!(close(hsl.h, 0.0) && close(hsl.s, 1.0) && close(hsl.l, 0.5));
+        let hsl = Color::Rgb(0, 0, 255).to_hsl().unwrap();
+        assert!(close(hsl.h, 240.0));
+        let hsl = Color::Rgb(128, 128, 128).to_hsl().unwrap();
+        assert!(close(hsl.s, 0.0));
+
+        assert_eq!(
+            Color::from_hsl(Hsl {
+                h: 120.0,
+                s: 1.0,
+                l: 0.25
+            }),
+            Color::Rgb(0, 128, 0)
+        );
+        for color in [Color::Rgb(12, 200, 99), Color::Rgb(250, 128, 3), Color::Indexed(67)] {
+            let rgb = color.to_rgb().unwrap();
+            assert_eq!(Color::from_hsl(color.to_hsl().unwrap()).to_rgb(), Some(rgb));
+        }
+        assert_eq!(Color::Reset.to_hsl(), None);
+    }
+
+    #[test]
+    fn test_oklab_round_trip() {
+        for color in [
+            Color::Rgb(0, 0, 0),
+            Color::Rgb(255, 255, 255),
+            Color::Rgb(12, 200, 99),
+            Color::Rgb(250, 128, 3),
+            Color::Rgb(30, 10, 220),
+        ] {
+            assert_eq!(Color::from_oklab(color.to_oklab().unwrap()), color);
+            assert_eq!(Color::from_oklch(color.to_oklch().unwrap()), color);
+        }
+
+        let white = Color::Rgb(255, 255, 255).to_oklch().unwrap();
+        assert!(close(white.l, 1.0) && close(white.c, 0.0));
+        let red = Color::Rgb(255, 0, 0).to_oklch().unwrap();
+        assert!(close(red.l, 0.628) && close(red.c, 0.258) && (red.h - 29.2).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_lighten_darken() {
+        let gray = Color::Rgb(100, 100, 100);
+        let lighter = gray.lighten(0.1);
+        let darker = gray.darken(0.1);
+        let l = |c: Color| c.to_oklch().unwrap().l;
+        assert!(close(l(lighter), l(gray) + 0.1));
+        assert!(close(l(darker), l(gray) - 0.1));
+        assert_eq!(gray.lighten(2.0), Color::Rgb(255, 255, 255));
+        assert_eq!(gray.darken(2.0), Color::Rgb(0, 0, 0));
+        assert_eq!(Color::Reset.lighten(0.5), Color::Reset);
+        assert!(matches!(Color::Blue.darken(0.1), Color::Rgb(..)));
+    }
+
+    #[test]
+    fn test_mix() {
+        let (black, white) = (Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255));
+        assert_eq!(black.mix(white, 0.0), black);
+        assert_eq!(black.mix(white, 1.0), white);
+        let middle = black.mix(white, 0.5).to_oklab().unwrap();
+        assert!(close(middle.l, 0.5));
+        assert_eq!(Color::Reset.mix(white, 0.2), Color::Reset);
+        assert_eq!(Color::Reset.mix(white, 0.8), white);
+    }
+
+    #[test]
+    fn test_blend() {
+        let (black, white) = (Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255));
+        assert_eq!(black.blend(white, 0.0), black);
+        assert_eq!(black.blend(white, 1.0), white);
+        // Half the light of white is brighter than the middle gray
+        assert_eq!(black.blend(white, 0.5), Color::Rgb(188, 188, 188));
+        assert_eq!(Color::Red.blend(Color::Reset, 0.3), Color::Red);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let ratio = |a: Color, b: Color| a.contrast_ratio(b).unwrap();
+        assert!(close(ratio(Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255)), 21.0));
+        assert!(close(ratio(Color::Rgb(255, 255, 255), Color::Rgb(0, 0, 0)), 21.0));
+        assert!(close(ratio(Color::Red, Color::Red), 1.0));
+        assert!(close(ratio(Color::Rgb(119, 119, 119), Color::Rgb(255, 255, 255)), 4.48));
+        assert_eq!(Color::Reset.contrast_ratio(Color::Black), None);
+        assert_eq!(Color::Black.relative_luminance(), Some(0.0));
+    }
+
+    #[test]
+    fn test_gradient() {
+        let (black, white) = (Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255));
+        let gradient = Gradient::new([black, Color::Rgb(255, 0, 0), white]);
+        assert_eq!(gradient.at(0.0), black);
+        assert_eq!(gradient.at(0.5), Color::Rgb(255, 0, 0));
+        assert_eq!(gradient.at(1.0), white);
+        assert_eq!(gradient.at(0.25), black.mix(Color::Rgb(255, 0, 0), 0.5));
+        assert_eq!(gradient.at(-1.0), black);
+
+        let steps = gradient.steps(3);
+        assert_eq!(steps, vec![black, Color::Rgb(255, 0, 0), white]);
+        assert_eq!(gradient.steps(1), vec![black]);
+        assert!(gradient.steps(0).is_empty());
+    }
+
+    #[test]
+    fn test_gradient_stops() {
+        let gradient = Gradient::with_stops([(0.8, Color::Red), (0.2, Color::Green)]);
+        assert_eq!(gradient.at(0.0), Color::Green);
+        assert_eq!(gradient.at(0.2), Color::Green);
+        assert_eq!(gradient.at(0.9), Color::Red);
+        assert_eq!(gradient.at(0.5), Color::Green.mix(Color::Red, 0.5));
+
+        assert_eq!(Gradient::new([]).at(0.5), Color::Reset);
+        assert_eq!(Gradient::new([Color::Cyan]).at(0.7), Color::Cyan);
+    }
+}
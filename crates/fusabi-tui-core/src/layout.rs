@@ -214,11 +214,12 @@ pub enum Constraint {
     Percentage(u16),
     /// Ratio of available space (numerator, denominator)
     Ratio(u32, u32),
-    /// Minimum number of cells
+    /// Minimum number of cells. Grows into leftover space when the layout
+    /// has no `Fill` constraints.
     Min(u16),
     /// Maximum number of cells
     Max(u16),
-    /// Fill remaining space equally among all Fill constraints
+    /// Share of the leftover space, proportional to the weight
     Fill(u16),
 }
 
@@ -316,7 +317,24 @@ impl Layout {
 
     /// Splits the given area according to this layout's constraints.
     ///
-    /// Returns a vector of rectangles, one for each constraint.
+    /// Returns a vector of rectangles, one for each constraint. The rectangles
+    /// always tile the area exactly: they are adjacent and their sizes add up
+    /// to the size of the area along the split direction.
+    ///
+    /// Constraints are resolved in order of strength:
+    ///
+    /// 1. `Min` and `Max` bounds.
+    /// 2. The preferred sizes of `Length`, `Percentage`, `Ratio` and `Max`.
+    ///    Percentages and ratios are taken of the whole area.
+    /// 3. Leftover space goes to `Fill` constraints in proportion to their
+    ///    weights, or to `Min` constraints in equal shares if there are no
+    ///    fills. Failing both, it goes to the last element that can grow
+    ///    without breaking its `Max` bound.
+    ///
+    /// When the constraints ask for more space than is available, elements are
+    /// shrunk from last to first, first down to their `Min` bounds and then,
+    /// if that is still not enough, down to zero. Earlier constraints
+    /// therefore win conflicts.
     pub fn split(&self, area: Rect) -> Vec<Rect> {
         let area = area.inner(Margin::symmetric(self.margin));
 
@@ -329,63 +347,7 @@ impl Layout {
             Direction::Vertical => (area.height, area.width),
         };
 
-        // First pass: calculate sizes for non-Fill constraints
-        let mut sizes = vec![0u16; self.constraints.len()];
-        let mut remaining = main_axis_size;
-        let mut fill_count = 0;
-
-        for (i, constraint) in self.constraints.iter().enumerate() {
-            match constraint {
-                Constraint::Fill(_) => {
-                    fill_count += 1;
-                }
-                Constraint::Percentage(_) | Constraint::Ratio(_, _) => {
-                    // Percentage and Ratio apply to the original size, not remaining
-                    let size = constraint.apply(main_axis_size);
-                    sizes[i] = size;
-                    remaining = remaining.saturating_sub(size);
-                }
-                _ => {
-                    let size = constraint.apply(remaining);
-                    sizes[i] = size;
-                    remaining = remaining.saturating_sub(size);
-                }
-            }
-        }
-
-        // Second pass: distribute remaining space among Fill constraints
-        if fill_count > 0 {
-            let fill_size = remaining / fill_count as u16;
-            let fill_remainder = remaining % fill_count as u16;
-            let mut remainder_distributed = 0;
-
-            for (i, constraint) in self.constraints.iter().enumerate() {
-                if let Constraint::Fill(_) = constraint {
-                    sizes[i] = fill_size;
-                    if remainder_distributed < fill_remainder {
-                        sizes[i] += 1;
-                        remainder_distributed += 1;
-                    }
-                }
-            }
-        }
-
-        // Third pass: apply Min and Max constraints
-        for (i, constraint) in self.constraints.iter().enumerate() {
-            match constraint {
-                Constraint::Min(min) => {
-                    if sizes[i] < *min {
-                        sizes[i] = *min;
-                    }
-                }
-                Constraint::Max(max) => {
-                    if sizes[i] > *max {
-                        sizes[i] = *max;
-                    }
-                }
-                _ => {}
-            }
-        }
+        let sizes = solve(&self.constraints, main_axis_size);
 
         // Build the result rectangles
         let mut results = Vec::with_capacity(self.constraints.len());
@@ -414,6 +376,156 @@ impl Layout {
     }
 }
 
+/// How an element takes part in sharing out leftover space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grow {
+    /// Keeps its preferred size.
+    Fixed,
+    /// Grows when there are no `Fill` elements.
+    Min,
+    /// Grows in proportion to its weight.
+    Fill(u32),
+}
+
+/// Bounds and preferences derived from a single constraint.
+#[derive(Debug, Clone, Copy)]
+struct Element {
+    /// Strong lower bound.
+    min: u32,
+    /// Strong upper bound.
+    max: u32,
+    /// Size the element asks for before leftover space is shared out.
+    preferred: u32,
+    grow: Grow,
+}
+
+impl Element {
+    fn new(constraint: Constraint, total: u16) -> Self {
+        let (min, max, preferred, grow) = match constraint {
+            Constraint::Length(_) | Constraint::Percentage(_) | Constraint::Ratio(_, _) => {
+                (0, u32::MAX, u32::from(constraint.apply(total)), Grow::Fixed)
+            }
+            Constraint::Min(m) => (u32::from(m), u32::MAX, u32::from(m), Grow::Min),
+            Constraint::Max(m) => (0, u32::from(m), u32::from(m), Grow::Fixed),
+            Constraint::Fill(weight) => (0, u32::MAX, 0, Grow::Fill(u32::from(weight))),
+        };
+        Self {
+            min,
+            max,
+            preferred: min.max(preferred.min(max)),
+            grow,
+        }
+    }
+}
+
+/// Resolves `constraints` into sizes that add up to exactly `total`.
+///
+/// See [`Layout::split`] for the rules.
+fn solve(constraints: &[Constraint], total: u16) -> Vec<u16> {
+    let elements: Vec<Element> = constraints
+        .iter()
+        .map(|&constraint| Element::new(constraint, total))
+        .collect();
+    let mut sizes: Vec<u32> = elements.iter().map(|e| e.preferred).collect();
+
+    let total = u32::from(total);
+    let used: u32 = sizes.iter().sum();
+    if used < total {
+        grow(&elements, &mut sizes, total - used);
+    } else if used > total {
+        shrink(&elements, &mut sizes, used - total);
+    }
+
+    sizes
+        .into_iter()
+        .map(|size| u16::try_from(size).unwrap_or(u16::MAX))
+        .collect()
+}
+
+/// Hands out `excess` cells to the elements that are allowed to grow.
+fn grow(elements: &[Element], sizes: &mut [u32], mut excess: u32) {
+    let fills: Vec<(usize, u32)> = elements
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| match e.grow {
+            Grow::Fill(weight) => Some((i, weight)),
+            _ => None,
+        })
+        .collect();
+
+    if !fills.is_empty() {
+        // Zero weights only count when every fill has a zero weight.
+        if fills.iter().all(|&(_, weight)| weight == 0) {
+            let equal: Vec<(usize, u32)> = fills.iter().map(|&(i, _)| (i, 1)).collect();
+            distribute(sizes, &equal, excess);
+        } else {
+            distribute(sizes, &fills, excess);
+        }
+        return;
+    }
+
+    let mins: Vec<(usize, u32)> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.grow == Grow::Min)
+        .map(|(i, _)| (i, 1))
+        .collect();
+    if !mins.is_empty() {
+        distribute(sizes, &mins, excess);
+        return;
+    }
+
+    for (i, element) in elements.iter().enumerate().rev() {
+        let room = element.max.saturating_sub(sizes[i]).min(excess);
+        sizes[i] += room;
+        excess -= room;
+    }
+    // Every element is at its maximum; tiling the area takes precedence.
+    if let Some(last) = sizes.last_mut() {
+        *last += excess;
+    }
+}
+
+/// Takes `deficit` cells away, starting with the last element.
+fn shrink(elements: &[Element], sizes: &mut [u32], mut deficit: u32) {
+    for (i, element) in elements.iter().enumerate().rev() {
+        let slack = sizes[i].saturating_sub(element.min).min(deficit);
+        sizes[i] -= slack;
+        deficit -= slack;
+    }
+    for size in sizes.iter_mut().rev() {
+        let slack = (*size).min(deficit);
+        *size -= slack;
+        deficit -= slack;
+    }
+}
+
+/// Splits `amount` between `(index, weight)` entries in proportion to their
+/// weights, using the largest remainder method so that nothing is lost to
+/// rounding. Ties go to the earlier entry.
+fn distribute(sizes: &mut [u32], weighted: &[(usize, u32)], amount: u32) {
+    let total_weight: u64 = weighted.iter().map(|&(_, weight)| u64::from(weight)).sum();
+    if total_weight == 0 {
+        return;
+    }
+
+    let mut given = 0;
+    let mut remainders = Vec::with_capacity(weighted.len());
+    for (slot, &(i, weight)) in weighted.iter().enumerate() {
+        let exact = u64::from(amount) * u64::from(weight);
+        // The share never exceeds `amount`, so it fits in a u32.
+        let share = u32::try_from(exact / total_weight).unwrap_or(amount);
+        sizes[i] += share;
+        given += share;
+        remainders.push((exact % total_weight, slot));
+    }
+
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, slot) in remainders.iter().take((amount - given) as usize) {
+        sizes[weighted[slot].0] += 1;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -574,7 +686,7 @@ mod tests {
         assert_eq!(chunks.len(), 3);
         assert_eq!(chunks[0], Rect::new(0, 0, 100, 10));
         assert_eq!(chunks[1], Rect::new(0, 10, 100, 20));
-        assert_eq!(chunks[2], Rect::new(0, 30, 100, 30));
+        assert_eq!(chunks[2], Rect::new(0, 30, 100, 70));
     }
 
     #[test]
@@ -592,7 +704,7 @@ mod tests {
         assert_eq!(chunks.len(), 3);
         assert_eq!(chunks[0], Rect::new(0, 0, 10, 100));
         assert_eq!(chunks[1], Rect::new(10, 0, 20, 100));
-        assert_eq!(chunks[2], Rect::new(30, 0, 30, 100));
+        assert_eq!(chunks[2], Rect::new(30, 0, 70, 100));
     }
 
     #[test]
@@ -641,6 +753,104 @@ mod tests {
         assert_eq!(chunks[2].height, 45);
     }
 
+    fn heights(constraints: &[Constraint], height: u16) -> Vec<u16> {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(Rect::new(0, 0, 10, height))
+            .iter()
+            .map(|r| r.height)
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_tiles_area() {
+        let cases: &[&[Constraint]] = &[
+            &[Constraint::Length(50), Constraint::Length(80)],
+            &[Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(33)],
+            &[Constraint::Min(60), Constraint::Min(60), Constraint::Fill(1)],
+            &[Constraint::Max(10), Constraint::Max(10)],
+            &[Constraint::Ratio(1, 3), Constraint::Ratio(1, 3), Constraint::Ratio(1, 3)],
+            &[Constraint::Fill(0), Constraint::Length(5)],
+        ];
+
+        for constraints in cases {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints)
+                .split(Rect::new(0, 3, 10, 100));
+            let mut y = 3;
+            for chunk in &chunks {
+                assert_eq!(chunk.y, y, "gap or overlap for {constraints:?}");
+                y += chunk.height;
+            }
+            assert_eq!(y, 103, "area not covered for {constraints:?}");
+        }
+    }
+
+    #[test]
+    fn test_layout_min_grows_without_fill() {
+        let sizes = heights(
+            &[Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)],
+            50,
+        );
+        assert_eq!(sizes, vec![3, 44, 3]);
+    }
+
+    #[test]
+    fn test_layout_min_yields_to_fill() {
+        let sizes = heights(&[Constraint::Min(10), Constraint::Fill(1)], 100);
+        assert_eq!(sizes, vec![10, 90]);
+    }
+
+    #[test]
+    fn test_layout_min_beats_length() {
+        let sizes = heights(&[Constraint::Length(10), Constraint::Min(95)], 100);
+        assert_eq!(sizes, vec![5, 95]);
+    }
+
+    #[test]
+    fn test_layout_min_shrinks_when_impossible() {
+        let sizes = heights(&[Constraint::Min(30), Constraint::Fill(1)], 20);
+        assert_eq!(sizes, vec![20, 0]);
+    }
+
+    #[test]
+    fn test_layout_max_caps_fill() {
+        let sizes = heights(&[Constraint::Max(20), Constraint::Fill(1)], 100);
+        assert_eq!(sizes, vec![20, 80]);
+
+        let sizes = heights(&[Constraint::Max(20), Constraint::Length(10)], 100);
+        assert_eq!(sizes, vec![20, 80]);
+
+        let sizes = heights(&[Constraint::Length(10), Constraint::Max(20)], 100);
+        assert_eq!(sizes, vec![80, 20]);
+    }
+
+    #[test]
+    fn test_layout_fill_weights() {
+        let sizes = heights(&[Constraint::Fill(1), Constraint::Fill(3)], 100);
+        assert_eq!(sizes, vec![25, 75]);
+
+        let sizes = heights(&[Constraint::Fill(1), Constraint::Fill(2)], 10);
+        assert_eq!(sizes, vec![3, 7]);
+
+        let sizes = heights(&[Constraint::Fill(0), Constraint::Fill(1)], 10);
+        assert_eq!(sizes, vec![0, 10]);
+
+        let sizes = heights(&[Constraint::Fill(0), Constraint::Fill(0)], 11);
+        assert_eq!(sizes, vec![6, 5]);
+    }
+
+    #[test]
+    fn test_layout_overflow_earlier_wins() {
+        let sizes = heights(
+            &[Constraint::Length(60), Constraint::Percentage(60), Constraint::Fill(1)],
+            100,
+        );
+        assert_eq!(sizes, vec![60, 40, 0]);
+    }
+
     #[test]
     fn test_layout_margin() {
         let area = Rect::new(0, 0, 100, 100);
@@ -9,6 +9,7 @@
 //! The crate is organized into several modules:
 //!
 //! - [`style`] - Color, text modifiers, and combined styles
+//! - [`color`] - Color spaces, color math and gradients
 //! - [`buffer`] - Terminal cell and buffer management
 //! - [`layout`] - Rectangular areas and constraint-based layouts
 //! - [`symbols`] - Unicode characters for drawing borders and UI elements
@@ -67,13 +68,15 @@
 #![allow(clippy::module_name_repetitions)]
 
 pub mod buffer;
+pub mod color;
 pub mod layout;
 pub mod style;
 pub mod symbols;
 pub mod theme;
 
 // Re-export commonly used types at the crate root for convenience
-pub use buffer::{Buffer, Cell};
+pub use buffer::{Buffer, Cell, Hyperlink};
+pub use color::{Gradient, Hsl, Oklab, Oklch};
 pub use layout::{Constraint, Direction, Layout, Margin, Rect};
-pub use style::{Color, Modifier, Style};
+pub use style::{Color, Modifier, Style, UnderlineStyle};
 pub use theme::{ColorPalette, StyleMap, Theme};
@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 /// Represents a color in the terminal.
 ///
 /// Supports both standard ANSI colors and extended 256-color/RGB modes.
+/// The [`color`](crate::color) module adds color spaces and color math.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Color {
@@ -206,6 +207,58 @@ impl std::ops::Not for Modifier {
     }
 }
 
+/// The shape of an underline.
+///
+/// The shape only shows when [`Modifier::UNDERLINED`] is set. Terminals
+/// without styled underlines draw every shape as a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnderlineStyle {
+    /// A single straight line (SGR 4)
+    #[default]
+    Single,
+    /// Two straight lines (SGR 4:2)
+    Double,
+    /// A wavy line, often used for errors (SGR 4:3)
+    Curly,
+    /// A dotted line (SGR 4:4)
+    Dotted,
+    /// A dashed line (SGR 4:5)
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// All underline styles, in SGR order.
+    pub const ALL: [Self; 5] = [
+        Self::Single,
+        Self::Double,
+        Self::Curly,
+        Self::Dotted,
+        Self::Dashed,
+    ];
+
+    /// Returns the underline style with the given name, ignoring case.
+    ///
+    /// The names are the ones written by the `Display` implementation.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|style| style.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for UnderlineStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnderlineStyle::Single => write!(f, "single"),
+            UnderlineStyle::Double => write!(f, "double"),
+            UnderlineStyle::Curly => write!(f, "curly"),
+            UnderlineStyle::Dotted => write!(f, "dotted"),
+            UnderlineStyle::Dashed => write!(f, "dashed"),
+        }
+    }
+}
+
 /// A style combining foreground color, background color, and text modifiers.
 ///
 /// # Examples
@@ -218,6 +271,17 @@ impl std::ops::Not for Modifier {
 ///     .bg(Color::Black)
 ///     .add_modifier(Modifier::BOLD);
 /// ```
+///
+/// Underlines can have a shape and a color of their own:
+///
+/// ```
+/// use fusabi_tui_core::style::{Color, Modifier, Style, UnderlineStyle};
+///
+/// let error = Style::default()
+///     .underline_style(UnderlineStyle::Curly)
+///     .underline_color(Color::Red);
+/// assert!(error.modifiers.contains(Modifier::UNDERLINED));
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Style {
@@ -227,6 +291,18 @@ pub struct Style {
     pub bg: Option<Color>,
     /// Text modifiers
     pub modifiers: Modifier,
+    /// Underline shape
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub underline_style: Option<UnderlineStyle>,
+    /// Underline color, the foreground color when unset
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub underline_color: Option<Color>,
 }
 
 impl Default for Style {
@@ -235,6 +311,8 @@ impl Default for Style {
             fg: None,
             bg: None,
             modifiers: Modifier::EMPTY,
+            underline_style: None,
+            underline_color: None,
         }
     }
 }
@@ -247,6 +325,8 @@ impl Style {
             fg: None,
             bg: None,
             modifiers: Modifier::EMPTY,
+            underline_style: None,
+            underline_color: None,
         }
     }
 
@@ -278,12 +358,31 @@ impl Style {
         self
     }
 
+    /// Sets the underline shape and adds [`Modifier::UNDERLINED`].
+    #[inline]
+    pub const fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.underline_style = Some(style);
+        self.modifiers = self.modifiers.insert(Modifier::UNDERLINED);
+        self
+    }
+
+    /// Sets the underline color.
+    ///
+    /// The color only shows on underlined text.
+    #[inline]
+    pub const fn underline_color(mut self, color: Color) -> Self {
+        self.underline_color = Some(color);
+        self
+    }
+
     /// Resets this style to default values.
     #[inline]
     pub const fn reset(mut self) -> Self {
         self.fg = None;
         self.bg = None;
         self.modifiers = Modifier::EMPTY;
+        self.underline_style = None;
+        self.underline_color = None;
         self
     }
 
@@ -298,6 +397,12 @@ impl Style {
             self.bg = other.bg;
         }
         self.modifiers = self.modifiers.insert(other.modifiers);
+        if other.underline_style.is_some() {
+            self.underline_style = other.underline_style;
+        }
+        if other.underline_color.is_some() {
+            self.underline_color = other.underline_color;
+        }
         self
     }
 }
@@ -436,4 +541,31 @@ mod tests {
         let patched = style1.patch(style2);
         assert_eq!(patched.fg, Some(Color::Blue));
     }
+
+    #[test]
+    fn test_style_underline() {
+        let style = Style::new()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Red);
+        assert!(style.modifiers.contains(Modifier::UNDERLINED));
+        assert_eq!(style.underline_style, Some(UnderlineStyle::Curly));
+        assert_eq!(style.underline_color, Some(Color::Red));
+
+        let patched = style.patch(Style::new().underline_style(UnderlineStyle::Dotted));
+        assert_eq!(patched.underline_style, Some(UnderlineStyle::Dotted));
+        assert_eq!(patched.underline_color, Some(Color::Red));
+
+        let reset = style.reset();
+        assert_eq!(reset, Style::default());
+    }
+
+    #[test]
+    fn test_underline_style_names() {
+        for style in UnderlineStyle::ALL {
+            assert_eq!(UnderlineStyle::from_name(&style.to_string()), Some(style));
+        }
+        assert_eq!(UnderlineStyle::from_name("Curly"), Some(UnderlineStyle::Curly));
+        assert_eq!(UnderlineStyle::from_name("wavy"), None);
+        assert_eq!(UnderlineStyle::default(), UnderlineStyle::Single);
+    }
 }
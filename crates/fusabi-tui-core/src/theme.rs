@@ -47,6 +47,38 @@
 //! - [`Theme::dark()`] - A dark theme with subtle colors
 //! - [`Theme::light()`] - A light theme suitable for bright environments
 //! - [`Theme::slime()`] - The Slime theme from Scarab terminal with green accents
+//!
+//! # Theme Files
+//!
+//! With the `serde` feature, themes are loaded from TOML with
+//! [`Theme::from_toml`]. A theme can extend a built-in theme and override
+//! only some palette colors and styles. Styles refer to palette colors by
+//! name, and follow the overrides of the theme that uses them:
+//!
+//! ```toml
+//! name = "Ocean"
+//! extends = "dark"
+//!
+//! [colors]
+//! primary = "#3a86ff"
+//! accent = "LightCyan"
+//!
+//! [styles.title]
+//! fg = "primary"
+//! modifiers = ["bold"]
+//!
+//! [styles.border]
+//! fg = { Indexed = 240 }
+//! ```
+//!
+//! Colors are palette names, color names such as `"LightCyan"`, `#rrggbb`
+//! hex values, or `{ Rgb = [r, g, b] }` and `{ Indexed = i }` tables.
+//! Modifiers are a list of names (`bold`, `dim`, `italic`, `underlined`,
+//! `slow_blink`, `rapid_blink`, `reversed`, `hidden`, `crossed_out`).
+//! `underline_style` is `single`, `double`, `curly`, `dotted` or `dashed`
+//! and also underlines the text; `underline_color` is a color like `fg`.
+//! Styles that are not listed keep their defaults, derived from the
+//! resulting palette.
 
 use crate::style::{Color, Style};
 use std::collections::HashMap;
@@ -86,6 +118,66 @@ impl Default for ColorPalette {
 }
 
 impl ColorPalette {
+    /// Names of the palette colors, as used by [`get`](Self::get), theme
+    /// files and palette references in styles.
+    pub const NAMES: [&'static str; 8] = [
+        "background",
+        "foreground",
+        "primary",
+        "secondary",
+        "accent",
+        "error",
+        "warning",
+        "success",
+    ];
+
+    /// Gets a palette color by name, such as `"primary"`.
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.slot(name).copied()
+    }
+
+    /// Sets a palette color by name.
+    ///
+    /// Returns `false`, leaving the palette unchanged, if there is no color
+    /// with that name.
+    pub fn set(&mut self, name: &str, color: Color) -> bool {
+        match self.slot_mut(name) {
+            Some(slot) => {
+                *slot = color;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn slot(&self, name: &str) -> Option<&Color> {
+        Some(match name {
+            "background" => &self.background,
+            "foreground" => &self.foreground,
+            "primary" => &self.primary,
+            "secondary" => &self.secondary,
+            "accent" => &self.accent,
+            "error" => &self.error,
+            "warning" => &self.warning,
+            "success" => &self.success,
+            _ => return None,
+        })
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "background" => &mut self.background,
+            "foreground" => &mut self.foreground,
+            "primary" => &mut self.primary,
+            "secondary" => &mut self.secondary,
+            "accent" => &mut self.accent,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "success" => &mut self.success,
+            _ => return None,
+        })
+    }
+
     /// Creates a dark color palette.
     pub fn dark() -> Self {
         Self {
@@ -192,6 +284,18 @@ impl Theme {
         Self::new("Slime", ColorPalette::slime())
     }
 
+    /// Gets a built-in theme by name: `dark`, `light` or `slime`, in any case.
+    ///
+    /// These are the names theme files can [extend](self#theme-files).
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "slime" => Some(Self::slime()),
+            _ => None,
+        }
+    }
+
     /// Gets a style by name, falling back to default if not found.
     ///
     /// # Examples
@@ -254,9 +358,16 @@ impl Theme {
 
     /// Loads a theme from a TOML string.
     ///
+    /// The theme may extend a built-in theme and refer to palette colors
+    /// from its styles; see [Theme Files](self#theme-files). Without
+    /// `extends`, every palette color must be given. Styles that are not
+    /// listed get their defaults for the resulting palette.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the TOML is invalid or cannot be deserialized.
+    /// Returns an error if the TOML is invalid or cannot be deserialized,
+    /// names an unknown theme, palette color or modifier, or leaves out a
+    /// palette color without extending a theme.
     ///
     /// # Examples
     ///
@@ -278,18 +389,25 @@ impl Theme {
     /// "#;
     ///
     /// let theme = Theme::from_toml(toml_str).unwrap();
+    ///
+    /// // Only the differences from a built-in theme
+    /// let variant = Theme::from_toml(r##"
+    ///     name = "Dark Orange"
+    ///     extends = "dark"
+    ///
+    ///     [colors]
+    ///     primary = "#ff8800"
+    ///
+    ///     [styles.title]
+    ///     fg = "primary"
+    ///     modifiers = ["bold"]
+    /// "##).unwrap();
     /// ```
     #[cfg(feature = "serde")]
     pub fn from_toml(toml_str: &str) -> Result<Self, String> {
-        let mut theme: Self = toml::from_str(toml_str)
-            .map_err(|e| format!("Failed to parse TOML: {}", e))?;
-
-        // Generate default styles if none were provided
-        if theme.styles.is_empty() {
-            theme.styles = Self::default_styles(&theme.colors);
-        }
-
-        Ok(theme)
+        let file: file::ThemeFile =
+            toml::from_str(toml_str).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+        file.resolve()
     }
 
     /// Loads a theme from a TOML file.
@@ -392,6 +510,202 @@ impl Theme {
     }
 }
 
+/// Theme files, before `extends` and palette references are resolved.
+#[cfg(feature = "serde")]
+mod file {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::{ColorPalette, Theme};
+    use crate::style::{Color, Modifier, Style, UnderlineStyle};
+
+    /// Modifier names used in theme files.
+    const MODIFIERS: &[(&str, Modifier)] = &[
+        ("bold", Modifier::BOLD),
+        ("dim", Modifier::DIM),
+        ("italic", Modifier::ITALIC),
+        ("underlined", Modifier::UNDERLINED),
+        ("slow_blink", Modifier::SLOW_BLINK),
+        ("rapid_blink", Modifier::RAPID_BLINK),
+        ("reversed", Modifier::REVERSED),
+        ("hidden", Modifier::HIDDEN),
+        ("crossed_out", Modifier::CROSSED_OUT),
+    ];
+
+    /// Named colors, matched without regard to case.
+    const COLORS: &[Color] = &[
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::LightWhite,
+        Color::Reset,
+    ];
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub(super) struct ThemeFile {
+        name: Option<String>,
+        extends: Option<String>,
+        #[serde(default)]
+        colors: BTreeMap<String, ColorValue>,
+        #[serde(default)]
+        styles: BTreeMap<String, StyleValue>,
+    }
+
+    /// A color name, palette name or hex value, or a serialized [`Color`].
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ColorValue {
+        Name(String),
+        Color(Color),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct StyleValue {
+        fg: Option<ColorValue>,
+        bg: Option<ColorValue>,
+        modifiers: Option<ModifierValue>,
+        underline_style: Option<String>,
+        underline_color: Option<ColorValue>,
+    }
+
+    /// Modifier names, or the bits written by [`Theme::to_toml`].
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ModifierValue {
+        Names(Vec<String>),
+        Bits(Modifier),
+    }
+
+    impl ThemeFile {
+        pub(super) fn resolve(self) -> Result<Theme, String> {
+            let base = match &self.extends {
+                Some(name) => Some(Theme::builtin(name).ok_or_else(|| {
+                    format!("Unknown theme `{}` (expected dark, light or slime)", name)
+                })?),
+                None => None,
+            };
+
+            let mut colors = base.as_ref().map_or_else(ColorPalette::dark, |b| b.colors.clone());
+            for (name, value) in &self.colors {
+                let color = value.resolve(None)?;
+                if !colors.set(name, color) {
+                    return Err(format!("Unknown palette color `{}`", name));
+                }
+            }
+            if base.is_none() {
+                let missing = ColorPalette::NAMES
+                    .iter()
+                    .find(|name| !self.colors.contains_key(**name));
+                if let Some(name) = missing {
+                    return Err(format!(
+                        "Missing palette color `{}` (set it, or extend a built-in theme)",
+                        name
+                    ));
+                }
+            }
+
+            let name = self
+                .name
+                .or_else(|| base.map(|b| b.name))
+                .ok_or_else(|| "Missing theme `name`".to_string())?;
+
+            // Styles that are not overridden follow the new palette
+            let mut styles = Theme::default_styles(&colors);
+            for (key, value) in self.styles {
+                let style = value
+                    .resolve(&colors)
+                    .map_err(|e| format!("Invalid style `{}`: {}", key, e))?;
+                styles.insert(key, style);
+            }
+
+            Ok(Theme {
+                name,
+                colors,
+                styles,
+            })
+        }
+    }
+
+    impl ColorValue {
+        /// Resolve the color, looking names up in `palette` first if given.
+        fn resolve(&self, palette: Option<&ColorPalette>) -> Result<Color, String> {
+            let name = match self {
+                Self::Color(color) => return Ok(*color),
+                Self::Name(name) => name.as_str(),
+            };
+            if let Some(color) = palette.and_then(|p| p.get(name)) {
+                return Ok(color);
+            }
+            if let Some(hex) = name.strip_prefix('#') {
+                return parse_hex(hex).ok_or_else(|| format!("Invalid hex color `{}`", name));
+            }
+            COLORS
+                .iter()
+                .find(|color| color.to_string().eq_ignore_ascii_case(name))
+                .copied()
+                .ok_or_else(|| format!("Unknown color `{}`", name))
+        }
+    }
+
+    impl StyleValue {
+        fn resolve(&self, palette: &ColorPalette) -> Result<Style, String> {
+            let mut style = Style::new();
+            if let Some(fg) = &self.fg {
+                style = style.fg(fg.resolve(Some(palette))?);
+            }
+            if let Some(bg) = &self.bg {
+                style = style.bg(bg.resolve(Some(palette))?);
+            }
+            match &self.modifiers {
+                Some(ModifierValue::Bits(bits)) => style = style.add_modifier(*bits),
+                Some(ModifierValue::Names(names)) => {
+                    for name in names {
+                        let modifier = MODIFIERS
+                            .iter()
+                            .find(|(n, _)| n == name)
+                            .map(|(_, m)| *m)
+                            .ok_or_else(|| format!("Unknown modifier `{}`", name))?;
+                        style = style.add_modifier(modifier);
+                    }
+                }
+                None => {}
+            }
+            if let Some(name) = &self.underline_style {
+                let underline = UnderlineStyle::from_name(name)
+                    .ok_or_else(|| format!("Unknown underline style `{}`", name))?;
+                style = style.underline_style(underline);
+            }
+            if let Some(color) = &self.underline_color {
+                style = style.underline_color(color.resolve(Some(palette))?);
+            }
+            Ok(style)
+        }
+    }
+
+    fn parse_hex(hex: &str) -> Option<Color> {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -559,4 +873,108 @@ mod tests {
         assert_eq!(theme.colors.foreground, Color::White);
         assert_eq!(theme.colors.primary, Color::Blue);
     }
+
+    #[test]
+    fn test_palette_get_set() {
+        let mut palette = ColorPalette::dark();
+        assert_eq!(palette.get("primary"), Some(Color::Blue));
+        assert_eq!(palette.get("title"), None);
+
+        assert!(palette.set("primary", Color::Red));
+        assert_eq!(palette.primary, Color::Red);
+        assert!(!palette.set("title", Color::Red));
+        assert!(ColorPalette::NAMES.iter().all(|name| palette.get(name).is_some()));
+    }
+
+    #[test]
+    fn test_theme_builtin() {
+        assert_eq!(Theme::builtin("Slime").unwrap().name, "Slime");
+        assert_eq!(Theme::builtin("light").unwrap().colors, ColorPalette::light());
+        assert!(Theme::builtin("solarized").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_theme_extends() {
+        let theme = Theme::from_toml(
+            r##"
+            name = "Ocean"
+            extends = "dark"
+
+            [colors]
+            primary = "#3a86ff"
+            accent = "lightcyan"
+
+            [styles.title]
+            fg = "accent"
+            bg = { Indexed = 236 }
+            modifiers = ["bold", "italic"]
+
+            [styles.error]
+            underline_style = "curly"
+            underline_color = "error"
+        "##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "Ocean");
+        assert_eq!(theme.colors.primary, Color::Rgb(0x3a, 0x86, 0xff));
+        assert_eq!(theme.colors.accent, Color::LightCyan);
+        assert_eq!(theme.colors.background, Color::Black);
+        assert_eq!(
+            theme.get_style("title"),
+            Style::new()
+                .fg(Color::LightCyan)
+                .bg(Color::Indexed(236))
+                .add_modifier(crate::style::Modifier::BOLD | crate::style::Modifier::ITALIC)
+        );
+        // Inherited styles follow the overridden palette
+        assert_eq!(theme.get_style("focus").fg, Some(Color::Rgb(0x3a, 0x86, 0xff)));
+        assert_eq!(theme.get_style("border").fg, Some(Color::Cyan));
+        assert_eq!(
+            theme.get_style("error"),
+            Style::new()
+                .underline_style(crate::style::UnderlineStyle::Curly)
+                .underline_color(Color::Red)
+        );
+
+        let unnamed = Theme::from_toml("extends = \"slime\"").unwrap();
+        assert_eq!(unnamed.name, "Slime");
+        assert_eq!(unnamed.styles, Theme::slime().styles);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_theme_file_errors() {
+        let err = |toml: &str| Theme::from_toml(toml).unwrap_err();
+
+        assert!(err("extends = \"solarized\"").contains("Unknown theme `solarized`"));
+        assert!(err("name = \"X\"\n[colors]\nprimary = \"Red\"")
+            .contains("Missing palette color `background`"));
+        assert!(err("extends = \"dark\"\n[colors]\ntitle = \"Red\"")
+            .contains("Unknown palette color `title`"));
+        assert!(err("extends = \"dark\"\n[colors]\nprimary = \"#12345\"")
+            .contains("Invalid hex color"));
+        assert!(err("extends = \"dark\"\n[styles.title]\nfg = \"pink\"")
+            .contains("Invalid style `title`: Unknown color `pink`"));
+        assert!(err("extends = \"dark\"\n[styles.title]\nmodifiers = [\"loud\"]")
+            .contains("Unknown modifier `loud`"));
+        assert!(err("extends = \"dark\"\n[styles.title]\nunderline_style = \"wavy\"")
+            .contains("Unknown underline style `wavy`"));
+        assert!(err("extend = \"dark\"").contains("Failed to parse TOML"));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_theme_toml_roundtrip_styles() {
+        let mut original = Theme::slime();
+        let custom = Style::new().fg(Color::Rgb(1, 2, 3)).add_modifier(crate::style::Modifier::DIM);
+        original.set_style("custom", custom);
+        let underlined = Style::new()
+            .underline_style(crate::style::UnderlineStyle::Dashed)
+            .underline_color(Color::Indexed(9));
+        original.set_style("underlined", underlined);
+        let parsed = Theme::from_toml(&original.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed.styles, original.styles);
+    }
 }
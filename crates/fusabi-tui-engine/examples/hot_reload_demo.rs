@@ -92,8 +92,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             if !changes.is_empty() {
                 let elapsed = last_reload_time.elapsed();
                 println!("\n[{}] File changes detected:", format_duration(elapsed));
-                for path in &changes {
-                    println!("  - {}", path.display());
+                for change in &changes {
+                    println!("  - {} ({:?})", change.path.display(), change.kind);
                 }
 
                 // Reload the dashboard
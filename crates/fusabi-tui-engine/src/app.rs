@@ -0,0 +1,217 @@
+//! Options for the [`DashboardEngine::run`](crate::dashboard::DashboardEngine::run) app loop
+//! and terminal setup.
+
+use std::time::Duration;
+
+/// Configuration for [`DashboardEngine::run`](crate::dashboard::DashboardEngine::run).
+///
+/// # Example
+///
+/// ```
+/// use fusabi_tui_engine::app::RunOptions;
+/// use std::time::Duration;
+///
+/// let options = RunOptions::default()
+///     .frame_rate(30)
+///     .tick_rate(Duration::from_millis(250));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunOptions {
+    frame_rate: u32,
+    tick_rate: Option<Duration>,
+    mouse_capture: bool,
+}
+
+impl Default for RunOptions {
+    /// At most 60 frames per second, no ticks and no mouse capture.
+    fn default() -> Self {
+        Self {
+            frame_rate: 60,
+            tick_rate: None,
+            mouse_capture: false,
+        }
+    }
+}
+
+impl RunOptions {
+    /// Render at most `fps` frames per second. Zero removes the cap.
+    ///
+    /// Frames are only drawn when the dashboard state is dirty, so this is an
+    /// upper bound rather than a target.
+    pub fn frame_rate(mut self, fps: u32) -> Self {
+        self.frame_rate = fps;
+        self
+    }
+
+    /// Send [`Event::Tick`](crate::event::Event::Tick) every `interval`.
+    ///
+    /// Each tick marks the state dirty, so this also sets how often
+    /// time-based content is redrawn. A zero interval disables ticks.
+    pub fn tick_rate(mut self, interval: Duration) -> Self {
+        self.tick_rate = Some(interval).filter(|interval| !interval.is_zero());
+        self
+    }
+
+    /// Enable mouse capture while the app is running.
+    pub fn mouse_capture(mut self, enabled: bool) -> Self {
+        self.mouse_capture = enabled;
+        self
+    }
+
+    /// Get the maximum frame rate. Zero means uncapped.
+    pub fn max_frame_rate(&self) -> u32 {
+        self.frame_rate
+    }
+
+    /// Get the tick interval, if ticks are enabled.
+    pub fn tick_interval(&self) -> Option<Duration> {
+        self.tick_rate
+    }
+
+    /// Check if mouse capture is enabled.
+    pub fn is_mouse_capture_enabled(&self) -> bool {
+        self.mouse_capture
+    }
+
+    /// Minimum time between two frames.
+    pub(crate) fn frame_interval(&self) -> Duration {
+        if self.frame_rate == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / self.frame_rate
+        }
+    }
+}
+
+#[cfg(feature = "crossterm-backend")]
+pub use self::terminal_guard::TerminalGuard;
+
+#[cfg(feature = "crossterm-backend")]
+mod terminal_guard {
+    use std::io::{stdout, Write};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Once;
+
+    use crossterm::{cursor, event, terminal, ExecutableCommand};
+
+    use crate::error::EngineResult;
+
+    /// Whether a guard currently has the terminal set up.
+    static ACTIVE: AtomicBool = AtomicBool::new(false);
+    /// Whether the active guard enabled mouse capture.
+    static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(false);
+    static PANIC_HOOK: Once = Once::new();
+
+    /// Puts the terminal into raw mode on the alternate screen and restores it
+    /// when dropped.
+    ///
+    /// The terminal is also restored before a panic message is printed, so the
+    /// message is not lost on the alternate screen. Panics caught while
+    /// evaluating or rendering a script are not printed and keep the terminal
+    /// as it is.
+    #[derive(Debug)]
+    pub struct TerminalGuard {
+        _private: (),
+    }
+
+    impl TerminalGuard {
+        /// Enable raw mode, enter the alternate screen and hide the cursor.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the terminal cannot be set up. Anything already
+        /// changed is restored first.
+        pub fn enter(mouse_capture: bool) -> EngineResult<Self> {
+            PANIC_HOOK.call_once(|| {
+                let previous = std::panic::take_hook();
+                std::panic::set_hook(Box::new(move |info| {
+                    // Panics caught in scripts leave the terminal as it is
+                    if !crate::fusabi_runtime::is_catching_panic() {
+                        restore();
+                    }
+                    previous(info);
+                }));
+            });
+
+            ACTIVE.store(true, Ordering::SeqCst);
+            MOUSE_CAPTURE.store(mouse_capture, Ordering::SeqCst);
+            if let Err(err) = setup(mouse_capture) {
+                restore();
+                return Err(err);
+            }
+            Ok(Self { _private: () })
+        }
+
+        /// Restore the terminal now rather than when the guard is dropped.
+        pub fn restore(self) {}
+    }
+
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            restore();
+        }
+    }
+
+    fn setup(mouse_capture: bool) -> EngineResult<()> {
+        terminal::enable_raw_mode()?;
+        let mut out = stdout();
+        out.execute(terminal::EnterAlternateScreen)?;
+        out.execute(cursor::Hide)?;
+        if mouse_capture {
+            out.execute(event::EnableMouseCapture)?;
+        }
+        Ok(())
+    }
+
+    /// Undo [`setup`]. Does nothing if the terminal is not set up, so it is
+    /// safe to call from both the panic hook and `Drop`.
+    fn restore() {
+        if !ACTIVE.swap(false, Ordering::SeqCst) {
+            return;
+        }
+
+        // Keep going on errors so as much as possible is restored
+        let mut out = stdout();
+        if MOUSE_CAPTURE.swap(false, Ordering::SeqCst) {
+            let _ = out.execute(event::DisableMouseCapture);
+        }
+        let _ = out.execute(terminal::EndSynchronizedUpdate);
+        let _ = out.execute(cursor::Show);
+        let _ = out.execute(terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        let _ = out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_options_default() {
+        let options = RunOptions::default();
+        assert_eq!(options.max_frame_rate(), 60);
+        assert_eq!(options.tick_interval(), None);
+        assert!(!options.is_mouse_capture_enabled());
+    }
+
+    #[test]
+    fn test_zero_tick_rate() {
+        let options = RunOptions::default().tick_rate(Duration::from_millis(250));
+        assert_eq!(options.tick_interval(), Some(Duration::from_millis(250)));
+        let options = options.tick_rate(Duration::ZERO);
+        assert_eq!(options.tick_interval(), None);
+    }
+
+    #[test]
+    fn test_frame_interval() {
+        assert_eq!(
+            RunOptions::default().frame_rate(50).frame_interval(),
+            Duration::from_millis(20)
+        );
+        assert_eq!(
+            RunOptions::default().frame_rate(0).frame_interval(),
+            Duration::ZERO
+        );
+    }
+}
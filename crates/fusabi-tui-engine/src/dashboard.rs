@@ -1,21 +1,116 @@
 //! Dashboard engine for managing hot-reloadable TUI applications.
 
+use crate::app::RunOptions;
 use crate::error::{EngineError, EngineResult};
-use crate::event::{Action, Event};
+use crate::event::{Action, Event, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use crate::focus::FocusRing;
+use crate::fusabi_runtime::FusabiContext;
+use crate::hit::{HitMap, HitPart};
+use crate::input::EventReader;
+use crate::keymap::{KeyResult, Keymap};
 use crate::loader::FileLoader;
-use crate::overlay::ErrorOverlay;
-use crate::state::DashboardState;
-use crate::watcher::FileWatcher;
+use crate::overlay::{ErrorMessage, ErrorOverlay, HelpOverlay};
+use crate::recording::{frame_rows, FrameMismatch, Recorder, Recording};
+#[cfg(feature = "serde")]
+use crate::snapshot::StateSnapshot;
+use crate::state::{DashboardState, WidgetState};
+use crate::watcher::{FileChange, FileWatcher};
 use fusabi_tui_core::buffer::Buffer;
 use fusabi_tui_core::layout::Rect;
 use fusabi_tui_core::style::{Color, Modifier, Style};
+use fusabi_tui_core::theme::Theme;
 use fusabi_tui_render::renderer::Renderer;
+use fusabi_tui_render::test::TestRenderer;
 use fusabi_tui_widgets::block::Block;
 use fusabi_tui_widgets::borders::{BorderType, Borders};
 use fusabi_tui_widgets::paragraph::Paragraph;
 // Text types for paragraphs
 use fusabi_tui_widgets::widget::Widget;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_stream::{Stream, StreamExt};
+
+/// How often [`DashboardEngine::run_with`] checks the file watcher and
+/// [`DashboardHandle`] updates.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How many custom actions one action may lead to through the action handler
+/// before [`DashboardEngine::perform`] gives up.
+const MAX_CUSTOM_ACTIONS: usize = 64;
+
+/// Handler for [`Action::Custom`] actions, see [`DashboardEngine::set_action_handler`].
+type ActionHandler = Box<dyn FnMut(&str, &mut DashboardState) -> Action + Send>;
+
+/// Handler for keys routed to the focused widget, see
+/// [`DashboardEngine::set_widget_key_handler`].
+type WidgetKeyHandler = Box<dyn FnMut(&str, &mut WidgetState, &KeyEvent) -> Action + Send>;
+
+/// A request sent through a [`DashboardHandle`].
+enum Update {
+    State(Box<dyn FnOnce(&mut DashboardState) + Send>),
+    Action(Action),
+}
+
+/// Sends state updates and actions to a running [`DashboardEngine`].
+///
+/// Get one with [`DashboardEngine::handle`]. Handles are cheap to clone and
+/// can be moved into background tasks and threads. Updates are applied by the
+/// async app loop in [`DashboardEngine::run_async`], which wakes up for them
+/// immediately, and by [`DashboardEngine::run`], which checks for them every
+/// 50 ms.
+#[derive(Clone)]
+pub struct DashboardHandle {
+    tx: UnboundedSender<Update>,
+}
+
+impl DashboardHandle {
+    /// Apply `update` to the dashboard state and render.
+    ///
+    /// Returns `false` if the engine has been dropped.
+    pub fn update<F>(&self, update: F) -> bool
+    where
+        F: FnOnce(&mut DashboardState) + Send + 'static,
+    {
+        self.tx.send(Update::State(Box::new(update))).is_ok()
+    }
+
+    /// Perform `action` as if an event had produced it.
+    ///
+    /// Returns `false` if the engine has been dropped.
+    pub fn send(&self, action: Action) -> bool {
+        self.tx.send(Update::Action(action)).is_ok()
+    }
+
+    /// Ask the app loop to quit.
+    ///
+    /// Returns `false` if the engine has been dropped.
+    pub fn quit(&self) -> bool {
+        self.send(Action::Quit)
+    }
+}
+
+impl std::fmt::Debug for DashboardHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DashboardHandle").finish_non_exhaustive()
+    }
+}
+
+/// The last frame the script rendered without errors.
+struct Frame {
+    buffer: Buffer,
+    focus_ring: FocusRing,
+    hit_map: HitMap,
+}
+
+/// What woke up the async app loop.
+enum Wake {
+    Event(Option<EngineResult<Event>>),
+    Changes(Vec<FileChange>),
+    Update(Update),
+    Frame,
+}
 
 /// The main dashboard engine that orchestrates hot reloading and rendering.
 ///
@@ -47,9 +142,51 @@ pub struct DashboardEngine<R: Renderer> {
     /// Error overlay for displaying errors during development.
     error_overlay: Option<ErrorOverlay>,
 
+    /// Key bindings used by [`handle_event`](Self::handle_event).
+    keymap: Keymap,
+
+    /// Whether the key binding help overlay is shown.
+    help_visible: bool,
+
+    /// Evaluated script for the entry file, if it has been loaded.
+    context: Option<FusabiContext>,
+
+    /// Shown again, with its focusable and clickable widgets, while the
+    /// script fails to render.
+    last_frame: Option<Frame>,
+
     /// Callback for widget rendering (set by Fusabi integration).
     /// This allows external code to provide the actual rendering logic.
     render_callback: Option<Box<dyn Fn(&mut Buffer, Rect, &DashboardState) + Send + Sync>>,
+
+    /// Handler for custom actions in the app loop.
+    action_handler: Option<ActionHandler>,
+
+    /// Handler for unbound keys, called with the focused widget's state.
+    widget_key_handler: Option<WidgetKeyHandler>,
+
+    /// Scrollbar whose thumb is being dragged with the mouse.
+    dragging: Option<String>,
+
+    /// Records the session between `start_recording` and `stop_recording`.
+    recorder: Option<Recorder>,
+
+    /// File the state is restored from and saved to when the app loop quits.
+    #[cfg(feature = "serde")]
+    state_file: Option<PathBuf>,
+
+    /// Theme the script reads with `tui.theme`.
+    theme: Arc<Theme>,
+
+    /// File the theme is loaded from, and loaded again when it changes.
+    #[cfg(feature = "serde")]
+    theme_file: Option<PathBuf>,
+
+    /// Sender cloned into every [`DashboardHandle`].
+    updates_tx: UnboundedSender<Update>,
+
+    /// Updates from handles, applied by the async app loop.
+    updates_rx: UnboundedReceiver<Update>,
 }
 
 impl<R: Renderer> DashboardEngine<R> {
@@ -71,6 +208,7 @@ impl<R: Renderer> DashboardEngine<R> {
     /// let engine = DashboardEngine::new(renderer, PathBuf::from("."));
     /// ```
     pub fn new(renderer: R, root_path: PathBuf) -> Self {
+        let (updates_tx, updates_rx) = unbounded_channel();
         Self {
             renderer,
             loader: FileLoader::new(),
@@ -79,7 +217,48 @@ impl<R: Renderer> DashboardEngine<R> {
             root_path,
             entry_file: None,
             error_overlay: None,
+            keymap: Keymap::with_defaults(),
+            help_visible: false,
+            context: None,
+            last_frame: None,
             render_callback: None,
+            action_handler: None,
+            widget_key_handler: None,
+            dragging: None,
+            recorder: None,
+            #[cfg(feature = "serde")]
+            state_file: None,
+            theme: Arc::default(),
+            #[cfg(feature = "serde")]
+            theme_file: None,
+            updates_tx,
+            updates_rx,
+        }
+    }
+
+    /// Get a handle for pushing state updates from background tasks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fusabi_tui_engine::dashboard::DashboardEngine;
+    /// # use fusabi_tui_engine::state::{ListState, WidgetState};
+    /// # use fusabi_tui_render::test::TestRenderer;
+    /// # use std::path::PathBuf;
+    /// # let renderer = TestRenderer::new(80, 24);
+    /// # let engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// let handle = engine.handle();
+    /// tokio::spawn(async move {
+    ///     let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+    ///     loop {
+    ///         interval.tick().await;
+    ///         handle.update(|state| state.set_focus("cpu"));
+    ///     }
+    /// });
+    /// ```
+    pub fn handle(&self) -> DashboardHandle {
+        DashboardHandle {
+            tx: self.updates_tx.clone(),
         }
     }
 
@@ -113,15 +292,124 @@ impl<R: Renderer> DashboardEngine<R> {
         self.render_callback = None;
     }
 
+    /// Set the handler for [`Action::Custom`] actions in [`run`](Self::run).
+    ///
+    /// The handler receives the action name and the dashboard state, and
+    /// returns a follow-up action, which may be another custom action. A
+    /// chain of more than 64 custom actions is stopped and shown as an error,
+    /// so a handler that maps an action back to itself cannot hang the loop.
+    /// Messages sent as [`Event::Custom`] arrive here as custom actions.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fusabi_tui_engine::dashboard::DashboardEngine;
+    /// # use fusabi_tui_engine::event::Action;
+    /// # use fusabi_tui_render::test::TestRenderer;
+    /// # use std::path::PathBuf;
+    /// # let renderer = TestRenderer::new(80, 24);
+    /// # let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// engine.set_action_handler(|name, _state| match name {
+    ///     "exit" => Action::Quit,
+    ///     "refresh" => Action::Render,
+    ///     _ => Action::None,
+    /// });
+    /// ```
+    pub fn set_action_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&str, &mut DashboardState) -> Action + Send + 'static,
+    {
+        self.action_handler = Some(Box::new(handler));
+    }
+
+    /// Clear the custom action handler.
+    pub fn clear_action_handler(&mut self) {
+        self.action_handler = None;
+    }
+
+    /// Set the handler for key presses that have no key binding.
+    ///
+    /// The handler is only called while a widget is focused and has a
+    /// [`WidgetState`], and receives the widget id, its state and the key.
+    /// Other widgets never see the key. The returned action is performed like
+    /// one from a key binding.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fusabi_tui_engine::dashboard::DashboardEngine;
+    /// # use fusabi_tui_engine::event::{Action, KeyCode};
+    /// # use fusabi_tui_engine::state::WidgetState;
+    /// # use fusabi_tui_render::test::TestRenderer;
+    /// # use std::path::PathBuf;
+    /// # let renderer = TestRenderer::new(80, 24);
+    /// # let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// engine.set_widget_key_handler(|_id, widget, key| match (widget, key.code) {
+    ///     (WidgetState::List(list), KeyCode::Down) => {
+    ///         list.select_next(10);
+    ///         Action::Render
+    ///     }
+    ///     (WidgetState::List(list), KeyCode::Up) => {
+    ///         list.select_previous(10);
+    ///         Action::Render
+    ///     }
+    ///     _ => Action::None,
+    /// });
+    /// ```
+    pub fn set_widget_key_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&str, &mut WidgetState, &KeyEvent) -> Action + Send + 'static,
+    {
+        self.widget_key_handler = Some(Box::new(handler));
+    }
+
+    /// Clear the widget key handler.
+    pub fn clear_widget_key_handler(&mut self) {
+        self.widget_key_handler = None;
+    }
+
     /// Set an error to be displayed as an overlay.
     ///
     /// This is useful for displaying compilation or runtime errors to the user
-    /// without crashing the application.
+    /// without crashing the application. Errors in loaded scripts are shown
+    /// with the offending lines.
+    ///
+    /// When the script has rendered successfully before, the overlay is
+    /// docked along the bottom of the screen and the dashboard stays
+    /// interactive behind it.
     pub fn show_error(&mut self, error: &EngineError) {
-        self.error_overlay = Some(ErrorOverlay::from_engine_error(error));
+        let mut overlay = ErrorOverlay::new(self.error_message(error));
+        overlay.set_docked(self.last_frame.is_some());
+        self.error_overlay = Some(overlay);
         self.state.mark_dirty();
     }
 
+    /// Add an error to the overlay, or show it if no overlay is visible.
+    ///
+    /// An error that is already listed is not added again, so an error
+    /// raised on every frame is listed once.
+    pub fn push_error(&mut self, error: &EngineError) {
+        let message = self.error_message(error);
+        match &mut self.error_overlay {
+            Some(overlay) if overlay.is_visible() => {
+                if overlay.push(message) {
+                    self.state.mark_dirty();
+                }
+            }
+            _ => {
+                let mut overlay = ErrorOverlay::new(message);
+                overlay.set_docked(self.last_frame.is_some());
+                self.error_overlay = Some(overlay);
+                self.state.mark_dirty();
+            }
+        }
+    }
+
+    /// Describe an error, with the text of the scripts it points into.
+    fn error_message(&self, error: &EngineError) -> ErrorMessage {
+        ErrorMessage::from_engine_error(error).with_sources_from(&self.loader)
+    }
+
     /// Dismiss the current error overlay.
     pub fn dismiss_error(&mut self) {
         if self.error_overlay.is_some() {
@@ -143,10 +431,252 @@ impl<R: Renderer> DashboardEngine<R> {
         self.error_overlay.as_ref()
     }
 
+    /// Get the key bindings.
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Get a mutable reference to the key bindings, to rebind or disable keys.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fusabi_tui_engine::dashboard::DashboardEngine;
+    /// # use fusabi_tui_engine::event::Action;
+    /// # use fusabi_tui_render::test::TestRenderer;
+    /// # use std::path::PathBuf;
+    /// # let renderer = TestRenderer::new(80, 24);
+    /// # let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// // Quit with `q` instead of Ctrl+C
+    /// engine.keymap_mut().bind("q", Action::Quit).unwrap();
+    /// engine.keymap_mut().bind("ctrl+c", Action::None).unwrap();
+    /// ```
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    /// Replace the key bindings.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+        self.state.mark_dirty();
+    }
+
+    /// Load the key bindings from a TOML file.
+    ///
+    /// See [`Keymap::from_toml`] for the file format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed. The current
+    /// bindings are kept in that case.
+    #[cfg(feature = "serde")]
+    pub fn load_keymap(&mut self, path: &Path) -> EngineResult<()> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root_path.join(path)
+        };
+        self.set_keymap(Keymap::from_toml_file(path)?);
+        Ok(())
+    }
+
+    /// Keep the dashboard state in a file across restarts.
+    ///
+    /// The [snapshot](DashboardState::snapshot) in `path` is restored now if
+    /// the file exists, and the app loop saves a new one when it quits. Call
+    /// this after inserting the widget states, since snapshots of widgets
+    /// that do not exist are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed. The
+    /// file is still used for saving, which replaces the broken one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fusabi_tui_engine::dashboard::DashboardEngine;
+    /// # use fusabi_tui_engine::state::{ListState, WidgetState};
+    /// # use fusabi_tui_render::test::TestRenderer;
+    /// # use std::path::{Path, PathBuf};
+    /// # let renderer = TestRenderer::new(80, 24);
+    /// # let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// engine
+    ///     .state_mut()
+    ///     .insert_widget("files", WidgetState::List(ListState::new()));
+    /// engine.set_state_file(Path::new(".dashboard-state.toml")).unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn set_state_file(&mut self, path: &Path) -> EngineResult<()> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root_path.join(path)
+        };
+        let snapshot = StateSnapshot::load(&path);
+        self.state_file = Some(path);
+        if let Some(snapshot) = snapshot? {
+            self.state.restore(&snapshot);
+        }
+        Ok(())
+    }
+
+    /// Get the file the state is kept in, if any.
+    #[cfg(feature = "serde")]
+    pub fn state_file(&self) -> Option<&Path> {
+        self.state_file.as_deref()
+    }
+
+    /// Save a snapshot of the state to the [state file](Self::set_state_file),
+    /// if one is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    #[cfg(feature = "serde")]
+    pub fn save_state(&self) -> EngineResult<()> {
+        if let Some(path) = &self.state_file {
+            self.state.snapshot().save(path)?;
+        }
+        Ok(())
+    }
+
+    /// Get the theme the script reads with `tui.theme`.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Set the theme the script reads with `tui.theme`.
+    ///
+    /// The dashboard is rendered again with the new theme, without
+    /// evaluating the script again.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = Arc::new(theme);
+        if let Some(context) = &mut self.context {
+            context.set_theme(Arc::clone(&self.theme));
+        }
+        self.state.mark_dirty();
+    }
+
+    /// Load the theme from a TOML file, and load it again whenever the file
+    /// changes while hot reload is enabled.
+    ///
+    /// See [`Theme::from_toml`] for the file format. Themes can extend a
+    /// built-in theme and override only some colors and styles.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or cannot be
+    /// watched. The current theme is kept in that case, and the file is
+    /// still watched so that a fixed version is picked up.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fusabi_tui_engine::dashboard::DashboardEngine;
+    /// # use fusabi_tui_render::test::TestRenderer;
+    /// # use std::path::{Path, PathBuf};
+    /// # let renderer = TestRenderer::new(80, 24);
+    /// # let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// engine.enable_hot_reload().unwrap();
+    /// engine.load_theme(Path::new("theme.toml")).unwrap();
+    /// engine.load(Path::new("dashboard.fsx")).unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load_theme(&mut self, path: &Path) -> EngineResult<()> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root_path.join(path)
+        };
+        // Changes are reported with canonical paths
+        let path = path.canonicalize().unwrap_or(path);
+        self.theme_file = Some(path.clone());
+        if let Some(watcher) = &mut self.watcher {
+            watcher.watch(&path)?;
+        }
+        self.reload_theme()
+    }
+
+    /// Get the file the theme is loaded from, if any.
+    #[cfg(feature = "serde")]
+    pub fn theme_file(&self) -> Option<&Path> {
+        self.theme_file.as_deref()
+    }
+
+    /// Load the [theme file](Self::load_theme) again, if one is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::Theme`] if the file cannot be read or parsed.
+    /// The current theme is kept in that case.
+    #[cfg(feature = "serde")]
+    pub fn reload_theme(&mut self) -> EngineResult<()> {
+        let Some(path) = self.theme_file.clone() else {
+            return Ok(());
+        };
+        let theme = Theme::from_toml_file(&path).map_err(|reason| EngineError::Theme {
+            path: path.clone(),
+            reason,
+        })?;
+        self.set_theme(theme);
+
+        // A theme that loads resolves its own earlier errors, not the others
+        self.resolve_theme_errors(&path);
+        Ok(())
+    }
+
+    /// Remove the errors of the theme file at `path` from the error overlay,
+    /// and dismiss it if no other error is left.
+    #[cfg(feature = "serde")]
+    fn resolve_theme_errors(&mut self, path: &Path) {
+        let Some(overlay) = &self.error_overlay else {
+            return;
+        };
+        let source = path.display().to_string();
+        let is_resolved = |error: &ErrorMessage| {
+            error.title == "Theme Error" && error.source.as_deref() == Some(source.as_str())
+        };
+        if !overlay.errors().iter().any(is_resolved) {
+            return;
+        }
+
+        let remaining: Vec<ErrorMessage> = overlay
+            .errors()
+            .iter()
+            .filter(|error| !is_resolved(error))
+            .cloned()
+            .collect();
+        if remaining.is_empty() {
+            self.dismiss_error();
+        } else {
+            let mut resolved = ErrorOverlay::from_errors(remaining);
+            resolved.set_docked(overlay.is_docked());
+            if !overlay.is_visible() {
+                resolved.dismiss();
+            }
+            self.error_overlay = Some(resolved);
+            self.state.mark_dirty();
+        }
+    }
+
+    /// Show or hide the key binding help overlay.
+    pub fn toggle_help(&mut self) {
+        self.help_visible = !self.help_visible;
+        self.state.mark_dirty();
+    }
+
+    /// Check if the key binding help overlay is shown.
+    pub fn is_help_visible(&self) -> bool {
+        self.help_visible
+    }
+
     /// Load a dashboard file.
     ///
-    /// This loads the specified file and all its dependencies. If hot reload
-    /// is enabled, it will also start watching the file for changes.
+    /// This loads the specified file and all its dependencies, then evaluates
+    /// it as a Fusabi script. If hot reload is enabled, it will also start
+    /// watching the file for changes, even when evaluation fails, so that a
+    /// fixed script is picked up on the next save.
     ///
     /// # Arguments
     ///
@@ -154,7 +684,8 @@ impl<R: Renderer> DashboardEngine<R> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be loaded or parsed.
+    /// Returns an error if the file cannot be loaded, or
+    /// [`EngineError::Script`] if the script fails to parse or evaluate.
     ///
     /// # Example
     ///
@@ -173,26 +704,27 @@ impl<R: Renderer> DashboardEngine<R> {
             self.root_path.join(entry)
         };
 
-        // Load the file
+        // Load the file and everything it loads
         let loaded_file = self.loader.load(&path)?;
+        let source = loaded_file.content.clone();
+        let entry_path = loaded_file.path.clone();
 
         // Store the entry file path
-        self.entry_file = Some(loaded_file.path.clone());
-
-        // If watcher is enabled, watch this file
-        if let Some(watcher) = &mut self.watcher {
-            watcher.watch(&loaded_file.path)?;
+        self.entry_file = Some(entry_path.clone());
+        let mut context = FusabiContext::new(entry_path.clone());
+        context.set_theme(Arc::clone(&self.theme));
+        self.last_frame = None;
 
-            // Also watch dependencies
-            for dep in &loaded_file.dependencies {
-                watcher.watch(dep)?;
-            }
-        }
+        // If watcher is enabled, watch this file and its dependencies
+        self.watch_modules(&entry_path)?;
 
         // Mark state as dirty to trigger a render
         self.state.mark_dirty();
 
-        Ok(())
+        // Evaluate the script
+        let result = context.evaluate_with(&source, &self.loader);
+        self.context = Some(context);
+        result
     }
 
     /// Reload the current dashboard.
@@ -200,6 +732,10 @@ impl<R: Renderer> DashboardEngine<R> {
     /// This invalidates the cache for the entry file and all its dependents,
     /// then reloads everything.
     ///
+    /// The [`DashboardState`] is kept as it is: selections, scroll offsets,
+    /// the focused widget, the input mode and the custom values set by the
+    /// script all survive the reload, whether or not it succeeds.
+    ///
     /// # Errors
     ///
     /// Returns an error if the reload fails.
@@ -208,23 +744,74 @@ impl<R: Renderer> DashboardEngine<R> {
             .entry_file
             .clone()
             .ok_or_else(|| EngineError::InvalidState("No entry file loaded".to_string()))?;
+        self.reload_changed(&entry_path)
+    }
 
-        // Invalidate the entry file and all dependents
-        let _invalidated = self.loader.invalidate(&entry_path);
+    /// Reload the dashboard after `changed` was modified.
+    ///
+    /// Only `changed` and the files that load it, directly or indirectly,
+    /// are read again and recompiled. Other modules keep their compiled
+    /// programs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reload fails, including
+    /// [`LoadError::CircularDependency`](crate::error::LoadError::CircularDependency)
+    /// if the files now load each other in a cycle.
+    pub fn reload_changed(&mut self, changed: &Path) -> EngineResult<()> {
+        self.reload_modules(&[changed])
+    }
 
-        // Reload the entry file
-        let loaded_file = self.loader.load(&entry_path)?;
+    /// Reload the dashboard after the files in `changed` were modified.
+    fn reload_modules(&mut self, changed: &[&Path]) -> EngineResult<()> {
+        let entry_path = self
+            .entry_file
+            .clone()
+            .ok_or_else(|| EngineError::InvalidState("No entry file loaded".to_string()))?;
 
-        // Update watches for new dependencies
-        if let Some(watcher) = &mut self.watcher {
-            for dep in &loaded_file.dependencies {
-                watcher.watch(dep)?;
+        // Invalidate the changed files and all dependents
+        let mut invalidated = Vec::new();
+        for path in changed {
+            for module in self.loader.invalidate(path) {
+                if !invalidated.contains(&module) {
+                    invalidated.push(module);
+                }
             }
         }
 
+        // Reload the entry file; unchanged dependencies come from the cache
+        let loaded_file = self.loader.load(&entry_path)?;
+        let source = loaded_file.content.clone();
+
+        // Update watches for new dependencies
+        self.watch_modules(&entry_path)?;
+
         // Mark state as dirty
         self.state.mark_dirty();
 
+        // Re-evaluate the script
+        let theme = &self.theme;
+        let context = self.context.get_or_insert_with(|| {
+            let mut context = FusabiContext::new(entry_path.clone());
+            context.set_theme(Arc::clone(theme));
+            context
+        });
+        context.invalidate(&invalidated);
+        context.evaluate_with(&source, &self.loader)?;
+
+        // A successful reload resolves the error that was on screen
+        self.dismiss_error();
+
+        Ok(())
+    }
+
+    /// Watch a file and every file it loads, if hot reload is enabled.
+    fn watch_modules(&mut self, entry: &Path) -> EngineResult<()> {
+        if let Some(watcher) = &mut self.watcher {
+            for module in self.loader.modules(entry) {
+                watcher.watch(&module)?;
+            }
+        }
         Ok(())
     }
 
@@ -234,9 +821,21 @@ impl<R: Renderer> DashboardEngine<R> {
     /// to the renderer backend.
     ///
     /// The render method follows this order:
+    ///
     /// 1. If a render callback is set, use it for widget rendering
-    /// 2. Otherwise, render a default placeholder
-    /// 3. If an error overlay is active, render it on top
+    /// 2. Otherwise, call the script's `render` function if it defines one
+    /// 3. Otherwise, render a default placeholder
+    /// 4. If an error overlay is active, render it on top
+    /// 5. If the help overlay is shown, render it on top
+    ///
+    /// The [focus ring](DashboardState::focus_ring) and
+    /// [hit map](DashboardState::hit_map) are cleared first, so that they
+    /// only hold the widgets that register during this frame.
+    ///
+    /// Errors raised by the script's `render` function are shown in the error
+    /// overlay rather than returned. Panics in the script are caught the same
+    /// way. Until the script renders again, the last frame it rendered
+    /// successfully is shown in its place.
     ///
     /// # Errors
     ///
@@ -248,10 +847,41 @@ impl<R: Renderer> DashboardEngine<R> {
         // Create a buffer for the current frame
         let mut buffer = Buffer::new(size);
 
+        // Focusable and clickable widgets register again as they render
+        self.state.focus_ring.clear();
+        self.state.hit_map.clear();
+
         // Render content based on available render callback
         if let Some(callback) = &self.render_callback {
             // Use the custom render callback (typically from Fusabi integration)
             callback(&mut buffer, size, &self.state);
+        } else if let Some(context) = self.context.as_mut().filter(|c| c.has_render()) {
+            // Call the evaluated script's render function
+            let result = context.render(&mut buffer, size, &self.state);
+            if let Some(custom) = context.take_custom_state() {
+                self.state.custom = custom;
+            }
+            for (id, widget_state) in context.take_widget_states() {
+                self.state.widgets.entry(id).or_insert(widget_state);
+            }
+            match result {
+                Ok(()) => {
+                    self.last_frame = Some(Frame {
+                        buffer: buffer.clone(),
+                        focus_ring: self.state.focus_ring.clone(),
+                        hit_map: self.state.hit_map.clone(),
+                    });
+                }
+                Err(err) => {
+                    let last_frame = self.last_frame.as_ref().filter(|f| f.buffer.area == size);
+                    if let Some(frame) = last_frame {
+                        buffer = frame.buffer.clone();
+                        self.state.focus_ring = frame.focus_ring.clone();
+                        self.state.hit_map = frame.hit_map.clone();
+                    }
+                    self.push_error(&err);
+                }
+            }
         } else if self.entry_file.is_some() {
             // Render a loading/ready placeholder when a file is loaded
             // but no render callback is set yet
@@ -265,14 +895,23 @@ impl<R: Renderer> DashboardEngine<R> {
         if let Some(overlay) = &mut self.error_overlay {
             overlay.update();
             if overlay.is_visible() {
+                overlay.set_keys(&self.keymap, &self.state);
                 overlay.render(size, &mut buffer);
             }
         }
 
+        if self.help_visible {
+            HelpOverlay::from_keymap(&self.keymap, &self.state).render(size, &mut buffer);
+        }
+
         // Draw the buffer to the renderer
         self.renderer.draw(&buffer)?;
         self.renderer.flush()?;
 
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_frame(&buffer);
+        }
+
         // Clear dirty flag
         self.state.clear_dirty();
 
@@ -303,7 +942,7 @@ impl<R: Renderer> DashboardEngine<R> {
             "Loaded: {}\n\n\
              Hot reload: {}\n\
              Widgets: {}\n\n\
-             Waiting for Fusabi render callback...\n\n\
+             Waiting for the script to define `render`...\n\n\
              Press Ctrl+R to reload, Ctrl+C to quit",
             entry_file,
             if self.watcher.is_some() { "enabled" } else { "disabled" },
@@ -375,18 +1014,13 @@ impl<R: Renderer> DashboardEngine<R> {
         let watcher = FileWatcher::new(debounce_ms)?;
         self.watcher = Some(watcher);
 
-        // If we have an entry file, start watching it
-        if let Some(entry_path) = &self.entry_file {
-            if let Some(watcher) = &mut self.watcher {
-                watcher.watch(entry_path)?;
-
-                // Watch dependencies
-                if let Some(loaded) = self.loader.get(entry_path) {
-                    for dep in &loaded.dependencies {
-                        watcher.watch(dep)?;
-                    }
-                }
-            }
+        // If we have an entry file, start watching it and its modules
+        if let Some(entry_path) = self.entry_file.clone() {
+            self.watch_modules(&entry_path)?;
+        }
+        #[cfg(feature = "serde")]
+        if let (Some(watcher), Some(theme_file)) = (&mut self.watcher, &self.theme_file) {
+            watcher.watch(theme_file)?;
         }
 
         Ok(())
@@ -400,14 +1034,33 @@ impl<R: Renderer> DashboardEngine<R> {
     /// Poll for file changes and return the list of changed files.
     ///
     /// If hot reload is not enabled, this returns `None`.
-    pub fn poll_changes(&mut self) -> Option<Vec<PathBuf>> {
+    pub fn poll_changes(&mut self) -> Option<Vec<FileChange>> {
         self.watcher.as_mut().map(|w| w.poll())
     }
 
-    /// Handle an input event and return the resulting action.
-    ///
-    /// This is where application-specific event handling logic would go.
-    /// The default implementation handles basic events like Ctrl+C to quit.
+    /// Get mutable access to the file watcher, if hot reload is enabled.
+    ///
+    /// Use it to watch more files or directories, such as data files the
+    /// script reads, or to set a [`WatchFilter`](crate::watcher::WatchFilter).
+    /// Every change reported by the watcher reloads the dashboard.
+    pub fn watcher_mut(&mut self) -> Option<&mut FileWatcher> {
+        self.watcher.as_mut()
+    }
+
+    /// Handle an input event and return the resulting action.
+    ///
+    /// Key presses are looked up in the [keymap](Self::keymap_mut). Reloading,
+    /// dismissing the error overlay, toggling help and moving focus are done
+    /// here and reported as [`Action::Render`]; other bound actions are
+    /// returned. Keys without a binding scroll the error overlay or switch
+    /// between its errors if it is visible and not docked (see
+    /// [`ErrorOverlay::handle_key`]), and otherwise go to the
+    /// [widget key handler](Self::set_widget_key_handler).
+    ///
+    /// Mouse events go to the widget under the cursor in the
+    /// [hit map](DashboardState::hit_map): a left click focuses the widget and
+    /// selects the row or tab under the cursor, the wheel scrolls it, and
+    /// dragging a scrollbar moves its thumb.
     ///
     /// # Arguments
     ///
@@ -417,48 +1070,510 @@ impl<R: Renderer> DashboardEngine<R> {
     ///
     /// Returns an action indicating what should be done in response to the event.
     pub fn handle_event(&mut self, event: Event) -> EngineResult<Action> {
-        // Handle file change events
-        if let Event::FileChange(path) = &event {
-            // Invalidate changed files
-            let _invalidated = self.loader.invalidate(path);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&event);
+        }
 
-            // Reload the dashboard
-            self.reload()?;
+        // Handle file change events
+        if let Event::FileChange(change) = &event {
+            let paths = change.paths();
+
+            // A changed theme re-styles the dashboard without a reload
+            #[cfg(feature = "serde")]
+            let (paths, theme_error) = match self.theme_file.clone() {
+                Some(theme_file) if paths.contains(&theme_file.as_path()) => {
+                    let theme_error = self.reload_theme().err();
+                    let paths = paths.into_iter().filter(|p| *p != theme_file).collect();
+                    (paths, theme_error)
+                }
+                _ => (paths, None),
+            };
+
+            // Recompile the changed file and the files that load it. A
+            // renamed file also affects the files that loaded its old path
+            let reloaded = if paths.is_empty() {
+                Ok(())
+            } else {
+                self.reload_modules(&paths)
+            };
+
+            // A broken theme does not hold up the scripts. Its error is shown
+            // after the reload, which dismisses the overlay when it succeeds,
+            // and next to the reload error if there is one
+            #[cfg(feature = "serde")]
+            if let Some(theme_error) = theme_error {
+                if let Err(err) = &reloaded {
+                    self.show_error(err);
+                }
+                self.push_error(&theme_error);
+                return Ok(Action::Render);
+            }
 
+            reloaded?;
             return Ok(Action::Render);
         }
 
         // Handle resize events
-        if let Event::Resize(_, _) = event {
+        if let Event::Resize(width, height) = event {
+            self.renderer.resize(Rect::new(0, 0, width, height))?;
             self.state.mark_dirty();
             return Ok(Action::Render);
         }
 
-        // Default event handling
-        use crate::event::KeyCode;
+        // Ticks redraw time-based content
+        if let Event::Tick = event {
+            self.state.mark_dirty();
+            return Ok(Action::Render);
+        }
+
+        // Custom messages become custom actions
+        if let Event::Custom(name) = event {
+            return Ok(Action::Custom(name));
+        }
+
+        if let Event::Mouse(mouse) = event {
+            return Ok(self.route_mouse(&mouse));
+        }
 
         if let Event::Key(key_event) = event {
-            // Ctrl+C to quit
-            if key_event.code == KeyCode::Char('c') && key_event.modifiers.ctrl {
-                return Ok(Action::Quit);
-            }
+            return match self.keymap.handle_key(&key_event, &self.state) {
+                KeyResult::Action(action) => self.apply_builtin(action),
+                KeyResult::Pending => Ok(Action::None),
+                KeyResult::Unbound => {
+                    // The error overlay scrolls and switches errors with
+                    // keys that have no binding, unless it is docked below
+                    // a dashboard that is still running
+                    let overlay = self
+                        .error_overlay
+                        .as_mut()
+                        .filter(|o| o.is_visible() && !o.is_docked());
+                    if overlay.is_some_and(|o| o.handle_key(&key_event)) {
+                        self.state.mark_dirty();
+                        Ok(Action::Render)
+                    } else {
+                        self.route_key(&key_event)
+                    }
+                }
+            };
+        }
+
+        Ok(Action::None)
+    }
 
-            // Ctrl+R to force reload
-            if key_event.code == KeyCode::Char('r') && key_event.modifiers.ctrl {
+    /// Perform the engine's own actions from a key binding and return what
+    /// is left for the caller.
+    fn apply_builtin(&mut self, action: Action) -> EngineResult<Action> {
+        match action {
+            Action::Reload => {
                 self.reload()?;
-                return Ok(Action::Render);
+                Ok(Action::Render)
             }
-
-            // Ctrl+D to dismiss error overlay
-            if key_event.code == KeyCode::Char('d') && key_event.modifiers.ctrl {
+            Action::DismissError => {
                 if self.has_error() {
                     self.dismiss_error();
-                    return Ok(Action::Render);
+                    Ok(Action::Render)
+                } else {
+                    Ok(Action::None)
                 }
             }
+            Action::ToggleHelp => {
+                self.toggle_help();
+                Ok(Action::Render)
+            }
+            Action::FocusNext | Action::FocusPrevious | Action::Focus(_) => {
+                if self.move_focus(&action) {
+                    Ok(Action::Render)
+                } else {
+                    Ok(Action::None)
+                }
+            }
+            Action::Batch(actions) => Ok(Action::Batch(
+                actions
+                    .into_iter()
+                    .map(|action| self.apply_builtin(action))
+                    .collect::<EngineResult<_>>()?,
+            )),
+            action => Ok(action),
         }
+    }
 
-        Ok(Action::None)
+    /// Perform a focus action. Returns `true` if focus moved.
+    fn move_focus(&mut self, action: &Action) -> bool {
+        match action {
+            Action::FocusNext => self.state.focus_next(),
+            Action::FocusPrevious => self.state.focus_previous(),
+            Action::Focus(direction) => self.state.focus_direction(*direction),
+            _ => false,
+        }
+    }
+
+    /// Pass a key without a binding to the focused widget's state.
+    fn route_key(&mut self, key: &KeyEvent) -> EngineResult<Action> {
+        let Some(handler) = self.widget_key_handler.as_mut() else {
+            return Ok(Action::None);
+        };
+        let Some(id) = self.state.focus.as_deref() else {
+            return Ok(Action::None);
+        };
+        let Some(widget) = self.state.widgets.get_mut(id) else {
+            return Ok(Action::None);
+        };
+        let action = handler(id, widget, key);
+        self.apply_builtin(action)
+    }
+
+    /// Apply a mouse event to the widget under the cursor.
+    fn route_mouse(&mut self, mouse: &MouseEvent) -> Action {
+        let hit_map = &self.state.hit_map;
+        let changed = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(hit) = hit_map.hit(mouse.x, mouse.y) else {
+                    return Action::None;
+                };
+                if let HitPart::Scrollbar { .. } = hit.part {
+                    self.dragging = Some(hit.id.clone());
+                }
+                let mut changed = false;
+                if self.state.focus_ring.contains(&hit.id) && !self.state.is_focused(&hit.id) {
+                    self.state.set_focus(hit.id.clone());
+                    changed = true;
+                }
+                let clicked = self
+                    .state
+                    .widgets
+                    .get_mut(&hit.id)
+                    .is_some_and(|w| w.click(hit.part));
+                changed || clicked
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some(id) = self.dragging.clone() else {
+                    return Action::None;
+                };
+                let Some(part) = hit_map.scrollbar_part(&id, mouse.x, mouse.y) else {
+                    return Action::None;
+                };
+                let changed = self
+                    .state
+                    .widgets
+                    .get_mut(&id)
+                    .is_some_and(|w| w.click(part));
+                changed
+            }
+            MouseEventKind::Up(_) => {
+                self.dragging = None;
+                return Action::None;
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let Some(hit) = hit_map.hit(mouse.x, mouse.y) else {
+                    return Action::None;
+                };
+                let len = hit_map.row_count(&hit.id);
+                let changed = self.state.widgets.get_mut(&hit.id).is_some_and(|w| {
+                    if mouse.kind == MouseEventKind::ScrollUp {
+                        w.scroll_up()
+                    } else {
+                        w.scroll_down(len)
+                    }
+                });
+                changed
+            }
+            _ => return Action::None,
+        };
+
+        if changed {
+            self.state.mark_dirty();
+            Action::Render
+        } else {
+            Action::None
+        }
+    }
+
+    /// Run the app loop on the terminal until a quit action.
+    ///
+    /// Sets up the terminal with a [`TerminalGuard`](crate::app::TerminalGuard),
+    /// reads input with [`CrosstermEvents`](crate::input::CrosstermEvents) and
+    /// then runs [`run_with`](Self::run_with). The terminal is restored when
+    /// the loop ends, returns an error or panics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terminal cannot be set up, input cannot be
+    /// read, or rendering fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fusabi_tui_engine::app::RunOptions;
+    /// use fusabi_tui_engine::prelude::*;
+    /// use fusabi_tui_render::crossterm::CrosstermRenderer;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let renderer = CrosstermRenderer::new(std::io::stdout())?;
+    /// let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// engine.enable_hot_reload()?;
+    /// engine.load(Path::new("dashboard.fsx"))?;
+    /// engine.run(RunOptions::default().frame_rate(30))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "crossterm-backend")]
+    pub fn run(&mut self, options: RunOptions) -> EngineResult<()> {
+        use crate::app::TerminalGuard;
+        use crate::input::CrosstermEvents;
+
+        let _guard = TerminalGuard::enter(options.is_mouse_capture_enabled())?;
+        self.renderer.resize(self.renderer.size()?)?;
+        let events = EventReader::new().with_source(CrosstermEvents::new());
+        self.run_with(events, options)
+    }
+
+    /// Run the app loop with the given event reader until a quit action.
+    ///
+    /// Each iteration:
+    /// 1. Reloads the dashboard if hot reload reported changed files
+    /// 2. Applies updates sent through [`DashboardHandle`]s
+    /// 3. Renders if the state is dirty and the frame-rate cap allows it
+    /// 4. Waits for the next event and handles it with
+    ///    [`handle_event`](Self::handle_event)
+    /// 5. Performs the resulting action, passing custom actions to the
+    ///    [action handler](Self::set_action_handler)
+    ///
+    /// Errors from reloading are shown in the error overlay rather than
+    /// ending the loop. Unlike [`run`](Self::run), this does not set up the
+    /// terminal, which makes it usable with other renderers. On quit, the
+    /// state is saved to the [state file](Self::set_state_file), if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading events or rendering fails.
+    pub fn run_with(&mut self, mut events: EventReader, options: RunOptions) -> EngineResult<()> {
+        if let Some(interval) = options.tick_interval() {
+            events.add_tick_timer(interval);
+        }
+        let frame_interval = options.frame_interval();
+        let mut last_frame: Option<Instant> = None;
+        self.state.mark_dirty();
+
+        loop {
+            for change in self.poll_changes().unwrap_or_default() {
+                if self.dispatch(Event::FileChange(change))? {
+                    return self.finish();
+                }
+            }
+            while let Ok(update) = self.updates_rx.try_recv() {
+                if self.apply_update(update)? {
+                    return self.finish();
+                }
+            }
+
+            // Wait for the next event, but wake up in time for a pending
+            // frame and to check the file watcher and handles
+            let mut wait = POLL_INTERVAL;
+            if self.state.dirty {
+                let now = Instant::now();
+                match last_frame.map(|t| t + frame_interval) {
+                    Some(next) if next > now => wait = wait.min(next - now),
+                    _ => {
+                        self.render()?;
+                        last_frame = Some(now);
+                    }
+                }
+            }
+
+            if let Some(event) = events.next_event_timeout(wait)? {
+                if self.dispatch(event)? {
+                    return self.finish();
+                }
+            }
+        }
+    }
+
+    /// Run the app loop asynchronously on the terminal until a quit action.
+    ///
+    /// The async counterpart of [`run`](Self::run): terminal input arrives
+    /// through [`stream::terminal`](crate::stream::terminal), and the loop
+    /// sleeps until input, a file change, a tick, a frame or an update from a
+    /// [`DashboardHandle`] is due instead of polling.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terminal cannot be set up, input cannot be
+    /// read, or rendering fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fusabi_tui_engine::prelude::*;
+    /// use fusabi_tui_render::crossterm::CrosstermRenderer;
+    /// use std::path::{Path, PathBuf};
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let renderer = CrosstermRenderer::new(std::io::stdout())?;
+    /// let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+    /// engine.enable_hot_reload()?;
+    /// engine.load(Path::new("dashboard.fsx"))?;
+    ///
+    /// let handle = engine.handle();
+    /// tokio::spawn(async move {
+    ///     loop {
+    ///         tokio::time::sleep(Duration::from_secs(1)).await;
+    ///         handle.update(|state| state.mark_dirty());
+    ///     }
+    /// });
+    ///
+    /// engine.run_async(RunOptions::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "crossterm-backend")]
+    pub async fn run_async(&mut self, options: RunOptions) -> EngineResult<()> {
+        use crate::app::TerminalGuard;
+
+        let _guard = TerminalGuard::enter(options.is_mouse_capture_enabled())?;
+        self.renderer.resize(self.renderer.size()?)?;
+        self.run_async_with(crate::stream::terminal(), options).await
+    }
+
+    /// Run the app loop asynchronously on `events` until a quit action.
+    ///
+    /// Behaves like [`run_with`](Self::run_with), and also applies updates
+    /// sent through [`DashboardHandle`]s. Hot reload changes from the engine's
+    /// own file watcher are picked up without polling. The loop keeps running
+    /// after `events` ends, so a handle can still quit it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `events` yields an error or rendering fails.
+    pub async fn run_async_with<S>(&mut self, events: S, options: RunOptions) -> EngineResult<()>
+    where
+        S: Stream<Item = EngineResult<Event>> + Unpin,
+    {
+        let mut events = events;
+        let mut events_ended = false;
+        let mut ticks = options.tick_interval().map(crate::stream::ticks);
+        let frame_interval = options.frame_interval();
+        let mut last_frame: Option<Instant> = None;
+        self.state.mark_dirty();
+
+        loop {
+            let mut next_frame = None;
+            if self.state.dirty {
+                let now = Instant::now();
+                match last_frame.map(|t| t + frame_interval) {
+                    Some(next) if next > now => next_frame = Some(next),
+                    _ => {
+                        self.render()?;
+                        last_frame = Some(now);
+                    }
+                }
+            }
+
+            let wake = tokio::select! {
+                event = events.next(), if !events_ended => Wake::Event(event),
+                Some(Ok(tick)) = next_item(ticks.as_mut()) => Wake::Event(Some(Ok(tick))),
+                changes = changed(self.watcher.as_mut()) => Wake::Changes(changes),
+                Some(update) = self.updates_rx.recv() => Wake::Update(update),
+                () = sleep_until(next_frame) => Wake::Frame,
+            };
+
+            let quit = match wake {
+                Wake::Event(Some(event)) => self.dispatch(event?)?,
+                // The stream ended; keep serving ticks, reloads and handles
+                Wake::Event(None) => {
+                    events_ended = true;
+                    false
+                }
+                Wake::Changes(changes) => {
+                    let mut quit = false;
+                    for change in changes {
+                        quit = quit || self.dispatch(Event::FileChange(change))?;
+                    }
+                    quit
+                }
+                Wake::Update(update) => self.apply_update(update)?,
+                Wake::Frame => false,
+            };
+            if quit {
+                return self.finish();
+            }
+        }
+    }
+
+    /// Apply an update sent through a [`DashboardHandle`]. Returns `true` to
+    /// quit.
+    fn apply_update(&mut self, update: Update) -> EngineResult<bool> {
+        match update {
+            Update::State(update) => {
+                update(&mut self.state);
+                self.state.mark_dirty();
+                Ok(false)
+            }
+            Update::Action(action) => self.perform(action),
+        }
+    }
+
+    /// Clean up when the app loop quits.
+    fn finish(&self) -> EngineResult<()> {
+        #[cfg(feature = "serde")]
+        self.save_state()?;
+        Ok(())
+    }
+
+    /// Handle an event in the app loop. Returns `true` to quit.
+    fn dispatch(&mut self, event: Event) -> EngineResult<bool> {
+        match self.handle_event(event) {
+            Ok(action) => self.perform(action),
+            Err(err) => {
+                self.show_error(&err);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Perform an action in the app loop. Returns `true` to quit.
+    ///
+    /// Batches and the follow-up actions of custom actions are performed in
+    /// order from a work list rather than recursively, and at most
+    /// [`MAX_CUSTOM_ACTIONS`] custom actions are passed to the handler.
+    fn perform(&mut self, action: Action) -> EngineResult<bool> {
+        let mut pending = vec![action];
+        let mut custom_actions = 0;
+        while let Some(action) = pending.pop() {
+            match action {
+                Action::None => {}
+                Action::Render => self.state.mark_dirty(),
+                Action::Reload => {
+                    if let Err(err) = self.reload() {
+                        self.show_error(&err);
+                    }
+                }
+                Action::Quit => return Ok(true),
+                Action::DismissError => self.dismiss_error(),
+                Action::ToggleHelp => self.toggle_help(),
+                Action::FocusNext | Action::FocusPrevious | Action::Focus(_) => {
+                    self.move_focus(&action);
+                }
+                Action::Custom(name) => {
+                    let Some(handler) = self.action_handler.as_mut() else {
+                        continue;
+                    };
+                    custom_actions += 1;
+                    if custom_actions > MAX_CUSTOM_ACTIONS {
+                        self.push_error(&EngineError::InvalidState(format!(
+                            "custom action `{name}` led to more than \
+                             {MAX_CUSTOM_ACTIONS} custom actions"
+                        )));
+                        break;
+                    }
+                    pending.push(handler(&name, &mut self.state));
+                }
+                Action::Batch(actions) => pending.extend(actions.into_iter().rev()),
+            }
+        }
+        Ok(false)
     }
 
     /// Get a reference to the dashboard state.
@@ -496,6 +1611,34 @@ impl<R: Renderer> DashboardEngine<R> {
         self.watcher.is_some()
     }
 
+    /// Start recording every event passed to
+    /// [`handle_event`](Self::handle_event), replacing a recording in
+    /// progress.
+    ///
+    /// With `frames`, the screen after each event is recorded as well, as
+    /// drawn by the next [`render`](Self::render). Get the recording with
+    /// [`stop_recording`](Self::stop_recording) and replay it with
+    /// [`replay`](DashboardEngine::replay).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the screen size cannot be determined.
+    pub fn start_recording(&mut self, frames: bool) -> EngineResult<()> {
+        let size = self.renderer.size()?;
+        self.recorder = Some(Recorder::new(size, frames));
+        Ok(())
+    }
+
+    /// Stop recording and get the recording, if one was in progress.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    /// Get the recording in progress, if any.
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recorder.as_ref().map(Recorder::recording)
+    }
+
     /// Clear the renderer and state.
     pub fn clear(&mut self) -> EngineResult<()> {
         self.renderer.clear()?;
@@ -504,11 +1647,92 @@ impl<R: Renderer> DashboardEngine<R> {
     }
 }
 
+impl DashboardEngine<TestRenderer> {
+    /// Replay a recorded session.
+    ///
+    /// The renderer is resized to the recorded screen size, then each event
+    /// is handled as the app loop would: errors are shown in the error
+    /// overlay, actions are performed and the dashboard is rendered when it
+    /// is dirty. Timestamps are not waited for. Replay stops at an event that
+    /// quits.
+    ///
+    /// Where the recording has the screen after an event, it is compared
+    /// with the screen after replaying the event. File change events reload
+    /// the files as they are on disk now.
+    ///
+    /// Returns the screens that differ, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recording was made with a newer format, or if
+    /// rendering fails.
+    pub fn replay(&mut self, recording: &Recording) -> EngineResult<Vec<FrameMismatch>> {
+        recording.check_version()?;
+        self.renderer
+            .resize(Rect::new(0, 0, recording.width, recording.height))?;
+        self.state.mark_dirty();
+        self.render()?;
+
+        let mut mismatches = Vec::new();
+        for (index, recorded) in recording.events.iter().enumerate() {
+            let quit = self.dispatch(recorded.event.clone())?;
+            if self.state.dirty {
+                self.render()?;
+            }
+
+            if let Some(expected) = &recorded.frame {
+                let actual = frame_rows(self.renderer.buffer());
+                if actual != *expected {
+                    mismatches.push(FrameMismatch {
+                        index,
+                        expected: expected.clone(),
+                        actual,
+                    });
+                }
+            }
+            if quit {
+                break;
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+/// Wait for the next item of an optional stream, forever if there is none.
+async fn next_item<S: Stream + Unpin>(stream: Option<&mut S>) -> Option<S::Item> {
+    match stream {
+        Some(stream) => stream.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for changes from an optional file watcher, forever if there is none.
+async fn changed(watcher: Option<&mut FileWatcher>) -> Vec<FileChange> {
+    match watcher {
+        Some(watcher) => watcher.changed().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Sleep until `deadline`, forever if there is none.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::event::{KeyCode, KeyEvent, KeyModifiers};
-    use fusabi_tui_render::test::TestRenderer;
+    use crate::input::EventSource;
+    use crate::error::LoadError;
+    use crate::state::ListState;
+    use crate::watcher::FileChangeKind;
+    use std::sync::Arc;
+    use fusabi_tui_render::test::{FrameEvent, TestRenderer};
+    use std::collections::VecDeque;
     use std::io::Write;
     use tempfile::{tempdir, NamedTempFile};
 
@@ -615,6 +1839,159 @@ mod tests {
         assert!(engine.state().dirty);
     }
 
+    #[test]
+    fn test_handle_rebound_keys() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        engine.keymap_mut().bind("ctrl+c", Action::None).unwrap();
+        engine.keymap_mut().bind("q", Action::Quit).unwrap();
+        engine
+            .keymap_mut()
+            .bind("g g", Action::Custom("top".to_string()))
+            .unwrap();
+
+        let ctrl_c = key(KeyCode::Char('c'), KeyModifiers::ctrl());
+        assert_eq!(engine.handle_event(ctrl_c).unwrap(), Action::None);
+        let q = key(KeyCode::Char('q'), KeyModifiers::none());
+        assert_eq!(engine.handle_event(q).unwrap(), Action::Quit);
+
+        let g = key(KeyCode::Char('g'), KeyModifiers::none());
+        assert_eq!(engine.handle_event(g.clone()).unwrap(), Action::None);
+        assert_eq!(
+            engine.handle_event(g).unwrap(),
+            Action::Custom("top".to_string())
+        );
+    }
+
+    #[test]
+    fn test_handle_help_and_dismiss_keys() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let f1 = key(KeyCode::F(1), KeyModifiers::none());
+        assert_eq!(engine.handle_event(f1.clone()).unwrap(), Action::Render);
+        assert!(engine.is_help_visible());
+        engine.render().unwrap();
+        assert_eq!(engine.handle_event(f1).unwrap(), Action::Render);
+        assert!(!engine.is_help_visible());
+
+        let ctrl_d = key(KeyCode::Char('d'), KeyModifiers::ctrl());
+        assert_eq!(engine.handle_event(ctrl_d.clone()).unwrap(), Action::None);
+        engine.show_error(&EngineError::InvalidState("broken".to_string()));
+        assert_eq!(engine.handle_event(ctrl_d).unwrap(), Action::Render);
+        assert!(!engine.has_error());
+    }
+
+    #[test]
+    fn test_focus_traversal_and_key_routing() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        engine.set_render_callback(|_buffer, area, state| {
+            let half = area.width / 2;
+            state.focus_ring.register("left", Rect::new(0, 0, half, area.height));
+            state.focus_ring.register("right", Rect::new(half, 0, half, area.height));
+        });
+        engine.set_widget_key_handler(|_id, widget, key| match (widget, key.code) {
+            (WidgetState::List(list), KeyCode::Down) => {
+                list.select_next(3);
+                Action::Render
+            }
+            _ => Action::None,
+        });
+        engine.state_mut().insert_widget("left", WidgetState::List(Default::default()));
+        engine.state_mut().insert_widget("right", WidgetState::List(Default::default()));
+        engine.render().unwrap();
+
+        let tab = key(KeyCode::Tab, KeyModifiers::none());
+        assert_eq!(engine.handle_event(tab).unwrap(), Action::Render);
+        assert!(engine.state().is_focused("left"));
+
+        let alt_right = key(KeyCode::Right, KeyModifiers::alt());
+        assert_eq!(engine.handle_event(alt_right.clone()).unwrap(), Action::Render);
+        assert!(engine.state().is_focused("right"));
+        assert_eq!(engine.handle_event(alt_right).unwrap(), Action::None);
+
+        let down = key(KeyCode::Down, KeyModifiers::none());
+        assert_eq!(engine.handle_event(down).unwrap(), Action::Render);
+        let selected = |engine: &DashboardEngine<TestRenderer>, id: &str| {
+            match engine.state().get_widget(id) {
+                Some(WidgetState::List(list)) => list.selected(),
+                _ => None,
+            }
+        };
+        assert_eq!(selected(&engine, "right"), Some(0));
+        assert_eq!(selected(&engine, "left"), None);
+    }
+
+    #[test]
+    fn test_mouse_routing() {
+        use crate::state::TabsState;
+        use fusabi_tui_widgets::scrollbar::ScrollbarState;
+
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        engine.set_render_callback(|_buffer, _area, state| {
+            state.hit_map.tabs("tabs", vec![Rect::new(0, 0, 4, 1), Rect::new(5, 0, 4, 1)]);
+            state.focus_ring.register("list", Rect::new(0, 1, 20, 9));
+            state.hit_map.widget("list", Rect::new(0, 1, 20, 9));
+            state.hit_map.rows("list", Rect::new(1, 2, 18, 7), 0, 3);
+            state.hit_map.scrollbar("bar", Rect::new(39, 1, 1, 9), true);
+        });
+        let state = engine.state_mut();
+        state.insert_widget("tabs", WidgetState::Tabs(TabsState::new()));
+        state.insert_widget("list", WidgetState::List(ListState::new()));
+        let scrollbar = ScrollbarState::new(90).viewport_content_length(10);
+        state.insert_widget("bar", WidgetState::Scrollbar(scrollbar));
+        engine.render().unwrap();
+
+        let mouse = |kind, x, y| {
+            Event::Mouse(MouseEvent {
+                kind,
+                x,
+                y,
+                modifiers: KeyModifiers::none(),
+            })
+        };
+        let left = MouseEventKind::Down(MouseButton::Left);
+
+        // Clicking a row focuses the list and selects the row
+        assert_eq!(engine.handle_event(mouse(left, 3, 3)).unwrap(), Action::Render);
+        assert!(engine.state().is_focused("list"));
+        assert!(matches!(
+            engine.state().get_widget("list"),
+            Some(WidgetState::List(list)) if list.selected() == Some(1)
+        ));
+        assert_eq!(engine.handle_event(mouse(left, 3, 3)).unwrap(), Action::None);
+
+        // Scrolling stops at the last row
+        let down = MouseEventKind::ScrollDown;
+        assert_eq!(engine.handle_event(mouse(down, 0, 1)).unwrap(), Action::Render);
+        assert_eq!(engine.handle_event(mouse(down, 0, 1)).unwrap(), Action::None);
+
+        assert_eq!(engine.handle_event(mouse(left, 6, 0)).unwrap(), Action::Render);
+        assert!(matches!(
+            engine.state().get_widget("tabs"),
+            Some(WidgetState::Tabs(TabsState { selected: 1 }))
+        ));
+
+        // Dragging the scrollbar thumb past the end scrolls to the bottom
+        assert_eq!(engine.handle_event(mouse(left, 39, 1)).unwrap(), Action::None);
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        assert_eq!(engine.handle_event(mouse(drag, 30, 20)).unwrap(), Action::Render);
+        let position = |engine: &DashboardEngine<TestRenderer>| {
+            match engine.state().get_widget("bar") {
+                Some(WidgetState::Scrollbar(bar)) => bar.get_position(),
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(position(&engine), 80);
+
+        let up = MouseEventKind::Up(MouseButton::Left);
+        engine.handle_event(mouse(up, 30, 20)).unwrap();
+        assert_eq!(engine.handle_event(mouse(drag, 39, 1)).unwrap(), Action::None);
+        assert_eq!(position(&engine), 80);
+    }
+
     #[test]
     fn test_render() {
         let renderer = TestRenderer::new(80, 24);
@@ -632,4 +2009,771 @@ mod tests {
         let result = engine.clear();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_render_script() {
+        let renderer = TestRenderer::new(20, 3);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let render buffer area state =").unwrap();
+        writeln!(temp_file, "    tui.widget.paragraph \"from script\"").unwrap();
+
+        engine.load(temp_file.path()).unwrap();
+        engine.render().unwrap();
+
+        let buffer = engine.renderer().buffer();
+        let line: String = (0..11).map(|x| buffer.get(x, 0).unwrap().symbol.clone()).collect();
+        assert_eq!(line, "from script");
+        assert!(!engine.has_error());
+    }
+
+    #[test]
+    fn test_script_focus_traversal() {
+        let renderer = TestRenderer::new(20, 3);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let source = r#"
+let render buffer area state =
+    let left = tui.layout.rect 0 0 10 3
+    let right = tui.layout.rect 10 0 10 3
+    tui.widget.renderWithId "left" buffer left (tui.widget.list ["a"; "b"])
+    tui.widget.renderWithId "right" buffer right (tui.widget.list ["c"; "d"])
+"#;
+        write!(temp_file, "{source}").unwrap();
+        engine.load(temp_file.path()).unwrap();
+        engine.render().unwrap();
+
+        let tab = key(KeyCode::Tab, KeyModifiers::none());
+        assert_eq!(engine.handle_event(tab.clone()).unwrap(), Action::Render);
+        assert!(engine.state().is_focused("left"));
+        engine.handle_event(tab).unwrap();
+        assert!(engine.state().is_focused("right"));
+        assert!(matches!(engine.state().get_widget("right"), Some(WidgetState::List(_))));
+    }
+
+    #[test]
+    fn test_script_mouse_routing() {
+        use crate::hit::HitKind;
+        use crate::state::TabsState;
+
+        let renderer = TestRenderer::new(20, 6);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let source = r#"
+let render buffer area state =
+    let tabs = tui.widget.tabs ["One"; "Two"]
+    tui.widget.renderWithId "tabs" buffer (tui.layout.rect 0 0 20 1) tabs
+    let block = tui.widget.block () |> tui.widget.blockBorders "all"
+    let list = tui.widget.list ["a"; "b"; "c"] |> tui.widget.withBlock block
+    tui.widget.renderWithId "list" buffer (tui.layout.rect 0 1 20 5) list
+"#;
+        write!(temp_file, "{source}").unwrap();
+        engine.load(temp_file.path()).unwrap();
+        engine.render().unwrap();
+
+        let mouse = |x, y| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                x,
+                y,
+                modifiers: KeyModifiers::none(),
+            })
+        };
+
+        // The first row is inside the list's border
+        assert_eq!(engine.handle_event(mouse(3, 3)).unwrap(), Action::Render);
+        assert!(engine.state().is_focused("list"));
+        assert!(matches!(
+            engine.state().get_widget("list"),
+            Some(WidgetState::List(list)) if list.selected() == Some(1)
+        ));
+
+        let second_tab = engine.state().hit_map.regions()[1].clone();
+        let HitKind::Tabs(titles) = second_tab.kind else {
+            panic!("unexpected region {:?}", second_tab);
+        };
+        engine.handle_event(mouse(titles[1].x, titles[1].y)).unwrap();
+        assert!(matches!(
+            engine.state().get_widget("tabs"),
+            Some(WidgetState::Tabs(TabsState { selected: 1 }))
+        ));
+    }
+
+    #[test]
+    fn test_load_script_error() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let x = (1 +").unwrap();
+
+        let result = engine.load(temp_file.path());
+        assert!(matches!(result, Err(EngineError::Script(_))));
+        assert!(engine.entry_file().is_some());
+
+        // Falls back to the placeholder
+        assert!(engine.render().is_ok());
+    }
+
+    #[test]
+    fn test_render_script_runtime_error_shows_overlay() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let render buffer area state = List.head []").unwrap();
+
+        engine.load(temp_file.path()).unwrap();
+        engine.render().unwrap();
+
+        assert!(engine.has_error());
+        let error = engine.error_overlay().unwrap().error();
+        assert_eq!(error.title, "Script Error");
+        assert_eq!(error.line, Some(1));
+        assert_eq!(
+            error.source_line(error.source.as_deref().unwrap(), 1),
+            Some("let render buffer area state = List.head []")
+        );
+        assert!(engine
+            .renderer()
+            .debug_output()
+            .contains("1 | let render buffer area state = List.head []"));
+
+        // The error is raised again on every frame but listed once
+        engine.render().unwrap();
+        assert_eq!(engine.error_overlay().unwrap().errors().len(), 1);
+    }
+
+    fn first_row(engine: &DashboardEngine<TestRenderer>, width: u16) -> String {
+        let buffer = engine.renderer().buffer();
+        (0..width).map(|x| buffer.get(x, 0).unwrap().symbol.clone()).collect()
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_last_good_version() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let main = root.join("main.fsx");
+        let version = |text: &str| {
+            format!("let render buffer area state = tui.widget.paragraph \"{}\"\n", text)
+        };
+        std::fs::write(&main, version("v1")).unwrap();
+
+        let mut engine = DashboardEngine::new(TestRenderer::new(40, 12), root.clone());
+        engine.load(Path::new("main.fsx")).unwrap();
+        engine.render().unwrap();
+
+        // A broken save shows the error below the running version
+        std::fs::write(&main, "let render buffer area state =\n    (1 +\n").unwrap();
+        let err = engine.handle_event(modified(&main)).unwrap_err();
+        engine.show_error(&err);
+        engine.render().unwrap();
+        assert_eq!(first_row(&engine, 2), "v1");
+        let overlay = engine.error_overlay().unwrap();
+        assert!(overlay.is_docked());
+        assert_eq!(overlay.error().title, "Syntax Error");
+
+        // Keys without a binding still go to the dashboard
+        engine.set_widget_key_handler(|_id, widget, key| match (widget, key.code) {
+            (WidgetState::List(list), KeyCode::Down) => {
+                list.select_next(3);
+                Action::Render
+            }
+            _ => Action::None,
+        });
+        engine.state_mut().insert_widget("list", WidgetState::List(Default::default()));
+        engine.state_mut().set_focus("list");
+        let down = key(KeyCode::Down, KeyModifiers::none());
+        assert_eq!(engine.handle_event(down).unwrap(), Action::Render);
+        match engine.state().get_widget("list") {
+            Some(WidgetState::List(list)) => assert_eq!(list.selected(), Some(0)),
+            other => panic!("unexpected widget {:?}", other),
+        }
+
+        // The fixed version replaces it and clears the error
+        std::fs::write(&main, version("v2")).unwrap();
+        engine.handle_event(modified(&main)).unwrap();
+        assert!(!engine.has_error());
+        engine.render().unwrap();
+        assert_eq!(first_row(&engine, 2), "v2");
+    }
+
+    #[test]
+    fn test_render_error_keeps_last_frame() {
+        let mut engine = DashboardEngine::new(TestRenderer::new(40, 12), PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let render buffer area state =").unwrap();
+        writeln!(temp_file, "    if state.focus = \"\" then tui.widget.paragraph \"good\"").unwrap();
+        writeln!(temp_file, "    else List.head []").unwrap();
+
+        engine.load(temp_file.path()).unwrap();
+        engine.render().unwrap();
+        assert!(!engine.has_error());
+
+        engine.state_mut().set_focus("anything");
+        engine.render().unwrap();
+        assert_eq!(first_row(&engine, 4), "good");
+        let overlay = engine.error_overlay().unwrap();
+        assert!(overlay.is_docked());
+        assert_eq!(overlay.error().title, "Script Error");
+        assert!(engine.renderer().debug_output().contains("ERROR: Script Error"));
+    }
+
+    fn modified(path: &Path) -> Event {
+        Event::FileChange(FileChange::new(path, FileChangeKind::Modified))
+    }
+
+    #[test]
+    fn test_file_change_recompiles_dependents() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let util = root.join("util.fsx");
+        let lib = root.join("lib.fsx");
+        let main = root.join("main.fsx");
+        std::fs::write(&util, "let twice x = x * 2\n").unwrap();
+        std::fs::write(&lib, "#load \"util.fsx\"\nlet four = twice 2\n").unwrap();
+        std::fs::write(&main, "#load \"lib.fsx\"\nlet x = four\n").unwrap();
+
+        let mut engine = DashboardEngine::new(TestRenderer::new(20, 3), root.clone());
+        engine.load(Path::new("main.fsx")).unwrap();
+        let program = |engine: &DashboardEngine<TestRenderer>, path: &Path| {
+            Arc::clone(&engine.context.as_ref().unwrap().module(path).unwrap().program)
+        };
+        let before = [program(&engine, &util), program(&engine, &lib), program(&engine, &main)];
+
+        std::fs::write(&lib, "#load \"util.fsx\"\nlet four = twice 2 + 0\n").unwrap();
+        let action = engine.handle_event(modified(&lib)).unwrap();
+        assert_eq!(action, Action::Render);
+        // lib.fsx and main.fsx, which loads it, are recompiled; util.fsx is not
+        assert!(Arc::ptr_eq(&before[0], &program(&engine, &util)));
+        assert!(!Arc::ptr_eq(&before[1], &program(&engine, &lib)));
+        assert!(!Arc::ptr_eq(&before[2], &program(&engine, &main)));
+
+        // A cycle is reported as a load error
+        std::fs::write(&util, "#load \"lib.fsx\"\n").unwrap();
+        let result = engine.handle_event(modified(&util));
+        assert!(matches!(
+            result,
+            Err(EngineError::LoadError(LoadError::CircularDependency(_)))
+        ));
+    }
+
+    #[test]
+    fn test_file_rename_reloads_old_dependents() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let lib = root.join("lib.fsx");
+        let moved = root.join("moved.fsx");
+        std::fs::write(&lib, "let four = 4\n").unwrap();
+        std::fs::write(root.join("main.fsx"), "#load \"lib.fsx\"\nlet x = four\n").unwrap();
+
+        let mut engine = DashboardEngine::new(TestRenderer::new(20, 3), root.clone());
+        engine.load(Path::new("main.fsx")).unwrap();
+
+        // main.fsx still loads the old path
+        std::fs::rename(&lib, &moved).unwrap();
+        let change = FileChange::new(&moved, FileChangeKind::Renamed { from: lib.clone() });
+        let result = engine.handle_event(Event::FileChange(change));
+        assert!(matches!(
+            result,
+            Err(EngineError::LoadError(LoadError::DependencyNotFound { .. }))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_theme_file_change_restyles() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let theme = root.join("theme.toml");
+        let main = root.join("main.fsx");
+        std::fs::write(&theme, "extends = \"dark\"\n[colors]\nprimary = \"Green\"\n").unwrap();
+        std::fs::write(
+            &main,
+            "let render buffer area state =\n    \
+             tui.buffer.setString 0 0 \"Hi\" (tui.theme.style \"title\") buffer\n",
+        )
+        .unwrap();
+
+        let mut engine = DashboardEngine::new(TestRenderer::new(4, 1), root.clone());
+        engine.load_theme(Path::new("theme.toml")).unwrap();
+        engine.load(Path::new("main.fsx")).unwrap();
+        engine.render().unwrap();
+        let fg = |engine: &DashboardEngine<TestRenderer>| {
+            engine.renderer().buffer().get(0, 0).unwrap().fg
+        };
+        assert_eq!(fg(&engine), Color::Green);
+
+        // Only the theme is loaded again, the broken script is not
+        std::fs::write(&theme, "extends = \"dark\"\n[colors]\nprimary = \"Red\"\n").unwrap();
+        std::fs::write(&main, "let render (").unwrap();
+        assert_eq!(engine.handle_event(modified(&theme)).unwrap(), Action::Render);
+        engine.render().unwrap();
+        assert_eq!(fg(&engine), Color::Red);
+        assert!(!engine.has_error());
+
+        // A broken theme keeps the current one and is shown in the overlay
+        std::fs::write(&theme, "extends = \"solarized\"\n").unwrap();
+        assert_eq!(engine.handle_event(modified(&theme)).unwrap(), Action::Render);
+        assert_eq!(engine.theme().colors.primary, Color::Red);
+        assert_eq!(engine.error_overlay().unwrap().error().title, "Theme Error");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_theme_errors_do_not_hide_script_errors() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let theme = root.join("theme.toml");
+        let main = root.join("main.fsx");
+        std::fs::write(&theme, "extends = \"dark\"\n").unwrap();
+        std::fs::write(&main, "let render (").unwrap();
+
+        // A theme loaded after a broken script leaves its error on screen
+        let mut engine = DashboardEngine::new(TestRenderer::new(40, 10), root.clone());
+        let err = engine.load(Path::new("main.fsx")).unwrap_err();
+        engine.show_error(&err);
+        engine.load_theme(Path::new("theme.toml")).unwrap();
+        assert!(engine.has_error());
+
+        // A broken theme does not stop the script in the same change from
+        // reloading, and both errors are shown
+        std::fs::write(&theme, "extends = \"solarized\"\n").unwrap();
+        std::fs::write(&main, "let render buffer area state = (").unwrap();
+        let from = main.clone();
+        let change = FileChange::new(&theme, FileChangeKind::Renamed { from });
+        assert_eq!(engine.handle_event(Event::FileChange(change)).unwrap(), Action::Render);
+        let titles = |engine: &DashboardEngine<TestRenderer>| -> Vec<String> {
+            let overlay = engine.error_overlay().unwrap();
+            overlay.errors().iter().map(|e| e.title.clone()).collect()
+        };
+        assert_eq!(titles(&engine), vec!["Syntax Error", "Theme Error"]);
+
+        // Fixing the theme removes only its error
+        std::fs::write(&theme, "extends = \"dark\"\n").unwrap();
+        engine.handle_event(modified(&theme)).unwrap();
+        assert_eq!(titles(&engine), vec!["Syntax Error"]);
+    }
+
+    #[test]
+    fn test_reload_keeps_state() {
+        let renderer = TestRenderer::new(20, 3);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let render buffer area state =").unwrap();
+        writeln!(temp_file, "    tui.state.set \"theme\" \"dark\"").unwrap();
+        engine.load(temp_file.path()).unwrap();
+
+        let state = engine.state_mut();
+        state.insert_widget("list", WidgetState::List(ListState::with_selected(2)));
+        state.set_focus("list");
+        engine.render().unwrap();
+        let before = engine.state().snapshot();
+        assert_eq!(before.custom.len(), 1);
+
+        // The new script fails; the state is kept either way
+        writeln!(temp_file, "let broken = (").unwrap();
+        assert!(engine.reload().is_err());
+        assert_eq!(engine.state().snapshot(), before);
+
+        std::fs::write(temp_file.path(), "let render buffer area state = ()\n").unwrap();
+        engine.reload().unwrap();
+        engine.render().unwrap();
+        assert_eq!(engine.state().snapshot(), before);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_file_saved_on_quit() {
+        use crate::state::StateValue;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state.toml");
+        let quit = || {
+            EventReader::new().with_source(Scripted(VecDeque::from([key(
+                KeyCode::Char('c'),
+                KeyModifiers::ctrl(),
+            )])))
+        };
+
+        let mut engine = DashboardEngine::new(TestRenderer::new(40, 10), PathBuf::from("."));
+        engine.set_state_file(&path).unwrap();
+        let state = engine.state_mut();
+        state.insert_widget("list", WidgetState::List(ListState::with_selected(2)));
+        state.set_focus("list");
+        state.set_value("filter", "err");
+        engine.run_with(quit(), RunOptions::default()).unwrap();
+        assert!(path.exists());
+
+        // On restart, the widget that still exists gets its selection back
+        let mut engine = DashboardEngine::new(TestRenderer::new(40, 10), PathBuf::from("."));
+        engine
+            .state_mut()
+            .insert_widget("list", WidgetState::List(ListState::new()));
+        engine.set_state_file(&path).unwrap();
+        assert!(engine.state().is_focused("list"));
+        assert!(matches!(
+            engine.state().get_widget("list"),
+            Some(WidgetState::List(list)) if list.selected() == Some(2)
+        ));
+        assert_eq!(engine.state().value("filter"), Some(&StateValue::from("err")));
+
+        std::fs::write(&path, "focus = [").unwrap();
+        assert!(matches!(
+            engine.set_state_file(&path),
+            Err(EngineError::Snapshot(_))
+        ));
+    }
+
+    /// Source that hands out a fixed list of events.
+    struct Scripted(VecDeque<Event>);
+
+    impl EventSource for Scripted {
+        fn poll_event(&mut self, timeout: Duration) -> EngineResult<Option<Event>> {
+            match self.0.pop_front() {
+                Some(event) => Ok(Some(event)),
+                None => {
+                    std::thread::sleep(timeout);
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent { code, modifiers })
+    }
+
+    /// An engine showing the selection of a list that Down moves by `step`.
+    fn list_engine(step: usize) -> DashboardEngine<TestRenderer> {
+        let mut engine = DashboardEngine::new(TestRenderer::new(20, 2), PathBuf::from("."));
+        engine.set_render_callback(|buffer, _area, state| {
+            if let Some(WidgetState::List(list)) = state.get_widget("items") {
+                let text = format!("selected {:?}", list.selected());
+                buffer.set_string(0, 0, &text, Style::default());
+            }
+        });
+        engine.set_widget_key_handler(move |_id, widget, key| match (widget, key.code) {
+            (WidgetState::List(list), KeyCode::Down) => {
+                for _ in 0..step {
+                    list.select_next(10);
+                }
+                Action::Render
+            }
+            _ => Action::None,
+        });
+        engine.state_mut().insert_widget("items", WidgetState::List(Default::default()));
+        engine.state_mut().set_focus("items");
+        engine
+    }
+
+    #[test]
+    fn test_record_and_replay_session() {
+        let down = key(KeyCode::Down, KeyModifiers::none());
+        let mut engine = list_engine(1);
+        engine.render().unwrap();
+        engine.start_recording(true).unwrap();
+        for event in [down.clone(), down.clone(), Event::Resize(24, 3)] {
+            engine.handle_event(event).unwrap();
+            engine.render().unwrap();
+        }
+        assert_eq!(engine.recording().unwrap().events.len(), 3);
+        let recording = engine.stop_recording().unwrap();
+        assert!(engine.recording().is_none());
+
+        assert_eq!((recording.width, recording.height), (20, 2));
+        assert_eq!(recording.events[0].event, down);
+        let frame = recording.events[1].frame.as_ref().unwrap();
+        assert_eq!(frame[0].trim_end(), "selected Some(1)");
+        assert_eq!(recording.events[2].frame.as_ref().unwrap().len(), 3);
+
+        // The same dashboard replays the same screens
+        let mut replayed = list_engine(1);
+        assert_eq!(replayed.replay(&recording).unwrap(), Vec::new());
+        assert_eq!(replayed.renderer().buffer().area, Rect::new(0, 0, 24, 3));
+
+        // A changed dashboard is caught
+        let mismatches = list_engine(2).replay(&recording).unwrap();
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(mismatches[0].index, 0);
+        assert_eq!(mismatches[0].actual[0].trim_end(), "selected Some(1)");
+        assert_eq!(mismatches[0].expected[0].trim_end(), "selected Some(0)");
+    }
+
+    #[test]
+    fn test_replay_stops_at_quit() {
+        let mut recording = Recording::new(20, 2);
+        for event in [
+            key(KeyCode::Char('c'), KeyModifiers::ctrl()),
+            key(KeyCode::Down, KeyModifiers::none()),
+        ] {
+            recording.events.push(crate::recording::RecordedEvent {
+                at: Duration::ZERO,
+                event,
+                frame: Some(vec!["never matches".to_string()]),
+            });
+        }
+
+        let mismatches = list_engine(1).replay(&recording).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 0);
+    }
+
+    fn draws(engine: &DashboardEngine<TestRenderer>) -> usize {
+        engine
+            .renderer()
+            .events()
+            .iter()
+            .filter(|e| **e == FrameEvent::Draw)
+            .count()
+    }
+
+    #[test]
+    fn test_run_renders_only_when_dirty() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let events = EventReader::new().with_source(Scripted(VecDeque::from([
+            key(KeyCode::Char('x'), KeyModifiers::none()),
+            key(KeyCode::Down, KeyModifiers::none()),
+            key(KeyCode::Char('c'), KeyModifiers::ctrl()),
+        ])));
+        engine.run_with(events, RunOptions::default()).unwrap();
+
+        // Only the initial frame: the keys did not change anything
+        assert_eq!(draws(&engine), 1);
+    }
+
+    #[test]
+    fn test_run_custom_actions() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        engine.set_action_handler(|name, state| match name {
+            "select" => {
+                state.set_focus("list");
+                Action::Batch(vec![Action::Render, Action::Custom("done".to_string())])
+            }
+            "done" => Action::Quit,
+            _ => Action::None,
+        });
+
+        let events = EventReader::new();
+        let sender = events.custom_sender();
+        sender.send("ignored".to_string()).unwrap();
+        sender.send("select".to_string()).unwrap();
+        engine.run_with(events, RunOptions::default()).unwrap();
+
+        assert!(engine.state().is_focused("list"));
+    }
+
+    #[test]
+    fn test_perform_custom_action_loop() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        engine.set_action_handler(move |name, _state| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Action::Batch(vec![Action::Render, Action::Custom(name.to_string())])
+        });
+
+        // A handler that maps an action back to itself is stopped with an error
+        let quit = engine.perform(Action::Custom("again".to_string())).unwrap();
+        assert!(!quit);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), MAX_CUSTOM_ACTIONS);
+        let error = engine.error_overlay().unwrap().error();
+        assert!(error.message.contains("custom action `again`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_run_frame_rate_cap() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        engine.set_action_handler(|name, _state| match name {
+            "quit" => Action::Quit,
+            _ => Action::Render,
+        });
+
+        let events = EventReader::new();
+        let sender = events.custom_sender();
+        for _ in 0..5 {
+            sender.send("update".to_string()).unwrap();
+        }
+        sender.send("quit".to_string()).unwrap();
+        engine
+            .run_with(events, RunOptions::default().frame_rate(1))
+            .unwrap();
+
+        // The updates arrive within one frame interval of the first frame
+        assert_eq!(draws(&engine), 1);
+    }
+
+    #[test]
+    fn test_run_ticks_redraw() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let events = EventReader::new();
+        let sender = events.custom_sender();
+        let ticks = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        engine.set_render_callback({
+            let ticks = ticks.clone();
+            move |_, _, _| {
+                if ticks.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 2 {
+                    sender.send("quit".to_string()).unwrap();
+                }
+            }
+        });
+        engine.set_action_handler(|_, _| Action::Quit);
+
+        let options = RunOptions::default()
+            .frame_rate(0)
+            .tick_rate(Duration::from_millis(5));
+        engine.run_with(events, options).unwrap();
+
+        assert_eq!(draws(&engine), 3);
+    }
+
+    #[test]
+    fn test_run_shows_reload_error() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let x = 42").unwrap();
+        engine.load(temp_file.path()).unwrap();
+
+        // Break the script, then reload it from the loop
+        std::fs::write(temp_file.path(), "let x = (1 +\n").unwrap();
+        let events = EventReader::new().with_source(Scripted(VecDeque::from([
+            key(KeyCode::Char('r'), KeyModifiers::ctrl()),
+            key(KeyCode::Char('c'), KeyModifiers::ctrl()),
+        ])));
+        engine.run_with(events, RunOptions::default()).unwrap();
+
+        assert!(engine.has_error());
+    }
+
+    #[test]
+    fn test_handle_custom_event() {
+        let renderer = TestRenderer::new(80, 24);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let action = engine.handle_event(Event::Custom("refresh".to_string())).unwrap();
+        assert_eq!(action, Action::Custom("refresh".to_string()));
+    }
+
+    #[test]
+    fn test_run_background_updates() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let handle = engine.handle();
+        let thread = std::thread::spawn(move || {
+            handle.update(|state| state.set_focus("cpu"));
+            handle.quit();
+        });
+
+        engine.run_with(EventReader::new(), RunOptions::default()).unwrap();
+        thread.join().unwrap();
+
+        assert!(engine.state().is_focused("cpu"));
+    }
+
+    #[tokio::test]
+    async fn test_run_async_background_updates() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let handle = engine.handle();
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            handle.update(|state| state.set_focus("cpu"));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            handle.quit();
+        });
+
+        let events = tokio_stream::pending::<EngineResult<Event>>();
+        let options = RunOptions::default().frame_rate(0);
+        engine.run_async_with(events, options).await.unwrap();
+        task.await.unwrap();
+
+        assert!(engine.state().is_focused("cpu"));
+        // The initial frame and the frame for the update
+        assert_eq!(draws(&engine), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_async_events() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+        engine.set_action_handler(|name, state| {
+            state.set_focus(name);
+            Action::Render
+        });
+
+        let events = tokio_stream::iter(vec![
+            Ok(key(KeyCode::Char('x'), KeyModifiers::none())),
+            Ok(Event::Custom("logs".to_string())),
+            Ok(key(KeyCode::Char('c'), KeyModifiers::ctrl())),
+        ]);
+        engine
+            .run_async_with(events, RunOptions::default())
+            .await
+            .unwrap();
+
+        assert!(engine.state().is_focused("logs"));
+    }
+
+    #[tokio::test]
+    async fn test_run_async_ticks_after_stream_ends() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        // Quit from the third frame; only ticks can cause the later frames
+        let handle = engine.handle();
+        let frames = std::sync::atomic::AtomicUsize::new(0);
+        engine.set_render_callback(move |_, _, _| {
+            if frames.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 2 {
+                handle.quit();
+            }
+        });
+
+        let options = RunOptions::default().tick_rate(Duration::from_millis(5));
+        let events = tokio_stream::empty();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            engine.run_async_with(events, options),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(draws(&engine), 3);
+    }
+
+    #[tokio::test]
+    async fn test_run_async_zero_tick_rate() {
+        let renderer = TestRenderer::new(40, 10);
+        let mut engine = DashboardEngine::new(renderer, PathBuf::from("."));
+
+        let handle = engine.handle();
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            handle.quit();
+        });
+
+        // A zero tick rate disables ticks, as in the blocking loop
+        let options = RunOptions::default().tick_rate(Duration::ZERO);
+        let events = tokio_stream::pending::<EngineResult<Event>>();
+        engine.run_async_with(events, options).await.unwrap();
+        task.await.unwrap();
+
+        assert_eq!(draws(&engine), 1);
+    }
 }
@@ -25,6 +25,24 @@ pub enum EngineError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Script error: {0}")]
+    Script(#[from] ScriptError),
+
+    #[error("Script panicked: {0}")]
+    ScriptPanic(String),
+
+    #[error("Keymap error: {0}")]
+    Keymap(#[from] KeymapError),
+
+    #[error("Theme error: {path}: {reason}")]
+    Theme { path: PathBuf, reason: String },
+
+    #[error("State snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Recording error: {0}")]
+    Recording(#[from] RecordingError),
+
     #[error("Custom error: {0}")]
     Custom(String),
 }
@@ -44,7 +62,7 @@ pub enum LoadError {
     #[error("Failed to parse file: {path}: {reason}")]
     ParseFailed { path: PathBuf, reason: String },
 
-    #[error("Circular dependency detected: {0:?}")]
+    #[error("Circular dependency detected: {}", display_cycle(.0))]
     CircularDependency(Vec<PathBuf>),
 
     #[error("Invalid file format: {path}")]
@@ -57,6 +75,15 @@ pub enum LoadError {
     },
 }
 
+/// Format a dependency cycle as `a.fsx -> b.fsx -> a.fsx`.
+fn display_cycle(cycle: &[PathBuf]) -> String {
+    cycle
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
 /// Error type for file watching operations.
 #[derive(Debug, Error)]
 pub enum WatchError {
@@ -69,6 +96,9 @@ pub enum WatchError {
     #[error("Failed to unwatch path: {path}: {reason}")]
     UnwatchFailed { path: PathBuf, reason: String },
 
+    #[error("Invalid glob pattern `{pattern}`: {reason}")]
+    InvalidPattern { pattern: String, reason: String },
+
     #[error("Watcher channel closed")]
     ChannelClosed,
 
@@ -76,6 +106,134 @@ pub enum WatchError {
     Notify(#[from] notify::Error),
 }
 
+/// Error type for key binding configuration.
+#[derive(Debug, Error)]
+pub enum KeymapError {
+    #[error("Invalid key `{key}`: {reason}")]
+    InvalidKey { key: String, reason: String },
+
+    #[error("Failed to read keymap file: {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse keymap: {0}")]
+    ParseFailed(String),
+}
+
+/// Error type for saving and restoring state snapshots.
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Failed to read state file: {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write state file: {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse state: {0}")]
+    ParseFailed(String),
+
+    #[error("Failed to serialize state: {0}")]
+    SerializeFailed(String),
+}
+
+/// Error type for saving, loading and replaying session recordings.
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("Failed to read recording: {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write recording: {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse recording: {0}")]
+    ParseFailed(String),
+
+    #[error("Failed to serialize recording: {0}")]
+    SerializeFailed(String),
+
+    #[error("Unsupported recording version {found}, expected at most {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+/// Error raised while parsing or evaluating a Fusabi script.
+///
+/// Carries the 1-based source position of the offending expression so the
+/// error overlay can point at it.
+#[derive(Debug, Clone, Error)]
+#[error("{message} (line {line}, column {column})")]
+pub struct ScriptError {
+    /// Whether the error happened while parsing or while running the script.
+    pub kind: ScriptErrorKind,
+
+    /// Human readable description of the problem.
+    pub message: String,
+
+    /// Script file the error belongs to, if known.
+    pub path: Option<PathBuf>,
+
+    /// 1-based line number.
+    pub line: usize,
+
+    /// 1-based column number.
+    pub column: usize,
+}
+
+/// The phase in which a [`ScriptError`] was raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptErrorKind {
+    /// The source could not be tokenized or parsed.
+    Syntax,
+
+    /// Evaluation of a well-formed script failed.
+    Runtime,
+}
+
+impl ScriptError {
+    /// Create a syntax error at the given position.
+    pub fn syntax(message: impl Into<String>, line: usize, column: usize) -> Self {
+        Self {
+            kind: ScriptErrorKind::Syntax,
+            message: message.into(),
+            path: None,
+            line,
+            column,
+        }
+    }
+
+    /// Create a runtime error at the given position.
+    pub fn runtime(message: impl Into<String>, line: usize, column: usize) -> Self {
+        Self {
+            kind: ScriptErrorKind::Runtime,
+            message: message.into(),
+            path: None,
+            line,
+            column,
+        }
+    }
+
+    /// Attach the script path, keeping an already recorded one.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.into());
+        }
+        self
+    }
+}
+
 /// Result type using EngineError.
 pub type EngineResult<T> = Result<T, EngineError>;
 
@@ -84,3 +242,15 @@ pub type LoadResult<T> = Result<T, LoadError>;
 
 /// Result type using WatchError.
 pub type WatchResult<T> = Result<T, WatchError>;
+
+/// Result type using KeymapError.
+pub type KeymapResult<T> = Result<T, KeymapError>;
+
+/// Result type using SnapshotError.
+pub type SnapshotResult<T> = Result<T, SnapshotError>;
+
+/// Result type using RecordingError.
+pub type RecordingResult<T> = Result<T, RecordingError>;
+
+/// Result type using ScriptError.
+pub type ScriptResult<T> = Result<T, ScriptError>;
@@ -1,9 +1,15 @@
 //! Event handling and action types for the Fusabi TUI engine.
 
-use std::path::PathBuf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::focus::FocusDirection;
+use crate::watcher::FileChange;
 
 /// Input events that the dashboard engine can process.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Event {
     /// Keyboard input event.
     Key(KeyEvent),
@@ -15,7 +21,7 @@ pub enum Event {
     Resize(u16, u16),
 
     /// File change notification from the file watcher.
-    FileChange(PathBuf),
+    FileChange(FileChange),
 
     /// Periodic tick event for animations or timed updates.
     Tick,
@@ -26,6 +32,7 @@ pub enum Event {
 
 /// Keyboard event details.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyEvent {
     /// The key code that was pressed.
     pub code: KeyCode,
@@ -36,6 +43,8 @@ pub struct KeyEvent {
 
 /// Key codes for keyboard input.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum KeyCode {
     /// Character keys.
     Char(char),
@@ -85,6 +94,7 @@ pub enum KeyCode {
 
 /// Modifier keys for keyboard events.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyModifiers {
     pub shift: bool,
     pub ctrl: bool,
@@ -136,6 +146,7 @@ impl KeyModifiers {
 
 /// Mouse event details.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MouseEvent {
     /// The type of mouse event.
     pub kind: MouseEventKind,
@@ -152,6 +163,8 @@ pub struct MouseEvent {
 
 /// Types of mouse events.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MouseEventKind {
     /// Mouse button was pressed.
     Down(MouseButton),
@@ -159,6 +172,9 @@ pub enum MouseEventKind {
     /// Mouse button was released.
     Up(MouseButton),
 
+    /// Mouse was moved with a button held down.
+    Drag(MouseButton),
+
     /// Mouse was moved.
     Moved,
 
@@ -169,6 +185,8 @@ pub enum MouseEventKind {
 
 /// Mouse buttons.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MouseButton {
     Left,
     Right,
@@ -190,6 +208,21 @@ pub enum Action {
     /// Request to quit the application.
     Quit,
 
+    /// Dismiss the error overlay, if one is shown.
+    DismissError,
+
+    /// Show or hide the key binding help overlay.
+    ToggleHelp,
+
+    /// Move focus to the next widget in Tab order.
+    FocusNext,
+
+    /// Move focus to the previous widget in Tab order.
+    FocusPrevious,
+
+    /// Move focus to the nearest widget in a direction.
+    Focus(FocusDirection),
+
     /// Custom action with string identifier.
     Custom(String),
 
@@ -208,6 +241,8 @@ impl Action {
         match self {
             Action::Render => true,
             Action::Reload => true,
+            Action::DismissError | Action::ToggleHelp => true,
+            Action::FocusNext | Action::FocusPrevious | Action::Focus(_) => true,
             Action::Batch(actions) => actions.iter().any(|a| a.requires_render()),
             _ => false,
         }
@@ -0,0 +1,374 @@
+//! Focus tracking and traversal between widgets.
+//!
+//! Widgets that can take focus register their id and area in the
+//! [`FocusRing`] of the [`DashboardState`] while they render. The ring keeps
+//! them in render order, which is the Tab order, and knows where they are on
+//! screen for directional navigation with the arrow keys.
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_core::layout::Rect;
+//! use fusabi_tui_engine::focus::FocusDirection;
+//! use fusabi_tui_engine::state::DashboardState;
+//!
+//! let mut state = DashboardState::new();
+//!
+//! // Normally done by widgets while rendering
+//! state.focus_ring.register("sidebar", Rect::new(0, 0, 20, 24));
+//! state.focus_ring.register("logs", Rect::new(20, 0, 60, 12));
+//! state.focus_ring.register("metrics", Rect::new(20, 12, 60, 12));
+//!
+//! state.focus_next();
+//! assert!(state.is_focused("sidebar"));
+//! state.focus_direction(FocusDirection::Right);
+//! assert!(state.is_focused("logs"));
+//! state.focus_direction(FocusDirection::Down);
+//! assert!(state.is_focused("metrics"));
+//! ```
+
+use std::cell::RefCell;
+
+use fusabi_tui_core::layout::Rect;
+use fusabi_tui_core::style::Style;
+use fusabi_tui_core::theme::Theme;
+
+use crate::state::DashboardState;
+
+/// A direction for moving focus between widgets on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FocusDirection {
+    /// Towards the top of the screen.
+    Up,
+
+    /// Towards the bottom of the screen.
+    Down,
+
+    /// Towards the left of the screen.
+    Left,
+
+    /// Towards the right of the screen.
+    Right,
+}
+
+/// A focusable widget and the area it was rendered to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusTarget {
+    /// Widget id, as used with [`DashboardState::set_focus`].
+    pub id: String,
+
+    /// Where the widget was rendered.
+    pub area: Rect,
+}
+
+/// The focusable widgets of the last rendered frame, in Tab order.
+///
+/// Registration takes `&self` so widgets can register while rendering, when
+/// they only have shared access to the [`DashboardState`]. The engine clears
+/// the ring at the start of every frame.
+#[derive(Debug, Default)]
+pub struct FocusRing {
+    targets: RefCell<Vec<FocusTarget>>,
+}
+
+impl FocusRing {
+    /// Create an empty focus ring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a focusable widget.
+    ///
+    /// Widgets are traversed in the order they are first registered.
+    /// Registering an id again updates its area but keeps its position.
+    pub fn register(&self, id: impl Into<String>, area: Rect) {
+        let id = id.into();
+        let mut targets = self.targets.borrow_mut();
+        match targets.iter_mut().find(|t| t.id == id) {
+            Some(target) => target.area = area,
+            None => targets.push(FocusTarget { id, area }),
+        }
+    }
+
+    /// Remove all widgets.
+    pub fn clear(&self) {
+        self.targets.borrow_mut().clear();
+    }
+
+    /// Get the registered widgets in Tab order.
+    pub fn targets(&self) -> Vec<FocusTarget> {
+        self.targets.borrow().clone()
+    }
+
+    /// Get the area a widget was registered with.
+    pub fn area(&self, id: &str) -> Option<Rect> {
+        self.targets
+            .borrow()
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| t.area)
+    }
+
+    /// Check if a widget is registered.
+    pub fn contains(&self, id: &str) -> bool {
+        self.area(id).is_some()
+    }
+
+    /// Get the number of registered widgets.
+    pub fn len(&self) -> usize {
+        self.targets.borrow().len()
+    }
+
+    /// Check if no widgets are registered.
+    pub fn is_empty(&self) -> bool {
+        self.targets.borrow().is_empty()
+    }
+
+    /// Get the widget after `current` in Tab order, wrapping around.
+    ///
+    /// Returns the first widget if `current` is `None` or not registered.
+    pub fn next(&self, current: Option<&str>) -> Option<String> {
+        let targets = self.targets.borrow();
+        let index = match current.and_then(|id| targets.iter().position(|t| t.id == id)) {
+            Some(i) => (i + 1) % targets.len(),
+            None => 0,
+        };
+        targets.get(index).map(|t| t.id.clone())
+    }
+
+    /// Get the widget before `current` in Tab order, wrapping around.
+    ///
+    /// Returns the last widget if `current` is `None` or not registered.
+    pub fn previous(&self, current: Option<&str>) -> Option<String> {
+        let targets = self.targets.borrow();
+        let index = match current.and_then(|id| targets.iter().position(|t| t.id == id)) {
+            Some(i) => i.checked_sub(1).unwrap_or(targets.len() - 1),
+            None => targets.len().checked_sub(1)?,
+        };
+        targets.get(index).map(|t| t.id.clone())
+    }
+
+    /// Get the nearest widget in `direction` from `current`.
+    ///
+    /// Only widgets entirely past the edge of `current` in that direction are
+    /// candidates. Widgets that line up with `current` are preferred, then
+    /// the closest one. Returns `None` if there is no such widget or
+    /// `current` is not registered.
+    pub fn in_direction(&self, current: &str, direction: FocusDirection) -> Option<String> {
+        let from = self.area(current)?;
+        let targets = self.targets.borrow();
+        targets
+            .iter()
+            .filter(|t| t.id != current)
+            .filter_map(|t| score(from, t.area, direction).map(|s| (s, t)))
+            .min_by_key(|(s, _)| *s)
+            .map(|(_, t)| t.id.clone())
+    }
+}
+
+impl Clone for FocusRing {
+    fn clone(&self) -> Self {
+        Self {
+            targets: RefCell::new(self.targets.borrow().clone()),
+        }
+    }
+}
+
+/// Rank `to` as a focus target in `direction` from `from`; lower is better.
+///
+/// The score is (distance across the direction, distance along it, offset
+/// between the centers), or `None` if `to` is not in that direction.
+fn score(from: Rect, to: Rect, direction: FocusDirection) -> Option<(u16, u16, u16)> {
+    let (gap, from_span, to_span) = match direction {
+        FocusDirection::Right if to.left() >= from.right() => (
+            to.left() - from.right(),
+            (from.top(), from.bottom()),
+            (to.top(), to.bottom()),
+        ),
+        FocusDirection::Left if to.right() <= from.left() => (
+            from.left() - to.right(),
+            (from.top(), from.bottom()),
+            (to.top(), to.bottom()),
+        ),
+        FocusDirection::Down if to.top() >= from.bottom() => (
+            to.top() - from.bottom(),
+            (from.left(), from.right()),
+            (to.left(), to.right()),
+        ),
+        FocusDirection::Up if to.bottom() <= from.top() => (
+            from.top() - to.bottom(),
+            (from.left(), from.right()),
+            (to.left(), to.right()),
+        ),
+        _ => return None,
+    };
+
+    // Distance between the spans across the direction, zero if they overlap
+    let across = if to_span.1 <= from_span.0 {
+        from_span.0 - to_span.1
+    } else {
+        to_span.0.saturating_sub(from_span.1)
+    };
+    let from_center = (u32::from(from_span.0) + u32::from(from_span.1)) / 2;
+    let to_center = (u32::from(to_span.0) + u32::from(to_span.1)) / 2;
+    let offset = from_center.abs_diff(to_center) as u16;
+
+    Some((across, gap, offset))
+}
+
+/// Styles for focused and unfocused widgets.
+///
+/// # Example
+///
+/// ```
+/// use fusabi_tui_core::style::{Color, Style};
+/// use fusabi_tui_engine::focus::FocusStyle;
+/// use fusabi_tui_engine::state::DashboardState;
+///
+/// let styles = FocusStyle::new(Style::new().fg(Color::Yellow), Style::new().fg(Color::DarkGray));
+/// let mut state = DashboardState::new();
+/// state.set_focus("logs");
+///
+/// assert_eq!(styles.resolve(&state, "logs"), Style::new().fg(Color::Yellow));
+/// assert_eq!(styles.resolve(&state, "cpu"), Style::new().fg(Color::DarkGray));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FocusStyle {
+    /// Style for the focused widget.
+    pub focused: Style,
+
+    /// Style for all other widgets.
+    pub unfocused: Style,
+}
+
+impl FocusStyle {
+    /// Create focus styles.
+    pub fn new(focused: Style, unfocused: Style) -> Self {
+        Self { focused, unfocused }
+    }
+
+    /// Use the theme's `focus` style for the focused widget and its `border`
+    /// style for the others.
+    pub fn from_theme(theme: &Theme) -> Self {
+        Self::new(theme.get_style("focus"), theme.get_style("border"))
+    }
+
+    /// Get the style for `widget_id`.
+    pub fn resolve(&self, state: &DashboardState, widget_id: &str) -> Style {
+        if state.is_focused(widget_id) {
+            self.focused
+        } else {
+            self.unfocused
+        }
+    }
+
+    /// Get `base` patched with the style for `widget_id`.
+    pub fn apply(&self, state: &DashboardState, widget_id: &str, base: Style) -> Style {
+        base.patch(self.resolve(state, widget_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusabi_tui_core::style::Color;
+
+    /// Two columns: `a` on the left, `b` above `c` on the right.
+    fn ring() -> FocusRing {
+        let ring = FocusRing::new();
+        ring.register("a", Rect::new(0, 0, 10, 20));
+        ring.register("b", Rect::new(10, 0, 30, 10));
+        ring.register("c", Rect::new(10, 10, 30, 10));
+        ring
+    }
+
+    #[test]
+    fn test_register_keeps_order() {
+        let ring = ring();
+        ring.register("a", Rect::new(0, 0, 5, 5));
+
+        let ids: Vec<_> = ring.targets().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(ring.area("a"), Some(Rect::new(0, 0, 5, 5)));
+
+        ring.clear();
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn test_next_and_previous() {
+        let ring = ring();
+        assert_eq!(ring.next(None).as_deref(), Some("a"));
+        assert_eq!(ring.next(Some("b")).as_deref(), Some("c"));
+        assert_eq!(ring.next(Some("c")).as_deref(), Some("a"));
+        assert_eq!(ring.previous(None).as_deref(), Some("c"));
+        assert_eq!(ring.previous(Some("a")).as_deref(), Some("c"));
+        assert_eq!(ring.next(Some("gone")).as_deref(), Some("a"));
+
+        let empty = FocusRing::new();
+        assert_eq!(empty.next(None), None);
+        assert_eq!(empty.previous(None), None);
+    }
+
+    #[test]
+    fn test_in_direction() {
+        let ring = ring();
+        assert_eq!(
+            ring.in_direction("a", FocusDirection::Right).as_deref(),
+            Some("b")
+        );
+        assert_eq!(
+            ring.in_direction("b", FocusDirection::Down).as_deref(),
+            Some("c")
+        );
+        assert_eq!(
+            ring.in_direction("c", FocusDirection::Up).as_deref(),
+            Some("b")
+        );
+        assert_eq!(
+            ring.in_direction("c", FocusDirection::Left).as_deref(),
+            Some("a")
+        );
+        assert_eq!(ring.in_direction("a", FocusDirection::Left), None);
+        assert_eq!(ring.in_direction("a", FocusDirection::Up), None);
+    }
+
+    #[test]
+    fn test_in_direction_prefers_aligned() {
+        let ring = FocusRing::new();
+        ring.register("from", Rect::new(0, 10, 10, 5));
+        ring.register("near_diagonal", Rect::new(12, 0, 10, 5));
+        ring.register("far_aligned", Rect::new(30, 10, 10, 5));
+
+        assert_eq!(
+            ring.in_direction("from", FocusDirection::Right).as_deref(),
+            Some("far_aligned")
+        );
+    }
+
+    #[test]
+    fn test_focus_style() {
+        let styles = FocusStyle::new(
+            Style::new().fg(Color::Yellow),
+            Style::new().fg(Color::DarkGray),
+        );
+        let mut state = DashboardState::new();
+        state.set_focus("a");
+
+        let base = Style::new().bg(Color::Black);
+        assert_eq!(
+            styles.apply(&state, "a", base),
+            Style::new().fg(Color::Yellow).bg(Color::Black)
+        );
+        assert_eq!(
+            styles.resolve(&state, "b"),
+            Style::new().fg(Color::DarkGray)
+        );
+
+        let theme = Theme::dark();
+        assert_eq!(
+            FocusStyle::from_theme(&theme).focused,
+            theme.get_style("focus")
+        );
+    }
+}
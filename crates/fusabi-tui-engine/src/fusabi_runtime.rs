@@ -13,6 +13,15 @@
 //! 4. The `render` function in the script is called each frame
 //! 5. Widget operations are translated to buffer mutations
 //!
+//! # Error Isolation
+//!
+//! A script that fails to evaluate does not replace the one that is running:
+//! the context keeps the last version that evaluated successfully, and keeps
+//! rendering it until a fixed version is evaluated. Panics raised while
+//! evaluating or rendering a script, such as a bug in a host function, are
+//! caught and returned as [`EngineError::ScriptPanic`] instead of taking down
+//! the host process.
+//!
 //! # Host Functions
 //!
 //! The following host function modules are registered:
@@ -22,6 +31,8 @@
 //! - `tui.layout` - Rect and constraint-based layouts
 //! - `tui.widget` - Widget creation (Block, Paragraph, List, etc.)
 //! - `tui.buffer` - Direct buffer manipulation
+//! - `tui.state` - Custom values kept in the dashboard state
+//! - `tui.theme` - Styles and palette colors of the dashboard's theme
 //!
 //! # Example
 //!
@@ -30,22 +41,38 @@
 //! #load "tui.fsx"
 //!
 //! let render buffer area state =
-//!     let block = Block.create()
-//!         |> Block.title "My Dashboard"
-//!         |> Block.borders Borders.ALL
-//!     block |> Widget.render buffer area
+//!     let block =
+//!         tui.widget.block ()
+//!         |> tui.widget.blockTitle "My Dashboard"
+//!         |> tui.widget.blockBorders "all"
+//!     tui.widget.render buffer area block
 //! ```
 
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+mod ast;
+mod host;
+mod interpreter;
+mod lexer;
+mod parser;
+mod value;
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Once};
 
 use fusabi_tui_core::buffer::Buffer;
 use fusabi_tui_core::layout::Rect;
-use fusabi_tui_core::style::{Color, Modifier, Style};
+use fusabi_tui_core::theme::Theme;
+
+use crate::error::{EngineError, EngineResult, LoadError, ScriptError, ScriptResult};
+use crate::loader::FileLoader;
+use crate::state::{DashboardState, StateValue, WidgetState};
 
-use crate::error::{EngineError, EngineResult};
-use crate::state::DashboardState;
+use self::ast::Program;
+use self::interpreter::Interpreter;
+use self::value::{HostFn, Value};
 
 /// Context for Fusabi script evaluation.
 ///
@@ -65,6 +92,20 @@ pub struct FusabiContext {
 
     /// Whether the context has been successfully initialized.
     initialized: bool,
+
+    /// Whether the last evaluation failed and an earlier version of the
+    /// script is still running.
+    stale: bool,
+
+    /// Theme read by `tui.theme`, kept across evaluations.
+    theme: Arc<Theme>,
+
+    /// Interpreter holding host functions and the script's top-level bindings.
+    interpreter: Interpreter,
+
+    /// States for widgets rendered with `tui.widget.renderWithId` that the
+    /// dashboard had no state for.
+    widget_states: Vec<(String, WidgetState)>,
 }
 
 /// A compiled Fusabi module ready for execution.
@@ -78,27 +119,29 @@ pub struct CompiledModule {
 
     /// Dependencies loaded by this module.
     pub dependencies: Vec<PathBuf>,
+
+    /// Parsed program, reused while the source hash is unchanged.
+    pub(crate) program: Arc<Program>,
 }
 
 impl FusabiContext {
     /// Create a new Fusabi context for the given entry file.
     ///
     /// This initializes the Fusabi engine and registers all TUI host functions.
-    /// The script is not evaluated until [`evaluate`] is called.
+    /// The script is not evaluated until [`evaluate`](Self::evaluate) is called.
     pub fn new(entry_file: PathBuf) -> Self {
         let mut ctx = Self {
             entry_file,
             module_cache: HashMap::new(),
             registered_functions: Vec::new(),
             initialized: false,
+            stale: false,
+            theme: Arc::default(),
+            interpreter: Interpreter::new(),
+            widget_states: Vec::new(),
         };
 
-        // Register host functions
-        ctx.register_color_functions();
-        ctx.register_style_functions();
-        ctx.register_layout_functions();
-        ctx.register_widget_functions();
-        ctx.register_buffer_functions();
+        ctx.register_host_functions();
 
         ctx
     }
@@ -106,7 +149,14 @@ impl FusabiContext {
     /// Evaluate the entry script and all its dependencies.
     ///
     /// This compiles and executes the Fusabi script, making the `render`
-    /// function available for subsequent calls.
+    /// function available for subsequent calls. `#load` dependencies are read
+    /// from disk relative to the loading file and evaluated first, each at
+    /// most once; [`evaluate_with`](Self::evaluate_with) takes them from a
+    /// [`FileLoader`] instead.
+    ///
+    /// If evaluation fails, the context keeps the script it evaluated last,
+    /// so a dashboard that was running keeps rendering its last good version
+    /// and [`is_stale`](Self::is_stale) returns `true`.
     ///
     /// # Errors
     ///
@@ -115,21 +165,154 @@ impl FusabiContext {
     /// - The script contains syntax errors
     /// - A required dependency is missing
     /// - Runtime evaluation fails
+    /// - Evaluation panics, as [`EngineError::ScriptPanic`]
     pub fn evaluate(&mut self, source: &str) -> EngineResult<()> {
-        // TODO: Integrate with Fusabi v0.34+ engine
-        // 1. Create Engine instance
-        // 2. Register host functions via call_host API
-        // 3. Evaluate source with module namespace
-        // 4. Cache compiled bytecode
+        self.evaluate_sources(source, None)
+    }
 
-        self.initialized = true;
-        Ok(())
+    /// Evaluate the entry script like [`evaluate`](Self::evaluate), taking
+    /// the sources of `#load` dependencies from the loader's cache.
+    ///
+    /// Dependencies the loader has not loaded are read from disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`evaluate`](Self::evaluate).
+    pub fn evaluate_with(&mut self, source: &str, loader: &FileLoader) -> EngineResult<()> {
+        self.evaluate_sources(source, Some(loader))
+    }
+
+    fn evaluate_sources(&mut self, source: &str, loader: Option<&FileLoader>) -> EngineResult<()> {
+        // Start from a clean global scope so bindings removed from the script
+        // do not survive a reload. The previous scope is put back on failure.
+        let previous = std::mem::take(&mut self.interpreter);
+        self.interpreter.theme = Arc::clone(&self.theme);
+        self.register_host_functions();
+
+        let entry = self.entry_file.clone();
+        let mut loaded = HashSet::new();
+        loaded.insert(entry.clone());
+        let mut stack = vec![entry.clone()];
+        let result = catch_panic(|| {
+            self.evaluate_module(&entry, source, loader, &mut loaded, &mut stack)
+        });
+
+        match result {
+            Ok(Ok(())) => {
+                self.initialized = true;
+                self.stale = false;
+                Ok(())
+            }
+            Ok(Err(err)) => {
+                self.restore(previous);
+                Err(err.into())
+            }
+            Err(message) => {
+                self.restore(previous);
+                Err(EngineError::ScriptPanic(message))
+            }
+        }
+    }
+
+    /// Go back to the interpreter of the last successful evaluation.
+    fn restore(&mut self, previous: Interpreter) {
+        self.interpreter = previous;
+        self.stale = self.initialized;
+    }
+
+    /// Evaluate a module after its `#load` dependencies.
+    ///
+    /// Dependencies are taken from `loader` when it has them cached. `loaded`
+    /// holds every module evaluated so far, which are not evaluated again,
+    /// and `stack` the chain of modules being loaded, ending in `path`.
+    fn evaluate_module(
+        &mut self,
+        path: &Path,
+        source: &str,
+        loader: Option<&FileLoader>,
+        loaded: &mut HashSet<PathBuf>,
+        stack: &mut Vec<PathBuf>,
+    ) -> ScriptResult<()> {
+        let program = self.compile(path, source)?;
+        let parent = path.parent().unwrap_or(Path::new("."));
+
+        for (dep, line, column) in &program.loads {
+            let dep_path = parent.join(dep);
+            let dep_path = dep_path.canonicalize().unwrap_or(dep_path);
+            if let Some(start) = stack.iter().position(|p| *p == dep_path) {
+                let cycle = LoadError::CircularDependency(
+                    stack[start..].iter().chain([&dep_path]).cloned().collect(),
+                );
+                return Err(ScriptError::runtime(cycle.to_string(), *line, *column)
+                    .with_path(path));
+            }
+            if !loaded.insert(dep_path.clone()) {
+                continue;
+            }
+
+            let cached = loader.and_then(|loader| loader.get(&dep_path));
+            let dep_source = match cached {
+                Some(file) => Cow::Borrowed(file.content.as_str()),
+                None => Cow::Owned(std::fs::read_to_string(&dep_path).map_err(|e| {
+                    ScriptError::runtime(format!("cannot load \"{}\": {}", dep, e), *line, *column)
+                        .with_path(path)
+                })?),
+            };
+            stack.push(dep_path.clone());
+            self.evaluate_module(&dep_path, &dep_source, loader, loaded, stack)?;
+            stack.pop();
+        }
+
+        self.interpreter
+            .run(&program)
+            .map_err(|e| e.with_path(path))
+    }
+
+    /// Parse a module, reusing the cached program if its source is unchanged.
+    fn compile(&mut self, path: &Path, source: &str) -> ScriptResult<Arc<Program>> {
+        let source_hash = hash_source(source);
+        if let Some(module) = self.module_cache.get(path) {
+            if module.source_hash == source_hash {
+                return Ok(Arc::clone(&module.program));
+            }
+        }
+
+        let program = Arc::new(parser::parse(source).map_err(|e| e.with_path(path))?);
+        self.module_cache.insert(
+            path.to_path_buf(),
+            CompiledModule {
+                path: path.to_path_buf(),
+                source_hash,
+                dependencies: parse_load_directives(source, path),
+                program: Arc::clone(&program),
+            },
+        );
+        Ok(program)
+    }
+
+    /// Check whether the evaluated script defines a `render` function.
+    pub fn has_render(&self) -> bool {
+        self.initialized
+            && self
+                .interpreter
+                .global("render")
+                .is_some_and(Value::is_function)
     }
 
     /// Render the dashboard by calling the script's render function.
     ///
     /// This invokes the `render` function defined in the Fusabi script,
-    /// passing the buffer, area, and state as arguments.
+    /// passing the buffer, area, and state as arguments. The state is exposed
+    /// to the script as a record with a `focus` field (empty when nothing is
+    /// focused), a `widgets` record mapping widget ids to
+    /// `{ selected; offset }` (`selected` is `-1` when nothing is selected),
+    /// and a `custom` record with the [custom values](DashboardState::custom).
+    /// Scripts change custom values with `tui.state.set key value` and read
+    /// them with `tui.state.get key default`; see
+    /// [`take_custom_state`](Self::take_custom_state).
+    ///
+    /// If `render` returns a widget instead of drawing into the buffer itself,
+    /// the widget is rendered into `area`.
     ///
     /// # Arguments
     ///
@@ -139,7 +322,9 @@ impl FusabiContext {
     ///
     /// # Errors
     ///
-    /// Returns an error if the render function fails or is not defined.
+    /// Returns an error if the render function fails or is not defined, or
+    /// [`EngineError::ScriptPanic`] if it panics. The buffer keeps whatever
+    /// was drawn before the failure.
     pub fn render(
         &mut self,
         buffer: &mut Buffer,
@@ -152,23 +337,107 @@ impl FusabiContext {
             ));
         }
 
-        // TODO: Call Fusabi's render function
-        // 1. Set current buffer in thread-local or context
-        // 2. Call "render" function with (buffer, area, state)
-        // 3. Collect any widget render operations
-        // 4. Apply operations to buffer
+        let render = match self.interpreter.global("render") {
+            Some(render) if render.is_function() => render.clone(),
+            _ => {
+                return Err(ScriptError::runtime("script does not define `render`", 1, 1)
+                    .with_path(&self.entry_file)
+                    .into())
+            }
+        };
+
+        // Lend the buffer to the interpreter for the duration of the call.
+        let owned = std::mem::replace(buffer, Buffer::empty(Rect::default()));
+        self.interpreter.buffer = Some(owned);
+        self.interpreter.custom.clone_from(&state.custom);
+        self.interpreter.custom_changed = false;
+        self.interpreter.selections = widget_selections(state);
+
+        let args = vec![Value::Buffer, Value::Rect(area), state_to_value(state)];
+        let result = catch_panic(|| self.interpreter.apply(&render, args));
+
+        if let Some(owned) = self.interpreter.buffer.take() {
+            *buffer = owned;
+        }
+        for widget in std::mem::take(&mut self.interpreter.rendered) {
+            state.focus_ring.register(widget.id.clone(), widget.area);
+            state.hit_map.widget(widget.id.clone(), widget.area);
+            for (area, kind) in widget.parts {
+                state.hit_map.register(widget.id.clone(), area, kind);
+            }
+            if let Some(widget_state) = widget.state {
+                self.widget_states.push((widget.id, widget_state));
+            }
+        }
+
+        let result = result.map_err(|message| {
+            self.interpreter.reset_calls();
+            EngineError::ScriptPanic(message)
+        })?;
+        if let Value::Widget(widget) = result.map_err(|e| e.with_path(&self.entry_file))? {
+            catch_panic(|| host::render_widget(&widget, area, buffer))
+                .map_err(EngineError::ScriptPanic)?
+                .map_err(|e| e.with_path(&self.entry_file))?;
+        }
 
         Ok(())
     }
 
+    /// Take the custom values if the last [`render`](Self::render) changed
+    /// them with `tui.state.set`.
+    ///
+    /// The engine copies them back into the [`DashboardState`], so they
+    /// survive reloads and are included in snapshots.
+    pub fn take_custom_state(&mut self) -> Option<BTreeMap<String, StateValue>> {
+        if !self.interpreter.custom_changed {
+            return None;
+        }
+        self.interpreter.custom_changed = false;
+        Some(std::mem::take(&mut self.interpreter.custom))
+    }
+
+    /// Take the states of the widgets that the last [`render`](Self::render)
+    /// drew with `tui.widget.renderWithId` and the dashboard had no state for.
+    ///
+    /// The engine adds them to [`DashboardState::widgets`], so that keys and
+    /// clicks can move their selection.
+    pub fn take_widget_states(&mut self) -> Vec<(String, WidgetState)> {
+        std::mem::take(&mut self.widget_states)
+    }
+
+    /// Set the theme scripts read with `tui.theme.style` and
+    /// `tui.theme.color`.
+    ///
+    /// The theme applies from the next [`render`](Self::render) on, without
+    /// evaluating the script again.
+    pub fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.interpreter.theme = Arc::clone(&theme);
+        self.theme = theme;
+    }
+
+    /// Get the theme scripts read with `tui.theme`.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Get the compiled module for a path, if it is cached.
+    pub fn module(&self, path: &Path) -> Option<&CompiledModule> {
+        self.module_cache.get(path)
+    }
+
     /// Invalidate cached modules for the given paths.
     ///
-    /// Called when files change to trigger recompilation.
+    /// Called when files change to trigger recompilation, with the changed
+    /// file and its dependents as returned by
+    /// [`FileLoader::invalidate`](crate::loader::FileLoader::invalidate).
+    /// Modules that are not invalidated stay cached, and are reused as long
+    /// as their [source hash](hash_source) matches.
+    ///
+    /// The evaluated script keeps rendering until it is evaluated again.
     pub fn invalidate(&mut self, paths: &[PathBuf]) {
         for path in paths {
             self.module_cache.remove(path);
         }
-        self.initialized = false;
     }
 
     /// Check if the context is ready for rendering.
@@ -176,6 +445,12 @@ impl FusabiContext {
         self.initialized
     }
 
+    /// Check if the last [`evaluate`](Self::evaluate) failed while an
+    /// earlier version of the script kept running.
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
     /// Get the list of registered host functions (for debugging).
     pub fn registered_functions(&self) -> &[String] {
         &self.registered_functions
@@ -185,139 +460,291 @@ impl FusabiContext {
     // Host Function Registration
     // =========================================================================
 
+    fn register_host_functions(&mut self) {
+        self.registered_functions.clear();
+        self.register_color_functions();
+        self.register_style_functions();
+        self.register_layout_functions();
+        self.register_widget_functions();
+        self.register_buffer_functions();
+        self.register_state_functions();
+        self.register_theme_functions();
+        self.register_library_functions();
+    }
+
+    fn register(&mut self, name: &'static str, arity: usize, func: HostFn) {
+        self.interpreter.define_host(name, arity, func);
+        self.registered_functions.push(name.to_string());
+    }
+
+    fn register_value(&mut self, name: String, value: Value) {
+        self.registered_functions.push(name.clone());
+        self.interpreter.define(name, value);
+    }
+
     fn register_color_functions(&mut self) {
-        // tui.color.rgb(r, g, b) -> Color
-        self.registered_functions.push("tui.color.rgb".to_string());
+        // tui.color.rgb r g b -> Color
+        self.register("tui.color.rgb", 3, host::color_rgb);
+
+        // tui.color.indexed i -> Color
+        self.register("tui.color.indexed", 1, host::color_indexed);
+
+        // tui.color.reset () -> Color
+        self.register("tui.color.reset", 1, host::color_reset);
+
+        // tui.color.hsl h s l -> Color
+        self.register("tui.color.hsl", 3, host::color_hsl);
+
+        // tui.color.oklch l c h -> Color
+        self.register("tui.color.oklch", 3, host::color_oklch);
+
+        // tui.color.toRgb color -> (int, int, int)
+        self.register("tui.color.toRgb", 1, host::color_to_rgb);
+
+        // tui.color.toHsl color -> (float, float, float)
+        self.register("tui.color.toHsl", 1, host::color_to_hsl);
+
+        // tui.color.toOklch color -> (float, float, float)
+        self.register("tui.color.toOklch", 1, host::color_to_oklch);
+
+        // tui.color.lighten amount color -> Color
+        self.register("tui.color.lighten", 2, host::color_lighten);
 
-        // tui.color.indexed(i) -> Color
-        self.registered_functions
-            .push("tui.color.indexed".to_string());
+        // tui.color.darken amount color -> Color
+        self.register("tui.color.darken", 2, host::color_darken);
 
-        // tui.color.reset() -> Color
-        self.registered_functions.push("tui.color.reset".to_string());
+        // tui.color.mix other t color -> Color
+        self.register("tui.color.mix", 3, host::color_mix);
+
+        // tui.color.blend other alpha color -> Color
+        self.register("tui.color.blend", 3, host::color_blend);
+
+        // tui.color.contrast a b -> float
+        self.register("tui.color.contrast", 2, host::color_contrast);
+
+        // tui.color.gradient colors t -> Color
+        self.register("tui.color.gradient", 2, host::color_gradient);
+
+        // tui.color.gradientSteps colors count -> Color list
+        self.register("tui.color.gradientSteps", 2, host::color_gradient_steps);
 
         // Named colors
-        for name in &[
-            "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white", "darkGray",
-            "lightRed", "lightGreen", "lightYellow", "lightBlue", "lightMagenta", "lightCyan",
-        ] {
-            self.registered_functions
-                .push(format!("tui.color.{}", name));
+        for (name, color) in host::NAMED_COLORS {
+            self.register_value(format!("tui.color.{}", name), Value::Color(*color));
         }
     }
 
     fn register_style_functions(&mut self) {
-        // tui.style.new() -> Style
-        self.registered_functions.push("tui.style.new".to_string());
-
-        // tui.style.fg(color, style) -> Style
-        self.registered_functions.push("tui.style.fg".to_string());
-
-        // tui.style.bg(color, style) -> Style
-        self.registered_functions.push("tui.style.bg".to_string());
-
-        // Modifier functions
-        for modifier in &[
-            "bold",
-            "dim",
-            "italic",
-            "underlined",
-            "slowBlink",
-            "rapidBlink",
-            "reversed",
-            "hidden",
-            "crossedOut",
-        ] {
-            self.registered_functions
-                .push(format!("tui.style.{}", modifier));
-        }
+        // tui.style.new () -> Style
+        self.register("tui.style.new", 1, host::style_new);
+
+        // tui.style.fg color style -> Style
+        self.register("tui.style.fg", 2, host::style_fg);
+
+        // tui.style.bg color style -> Style
+        self.register("tui.style.bg", 2, host::style_bg);
+
+        // Modifier functions: tui.style.bold style -> Style
+        self.register("tui.style.bold", 1, host::style_bold);
+        self.register("tui.style.dim", 1, host::style_dim);
+        self.register("tui.style.italic", 1, host::style_italic);
+        self.register("tui.style.underlined", 1, host::style_underlined);
+        self.register("tui.style.slowBlink", 1, host::style_slow_blink);
+        self.register("tui.style.rapidBlink", 1, host::style_rapid_blink);
+        self.register("tui.style.reversed", 1, host::style_reversed);
+        self.register("tui.style.hidden", 1, host::style_hidden);
+        self.register("tui.style.crossedOut", 1, host::style_crossed_out);
+
+        // tui.style.underlineStyle name style -> Style
+        self.register("tui.style.underlineStyle", 2, host::style_underline_style);
+
+        // tui.style.underlineColor color style -> Style
+        self.register("tui.style.underlineColor", 2, host::style_underline_color);
     }
 
     fn register_layout_functions(&mut self) {
-        // tui.layout.rect(x, y, w, h) -> Rect
-        self.registered_functions
-            .push("tui.layout.rect".to_string());
+        // tui.layout.rect x y w h -> Rect
+        self.register("tui.layout.rect", 4, host::layout_rect);
 
-        // tui.layout.split(rect, direction, constraints) -> [Rect]
-        self.registered_functions
-            .push("tui.layout.split".to_string());
+        // tui.layout.split rect direction constraints -> [Rect]
+        self.register("tui.layout.split", 3, host::layout_split);
 
         // Constraint constructors
-        self.registered_functions
-            .push("tui.layout.length".to_string());
-        self.registered_functions
-            .push("tui.layout.percentage".to_string());
-        self.registered_functions
-            .push("tui.layout.ratio".to_string());
-        self.registered_functions
-            .push("tui.layout.fill".to_string());
-        self.registered_functions.push("tui.layout.min".to_string());
-        self.registered_functions.push("tui.layout.max".to_string());
+        self.register("tui.layout.length", 1, host::layout_length);
+        self.register("tui.layout.percentage", 1, host::layout_percentage);
+        self.register("tui.layout.ratio", 2, host::layout_ratio);
+        self.register("tui.layout.fill", 1, host::layout_fill);
+        self.register("tui.layout.min", 1, host::layout_min);
+        self.register("tui.layout.max", 1, host::layout_max);
+
+        // Directions
+        for (name, direction) in host::DIRECTIONS {
+            self.register_value(format!("tui.layout.{}", name), Value::Direction(*direction));
+        }
     }
 
     fn register_widget_functions(&mut self) {
         // Block widget
-        self.registered_functions
-            .push("tui.widget.block".to_string());
-        self.registered_functions
-            .push("tui.widget.blockTitle".to_string());
-        self.registered_functions
-            .push("tui.widget.blockBorders".to_string());
+        self.register("tui.widget.block", 1, host::widget_block);
+        self.register("tui.widget.blockTitle", 2, host::widget_block_title);
+        self.register("tui.widget.blockBorders", 2, host::widget_block_borders);
+        self.register("tui.widget.blockInner", 2, host::widget_block_inner);
 
         // Paragraph widget
-        self.registered_functions
-            .push("tui.widget.paragraph".to_string());
-        self.registered_functions
-            .push("tui.widget.paragraphAlignment".to_string());
+        self.register("tui.widget.paragraph", 1, host::widget_paragraph);
+        self.register(
+            "tui.widget.paragraphAlignment",
+            2,
+            host::widget_paragraph_alignment,
+        );
 
         // List widget
-        self.registered_functions
-            .push("tui.widget.list".to_string());
-        self.registered_functions
-            .push("tui.widget.listItem".to_string());
+        self.register("tui.widget.list", 1, host::widget_list);
+        self.register("tui.widget.listItem", 1, host::widget_list_item);
 
         // Gauge widget
-        self.registered_functions
-            .push("tui.widget.gauge".to_string());
-        self.registered_functions
-            .push("tui.widget.gaugePercent".to_string());
+        self.register("tui.widget.gauge", 1, host::widget_gauge);
+        self.register("tui.widget.gaugePercent", 2, host::widget_gauge_percent);
+        self.register("tui.widget.gaugeLabel", 2, host::widget_gauge_label);
 
         // Table widget
-        self.registered_functions
-            .push("tui.widget.table".to_string());
-        self.registered_functions
-            .push("tui.widget.tableRow".to_string());
+        self.register("tui.widget.table", 2, host::widget_table);
+        self.register("tui.widget.tableRow", 1, host::widget_table_row);
+        self.register("tui.widget.tableHeader", 2, host::widget_table_header);
 
         // Sparkline widget
-        self.registered_functions
-            .push("tui.widget.sparkline".to_string());
+        self.register("tui.widget.sparkline", 1, host::widget_sparkline);
 
         // Tabs widget
-        self.registered_functions
-            .push("tui.widget.tabs".to_string());
+        self.register("tui.widget.tabs", 1, host::widget_tabs);
+
+        // Common widget modifiers
+        self.register("tui.widget.withBlock", 2, host::widget_with_block);
+        self.register("tui.widget.style", 2, host::widget_style);
+        self.register("tui.widget.highlight", 2, host::widget_highlight);
+        self.register("tui.widget.select", 2, host::widget_select);
 
         // Render function
-        self.registered_functions
-            .push("tui.widget.render".to_string());
+        self.register("tui.widget.render", 3, host::widget_render);
+        // tui.widget.renderWithId id buffer area widget -> ()
+        self.register("tui.widget.renderWithId", 4, host::widget_render_with_id);
     }
 
     fn register_buffer_functions(&mut self) {
-        // tui.buffer.setString(x, y, text, style, buffer) -> ()
-        self.registered_functions
-            .push("tui.buffer.setString".to_string());
+        // tui.buffer.setString x y text style buffer -> int
+        self.register("tui.buffer.setString", 5, host::buffer_set_string);
+
+        // tui.buffer.setStyle area style buffer -> ()
+        self.register("tui.buffer.setStyle", 3, host::buffer_set_style);
+
+        // tui.buffer.setLink uri area buffer -> ()
+        self.register("tui.buffer.setLink", 3, host::buffer_set_link);
+
+        // tui.buffer.get x y buffer -> { symbol; fg; bg }
+        self.register("tui.buffer.get", 3, host::buffer_get);
+
+        // tui.buffer.clear buffer -> ()
+        self.register("tui.buffer.clear", 1, host::buffer_clear);
+    }
+
+    fn register_state_functions(&mut self) {
+        // tui.state.get key default -> value
+        self.register("tui.state.get", 2, host::state_get);
+
+        // tui.state.set key value -> ()
+        self.register("tui.state.set", 2, host::state_set);
+    }
+
+    fn register_theme_functions(&mut self) {
+        // tui.theme.style name -> Style
+        self.register("tui.theme.style", 1, host::theme_style);
 
-        // tui.buffer.setStyle(area, style, buffer) -> ()
-        self.registered_functions
-            .push("tui.buffer.setStyle".to_string());
+        // tui.theme.color name -> Color
+        self.register("tui.theme.color", 1, host::theme_color);
+
+        // tui.theme.name () -> string
+        self.register("tui.theme.name", 1, host::theme_name);
+    }
+
+    fn register_library_functions(&mut self) {
+        self.register("sprintf", 2, host::lib_sprintf);
+        self.register("string", 1, host::lib_string);
+        self.register("int", 1, host::lib_int);
+        self.register("float", 1, host::lib_float);
+        self.register("min", 2, host::lib_min);
+        self.register("max", 2, host::lib_max);
+        self.register("abs", 1, host::lib_abs);
+        self.register("not", 1, host::lib_not);
+        self.register("fst", 1, host::lib_fst);
+        self.register("snd", 1, host::lib_snd);
+
+        self.register("List.map", 2, host::list_map);
+        self.register("List.mapi", 2, host::list_mapi);
+        self.register("List.iter", 2, host::list_iter);
+        self.register("List.iteri", 2, host::list_iteri);
+        self.register("List.filter", 2, host::list_filter);
+        self.register("List.fold", 3, host::list_fold);
+        self.register("List.length", 1, host::list_length);
+        self.register("List.item", 2, host::list_item);
+        self.register("List.head", 1, host::list_head);
+
+        self.register("String.length", 1, host::string_length);
+        self.register("String.concat", 2, host::string_concat);
+    }
+}
 
-        // tui.buffer.get(x, y, buffer) -> Cell
-        self.registered_functions
-            .push("tui.buffer.get".to_string());
+/// Convert the dashboard state into the record passed to `render`.
+fn state_to_value(state: &DashboardState) -> Value {
+    let mut widgets = BTreeMap::new();
+    for (id, widget) in &state.widgets {
+        let (selected, offset) = match widget {
+            WidgetState::List(list) => (list.selected(), list.offset()),
+            WidgetState::Table(table) => (table.selected(), table.offset()),
+            WidgetState::Tabs(tabs) => (Some(tabs.selected), 0),
+            WidgetState::Scrollbar(scrollbar) => (None, scrollbar.get_position()),
+            WidgetState::Custom(_) => continue,
+        };
 
-        // tui.buffer.clear(buffer) -> ()
-        self.registered_functions
-            .push("tui.buffer.clear".to_string());
+        let mut fields = BTreeMap::new();
+        fields.insert(
+            "selected".to_string(),
+            Value::Int(selected.map_or(-1, |s| s as i64)),
+        );
+        fields.insert("offset".to_string(), Value::Int(offset as i64));
+        widgets.insert(id.clone(), Value::record(fields));
     }
+
+    let mut fields = BTreeMap::new();
+    fields.insert(
+        "focus".to_string(),
+        Value::str(state.focus.as_deref().unwrap_or("")),
+    );
+    fields.insert("widgets".to_string(), Value::record(widgets));
+    let custom = state
+        .custom
+        .iter()
+        .map(|(key, value)| (key.clone(), host::state_value_to_value(value)))
+        .collect();
+    fields.insert("custom".to_string(), Value::record(custom));
+    Value::record(fields)
+}
+
+/// Get the selection and offset of the list, table and tabs states, keyed
+/// by widget id.
+fn widget_selections(state: &DashboardState) -> HashMap<String, (Option<usize>, usize)> {
+    state
+        .widgets
+        .iter()
+        .filter_map(|(id, widget)| {
+            let selection = match widget {
+                WidgetState::List(list) => (list.selected(), list.offset()),
+                WidgetState::Table(table) => (table.selected(), table.offset()),
+                WidgetState::Tabs(tabs) => (Some(tabs.selected), 0),
+                WidgetState::Scrollbar(_) | WidgetState::Custom(_) => return None,
+            };
+            Some((id.clone(), selection))
+        })
+        .collect()
 }
 
 /// Parse `#load` directives from Fusabi source code.
@@ -381,6 +808,55 @@ fn extract_quoted_string(input: &str) -> Option<&str> {
     None
 }
 
+thread_local! {
+    /// Whether [`catch_panic`] is running on this thread.
+    static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+
+    /// Where the last panic caught on this thread was raised.
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static QUIET_PANIC_HOOK: Once = Once::new();
+
+/// Run `f`, turning a panic into an error message.
+///
+/// The panic hook does not print caught panics, so they do not garble the
+/// terminal; the message includes where the panic was raised instead.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    QUIET_PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if is_catching_panic() {
+                let location = info.location().map(ToString::to_string);
+                PANIC_LOCATION.with(|l| *l.borrow_mut() = location);
+            } else {
+                previous(info);
+            }
+        }));
+    });
+
+    let outer = CATCHING_PANIC.with(|c| c.replace(true));
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING_PANIC.with(|c| c.set(outer));
+
+    result.map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        match PANIC_LOCATION.with(|l| l.borrow_mut().take()) {
+            Some(location) => format!("{} at {}", message, location),
+            None => message,
+        }
+    })
+}
+
+/// Check if a panic on this thread would be caught by [`catch_panic`].
+pub(crate) fn is_catching_panic() -> bool {
+    CATCHING_PANIC.with(Cell::get)
+}
+
 /// Hash source content for cache invalidation.
 pub fn hash_source(source: &str) -> u64 {
     use std::hash::{Hash, Hasher};
@@ -392,6 +868,9 @@ pub fn hash_source(source: &str) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fusabi_tui_core::buffer::Hyperlink;
+    use fusabi_tui_core::color::Gradient;
+    use fusabi_tui_core::style::{Color, Modifier, UnderlineStyle};
 
     #[test]
     fn test_parse_load_directives() {
@@ -447,4 +926,440 @@ let x = 42
         assert!(funcs.contains(&"tui.widget.block".to_string()));
         assert!(funcs.contains(&"tui.buffer.setString".to_string()));
     }
+
+    fn render_script(source: &str, width: u16, height: u16) -> EngineResult<Buffer> {
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source)?;
+        let area = Rect::new(0, 0, width, height);
+        let mut buffer = Buffer::empty(area);
+        ctx.render(&mut buffer, area, &DashboardState::new())?;
+        Ok(buffer)
+    }
+
+    fn row_text(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).unwrap().symbol.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_evaluate_plain_bindings() {
+        let mut ctx = FusabiContext::new(PathBuf::from("test.fsx"));
+        ctx.evaluate("let x = 42\n").unwrap();
+        assert!(ctx.is_initialized());
+        assert!(!ctx.has_render());
+    }
+
+    #[test]
+    fn test_evaluate_syntax_error_has_position() {
+        let mut ctx = FusabiContext::new(PathBuf::from("bad.fsx"));
+        let err = ctx.evaluate("let x =\n    (1 + 2\n").unwrap_err();
+        match err {
+            EngineError::Script(e) => {
+                assert_eq!(e.kind, crate::error::ScriptErrorKind::Syntax);
+                assert_eq!(e.path.as_deref(), Some(std::path::Path::new("bad.fsx")));
+                assert_eq!(e.line, 3);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(!ctx.is_initialized());
+    }
+
+    #[test]
+    fn test_failed_evaluate_keeps_previous_script() {
+        let render = |ctx: &mut FusabiContext| {
+            let area = Rect::new(0, 0, 4, 1);
+            let mut buffer = Buffer::empty(area);
+            ctx.render(&mut buffer, area, &DashboardState::new()).unwrap();
+            row_text(&buffer, 0)
+        };
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate("let render buffer area state = tui.widget.paragraph \"v1\"\n")
+            .unwrap();
+
+        assert!(ctx.evaluate("let render buffer area state =\n    (1 +\n").is_err());
+        assert!(ctx.is_initialized());
+        assert!(ctx.is_stale());
+        assert_eq!(render(&mut ctx), "v1  ");
+
+        ctx.evaluate("let render buffer area state = tui.widget.paragraph \"v2\"\n")
+            .unwrap();
+        assert!(!ctx.is_stale());
+        assert_eq!(render(&mut ctx), "v2  ");
+    }
+
+    fn panicking_host(_: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+        panic!("host function bug")
+    }
+
+    fn unit_host(_: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+        Ok(Value::Unit)
+    }
+
+    #[test]
+    fn test_render_panic_is_caught() {
+        let source = r#"
+let render buffer area state =
+    tui.buffer.setString area.x area.y "Hi" (tui.style.new ()) buffer
+    test.hook ()
+"#;
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        ctx.interpreter.define_host("test.hook", 1, panicking_host);
+
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buffer = Buffer::empty(area);
+        match ctx.render(&mut buffer, area, &DashboardState::new()) {
+            Err(EngineError::ScriptPanic(message)) => {
+                assert!(message.starts_with("host function bug at "), "{}", message);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        // The buffer is handed back with what was drawn before the panic
+        assert_eq!(buffer.area, area);
+        assert_eq!(row_text(&buffer, 0), "Hi  ");
+
+        // The context keeps working
+        ctx.interpreter.define_host("test.hook", 1, unit_host);
+        ctx.render(&mut buffer, area, &DashboardState::new()).unwrap();
+    }
+
+    #[test]
+    fn test_render_calls_script_with_buffer() {
+        let source = r#"
+let render buffer area state =
+    let style = tui.style.new () |> tui.style.fg tui.color.red |> tui.style.bold
+    tui.buffer.setString area.x area.y "Hello" style buffer
+"#;
+        let buffer = render_script(source, 10, 2).unwrap();
+        assert_eq!(row_text(&buffer, 0), "Hello     ");
+        let cell = buffer.get(0, 0).unwrap();
+        assert_eq!(cell.fg, Color::Red);
+        assert!(cell.modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_render_widgets_and_layout() {
+        let source = r#"
+let render buffer area state =
+    let rows =
+        tui.layout.split area tui.layout.vertical [tui.layout.length 3; tui.layout.fill 1]
+    let header =
+        tui.widget.block ()
+        |> tui.widget.blockTitle "Top"
+        |> tui.widget.blockBorders "all"
+    tui.widget.render buffer (List.item 0 rows) header
+    let items = ["one"; "two"] |> List.map (fun s -> s ^ "!")
+    tui.widget.render buffer (List.item 1 rows) (tui.widget.list items)
+"#;
+        let buffer = render_script(source, 12, 6).unwrap();
+        assert!(row_text(&buffer, 0).contains("Top"));
+        assert_eq!(row_text(&buffer, 3).trim_end(), "one!");
+        assert_eq!(row_text(&buffer, 4).trim_end(), "two!");
+    }
+
+    #[test]
+    fn test_render_returned_widget_and_state() {
+        let source = r#"
+let render buffer area state =
+    let focus = if state.focus = "" then "none" else state.focus
+    tui.widget.paragraph (sprintf "focus=%s" focus)
+"#;
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buffer = Buffer::empty(area);
+        let mut state = DashboardState::new();
+        state.set_focus("cpu");
+        ctx.render(&mut buffer, area, &state).unwrap();
+        assert_eq!(row_text(&buffer, 0), "focus=cpu   ");
+    }
+
+    #[test]
+    fn test_render_custom_state() {
+        let source = r#"
+let render buffer area state =
+    let count = tui.state.get "count" 0
+    tui.state.set "count" (count + 1)
+    tui.widget.paragraph (sprintf "%s %d" (state.custom.label, count))
+"#;
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buffer = Buffer::empty(area);
+        let mut state = DashboardState::new();
+        state.set_value("label", "runs");
+
+        ctx.render(&mut buffer, area, &state).unwrap();
+        assert_eq!(row_text(&buffer, 0), "runs 0      ");
+        state.custom = ctx.take_custom_state().unwrap();
+        assert_eq!(state.value("count"), Some(&StateValue::Int(1)));
+        assert_eq!(ctx.take_custom_state(), None);
+
+        ctx.render(&mut buffer, area, &state).unwrap();
+        assert_eq!(row_text(&buffer, 0), "runs 1      ");
+    }
+
+    #[test]
+    fn test_render_theme() {
+        let source = r#"
+let render buffer area state =
+    let style = tui.theme.style "title"
+    tui.buffer.setString 0 0 (tui.theme.name ()) style buffer
+    let alert = tui.style.new () |> tui.style.bg (tui.theme.color "error")
+    tui.buffer.setStyle (tui.layout.rect 5 0 1 1) alert buffer
+"#;
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buffer = Buffer::empty(area);
+        ctx.render(&mut buffer, area, &DashboardState::new()).unwrap();
+        assert_eq!(row_text(&buffer, 0), "Dark  ");
+        assert_eq!(buffer.get(0, 0).unwrap().fg, Color::Blue);
+
+        // A new theme applies without evaluating the script again
+        ctx.set_theme(Arc::new(Theme::slime()));
+        ctx.render(&mut buffer, area, &DashboardState::new()).unwrap();
+        assert_eq!(row_text(&buffer, 0), "Slime ");
+        assert_eq!(buffer.get(0, 0).unwrap().fg, Theme::slime().colors.primary);
+        assert_eq!(buffer.get(5, 0).unwrap().bg, Theme::slime().colors.error);
+
+        // and survives reloads
+        ctx.evaluate(source).unwrap();
+        ctx.render(&mut buffer, area, &DashboardState::new()).unwrap();
+        assert_eq!(row_text(&buffer, 0), "Slime ");
+
+        let err = render_script("let render b a s = tui.theme.color \"title\"\n", 4, 1);
+        assert!(err.unwrap_err().to_string().contains("unknown palette color `title`"));
+    }
+
+    #[test]
+    fn test_render_underline_style() {
+        let source = r#"
+let render buffer area state =
+    let lint =
+        tui.style.new ()
+        |> tui.style.underlineStyle "curly"
+        |> tui.style.underlineColor tui.color.yellow
+    tui.buffer.setString 0 0 "warn" lint buffer
+"#;
+        let buffer = render_script(source, 4, 1).unwrap();
+        let cell = buffer.get(0, 0).unwrap();
+        assert!(cell.modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(cell.underline_style, UnderlineStyle::Curly);
+        assert_eq!(cell.underline_color, Color::Yellow);
+
+        let source = "let render b a s = tui.style.new () |> tui.style.underlineStyle \"wavy\"\n";
+        let err = render_script(source, 4, 1);
+        assert!(err.unwrap_err().to_string().contains("unknown underline style `wavy`"));
+    }
+
+    #[test]
+    fn test_render_link() {
+        let source = r#"
+let render buffer area state =
+    let width = tui.buffer.setString 0 0 "PROJ-7" (tui.style.new ()) buffer
+    tui.buffer.setLink "https://tracker.example/PROJ-7" (tui.layout.rect 0 0 width 1) buffer
+"#;
+        let buffer = render_script(source, 8, 1).unwrap();
+        let link = Hyperlink::new("https://tracker.example/PROJ-7");
+        assert_eq!(buffer.hyperlinks(), vec![(Rect::new(0, 0, 6, 1), &link)]);
+    }
+
+    #[test]
+    fn test_render_with_id() {
+        let source = r#"
+let render buffer area state =
+    let bold = tui.style.new () |> tui.style.bold
+    let items = tui.widget.list ["a"; "b"; "c"] |> tui.widget.highlight bold |> tui.widget.select 0
+    tui.widget.renderWithId "items" buffer (tui.layout.rect 0 0 4 3) items
+    tui.widget.renderWithId "title" buffer (tui.layout.rect 4 0 4 1) (tui.widget.paragraph "T")
+"#;
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        let area = Rect::new(0, 0, 8, 3);
+        let mut buffer = Buffer::empty(area);
+        let mut state = DashboardState::new();
+        ctx.render(&mut buffer, area, &state).unwrap();
+
+        let ids: Vec<String> = state.focus_ring.targets().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec!["items", "title"]);
+        assert_eq!(state.focus_ring.area("items"), Some(Rect::new(0, 0, 4, 3)));
+        assert_eq!(state.hit_map.row_count("items"), Some(3));
+        assert_eq!(state.hit_map.hit(5, 0).map(|hit| hit.id), Some("title".to_string()));
+
+        // The list gets a state with its own selection; the paragraph has none
+        let widget_states = ctx.take_widget_states();
+        assert_eq!(widget_states.len(), 1);
+        for (id, widget_state) in widget_states {
+            state.insert_widget(id, widget_state);
+        }
+        match state.get_widget("items") {
+            Some(WidgetState::List(list)) => assert_eq!(list.selected(), Some(0)),
+            other => panic!("unexpected widget {:?}", other),
+        }
+
+        // From then on the dashboard's selection is shown
+        if let Some(WidgetState::List(list)) = state.get_widget_mut("items") {
+            list.select(Some(2));
+        }
+        state.focus_ring.clear();
+        let mut buffer = Buffer::empty(area);
+        ctx.render(&mut buffer, area, &state).unwrap();
+        assert!(ctx.take_widget_states().is_empty());
+        assert_eq!(buffer.get(0, 2).unwrap().symbol, "c");
+        assert!(buffer.get(0, 2).unwrap().modifier.contains(Modifier::BOLD));
+        assert!(!buffer.get(0, 0).unwrap().modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_render_color_math() {
+        let source = r#"
+let render buffer area state =
+    let heat = tui.color.gradientSteps [tui.color.blue; tui.color.red] 3
+    List.iteri (fun i c ->
+        let style = tui.style.new () |> tui.style.bg c
+        tui.buffer.setStyle (tui.layout.rect i 0 1 1) style buffer) heat
+    let border = tui.color.rgb 100 100 100 |> tui.color.darken 0.1
+    let style = tui.style.new () |> tui.style.fg (border |> tui.color.mix tui.color.red 0.0)
+    tui.buffer.setStyle (tui.layout.rect 3 0 1 1) style buffer
+    let ratio = tui.color.contrast (tui.color.rgb 0 0 0) (tui.color.rgb 255 255 255)
+    tui.buffer.setString 0 1 (sprintf "%.0f" ratio) (tui.style.new ()) buffer
+    let green = tui.color.toRgb (tui.color.hsl 120 1 0.25)
+    tui.buffer.setString 3 1 (sprintf "%d,%d,%d" green) (tui.style.new ()) buffer
+"#;
+        let buffer = render_script(source, 12, 2).unwrap();
+        let heat = Gradient::new([Color::Blue, Color::Red]).steps(3);
+        for (x, color) in heat.into_iter().enumerate() {
+            assert_eq!(buffer.get(x as u16, 0).unwrap().bg, color);
+        }
+        assert_eq!(buffer.get(1, 0).unwrap().bg, Color::Blue.mix(Color::Red, 0.5));
+        assert_eq!(buffer.get(3, 0).unwrap().fg, Color::Rgb(100, 100, 100).darken(0.1));
+        assert_eq!(row_text(&buffer, 1), "21 0,128,0  ");
+
+        let source = "let render b a s = tui.color.toHsl (tui.color.reset ())\n";
+        let err = render_script(source, 4, 1).unwrap_err();
+        assert!(err.to_string().contains("the reset color has no RGB value"));
+    }
+
+    #[test]
+    fn test_render_runtime_error_keeps_buffer() {
+        let source = r#"
+let render buffer area state =
+    tui.buffer.setString 0 0 "ok" (tui.style.new ()) buffer
+    tui.color.rgb 300 0 0
+"#;
+        let mut ctx = FusabiContext::new(PathBuf::from("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buffer = Buffer::empty(area);
+        let err = ctx
+            .render(&mut buffer, area, &DashboardState::new())
+            .unwrap_err();
+        match err {
+            EngineError::Script(e) => {
+                assert!(e.message.contains("tui.color.rgb"));
+                assert_eq!((e.line, e.column), (4, 5));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(buffer.area, area);
+        assert_eq!(row_text(&buffer, 0), "ok  ");
+    }
+
+    #[test]
+    fn test_evaluate_load_directive() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.fsx"),
+            "let greeting name = \"hi \" ^ name\n",
+        )
+        .unwrap();
+        let entry = dir.path().join("dashboard.fsx");
+        let source = "#load \"lib.fsx\"\nlet render buffer area state =\n    tui.widget.paragraph (greeting \"bob\")\n";
+
+        let mut ctx = FusabiContext::new(entry);
+        ctx.evaluate(source).unwrap();
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buffer = Buffer::empty(area);
+        ctx.render(&mut buffer, area, &DashboardState::new()).unwrap();
+        assert_eq!(row_text(&buffer, 0), "hi bob");
+    }
+
+    #[test]
+    fn test_evaluate_reuses_cached_module() {
+        let mut ctx = FusabiContext::new(PathBuf::from("test.fsx"));
+        ctx.evaluate("let x = 1").unwrap();
+        let first = Arc::clone(&ctx.module_cache[&PathBuf::from("test.fsx")].program);
+        ctx.evaluate("let x = 1").unwrap();
+        let second = &ctx.module_cache[&PathBuf::from("test.fsx")].program;
+        assert!(Arc::ptr_eq(&first, second));
+    }
+
+    #[test]
+    fn test_evaluate_load_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.fsx"), "#load \"b.fsx\"\nlet a = 1\n").unwrap();
+        std::fs::write(root.join("b.fsx"), "#load \"a.fsx\"\nlet b = 2\n").unwrap();
+
+        let mut ctx = FusabiContext::new(root.join("dashboard.fsx"));
+        let err = match ctx.evaluate("#load \"a.fsx\"\n") {
+            Err(EngineError::Script(err)) => err,
+            other => panic!("expected a script error, got {:?}", other),
+        };
+        let a = root.join("a.fsx").display().to_string();
+        let b = root.join("b.fsx").display().to_string();
+        assert_eq!(
+            err.message,
+            format!("Circular dependency detected: {a} -> {b} -> {a}")
+        );
+        assert_eq!(err.path.as_deref(), Some(root.join("b.fsx").as_path()));
+        assert_eq!((err.line, err.column), (1, 1));
+    }
+
+    #[test]
+    fn test_evaluate_with_loader_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (entry, lib) = (root.join("dashboard.fsx"), root.join("lib.fsx"));
+        std::fs::write(&lib, "let x = 1\n").unwrap();
+        std::fs::write(&entry, "#load \"lib.fsx\"\nlet y = x\n").unwrap();
+
+        let mut loader = FileLoader::new();
+        let source = loader.load(&entry).unwrap().content.clone();
+
+        // The cached source is evaluated, not the file on disk
+        std::fs::write(&lib, "let x = (1 +\n").unwrap();
+        let mut ctx = FusabiContext::new(entry);
+        ctx.evaluate_with(&source, &loader).unwrap();
+        assert!(matches!(ctx.interpreter.global("y"), Some(Value::Int(1))));
+        assert!(ctx.evaluate(&source).is_err());
+    }
+
+    #[test]
+    fn test_invalidate_keeps_untouched_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (util, lib) = (root.join("util.fsx"), root.join("lib.fsx"));
+        std::fs::write(&util, "let twice x = x * 2\n").unwrap();
+        std::fs::write(&lib, "#load \"util.fsx\"\nlet four = twice 2\n").unwrap();
+        let source = "#load \"lib.fsx\"\nlet x = four\n";
+
+        let mut ctx = FusabiContext::new(root.join("dashboard.fsx"));
+        ctx.evaluate(source).unwrap();
+        let util_program = Arc::clone(&ctx.module(&util).unwrap().program);
+        let lib_program = Arc::clone(&ctx.module(&lib).unwrap().program);
+
+        std::fs::write(&lib, "#load \"util.fsx\"\nlet four = twice 2 + 0\n").unwrap();
+        ctx.invalidate(&[lib.clone(), root.join("dashboard.fsx")]);
+        ctx.evaluate(source).unwrap();
+
+        assert!(Arc::ptr_eq(&util_program, &ctx.module(&util).unwrap().program));
+        assert!(!Arc::ptr_eq(&lib_program, &ctx.module(&lib).unwrap().program));
+        assert_eq!(
+            ctx.module(&lib).unwrap().source_hash,
+            hash_source("#load \"util.fsx\"\nlet four = twice 2 + 0\n")
+        );
+    }
 }
@@ -0,0 +1,177 @@
+//! Syntax tree for Fusabi dashboard scripts.
+
+use std::sync::Arc;
+
+/// A parsed script file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Program {
+    /// `#load` directives in source order, with their positions.
+    pub loads: Vec<(String, usize, usize)>,
+    /// Top-level items in source order.
+    pub items: Vec<Item>,
+}
+
+/// A top-level item.
+#[derive(Debug, Clone)]
+pub(crate) enum Item {
+    /// `let` declaration that introduces global bindings.
+    Let(Binding),
+    /// Expression evaluated for its side effects.
+    Expr(Expr),
+}
+
+/// A `let` binding.
+#[derive(Debug, Clone)]
+pub(crate) struct Binding {
+    /// Pattern on the left-hand side (a plain variable for functions).
+    pub pattern: Pattern,
+    /// Whether the binding was declared with `let rec`.
+    pub recursive: bool,
+    /// Bound expression. For `let f a b = ...` this is a lambda.
+    pub body: Expr,
+}
+
+/// Expression node with its source position.
+#[derive(Debug, Clone)]
+pub(crate) struct Expr {
+    pub kind: ExprKind,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Expression variants.
+#[derive(Debug, Clone)]
+pub(crate) enum ExprKind {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(Arc<str>),
+    /// Variable or dotted path (`x`, `state.focus`, `tui.color.rgb`).
+    Path(Vec<String>),
+    List(Vec<Expr>),
+    Range(Box<Expr>, Box<Expr>),
+    Tuple(Vec<Expr>),
+    Record(Vec<(String, Expr)>),
+    RecordUpdate(Box<Expr>, Vec<(String, Expr)>),
+    App(Box<Expr>, Vec<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Match(Box<Expr>, Vec<Arm>),
+    Lambda(Arc<Lambda>),
+    Let(Box<Binding>, Box<Expr>),
+    Seq(Box<Expr>, Box<Expr>),
+}
+
+/// A `match` arm.
+#[derive(Debug, Clone)]
+pub(crate) struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+/// An anonymous function (`fun a b -> ...` or the right side of `let f a b`).
+#[derive(Debug)]
+pub(crate) struct Lambda {
+    pub params: Vec<Pattern>,
+    pub body: Expr,
+}
+
+/// Binding patterns.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Pattern {
+    Wildcard,
+    Var(String),
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    Tuple(Vec<Pattern>),
+    List(Vec<Pattern>),
+    Cons(Box<Pattern>, Box<Pattern>),
+}
+
+/// Binary operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Concat,
+    Append,
+    Cons,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    PipeRight,
+    PipeLeft,
+}
+
+impl BinOp {
+    /// Map an operator symbol to its variant, precedence and associativity.
+    ///
+    /// Higher precedence binds tighter. The levels follow F#.
+    pub fn from_symbol(sym: &str) -> Option<(Self, u8, bool)> {
+        // (operator, precedence, right associative)
+        Some(match sym {
+            "||" => (BinOp::Or, 1, false),
+            "&&" => (BinOp::And, 2, false),
+            "|>" => (BinOp::PipeRight, 3, false),
+            "<|" => (BinOp::PipeLeft, 3, false),
+            "=" => (BinOp::Eq, 3, false),
+            "<>" => (BinOp::Ne, 3, false),
+            "<" => (BinOp::Lt, 3, false),
+            "<=" => (BinOp::Le, 3, false),
+            ">" => (BinOp::Gt, 3, false),
+            ">=" => (BinOp::Ge, 3, false),
+            "^" => (BinOp::Concat, 4, true),
+            "@" => (BinOp::Append, 4, true),
+            "::" => (BinOp::Cons, 5, true),
+            "+" => (BinOp::Add, 6, false),
+            "-" => (BinOp::Sub, 6, false),
+            "*" => (BinOp::Mul, 7, false),
+            "/" => (BinOp::Div, 7, false),
+            "%" => (BinOp::Rem, 7, false),
+            _ => return None,
+        })
+    }
+
+    /// Source symbol, used in error messages.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Concat => "^",
+            BinOp::Append => "@",
+            BinOp::Cons => "::",
+            BinOp::Eq => "=",
+            BinOp::Ne => "<>",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::And => "&&",
+            BinOp::Or => "||",
+            BinOp::PipeRight => "|>",
+            BinOp::PipeLeft => "<|",
+        }
+    }
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, line: usize, col: usize) -> Self {
+        Self { kind, line, col }
+    }
+}
@@ -0,0 +1,1206 @@
+//! Host functions exposed to scripts under `tui.*`, plus a small core library.
+//!
+//! Every host function receives its arguments fully applied, in script order.
+//! Builder-style functions take the value being built as their *last*
+//! argument so that they compose with `|>`:
+//!
+//! ```fsharp
+//! tui.style.new ()
+//! |> tui.style.fg tui.color.cyan
+//! |> tui.style.bold
+//! ```
+
+use std::collections::BTreeMap;
+
+use fusabi_tui_core::buffer::{Buffer, Hyperlink};
+use fusabi_tui_core::color::{Gradient, Hsl, Oklch};
+use fusabi_tui_core::layout::{Constraint, Direction, Layout, Rect};
+use fusabi_tui_core::style::{Color, Modifier, Style, UnderlineStyle};
+use fusabi_tui_core::theme::ColorPalette;
+use fusabi_tui_widgets::block::Block;
+use fusabi_tui_widgets::borders::Borders;
+use fusabi_tui_widgets::gauge::Gauge;
+use fusabi_tui_widgets::list::{List, ListItem, ListState};
+use fusabi_tui_widgets::paragraph::{Alignment, Paragraph};
+use fusabi_tui_widgets::sparkline::Sparkline;
+use fusabi_tui_widgets::table::{Row, Table, TableState};
+use fusabi_tui_widgets::tabs::Tabs;
+use fusabi_tui_widgets::widget::{StatefulWidget, Widget};
+
+use super::interpreter::{runtime_error, Interpreter};
+use super::value::{ScriptWidget, Value};
+use crate::error::ScriptResult;
+use crate::hit::HitKind;
+use crate::state::{StateValue, TabsState, WidgetState};
+
+// =============================================================================
+// Argument helpers
+// =============================================================================
+
+fn expected(what: &str, found: &Value) -> crate::error::ScriptError {
+    runtime_error(format!("expected {}, found {}", what, found.type_name()))
+}
+
+fn int(value: &Value) -> ScriptResult<i64> {
+    match value {
+        Value::Int(i) => Ok(*i),
+        other => Err(expected("an int", other)),
+    }
+}
+
+fn float(value: &Value) -> ScriptResult<f64> {
+    match value {
+        Value::Int(i) => Ok(*i as f64),
+        Value::Float(f) => Ok(*f),
+        other => Err(expected("a number", other)),
+    }
+}
+
+fn u16_arg(value: &Value) -> ScriptResult<u16> {
+    let i = int(value)?;
+    u16::try_from(i).map_err(|_| runtime_error(format!("{} is out of range (0-65535)", i)))
+}
+
+fn u8_arg(value: &Value) -> ScriptResult<u8> {
+    let i = int(value)?;
+    u8::try_from(i).map_err(|_| runtime_error(format!("{} is out of range (0-255)", i)))
+}
+
+fn string(value: &Value) -> ScriptResult<String> {
+    match value {
+        Value::Str(s) => Ok(s.to_string()),
+        other => Err(expected("a string", other)),
+    }
+}
+
+fn list(value: &Value) -> ScriptResult<&[Value]> {
+    match value {
+        Value::List(items) => Ok(items),
+        other => Err(expected("a list", other)),
+    }
+}
+
+fn color(value: &Value) -> ScriptResult<Color> {
+    match value {
+        Value::Color(c) => Ok(*c),
+        other => Err(expected("a color", other)),
+    }
+}
+
+fn style(value: &Value) -> ScriptResult<Style> {
+    match value {
+        Value::Style(s) => Ok(*s),
+        other => Err(expected("a style", other)),
+    }
+}
+
+fn rect(value: &Value) -> ScriptResult<Rect> {
+    match value {
+        Value::Rect(r) => Ok(*r),
+        other => Err(expected("a rect", other)),
+    }
+}
+
+fn widget(value: &Value) -> ScriptResult<&ScriptWidget> {
+    match value {
+        Value::Widget(w) => Ok(w),
+        other => Err(expected("a widget", other)),
+    }
+}
+
+fn block(value: &Value) -> ScriptResult<Block> {
+    match widget(value)? {
+        ScriptWidget::Block(b) => Ok(b.clone()),
+        other => Err(runtime_error(format!(
+            "expected a block, found {}",
+            other.kind()
+        ))),
+    }
+}
+
+fn buffer<'a>(interp: &'a mut Interpreter, value: &Value) -> ScriptResult<&'a mut Buffer> {
+    if !matches!(value, Value::Buffer) {
+        return Err(expected("the render buffer", value));
+    }
+    interp
+        .buffer
+        .as_mut()
+        .ok_or_else(|| runtime_error("the buffer is only available during render"))
+}
+
+fn non_negative_index(value: &Value) -> ScriptResult<Option<usize>> {
+    let i = int(value)?;
+    Ok(usize::try_from(i).ok())
+}
+
+// =============================================================================
+// tui.color
+// =============================================================================
+
+/// Named colors exposed as `tui.color.<name>` values.
+pub(crate) const NAMED_COLORS: &[(&str, Color)] = &[
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    ("darkGray", Color::DarkGray),
+    ("lightRed", Color::LightRed),
+    ("lightGreen", Color::LightGreen),
+    ("lightYellow", Color::LightYellow),
+    ("lightBlue", Color::LightBlue),
+    ("lightMagenta", Color::LightMagenta),
+    ("lightCyan", Color::LightCyan),
+];
+
+pub(crate) fn color_rgb(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(Color::Rgb(
+        u8_arg(&args[0])?,
+        u8_arg(&args[1])?,
+        u8_arg(&args[2])?,
+    )))
+}
+
+pub(crate) fn color_indexed(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(Color::Indexed(u8_arg(&args[0])?)))
+}
+
+pub(crate) fn color_reset(_: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(Color::Reset))
+}
+
+pub(crate) fn color_hsl(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(Color::from_hsl(Hsl {
+        h: float(&args[0])? as f32,
+        s: float(&args[1])? as f32,
+        l: float(&args[2])? as f32,
+    })))
+}
+
+pub(crate) fn color_oklch(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(Color::from_oklch(Oklch {
+        l: float(&args[0])? as f32,
+        c: float(&args[1])? as f32,
+        h: float(&args[2])? as f32,
+    })))
+}
+
+fn no_rgb_value() -> crate::error::ScriptError {
+    runtime_error("the reset color has no RGB value")
+}
+
+fn floats(values: [f32; 3]) -> Value {
+    Value::tuple(values.into_iter().map(|v| Value::Float(f64::from(v))).collect())
+}
+
+/// `tui.color.toRgb color` returns `(r, g, b)`.
+pub(crate) fn color_to_rgb(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let (r, g, b) = color(&args[0])?.to_rgb().ok_or_else(no_rgb_value)?;
+    let channels = [r, g, b].into_iter().map(|c| Value::Int(i64::from(c)));
+    Ok(Value::tuple(channels.collect()))
+}
+
+/// `tui.color.toHsl color` returns `(h, s, l)`.
+pub(crate) fn color_to_hsl(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let hsl = color(&args[0])?.to_hsl().ok_or_else(no_rgb_value)?;
+    Ok(floats([hsl.h, hsl.s, hsl.l]))
+}
+
+/// `tui.color.toOklch color` returns `(l, c, h)`.
+pub(crate) fn color_to_oklch(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let lch = color(&args[0])?.to_oklch().ok_or_else(no_rgb_value)?;
+    Ok(floats([lch.l, lch.c, lch.h]))
+}
+
+/// `tui.color.lighten amount color`
+pub(crate) fn color_lighten(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(
+        color(&args[1])?.lighten(float(&args[0])? as f32),
+    ))
+}
+
+/// `tui.color.darken amount color`
+pub(crate) fn color_darken(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(
+        color(&args[1])?.darken(float(&args[0])? as f32),
+    ))
+}
+
+/// `tui.color.mix other t color` interpolates from `color` to `other`.
+pub(crate) fn color_mix(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let other = color(&args[0])?;
+    Ok(Value::Color(
+        color(&args[2])?.mix(other, float(&args[1])? as f32),
+    ))
+}
+
+/// `tui.color.blend other alpha color` lays `other` over `color`.
+pub(crate) fn color_blend(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let other = color(&args[0])?;
+    Ok(Value::Color(
+        color(&args[2])?.blend(other, float(&args[1])? as f32),
+    ))
+}
+
+/// `tui.color.contrast a b` returns the WCAG contrast ratio.
+pub(crate) fn color_contrast(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let ratio = color(&args[0])?.contrast_ratio(color(&args[1])?);
+    Ok(Value::Float(f64::from(ratio.ok_or_else(no_rgb_value)?)))
+}
+
+fn gradient(value: &Value) -> ScriptResult<Gradient> {
+    Ok(Gradient::new(
+        list(value)?
+            .iter()
+            .map(color)
+            .collect::<ScriptResult<Vec<_>>>()?,
+    ))
+}
+
+/// `tui.color.gradient colors t` returns the color at `t` from 0 to 1.
+pub(crate) fn color_gradient(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Color(
+        gradient(&args[0])?.at(float(&args[1])? as f32),
+    ))
+}
+
+/// `tui.color.gradientSteps colors count` returns `count` evenly spaced
+/// colors.
+pub(crate) fn color_gradient_steps(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let count = non_negative_index(&args[1])?.unwrap_or(0);
+    let steps = gradient(&args[0])?.steps(count);
+    Ok(Value::list(steps.into_iter().map(Value::Color).collect()))
+}
+
+// =============================================================================
+// tui.style
+// =============================================================================
+
+pub(crate) fn style_new(_: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Style(Style::new()))
+}
+
+pub(crate) fn style_fg(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Style(style(&args[1])?.fg(color(&args[0])?)))
+}
+
+pub(crate) fn style_bg(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Style(style(&args[1])?.bg(color(&args[0])?)))
+}
+
+/// Generate a host function that adds one modifier to a style.
+macro_rules! modifier_fn {
+    ($($name:ident => $modifier:expr),* $(,)?) => {
+        $(
+            pub(crate) fn $name(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+                Ok(Value::Style(style(&args[0])?.add_modifier($modifier)))
+            }
+        )*
+    };
+}
+
+modifier_fn! {
+    style_bold => Modifier::BOLD,
+    style_dim => Modifier::DIM,
+    style_italic => Modifier::ITALIC,
+    style_underlined => Modifier::UNDERLINED,
+    style_slow_blink => Modifier::SLOW_BLINK,
+    style_rapid_blink => Modifier::RAPID_BLINK,
+    style_reversed => Modifier::REVERSED,
+    style_hidden => Modifier::HIDDEN,
+    style_crossed_out => Modifier::CROSSED_OUT,
+}
+
+/// Underline a style with a named shape.
+pub(crate) fn style_underline_style(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let name = string(&args[0])?;
+    let underline = UnderlineStyle::from_name(&name).ok_or_else(|| {
+        runtime_error(format!(
+            "unknown underline style `{}` (expected single, double, curly, dotted or dashed)",
+            name
+        ))
+    })?;
+    Ok(Value::Style(style(&args[1])?.underline_style(underline)))
+}
+
+pub(crate) fn style_underline_color(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Style(style(&args[1])?.underline_color(color(&args[0])?)))
+}
+
+// =============================================================================
+// tui.layout
+// =============================================================================
+
+pub(crate) fn layout_rect(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Rect(Rect::new(
+        u16_arg(&args[0])?,
+        u16_arg(&args[1])?,
+        u16_arg(&args[2])?,
+        u16_arg(&args[3])?,
+    )))
+}
+
+pub(crate) fn layout_split(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let area = rect(&args[0])?;
+    let direction = match &args[1] {
+        Value::Direction(d) => *d,
+        other => return Err(expected("a direction", other)),
+    };
+    let constraints = list(&args[2])?
+        .iter()
+        .map(|c| match c {
+            Value::Constraint(c) => Ok(*c),
+            other => Err(expected("a constraint", other)),
+        })
+        .collect::<ScriptResult<Vec<_>>>()?;
+
+    let rects = Layout::default()
+        .direction(direction)
+        .constraints(&constraints)
+        .split(area);
+    Ok(Value::list(rects.into_iter().map(Value::Rect).collect()))
+}
+
+pub(crate) fn layout_length(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Constraint(Constraint::Length(u16_arg(&args[0])?)))
+}
+
+pub(crate) fn layout_percentage(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Constraint(Constraint::Percentage(u16_arg(
+        &args[0],
+    )?)))
+}
+
+pub(crate) fn layout_ratio(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let num = u32::from(u16_arg(&args[0])?);
+    let den = u32::from(u16_arg(&args[1])?);
+    Ok(Value::Constraint(Constraint::Ratio(num, den)))
+}
+
+pub(crate) fn layout_fill(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Constraint(Constraint::Fill(u16_arg(&args[0])?)))
+}
+
+pub(crate) fn layout_min(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Constraint(Constraint::Min(u16_arg(&args[0])?)))
+}
+
+pub(crate) fn layout_max(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Constraint(Constraint::Max(u16_arg(&args[0])?)))
+}
+
+/// Directions exposed as `tui.layout.<name>` values.
+pub(crate) const DIRECTIONS: &[(&str, Direction)] = &[
+    ("vertical", Direction::Vertical),
+    ("horizontal", Direction::Horizontal),
+];
+
+// =============================================================================
+// tui.widget
+// =============================================================================
+
+pub(crate) fn widget_block(_: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::widget(ScriptWidget::Block(Block::new())))
+}
+
+pub(crate) fn widget_block_title(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let title = string(&args[0])?;
+    Ok(Value::widget(ScriptWidget::Block(
+        block(&args[1])?.title(title),
+    )))
+}
+
+pub(crate) fn widget_block_borders(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let borders = match &args[0] {
+        Value::List(items) => items
+            .iter()
+            .map(|item| string(item).and_then(|s| parse_borders(&s)))
+            .collect::<ScriptResult<Vec<_>>>()?
+            .into_iter()
+            .fold(Borders::NONE, |acc, b| acc | b),
+        other => parse_borders(&string(other)?)?,
+    };
+    Ok(Value::widget(ScriptWidget::Block(
+        block(&args[1])?.borders(borders),
+    )))
+}
+
+fn parse_borders(name: &str) -> ScriptResult<Borders> {
+    Ok(match name {
+        "all" => Borders::ALL,
+        "none" => Borders::NONE,
+        "top" => Borders::TOP,
+        "bottom" => Borders::BOTTOM,
+        "left" => Borders::LEFT,
+        "right" => Borders::RIGHT,
+        other => {
+            return Err(runtime_error(format!(
+                "unknown border `{}` (expected all, none, top, bottom, left or right)",
+                other
+            )))
+        }
+    })
+}
+
+pub(crate) fn widget_block_inner(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Rect(block(&args[0])?.inner(rect(&args[1])?)))
+}
+
+pub(crate) fn widget_paragraph(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::widget(ScriptWidget::Paragraph(Paragraph::new(
+        string(&args[0])?,
+    ))))
+}
+
+pub(crate) fn widget_paragraph_alignment(
+    _: &mut Interpreter,
+    args: Vec<Value>,
+) -> ScriptResult<Value> {
+    let alignment = match string(&args[0])?.as_str() {
+        "left" => Alignment::Left,
+        "center" => Alignment::Center,
+        "right" => Alignment::Right,
+        other => {
+            return Err(runtime_error(format!(
+                "unknown alignment `{}` (expected left, center or right)",
+                other
+            )))
+        }
+    };
+    match widget(&args[1])? {
+        ScriptWidget::Paragraph(p) => Ok(Value::widget(ScriptWidget::Paragraph(
+            p.clone().alignment(alignment),
+        ))),
+        other => Err(runtime_error(format!(
+            "expected a paragraph, found {}",
+            other.kind()
+        ))),
+    }
+}
+
+pub(crate) fn widget_list(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let items = list(&args[0])?
+        .iter()
+        .map(|item| match item {
+            Value::Str(s) => Ok(ListItem::new(s.to_string())),
+            Value::Widget(w) => match &**w {
+                ScriptWidget::ListItem(item) => Ok(item.clone()),
+                other => Err(runtime_error(format!(
+                    "expected a list item, found {}",
+                    other.kind()
+                ))),
+            },
+            other => Err(expected("a string or list item", other)),
+        })
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::widget(ScriptWidget::List(List::new(items), None)))
+}
+
+pub(crate) fn widget_list_item(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::widget(ScriptWidget::ListItem(ListItem::new(
+        string(&args[0])?,
+    ))))
+}
+
+pub(crate) fn widget_gauge(_: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::widget(ScriptWidget::Gauge(Gauge::new())))
+}
+
+pub(crate) fn widget_gauge_percent(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let percent = u16_arg(&args[0])?;
+    match widget(&args[1])? {
+        ScriptWidget::Gauge(g) => Ok(Value::widget(ScriptWidget::Gauge(
+            g.clone().percent(percent),
+        ))),
+        other => Err(runtime_error(format!(
+            "expected a gauge, found {}",
+            other.kind()
+        ))),
+    }
+}
+
+pub(crate) fn widget_gauge_label(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let label = string(&args[0])?;
+    match widget(&args[1])? {
+        ScriptWidget::Gauge(g) => Ok(Value::widget(ScriptWidget::Gauge(g.clone().label(label)))),
+        other => Err(runtime_error(format!(
+            "expected a gauge, found {}",
+            other.kind()
+        ))),
+    }
+}
+
+pub(crate) fn widget_table(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let widths = list(&args[0])?
+        .iter()
+        .map(|c| match c {
+            Value::Constraint(c) => Ok(*c),
+            other => Err(expected("a constraint", other)),
+        })
+        .collect::<ScriptResult<Vec<_>>>()?;
+    let rows = list(&args[1])?
+        .iter()
+        .map(row)
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::widget(ScriptWidget::Table(
+        Table::new(rows).widths(&widths),
+        None,
+    )))
+}
+
+pub(crate) fn widget_table_row(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let cells = list(&args[0])?
+        .iter()
+        .map(|cell| match cell {
+            Value::Str(s) => Ok(s.to_string()),
+            other => Ok(other.to_string()),
+        })
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::widget(ScriptWidget::Row(Row::new(cells))))
+}
+
+pub(crate) fn widget_table_header(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let header = row(&args[0])?;
+    match widget(&args[1])? {
+        ScriptWidget::Table(t, selected) => Ok(Value::widget(ScriptWidget::Table(
+            t.clone().header(header),
+            *selected,
+        ))),
+        other => Err(runtime_error(format!(
+            "expected a table, found {}",
+            other.kind()
+        ))),
+    }
+}
+
+fn row(value: &Value) -> ScriptResult<Row> {
+    match value {
+        Value::Widget(w) => match &**w {
+            ScriptWidget::Row(r) => Ok(r.clone()),
+            other => Err(runtime_error(format!(
+                "expected a table row, found {}",
+                other.kind()
+            ))),
+        },
+        Value::List(cells) => Ok(Row::new(
+            cells.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        )),
+        other => Err(expected("a table row", other)),
+    }
+}
+
+pub(crate) fn widget_sparkline(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let data = list(&args[0])?
+        .iter()
+        .map(|v| {
+            let i = int(v)?;
+            u64::try_from(i).map_err(|_| runtime_error("sparkline data must be non-negative"))
+        })
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::widget(ScriptWidget::Sparkline(
+        Sparkline::new().data(&data),
+    )))
+}
+
+pub(crate) fn widget_tabs(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let titles = list(&args[0])?
+        .iter()
+        .map(string)
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::widget(ScriptWidget::Tabs(Tabs::new(titles))))
+}
+
+/// `tui.widget.withBlock block widget` wraps a widget in a block.
+pub(crate) fn widget_with_block(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let b = block(&args[0])?;
+    let wrapped = match widget(&args[1])?.clone() {
+        ScriptWidget::Paragraph(p) => ScriptWidget::Paragraph(p.block(b)),
+        ScriptWidget::List(l, sel) => ScriptWidget::List(l.block(b), sel),
+        ScriptWidget::Gauge(g) => ScriptWidget::Gauge(g.block(b)),
+        ScriptWidget::Sparkline(s) => ScriptWidget::Sparkline(s.block(b)),
+        ScriptWidget::Tabs(t) => ScriptWidget::Tabs(t.block(b)),
+        other => {
+            return Err(runtime_error(format!(
+                "a {} cannot be wrapped in a block",
+                other.kind()
+            )))
+        }
+    };
+    Ok(Value::widget(wrapped))
+}
+
+/// `tui.widget.style style widget` sets the base style of any widget.
+pub(crate) fn widget_style(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let s = style(&args[0])?;
+    let styled = match widget(&args[1])?.clone() {
+        ScriptWidget::Block(b) => ScriptWidget::Block(b.style(s)),
+        ScriptWidget::Paragraph(p) => ScriptWidget::Paragraph(p.style(s)),
+        ScriptWidget::List(l, sel) => ScriptWidget::List(l.style(s), sel),
+        ScriptWidget::ListItem(i) => ScriptWidget::ListItem(i.style(s)),
+        ScriptWidget::Table(t, sel) => ScriptWidget::Table(t.style(s), sel),
+        ScriptWidget::Row(r) => ScriptWidget::Row(r.style(s)),
+        ScriptWidget::Gauge(g) => ScriptWidget::Gauge(g.gauge_style(s)),
+        ScriptWidget::Sparkline(sp) => ScriptWidget::Sparkline(sp.style(s)),
+        ScriptWidget::Tabs(t) => ScriptWidget::Tabs(t.style(s)),
+    };
+    Ok(Value::widget(styled))
+}
+
+/// `tui.widget.highlight style widget` sets the selection style.
+pub(crate) fn widget_highlight(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let s = style(&args[0])?;
+    let highlighted = match widget(&args[1])?.clone() {
+        ScriptWidget::List(l, sel) => ScriptWidget::List(l.highlight_style(s), sel),
+        ScriptWidget::Table(t, sel) => ScriptWidget::Table(t.highlight_style(s), sel),
+        ScriptWidget::Tabs(t) => ScriptWidget::Tabs(t.highlight_style(s)),
+        other => {
+            return Err(runtime_error(format!(
+                "a {} has no highlight style",
+                other.kind()
+            )))
+        }
+    };
+    Ok(Value::widget(highlighted))
+}
+
+/// `tui.widget.select index widget` selects an item; a negative index clears
+/// the selection.
+pub(crate) fn widget_select(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let index = non_negative_index(&args[0])?;
+    let selected = match widget(&args[1])?.clone() {
+        ScriptWidget::List(l, _) => ScriptWidget::List(l, index),
+        ScriptWidget::Table(t, _) => ScriptWidget::Table(t, index),
+        ScriptWidget::Tabs(t) => ScriptWidget::Tabs(t.select(index.unwrap_or(0))),
+        other => {
+            return Err(runtime_error(format!(
+                "a {} has no selection",
+                other.kind()
+            )))
+        }
+    };
+    Ok(Value::widget(selected))
+}
+
+pub(crate) fn widget_render(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let area = rect(&args[1])?;
+    let w = widget(&args[2])?.clone();
+    let buf = buffer(interp, &args[0])?;
+    render_widget(&w, area, buf)?;
+    Ok(Value::Unit)
+}
+
+/// A widget rendered with `tui.widget.renderWithId`.
+#[derive(Debug)]
+pub(crate) struct RenderedWidget {
+    pub id: String,
+    pub area: Rect,
+    /// State to add to the dashboard if it has none for `id` yet.
+    pub state: Option<WidgetState>,
+    /// List rows or tab titles to register in the hit map on top of `area`.
+    pub parts: Vec<(Rect, HitKind)>,
+}
+
+/// `tui.widget.renderWithId id buffer area widget` renders a widget like
+/// `tui.widget.render` and registers it under `id` in the focus ring and the
+/// hit map.
+///
+/// Lists, tables and tabs show the selection of the dashboard's widget state
+/// for `id`, so keys and clicks handled by the engine move it. The first
+/// render creates that state from the widget's own selection.
+pub(crate) fn widget_render_with_id(
+    interp: &mut Interpreter,
+    args: Vec<Value>,
+) -> ScriptResult<Value> {
+    let id = string(&args[0])?;
+    let area = rect(&args[2])?;
+    let mut w = widget(&args[3])?.clone();
+
+    let mut offset = 0;
+    let state = match (&mut w, interp.selections.get(&id)) {
+        (
+            ScriptWidget::List(_, selected) | ScriptWidget::Table(_, selected),
+            Some(&(dashboard_selected, dashboard_offset)),
+        ) => {
+            *selected = dashboard_selected;
+            offset = dashboard_offset;
+            None
+        }
+        (ScriptWidget::Tabs(t), Some(&(dashboard_selected, _))) => {
+            *t = t.clone().select(dashboard_selected.unwrap_or(0));
+            None
+        }
+        (ScriptWidget::List(_, selected), None) => {
+            let mut state = ListState::default();
+            state.select(*selected);
+            Some(WidgetState::List(state))
+        }
+        (ScriptWidget::Table(_, selected), None) => {
+            let mut state = TableState::default();
+            state.select(*selected);
+            Some(WidgetState::Table(state))
+        }
+        (ScriptWidget::Tabs(t), None) => Some(WidgetState::Tabs(TabsState {
+            selected: t.selected(),
+        })),
+        _ => None,
+    };
+
+    let buf = buffer(interp, &args[1])?;
+    let area = area.intersection(buf.area);
+    let offset = render_scrolled(&w, area, buf, offset)?;
+
+    let parts = match &w {
+        ScriptWidget::List(l, _) => {
+            let rows = HitKind::Rows { offset, len: l.len() };
+            vec![(l.rows_area(area), rows)]
+        }
+        ScriptWidget::Table(t, _) => {
+            let rows = HitKind::Rows { offset, len: t.len() };
+            vec![(t.rows_area(area), rows)]
+        }
+        ScriptWidget::Tabs(t) => {
+            let titles = t.title_areas(area);
+            let titles_area = titles.iter().copied().reduce(Rect::union).unwrap_or_default();
+            vec![(titles_area, HitKind::Tabs(titles))]
+        }
+        _ => Vec::new(),
+    };
+    interp.rendered.push(RenderedWidget { id, area, state, parts });
+    Ok(Value::Unit)
+}
+
+/// Render a script widget into a buffer, clipped to the buffer's area.
+pub(crate) fn render_widget(
+    widget: &ScriptWidget,
+    area: Rect,
+    buf: &mut Buffer,
+) -> ScriptResult<()> {
+    render_scrolled(widget, area, buf, 0).map(|_| ())
+}
+
+/// Like [`render_widget`], with lists and tables scrolled down to `offset`.
+///
+/// Returns the offset the widget was rendered at, which keeps the selection
+/// in view.
+fn render_scrolled(
+    widget: &ScriptWidget,
+    area: Rect,
+    buf: &mut Buffer,
+    offset: usize,
+) -> ScriptResult<usize> {
+    let area = area.intersection(buf.area);
+    match widget {
+        ScriptWidget::Block(b) => b.render(area, buf),
+        ScriptWidget::Paragraph(p) => p.render(area, buf),
+        ScriptWidget::List(l, selected) => {
+            let mut state = ListState::default();
+            state.select(*selected);
+            state.set_offset(offset);
+            StatefulWidget::render(l, area, buf, &mut state);
+            return Ok(state.offset());
+        }
+        ScriptWidget::Table(t, selected) => {
+            let mut state = TableState::default();
+            state.select(*selected);
+            state.set_offset(offset);
+            StatefulWidget::render(t, area, buf, &mut state);
+            return Ok(state.offset());
+        }
+        ScriptWidget::Gauge(g) => g.render(area, buf),
+        ScriptWidget::Sparkline(s) => s.render(area, buf),
+        ScriptWidget::Tabs(t) => t.render(area, buf),
+        other @ (ScriptWidget::ListItem(_) | ScriptWidget::Row(_)) => {
+            return Err(runtime_error(format!(
+                "a {} cannot be rendered on its own",
+                other.kind()
+            )))
+        }
+    }
+    Ok(0)
+}
+
+// =============================================================================
+// tui.buffer
+// =============================================================================
+
+pub(crate) fn buffer_set_string(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let x = u16_arg(&args[0])?;
+    let y = u16_arg(&args[1])?;
+    let text = string(&args[2])?;
+    let s = style(&args[3])?;
+    let buf = buffer(interp, &args[4])?;
+    let width = buf.set_string(x, y, &text, s);
+    Ok(Value::Int(width as i64))
+}
+
+pub(crate) fn buffer_set_style(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let area = rect(&args[0])?;
+    let s = style(&args[1])?;
+    let buf = buffer(interp, &args[2])?;
+    buf.set_style(area, s);
+    Ok(Value::Unit)
+}
+
+/// Link the cells of an area to a URI.
+pub(crate) fn buffer_set_link(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let link = Hyperlink::new(string(&args[0])?);
+    let area = rect(&args[1])?;
+    let buf = buffer(interp, &args[2])?;
+    buf.set_hyperlink(area, Some(&link));
+    Ok(Value::Unit)
+}
+
+pub(crate) fn buffer_get(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let x = u16_arg(&args[0])?;
+    let y = u16_arg(&args[1])?;
+    let buf = buffer(interp, &args[2])?;
+    let cell = buf
+        .get(x, y)
+        .ok_or_else(|| runtime_error(format!("({}, {}) is outside the buffer", x, y)))?;
+
+    let mut fields = BTreeMap::new();
+    fields.insert("symbol".to_string(), Value::str(&cell.symbol));
+    fields.insert("fg".to_string(), Value::Color(cell.fg));
+    fields.insert("bg".to_string(), Value::Color(cell.bg));
+    Ok(Value::record(fields))
+}
+
+pub(crate) fn buffer_clear(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    buffer(interp, &args[0])?.clear();
+    Ok(Value::Unit)
+}
+
+// =============================================================================
+// tui.state
+// =============================================================================
+
+/// Convert a custom state value for use in a script.
+pub(crate) fn state_value_to_value(value: &StateValue) -> Value {
+    match value {
+        StateValue::Bool(b) => Value::Bool(*b),
+        StateValue::Int(i) => Value::Int(*i),
+        StateValue::Float(f) => Value::Float(*f),
+        StateValue::Str(s) => Value::str(s),
+    }
+}
+
+fn state_value(value: &Value) -> ScriptResult<StateValue> {
+    match value {
+        Value::Bool(b) => Ok(StateValue::Bool(*b)),
+        Value::Int(i) => Ok(StateValue::Int(*i)),
+        Value::Float(f) => Ok(StateValue::Float(*f)),
+        Value::Str(s) => Ok(StateValue::Str(s.to_string())),
+        other => Err(expected("a bool, number or string", other)),
+    }
+}
+
+pub(crate) fn state_get(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let key = string(&args[0])?;
+    Ok(interp
+        .custom
+        .get(&key)
+        .map_or_else(|| args[1].clone(), state_value_to_value))
+}
+
+pub(crate) fn state_set(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let key = string(&args[0])?;
+    let value = state_value(&args[1])?;
+    if interp.custom.get(&key) != Some(&value) {
+        interp.custom.insert(key, value);
+        interp.custom_changed = true;
+    }
+    Ok(Value::Unit)
+}
+
+// =============================================================================
+// tui.theme
+// =============================================================================
+
+pub(crate) fn theme_style(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Style(interp.theme.get_style(&string(&args[0])?)))
+}
+
+pub(crate) fn theme_color(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let name = string(&args[0])?;
+    interp.theme.colors.get(&name).map(Value::Color).ok_or_else(|| {
+        runtime_error(format!(
+            "unknown palette color `{}` (expected {})",
+            name,
+            ColorPalette::NAMES.join(", ")
+        ))
+    })
+}
+
+pub(crate) fn theme_name(interp: &mut Interpreter, _: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::str(&interp.theme.name))
+}
+
+// =============================================================================
+// Core library
+// =============================================================================
+
+pub(crate) fn lib_sprintf(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    // `sprintf` is variadic in F#. Scripts pass the arguments as a list or
+    // tuple: `sprintf "%s: %d" (name, count)`. A single value is also accepted.
+    let format = string(&args[0])?;
+    let values: Vec<Value> = match &args[1] {
+        Value::Tuple(items) => items.to_vec(),
+        Value::Unit => Vec::new(),
+        other => vec![other.clone()],
+    };
+    format_string(&format, &values).map(Value::str)
+}
+
+fn format_string(format: &str, values: &[Value]) -> ScriptResult<String> {
+    let mut out = String::new();
+    let mut chars = format.chars().peekable();
+    let mut next = values.iter();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            out.push('%');
+            continue;
+        }
+
+        let mut left_align = false;
+        if chars.peek() == Some(&'-') {
+            left_align = true;
+            chars.next();
+        }
+        let mut width = String::new();
+        while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+            width.push(*d);
+            chars.next();
+        }
+        let mut precision = None;
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut p = String::new();
+            while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                p.push(*d);
+                chars.next();
+            }
+            precision = p.parse::<usize>().ok();
+        }
+
+        let spec = chars
+            .next()
+            .ok_or_else(|| runtime_error("format string ends with `%`"))?;
+        let value = next
+            .next()
+            .ok_or_else(|| runtime_error("not enough arguments for format string"))?;
+
+        let text = match spec {
+            'd' | 'i' => int(value)?.to_string(),
+            'f' => format!("{:.*}", precision.unwrap_or(6), float(value)?),
+            's' => string(value)?,
+            'b' => match value {
+                Value::Bool(b) => b.to_string(),
+                other => return Err(expected("a bool", other)),
+            },
+            'A' | 'O' => value.to_string(),
+            other => return Err(runtime_error(format!("unsupported format `%{}`", other))),
+        };
+
+        let width = width.parse::<usize>().unwrap_or(0);
+        if left_align {
+            out.push_str(&format!("{:<width$}", text, width = width));
+        } else {
+            out.push_str(&format!("{:>width$}", text, width = width));
+        }
+    }
+
+    Ok(out)
+}
+
+pub(crate) fn lib_string(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::str(args[0].to_string()))
+}
+
+pub(crate) fn lib_int(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match &args[0] {
+        Value::Int(i) => Ok(Value::Int(*i)),
+        Value::Float(f) => Ok(Value::Int(*f as i64)),
+        Value::Str(s) => s
+            .trim()
+            .parse()
+            .map(Value::Int)
+            .map_err(|_| runtime_error(format!("cannot convert \"{}\" to int", s))),
+        other => Err(expected("a number or string", other)),
+    }
+}
+
+pub(crate) fn lib_float(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match &args[0] {
+        Value::Str(s) => s
+            .trim()
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| runtime_error(format!("cannot convert \"{}\" to float", s))),
+        other => float(other).map(Value::Float),
+    }
+}
+
+pub(crate) fn lib_min(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match args[0].compare(&args[1]) {
+        Some(std::cmp::Ordering::Greater) => Ok(args[1].clone()),
+        Some(_) => Ok(args[0].clone()),
+        None => Err(runtime_error("arguments are not comparable")),
+    }
+}
+
+pub(crate) fn lib_max(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match args[0].compare(&args[1]) {
+        Some(std::cmp::Ordering::Less) => Ok(args[1].clone()),
+        Some(_) => Ok(args[0].clone()),
+        None => Err(runtime_error("arguments are not comparable")),
+    }
+}
+
+pub(crate) fn lib_abs(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match &args[0] {
+        Value::Int(i) => Ok(Value::Int(i.wrapping_abs())),
+        Value::Float(f) => Ok(Value::Float(f.abs())),
+        other => Err(expected("a number", other)),
+    }
+}
+
+pub(crate) fn lib_not(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match &args[0] {
+        Value::Bool(b) => Ok(Value::Bool(!b)),
+        other => Err(expected("a bool", other)),
+    }
+}
+
+pub(crate) fn lib_fst(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match &args[0] {
+        Value::Tuple(items) if items.len() == 2 => Ok(items[0].clone()),
+        other => Err(expected("a pair", other)),
+    }
+}
+
+pub(crate) fn lib_snd(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    match &args[0] {
+        Value::Tuple(items) if items.len() == 2 => Ok(items[1].clone()),
+        other => Err(expected("a pair", other)),
+    }
+}
+
+pub(crate) fn list_map(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let items = list(&args[1])?
+        .iter()
+        .map(|item| interp.apply(&args[0], vec![item.clone()]))
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::list(items))
+}
+
+pub(crate) fn list_mapi(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let items = list(&args[1])?
+        .iter()
+        .enumerate()
+        .map(|(i, item)| interp.apply(&args[0], vec![Value::Int(i as i64), item.clone()]))
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::list(items))
+}
+
+pub(crate) fn list_iter(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    for item in list(&args[1])? {
+        interp.apply(&args[0], vec![item.clone()])?;
+    }
+    Ok(Value::Unit)
+}
+
+pub(crate) fn list_iteri(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    for (i, item) in list(&args[1])?.iter().enumerate() {
+        interp.apply(&args[0], vec![Value::Int(i as i64), item.clone()])?;
+    }
+    Ok(Value::Unit)
+}
+
+pub(crate) fn list_filter(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let mut kept = Vec::new();
+    for item in list(&args[1])? {
+        match interp.apply(&args[0], vec![item.clone()])? {
+            Value::Bool(true) => kept.push(item.clone()),
+            Value::Bool(false) => {}
+            other => return Err(expected("a bool from the predicate", &other)),
+        }
+    }
+    Ok(Value::list(kept))
+}
+
+pub(crate) fn list_fold(interp: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let mut acc = args[1].clone();
+    for item in list(&args[2])? {
+        acc = interp.apply(&args[0], vec![acc, item.clone()])?;
+    }
+    Ok(acc)
+}
+
+pub(crate) fn list_length(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Int(list(&args[0])?.len() as i64))
+}
+
+pub(crate) fn list_item(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let index = int(&args[0])?;
+    let items = list(&args[1])?;
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| items.get(i))
+        .cloned()
+        .ok_or_else(|| runtime_error(format!("index {} is out of range", index)))
+}
+
+pub(crate) fn list_head(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    list(&args[0])?
+        .first()
+        .cloned()
+        .ok_or_else(|| runtime_error("the list is empty"))
+}
+
+pub(crate) fn string_length(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    Ok(Value::Int(string(&args[0])?.chars().count() as i64))
+}
+
+pub(crate) fn string_concat(_: &mut Interpreter, args: Vec<Value>) -> ScriptResult<Value> {
+    let sep = string(&args[0])?;
+    let parts = list(&args[1])?
+        .iter()
+        .map(string)
+        .collect::<ScriptResult<Vec<_>>>()?;
+    Ok(Value::str(parts.join(&sep)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_string() {
+        let values = vec![Value::str("cpu"), Value::Int(42), Value::Float(0.5)];
+        assert_eq!(
+            format_string("%s=%d (%.1f) 100%%", &values).unwrap(),
+            "cpu=42 (0.5) 100%"
+        );
+        assert_eq!(
+            format_string("[%5d|%-4s]", &[Value::Int(7), Value::str("ab")]).unwrap(),
+            "[    7|ab  ]"
+        );
+        assert!(format_string("%d", &[]).is_err());
+    }
+
+    #[test]
+    fn test_render_widget_rejects_fragments() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+        let item = ScriptWidget::ListItem(ListItem::new("x"));
+        assert!(render_widget(&item, Rect::new(0, 0, 10, 2), &mut buf).is_err());
+    }
+}
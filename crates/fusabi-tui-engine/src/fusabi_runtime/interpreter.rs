@@ -0,0 +1,830 @@
+//! Tree-walking evaluator for Fusabi dashboard scripts.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use fusabi_tui_core::buffer::Buffer;
+use fusabi_tui_core::theme::Theme;
+
+use super::ast::{Arm, BinOp, Binding, Expr, ExprKind, Item, Pattern, Program};
+use super::host::RenderedWidget;
+use super::value::{Closure, Env, HostCall, HostFn, Value};
+use crate::error::{ScriptError, ScriptResult};
+use crate::state::StateValue;
+
+/// Maximum nesting of script function calls before evaluation is aborted.
+///
+/// Guards the host against runaway recursion in scripts. The limit is kept low
+/// enough to fit the default 2 MiB stack of spawned threads in debug builds.
+const MAX_CALL_DEPTH: usize = 128;
+
+/// Maximum nesting of expressions being evaluated, across function calls.
+///
+/// Like [`MAX_CALL_DEPTH`], keeps the stack within 2 MiB in debug builds,
+/// where the deepest nesting costs about 6 KiB per level. It is reached
+/// before the call limit only by recursive functions with deeply nested
+/// bodies.
+const MAX_EVAL_DEPTH: usize = 300;
+
+/// Script interpreter holding global bindings.
+#[derive(Debug, Default)]
+pub(crate) struct Interpreter {
+    /// Host functions, library functions and top-level script bindings.
+    globals: HashMap<String, Value>,
+    /// Buffer being rendered into, present only during a `render` call.
+    pub buffer: Option<Buffer>,
+    /// Custom dashboard state values, read and written by `tui.state`.
+    pub custom: BTreeMap<String, StateValue>,
+    /// Whether `tui.state.set` changed `custom` since it was last taken.
+    pub custom_changed: bool,
+    /// Theme read by `tui.theme`.
+    pub theme: Arc<Theme>,
+    /// Selection and scroll offset of the dashboard's list, table and tabs
+    /// states, read by `tui.widget.renderWithId`.
+    pub selections: HashMap<String, (Option<usize>, usize)>,
+    /// Widgets rendered with `tui.widget.renderWithId` during a `render` call.
+    pub rendered: Vec<RenderedWidget>,
+    depth: usize,
+    eval_depth: usize,
+}
+
+/// Create a runtime error without a position. The interpreter attaches the
+/// position of the call site when the error propagates out of a host call.
+pub(crate) fn runtime_error(message: impl Into<String>) -> ScriptError {
+    ScriptError::runtime(message, 0, 0)
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the calls and expressions that a panic unwound without
+    /// returning.
+    pub fn reset_calls(&mut self) {
+        self.depth = 0;
+        self.eval_depth = 0;
+    }
+
+    /// Register a native function under a (dotted) global name.
+    pub fn define_host(&mut self, name: &'static str, arity: usize, func: HostFn) {
+        let value = Value::Host(Arc::new(HostCall {
+            name,
+            arity,
+            func,
+            applied: Vec::new(),
+        }));
+        self.globals.insert(name.to_string(), value);
+    }
+
+    /// Bind a global value.
+    pub fn define(&mut self, name: impl Into<String>, value: Value) {
+        self.globals.insert(name.into(), value);
+    }
+
+    pub fn global(&self, name: &str) -> Option<&Value> {
+        self.globals.get(name)
+    }
+
+    /// Evaluate all top-level items of a program, binding its `let`s globally.
+    pub fn run(&mut self, program: &Program) -> ScriptResult<()> {
+        let env = Env::default();
+        for item in &program.items {
+            match item {
+                Item::Let(binding) => {
+                    let value = self.eval_binding(binding, &env)?;
+                    let mut bound = Vec::new();
+                    if !bind_pattern(&binding.pattern, &value, &mut bound) {
+                        return Err(ScriptError::runtime(
+                            "value does not match the pattern of this binding",
+                            binding.body.line,
+                            binding.body.col,
+                        ));
+                    }
+                    for (name, value) in bound {
+                        self.globals.insert(name, value);
+                    }
+                }
+                Item::Expr(expr) => {
+                    self.eval(expr, &env)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a function value to arguments.
+    pub fn apply(&mut self, func: &Value, args: Vec<Value>) -> ScriptResult<Value> {
+        self.apply_at(func, args, 0, 0)
+    }
+
+    fn apply_at(
+        &mut self,
+        func: &Value,
+        args: Vec<Value>,
+        line: usize,
+        col: usize,
+    ) -> ScriptResult<Value> {
+        match func {
+            Value::Closure(closure) => self.apply_closure(closure, args, line, col),
+            Value::Host(host) => self.apply_host(host, args, line, col),
+            other => Err(ScriptError::runtime(
+                format!("a value of type {} is not a function", other.type_name()),
+                line,
+                col,
+            )),
+        }
+    }
+
+    #[inline(never)]
+    fn apply_closure(
+        &mut self,
+        closure: &Closure,
+        mut args: Vec<Value>,
+        line: usize,
+        col: usize,
+    ) -> ScriptResult<Value> {
+        let arity = closure.lambda.params.len();
+        let mut all = closure.applied.clone();
+        all.append(&mut args);
+
+        if all.len() < arity {
+            return Ok(Value::Closure(Arc::new(Closure {
+                lambda: Arc::clone(&closure.lambda),
+                env: closure.env.clone(),
+                rec_name: closure.rec_name.clone(),
+                applied: all,
+            })));
+        }
+
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(ScriptError::runtime(
+                "maximum call depth exceeded (runaway recursion?)",
+                line,
+                col,
+            ));
+        }
+
+        let rest = all.split_off(arity);
+        let env = bind_arguments(closure, all, line, col)?;
+
+        self.depth += 1;
+        let result = self.eval(&closure.lambda.body, &env);
+        self.depth -= 1;
+        let result = result?;
+
+        if rest.is_empty() {
+            Ok(result)
+        } else {
+            self.apply_at(&result, rest, line, col)
+        }
+    }
+
+    #[inline(never)]
+    fn apply_host(
+        &mut self,
+        host: &HostCall,
+        mut args: Vec<Value>,
+        line: usize,
+        col: usize,
+    ) -> ScriptResult<Value> {
+        let mut all = host.applied.clone();
+        all.append(&mut args);
+
+        if all.len() < host.arity {
+            return Ok(Value::Host(Arc::new(HostCall {
+                name: host.name,
+                arity: host.arity,
+                func: host.func,
+                applied: all,
+            })));
+        }
+
+        let rest = all.split_off(host.arity);
+        let result = (host.func)(self, all).map_err(|mut e| {
+            if e.line == 0 {
+                e.line = line;
+                e.column = col;
+                e.message = format!("{}: {}", host.name, e.message);
+            }
+            e
+        })?;
+
+        if rest.is_empty() {
+            Ok(result)
+        } else {
+            self.apply_at(&result, rest, line, col)
+        }
+    }
+
+    fn eval_binding(&mut self, binding: &Binding, env: &Env) -> ScriptResult<Value> {
+        let value = self.eval(&binding.body, env)?;
+        match (&binding.pattern, value) {
+            (Pattern::Var(name), Value::Closure(closure)) if binding.recursive => {
+                Ok(Value::Closure(Arc::new(Closure {
+                    lambda: Arc::clone(&closure.lambda),
+                    env: closure.env.clone(),
+                    rec_name: Some(name.clone()),
+                    applied: closure.applied.clone(),
+                })))
+            }
+            (_, value) => Ok(value),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr, env: &Env) -> ScriptResult<Value> {
+        if self.eval_depth >= MAX_EVAL_DEPTH {
+            return Err(ScriptError::runtime(
+                "maximum expression depth exceeded (runaway recursion?)",
+                expr.line,
+                expr.col,
+            ));
+        }
+
+        // Every non-trivial arm lives in its own function: the interpreter
+        // recurses through `eval`, so its stack frame must stay small. No arm
+        // returns early, so that the depth is always restored.
+        self.eval_depth += 1;
+        let result = match &expr.kind {
+            ExprKind::Unit => Ok(Value::Unit),
+            ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+            ExprKind::Int(i) => Ok(Value::Int(*i)),
+            ExprKind::Float(f) => Ok(Value::Float(*f)),
+            ExprKind::Str(s) => Ok(Value::Str(Arc::clone(s))),
+            ExprKind::Path(path) => self
+                .resolve(path, env)
+                .map_err(|message| ScriptError::runtime(message, expr.line, expr.col)),
+            ExprKind::List(items) => self.eval_all(items, env).map(Value::list),
+            ExprKind::Tuple(items) => {
+                self.eval_all(items, env).map(|items| Value::Tuple(Arc::new(items)))
+            }
+            ExprKind::Range(start, end) => self.eval_range(start, end, env, expr),
+            ExprKind::Record(fields) => self.eval_record(None, fields, env, expr),
+            ExprKind::RecordUpdate(base, fields) => self.eval_record(Some(base), fields, env, expr),
+            ExprKind::App(func, args) => self.eval_app(func, args, env, expr),
+            ExprKind::Binary(op, lhs, rhs) => self.eval_binary(*op, lhs, rhs, env, expr),
+            ExprKind::Neg(inner) => self.eval_neg(inner, env, expr),
+            ExprKind::If(cond, then_branch, else_branch) => {
+                self.eval_if(cond, then_branch, else_branch.as_deref(), env)
+            }
+            ExprKind::Match(scrutinee, arms) => self.eval_match(scrutinee, arms, env, expr),
+            ExprKind::Lambda(lambda) => Ok(Value::Closure(Arc::new(Closure {
+                lambda: Arc::clone(lambda),
+                env: env.clone(),
+                rec_name: None,
+                applied: Vec::new(),
+            }))),
+            ExprKind::Let(binding, body) => self.eval_let(binding, body, env),
+            ExprKind::Seq(first, rest) => {
+                self.eval(first, env).and_then(|_| self.eval(rest, env))
+            }
+        };
+        self.eval_depth -= 1;
+        result
+    }
+
+    #[inline(never)]
+    fn eval_all(&mut self, items: &[Expr], env: &Env) -> ScriptResult<Vec<Value>> {
+        items.iter().map(|item| self.eval(item, env)).collect()
+    }
+
+    #[inline(never)]
+    fn eval_range(
+        &mut self,
+        start: &Expr,
+        end: &Expr,
+        env: &Env,
+        expr: &Expr,
+    ) -> ScriptResult<Value> {
+        let start = self.eval(start, env)?;
+        let end = self.eval(end, env)?;
+        match (start, end) {
+            (Value::Int(a), Value::Int(b)) => {
+                if b.saturating_sub(a) > 100_000 {
+                    return Err(ScriptError::runtime(
+                        "range is too large",
+                        expr.line,
+                        expr.col,
+                    ));
+                }
+                Ok(Value::list((a..=b).map(Value::Int).collect()))
+            }
+            (a, b) => Err(ScriptError::runtime(
+                format!(
+                    "range bounds must be ints, found {} and {}",
+                    a.type_name(),
+                    b.type_name()
+                ),
+                expr.line,
+                expr.col,
+            )),
+        }
+    }
+
+    #[inline(never)]
+    fn eval_record(
+        &mut self,
+        base: Option<&Expr>,
+        fields: &[(String, Expr)],
+        env: &Env,
+        expr: &Expr,
+    ) -> ScriptResult<Value> {
+        let err = |message: String| ScriptError::runtime(message, expr.line, expr.col);
+
+        let mut map = match base {
+            Some(base) => match self.eval(base, env)? {
+                Value::Record(base) => (*base).clone(),
+                other => {
+                    return Err(err(format!(
+                        "`with` expects a record, found {}",
+                        other.type_name()
+                    )))
+                }
+            },
+            None => BTreeMap::new(),
+        };
+
+        for (name, value) in fields {
+            if base.is_some() && !map.contains_key(name) {
+                return Err(err(format!("record has no field `{}`", name)));
+            }
+            let value = self.eval(value, env)?;
+            map.insert(name.clone(), value);
+        }
+        Ok(Value::record(map))
+    }
+
+    #[inline(never)]
+    fn eval_app(
+        &mut self,
+        func: &Expr,
+        args: &[Expr],
+        env: &Env,
+        expr: &Expr,
+    ) -> ScriptResult<Value> {
+        let func = self.eval(func, env)?;
+        let args = self.eval_all(args, env)?;
+        self.apply_at(&func, args, expr.line, expr.col)
+    }
+
+    #[inline(never)]
+    fn eval_neg(&mut self, inner: &Expr, env: &Env, expr: &Expr) -> ScriptResult<Value> {
+        match self.eval(inner, env)? {
+            Value::Int(i) => Ok(Value::Int(i.wrapping_neg())),
+            Value::Float(f) => Ok(Value::Float(-f)),
+            other => Err(ScriptError::runtime(
+                format!("cannot negate a {}", other.type_name()),
+                expr.line,
+                expr.col,
+            )),
+        }
+    }
+
+    #[inline(never)]
+    fn eval_if(
+        &mut self,
+        cond: &Expr,
+        then_branch: &Expr,
+        else_branch: Option<&Expr>,
+        env: &Env,
+    ) -> ScriptResult<Value> {
+        match self.eval(cond, env)? {
+            Value::Bool(true) => self.eval(then_branch, env),
+            Value::Bool(false) => match else_branch {
+                Some(e) => self.eval(e, env),
+                None => Ok(Value::Unit),
+            },
+            other => Err(ScriptError::runtime(
+                format!("condition must be a bool, found {}", other.type_name()),
+                cond.line,
+                cond.col,
+            )),
+        }
+    }
+
+    #[inline(never)]
+    fn eval_match(
+        &mut self,
+        scrutinee: &Expr,
+        arms: &[Arm],
+        env: &Env,
+        expr: &Expr,
+    ) -> ScriptResult<Value> {
+        let value = self.eval(scrutinee, env)?;
+        for arm in arms {
+            let mut bound = Vec::new();
+            if !bind_pattern(&arm.pattern, &value, &mut bound) {
+                continue;
+            }
+            let mut arm_env = env.clone();
+            for (name, v) in bound {
+                arm_env = arm_env.bind(name, v);
+            }
+            if let Some(guard) = &arm.guard {
+                match self.eval(guard, &arm_env)? {
+                    Value::Bool(true) => {}
+                    Value::Bool(false) => continue,
+                    other => {
+                        return Err(ScriptError::runtime(
+                            format!("guard must be a bool, found {}", other.type_name()),
+                            guard.line,
+                            guard.col,
+                        ))
+                    }
+                }
+            }
+            return self.eval(&arm.body, &arm_env);
+        }
+        Err(ScriptError::runtime(
+            format!("no match arm matched the value {}", value),
+            expr.line,
+            expr.col,
+        ))
+    }
+
+    #[inline(never)]
+    fn eval_let(&mut self, binding: &Binding, body: &Expr, env: &Env) -> ScriptResult<Value> {
+        let value = self.eval_binding(binding, env)?;
+        let mut bound = Vec::new();
+        if !bind_pattern(&binding.pattern, &value, &mut bound) {
+            return Err(ScriptError::runtime(
+                "value does not match the pattern of this binding",
+                binding.body.line,
+                binding.body.col,
+            ));
+        }
+        let mut inner = env.clone();
+        for (name, v) in bound {
+            inner = inner.bind(name, v);
+        }
+        self.eval(body, &inner)
+    }
+
+    /// Resolve a possibly dotted path.
+    ///
+    /// The first segment is looked up among locals, falling back to the
+    /// longest matching global name (`tui.color.rgb`). Remaining segments are
+    /// record field accesses.
+    fn resolve(&self, path: &[String], env: &Env) -> Result<Value, String> {
+        let (mut value, consumed) = if let Some(local) = env.get(&path[0]) {
+            (local.clone(), 1)
+        } else {
+            let mut found = None;
+            for len in (1..=path.len()).rev() {
+                let name = path[..len].join(".");
+                if let Some(global) = self.globals.get(&name) {
+                    found = Some((global.clone(), len));
+                    break;
+                }
+            }
+            found.ok_or_else(|| format!("unbound identifier `{}`", path.join(".")))?
+        };
+
+        for field in &path[consumed..] {
+            value = match &value {
+                Value::Record(fields) => fields
+                    .get(field)
+                    .cloned()
+                    .ok_or_else(|| format!("record has no field `{}`", field))?,
+                Value::Rect(rect) => match field.as_str() {
+                    "x" => Value::Int(i64::from(rect.x)),
+                    "y" => Value::Int(i64::from(rect.y)),
+                    "width" => Value::Int(i64::from(rect.width)),
+                    "height" => Value::Int(i64::from(rect.height)),
+                    _ => return Err(format!("rect has no field `{}`", field)),
+                },
+                other => {
+                    return Err(format!(
+                        "cannot access field `{}` on a {}",
+                        field,
+                        other.type_name()
+                    ))
+                }
+            };
+        }
+
+        Ok(value)
+    }
+
+    #[inline(never)]
+    fn eval_binary(
+        &mut self,
+        op: BinOp,
+        lhs: &Expr,
+        rhs: &Expr,
+        env: &Env,
+        expr: &Expr,
+    ) -> ScriptResult<Value> {
+        // Short-circuiting and pipe operators evaluate operands themselves.
+        match op {
+            BinOp::And | BinOp::Or => {
+                let left = self.eval(lhs, env)?;
+                let Value::Bool(left) = left else {
+                    return Err(ScriptError::runtime(
+                        format!(
+                            "`{}` expects bools, found {}",
+                            op.symbol(),
+                            left.type_name()
+                        ),
+                        lhs.line,
+                        lhs.col,
+                    ));
+                };
+                if (op == BinOp::And && !left) || (op == BinOp::Or && left) {
+                    return Ok(Value::Bool(left));
+                }
+                return match self.eval(rhs, env)? {
+                    Value::Bool(b) => Ok(Value::Bool(b)),
+                    other => Err(ScriptError::runtime(
+                        format!(
+                            "`{}` expects bools, found {}",
+                            op.symbol(),
+                            other.type_name()
+                        ),
+                        rhs.line,
+                        rhs.col,
+                    )),
+                };
+            }
+            BinOp::PipeRight => {
+                let arg = self.eval(lhs, env)?;
+                let func = self.eval(rhs, env)?;
+                return self.apply_at(&func, vec![arg], rhs.line, rhs.col);
+            }
+            BinOp::PipeLeft => {
+                let func = self.eval(lhs, env)?;
+                let arg = self.eval(rhs, env)?;
+                return self.apply_at(&func, vec![arg], lhs.line, lhs.col);
+            }
+            _ => {}
+        }
+
+        let left = self.eval(lhs, env)?;
+        let right = self.eval(rhs, env)?;
+        binary_op(op, left, right)
+            .map_err(|message| ScriptError::runtime(message, expr.line, expr.col))
+    }
+}
+
+/// Apply a strict binary operator to evaluated operands.
+fn binary_op(op: BinOp, left: Value, right: Value) -> Result<Value, String> {
+    let mismatch = |l: &Value, r: &Value| {
+        format!(
+            "operator `{}` cannot be applied to {} and {}",
+            op.symbol(),
+            l.type_name(),
+            r.type_name()
+        )
+    };
+
+    match op {
+        BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Rem => match (&left, &right) {
+            (Value::Int(a), Value::Int(b)) => {
+                let (a, b) = (*a, *b);
+                let result = match op {
+                    BinOp::Add => a.checked_add(b),
+                    BinOp::Sub => a.checked_sub(b),
+                    BinOp::Mul => a.checked_mul(b),
+                    BinOp::Div if b == 0 => return Err("division by zero".to_string()),
+                    BinOp::Div => a.checked_div(b),
+                    BinOp::Rem if b == 0 => return Err("division by zero".to_string()),
+                    _ => a.checked_rem(b),
+                };
+                result
+                    .map(Value::Int)
+                    .ok_or_else(|| "integer overflow".to_string())
+            }
+            (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+                let a = as_float(&left);
+                let b = as_float(&right);
+                Ok(Value::Float(match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    _ => a % b,
+                }))
+            }
+            (Value::Str(a), Value::Str(b)) if op == BinOp::Add => {
+                Ok(Value::str(format!("{}{}", a, b)))
+            }
+            _ => Err(mismatch(&left, &right)),
+        },
+        BinOp::Concat => match (&left, &right) {
+            (Value::Str(a), Value::Str(b)) => Ok(Value::str(format!("{}{}", a, b))),
+            _ => Err(mismatch(&left, &right)),
+        },
+        BinOp::Append => match (&left, &right) {
+            (Value::List(a), Value::List(b)) => {
+                let mut items = (**a).clone();
+                items.extend(b.iter().cloned());
+                Ok(Value::list(items))
+            }
+            _ => Err(mismatch(&left, &right)),
+        },
+        BinOp::Cons => match right {
+            Value::List(tail) => {
+                let mut items = Vec::with_capacity(tail.len() + 1);
+                items.push(left);
+                items.extend(tail.iter().cloned());
+                Ok(Value::list(items))
+            }
+            other => Err(mismatch(&left, &other)),
+        },
+        BinOp::Eq => Ok(Value::Bool(left.equals(&right))),
+        BinOp::Ne => Ok(Value::Bool(!left.equals(&right))),
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            let ordering = left
+                .compare(&right)
+                .ok_or_else(|| mismatch(&left, &right))?;
+            Ok(Value::Bool(match op {
+                BinOp::Lt => ordering == Ordering::Less,
+                BinOp::Le => ordering != Ordering::Greater,
+                BinOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }))
+        }
+        BinOp::And | BinOp::Or | BinOp::PipeRight | BinOp::PipeLeft => {
+            unreachable!("handled in eval_binary")
+        }
+    }
+}
+
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Int(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => 0.0,
+    }
+}
+
+/// Build the environment for a closure call from its fully applied arguments.
+fn bind_arguments(
+    closure: &Closure,
+    args: Vec<Value>,
+    line: usize,
+    col: usize,
+) -> ScriptResult<Env> {
+    let mut env = closure.env.clone();
+    if let Some(name) = &closure.rec_name {
+        let itself = Value::Closure(Arc::new(Closure {
+            lambda: Arc::clone(&closure.lambda),
+            env: closure.env.clone(),
+            rec_name: closure.rec_name.clone(),
+            applied: Vec::new(),
+        }));
+        env = env.bind(name.clone(), itself);
+    }
+
+    for (param, arg) in closure.lambda.params.iter().zip(args) {
+        let mut bound = Vec::new();
+        if !bind_pattern(param, &arg, &mut bound) {
+            return Err(ScriptError::runtime(
+                format!("argument {} does not match the parameter pattern", arg),
+                line,
+                col,
+            ));
+        }
+        for (name, value) in bound {
+            env = env.bind(name, value);
+        }
+    }
+    Ok(env)
+}
+
+/// Match `value` against `pattern`, collecting variable bindings.
+fn bind_pattern(pattern: &Pattern, value: &Value, bound: &mut Vec<(String, Value)>) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) => true,
+        (Pattern::Var(name), v) => {
+            bound.push((name.clone(), v.clone()));
+            true
+        }
+        (Pattern::Unit, Value::Unit) => true,
+        (Pattern::Bool(a), Value::Bool(b)) => a == b,
+        (Pattern::Int(a), Value::Int(b)) => a == b,
+        (Pattern::Str(a), Value::Str(b)) => a.as_str() == &**b,
+        (Pattern::Tuple(pats), Value::Tuple(items)) => {
+            pats.len() == items.len()
+                && pats
+                    .iter()
+                    .zip(items.iter())
+                    .all(|(p, v)| bind_pattern(p, v, bound))
+        }
+        (Pattern::List(pats), Value::List(items)) => {
+            pats.len() == items.len()
+                && pats
+                    .iter()
+                    .zip(items.iter())
+                    .all(|(p, v)| bind_pattern(p, v, bound))
+        }
+        (Pattern::Cons(head, tail), Value::List(items)) => {
+            if items.is_empty() {
+                return false;
+            }
+            let rest = Value::list(items[1..].to_vec());
+            bind_pattern(head, &items[0], bound) && bind_pattern(tail, &rest, bound)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fusabi_runtime::parser::parse;
+
+    fn run(source: &str) -> Interpreter {
+        let mut interp = Interpreter::new();
+        interp.run(&parse(source).unwrap()).unwrap();
+        interp
+    }
+
+    fn global(interp: &Interpreter, name: &str) -> String {
+        interp.global(name).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_eval_arithmetic_and_functions() {
+        let interp = run("let add a b = a + b\nlet inc = add 1\nlet x = inc 41 * 2");
+        assert_eq!(global(&interp, "x"), "84");
+    }
+
+    #[test]
+    fn test_eval_recursion_and_match() {
+        let source = r#"
+let rec sum xs =
+    match xs with
+    | [] -> 0
+    | x :: rest -> x + sum rest
+let total = sum [1 .. 10]
+"#;
+        assert_eq!(global(&run(source), "total"), "55");
+    }
+
+    #[test]
+    fn test_eval_local_rec_and_closures() {
+        let source = r#"
+let result =
+    let rec fact n = if n <= 1 then 1 else n * fact (n - 1)
+    let k = 2
+    (fun x -> x * k) (fact 5)
+"#;
+        assert_eq!(global(&run(source), "result"), "240");
+    }
+
+    #[test]
+    fn test_eval_records_and_pipes() {
+        let source = r#"
+let r = { name = "cpu"; value = 3 }
+let r2 = { r with value = r.value + 1 }
+let label = r2.name ^ ":" ^ (if r2.value = 4 then "ok" else "bad")
+let piped = 3 |> (fun x -> x + 1) |> (fun x -> x * 10)
+"#;
+        let interp = run(source);
+        assert_eq!(global(&interp, "label"), "cpu:ok");
+        assert_eq!(global(&interp, "piped"), "40");
+    }
+
+    #[test]
+    fn test_eval_runtime_error_position() {
+        let mut interp = Interpreter::new();
+        let program = parse("let a = 1\nlet b = a + \"x\"").unwrap();
+        let err = interp.run(&program).unwrap_err();
+        assert_eq!(err.kind, crate::error::ScriptErrorKind::Runtime);
+        assert_eq!((err.line, err.column), (2, 9));
+    }
+
+    #[test]
+    fn test_eval_unbound_identifier() {
+        let mut interp = Interpreter::new();
+        let err = interp
+            .run(&parse("let a = missing + 1").unwrap())
+            .unwrap_err();
+        assert!(err.message.contains("unbound identifier `missing`"));
+    }
+
+    #[test]
+    fn test_eval_deep_nesting_is_reported() {
+        let mut interp = Interpreter::new();
+        let source = "let rec f n = if n = 0 then 0 else 1 + (1 + (1 + (1 + f (n - 1))))\n\
+                      let x = f 20\n\
+                      let y = f 100";
+        let err = interp.run(&parse(source).unwrap()).unwrap_err();
+        assert!(err.message.contains("maximum expression depth"));
+        assert_eq!(global(&interp, "x"), "80");
+
+        // The depth is restored after the error
+        interp.run(&parse("let z = f 20").unwrap()).unwrap();
+        assert_eq!(global(&interp, "z"), "80");
+    }
+
+    #[test]
+    fn test_eval_runaway_recursion_is_reported() {
+        let mut interp = Interpreter::new();
+        let program = parse("let rec loop n = 1 + loop n\nlet x = loop 0").unwrap();
+        let err = interp.run(&program).unwrap_err();
+        assert!(err.message.contains("maximum call depth"));
+    }
+}
@@ -0,0 +1,386 @@
+//! Tokenizer for Fusabi dashboard scripts.
+//!
+//! The lexer records the line and column of every token. The parser relies on
+//! those positions to implement F#-style indentation (offside) rules.
+
+use crate::error::{ScriptError, ScriptResult};
+
+/// A lexical token kind.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Tok {
+    /// Integer literal.
+    Int(i64),
+    /// Floating point literal.
+    Float(f64),
+    /// String literal with escapes already processed.
+    Str(String),
+    /// Identifier, possibly dotted (`tui.color.rgb`, `state.focus`).
+    Ident(String),
+    /// Reserved word.
+    Keyword(&'static str),
+    /// Operator or punctuation.
+    Sym(&'static str),
+    /// `#load "path"` directive, handled by the module loader.
+    Load(String),
+    /// End of input.
+    Eof,
+}
+
+/// A token together with its 1-based source position.
+#[derive(Debug, Clone)]
+pub(crate) struct Token {
+    pub tok: Tok,
+    pub line: usize,
+    pub col: usize,
+}
+
+const KEYWORDS: &[&str] = &[
+    "let", "rec", "in", "if", "then", "elif", "else", "match", "with", "when", "fun", "true",
+    "false", "open",
+];
+
+/// Symbols ordered longest first so greedy matching works.
+const SYMBOLS: &[&str] = &[
+    "|>", "<|", "->", "::", "<>", "<=", ">=", "&&", "||", "..", "(", ")", "[", "]", "{", "}", ";",
+    ",", "=", "<", ">", "+", "-", "*", "/", "%", "|", "@", "^", "_",
+];
+
+/// Tokenize a script.
+pub(crate) fn tokenize(source: &str) -> ScriptResult<Vec<Token>> {
+    Lexer::new(source).run()
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    col: usize,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().peekable(),
+            line: 1,
+            col: 1,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn peek2(&self) -> Option<char> {
+        let mut it = self.chars.clone();
+        it.next();
+        it.next()
+    }
+
+    fn push(&mut self, tok: Tok, line: usize, col: usize) {
+        self.tokens.push(Token { tok, line, col });
+    }
+
+    fn run(mut self) -> ScriptResult<Vec<Token>> {
+        while let Some(c) = self.peek() {
+            let (line, col) = (self.line, self.col);
+
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '/' && self.peek2() == Some('/') {
+                while let Some(c) = self.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.bump();
+                }
+            } else if c == '(' && self.peek2() == Some('*') {
+                self.block_comment(line, col)?;
+            } else if c == '#' {
+                self.directive(line, col)?;
+            } else if c == '"' {
+                let s = self.string(line, col)?;
+                self.push(Tok::Str(s), line, col);
+            } else if c.is_ascii_digit() {
+                let tok = self.number(line, col)?;
+                self.push(tok, line, col);
+            } else if c.is_alphabetic() || c == '_' && self.peek2().is_some_and(is_ident_char) {
+                let tok = self.ident();
+                self.push(tok, line, col);
+            } else {
+                let sym = self.symbol(line, col)?;
+                self.push(Tok::Sym(sym), line, col);
+            }
+        }
+
+        let (line, col) = (self.line, self.col);
+        self.push(Tok::Eof, line, col);
+        Ok(self.tokens)
+    }
+
+    fn block_comment(&mut self, line: usize, col: usize) -> ScriptResult<()> {
+        self.bump();
+        self.bump();
+        let mut depth = 1;
+        while depth > 0 {
+            match self.bump() {
+                Some('(') if self.peek() == Some('*') => {
+                    self.bump();
+                    depth += 1;
+                }
+                Some('*') if self.peek() == Some(')') => {
+                    self.bump();
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => return Err(ScriptError::syntax("unterminated comment", line, col)),
+            }
+        }
+        Ok(())
+    }
+
+    fn directive(&mut self, line: usize, col: usize) -> ScriptResult<()> {
+        self.bump();
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !c.is_alphanumeric() {
+                break;
+            }
+            name.push(c);
+            self.bump();
+        }
+
+        while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+            self.bump();
+        }
+
+        match name.as_str() {
+            "load" if self.peek() == Some('"') => {
+                let (sl, sc) = (self.line, self.col);
+                let path = self.string(sl, sc)?;
+                self.push(Tok::Load(path), line, col);
+                Ok(())
+            }
+            "load" => Err(ScriptError::syntax(
+                "expected a quoted path after #load",
+                line,
+                col,
+            )),
+            // Other directives (#r, #nowarn, ...) are ignored up to end of line.
+            _ => {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn string(&mut self, line: usize, col: usize) -> ScriptResult<String> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('0') => out.push('\0'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some(other) => {
+                        out.push('\\');
+                        out.push(other);
+                    }
+                    None => break,
+                },
+                Some(c) => out.push(c),
+                None => break,
+            }
+        }
+        Err(ScriptError::syntax(
+            "unterminated string literal",
+            line,
+            col,
+        ))
+    }
+
+    fn number(&mut self, line: usize, col: usize) -> ScriptResult<Tok> {
+        let mut text = String::new();
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || c == '_' {
+                if c != '_' {
+                    text.push(c);
+                }
+                self.bump();
+            } else if c == '.' && !is_float && self.peek2().is_some_and(|d| d.is_ascii_digit()) {
+                is_float = true;
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        // Accept (and drop) F# numeric suffixes such as `1.0f` or `10L`.
+        if self.peek().is_some_and(|c| matches!(c, 'f' | 'L' | 'u')) {
+            is_float |= self.peek() == Some('f');
+            self.bump();
+        }
+
+        if is_float {
+            text.parse()
+                .map(Tok::Float)
+                .map_err(|_| ScriptError::syntax(format!("invalid number `{}`", text), line, col))
+        } else {
+            text.parse()
+                .map(Tok::Int)
+                .map_err(|_| ScriptError::syntax(format!("invalid number `{}`", text), line, col))
+        }
+    }
+
+    fn ident(&mut self) -> Tok {
+        let mut text = String::new();
+        loop {
+            while let Some(c) = self.peek() {
+                if !is_ident_char(c) {
+                    break;
+                }
+                text.push(c);
+                self.bump();
+            }
+
+            // Join `a.b` into one dotted identifier when the dot is directly
+            // followed by another identifier.
+            if self.peek() == Some('.') && self.peek2().is_some_and(|c| c.is_alphabetic()) {
+                text.push('.');
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        match KEYWORDS.iter().find(|k| **k == text) {
+            Some(k) => Tok::Keyword(k),
+            None => Tok::Ident(text),
+        }
+    }
+
+    fn symbol(&mut self, line: usize, col: usize) -> ScriptResult<&'static str> {
+        let first = self.peek().unwrap_or_default();
+        let second = self.peek2();
+
+        for sym in SYMBOLS {
+            let mut it = sym.chars();
+            let a = it.next();
+            let b = it.next();
+            let matched = match b {
+                Some(b) => a == Some(first) && second == Some(b),
+                None => a == Some(first),
+            };
+            if matched {
+                for _ in 0..sym.len() {
+                    self.bump();
+                }
+                return Ok(sym);
+            }
+        }
+
+        Err(ScriptError::syntax(
+            format!("unexpected character `{}`", first),
+            line,
+            col,
+        ))
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<Tok> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.tok)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_let_binding() {
+        assert_eq!(
+            kinds("let x = 42"),
+            vec![
+                Tok::Keyword("let"),
+                Tok::Ident("x".to_string()),
+                Tok::Sym("="),
+                Tok::Int(42),
+                Tok::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_dotted_identifier() {
+        assert_eq!(
+            kinds("tui.color.rgb 1 2.5"),
+            vec![
+                Tok::Ident("tui.color.rgb".to_string()),
+                Tok::Int(1),
+                Tok::Float(2.5),
+                Tok::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_comments_and_load() {
+        let toks = kinds("// hello\n#load \"tui.fsx\"\n(* block (* nested *) *) x");
+        assert_eq!(
+            toks,
+            vec![
+                Tok::Load("tui.fsx".to_string()),
+                Tok::Ident("x".to_string()),
+                Tok::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_positions() {
+        let toks = tokenize("let a =\n    a |> f").unwrap();
+        let pipe = toks.iter().find(|t| t.tok == Tok::Sym("|>")).unwrap();
+        assert_eq!((pipe.line, pipe.col), (2, 7));
+    }
+
+    #[test]
+    fn test_tokenize_string_escapes() {
+        assert_eq!(
+            kinds(r#""a\n\"b\"""#),
+            vec![Tok::Str("a\n\"b\"".to_string()), Tok::Eof]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_unterminated_string() {
+        let err = tokenize("let s = \"oops").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 9));
+    }
+}
@@ -0,0 +1,934 @@
+//! Parser for Fusabi dashboard scripts.
+//!
+//! Scripts use F# "light" syntax, where indentation rather than `in` and
+//! `;;` delimits blocks. The parser implements a simplified offside rule:
+//!
+//! - Items of a block start at the block's column.
+//! - A token on a later line continues the current expression only when it is
+//!   indented past the block's column. Infix operators may sit on the block's
+//!   column itself, so `|>` pipelines can be aligned with their first line.
+//! - Inside parentheses the rule is lifted entirely.
+
+use std::sync::Arc;
+
+use super::ast::{Arm, BinOp, Binding, Expr, ExprKind, Item, Lambda, Pattern, Program};
+use super::lexer::{tokenize, Tok, Token};
+use crate::error::{ScriptError, ScriptResult};
+
+/// Parse a complete script.
+pub(crate) fn parse(source: &str) -> ScriptResult<Program> {
+    let tokens = tokenize(source)?;
+    Parser::new(tokens).program()
+}
+
+/// Offside column used inside parentheses, where indentation is not significant.
+const NO_OFFSIDE: usize = 0;
+
+/// Maximum nesting of expressions, block items and patterns.
+///
+/// The parser and the evaluator both recurse once per level, so deeper
+/// scripts are rejected with a syntax error instead of overflowing the stack.
+/// Each operator of a chain such as `1 + 1 + 1` counts as a level.
+const MAX_NESTING_DEPTH: usize = 200;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    // =========================================================================
+    // Token helpers
+    // =========================================================================
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        &self.tokens[(self.pos + offset).min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> Token {
+        let tok = self.peek().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn prev_line(&self) -> usize {
+        if self.pos == 0 {
+            0
+        } else {
+            self.tokens[self.pos - 1].line
+        }
+    }
+
+    fn is_sym(&self, sym: &str) -> bool {
+        matches!(self.peek().tok, Tok::Sym(s) if s == sym)
+    }
+
+    fn is_kw(&self, kw: &str) -> bool {
+        matches!(self.peek().tok, Tok::Keyword(k) if k == kw)
+    }
+
+    fn is_eof(&self) -> bool {
+        self.peek().tok == Tok::Eof
+    }
+
+    /// Whether the current token is on a new line relative to the previous one.
+    fn on_new_line(&self) -> bool {
+        self.peek().line > self.prev_line()
+    }
+
+    /// Whether the current token may continue an expression with the given offside column.
+    fn continues(&self, offside: usize) -> bool {
+        !self.is_eof() && (!self.on_new_line() || self.peek().col > offside)
+    }
+
+    /// Like [`continues`](Self::continues), but for infix operators, which may be
+    /// aligned with the offside column.
+    fn continues_infix(&self, offside: usize) -> bool {
+        !self.is_eof() && (!self.on_new_line() || self.peek().col >= offside)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ScriptError {
+        let tok = self.peek();
+        ScriptError::syntax(message, tok.line, tok.col)
+    }
+
+    /// Go one nesting level deeper, failing past [`MAX_NESTING_DEPTH`].
+    fn enter(&mut self) -> ScriptResult<()> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.error(format!(
+                "expression is nested more than {} levels deep",
+                MAX_NESTING_DEPTH
+            )));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Parse with `parse` one nesting level deeper.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> ScriptResult<T>,
+    ) -> ScriptResult<T> {
+        self.enter()?;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn unexpected(&self, expected: &str) -> ScriptError {
+        let found = match &self.peek().tok {
+            Tok::Int(i) => i.to_string(),
+            Tok::Float(f) => f.to_string(),
+            Tok::Str(s) => format!("\"{}\"", s),
+            Tok::Ident(s) => s.clone(),
+            Tok::Keyword(k) => (*k).to_string(),
+            Tok::Sym(s) => (*s).to_string(),
+            Tok::Load(_) => "#load".to_string(),
+            Tok::Eof => "end of file".to_string(),
+        };
+        self.error(format!("expected {}, found `{}`", expected, found))
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> ScriptResult<Token> {
+        if self.is_sym(sym) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(&format!("`{}`", sym)))
+        }
+    }
+
+    fn expect_kw(&mut self, kw: &str) -> ScriptResult<Token> {
+        if self.is_kw(kw) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(&format!("`{}`", kw)))
+        }
+    }
+
+    fn expect_ident(&mut self) -> ScriptResult<(String, Token)> {
+        match &self.peek().tok {
+            Tok::Ident(name) if !name.contains('.') => {
+                let name = name.clone();
+                Ok((name, self.advance()))
+            }
+            _ => Err(self.unexpected("an identifier")),
+        }
+    }
+
+    // =========================================================================
+    // Program and blocks
+    // =========================================================================
+
+    fn program(mut self) -> ScriptResult<Program> {
+        let mut program = Program::default();
+        let col = self.peek().col;
+
+        while !self.is_eof() {
+            if let Tok::Load(path) = self.peek().tok.clone() {
+                let tok = self.advance();
+                program.loads.push((path, tok.line, tok.col));
+                continue;
+            }
+
+            if self.is_kw("open") {
+                self.advance();
+                self.expect_qualified_name()?;
+                continue;
+            }
+
+            if self.peek().col != col {
+                return Err(self.error(format!(
+                    "top-level item must start in column {}, found column {}",
+                    col,
+                    self.peek().col
+                )));
+            }
+
+            if self.is_kw("let") {
+                let binding = self.let_binding(col)?;
+                if self.is_kw("in") {
+                    self.advance();
+                    let body = self.expr(col)?;
+                    let (line, c) = (binding.body.line, binding.body.col);
+                    program.items.push(Item::Expr(Expr::new(
+                        ExprKind::Let(Box::new(binding), Box::new(body)),
+                        line,
+                        c,
+                    )));
+                } else {
+                    program.items.push(Item::Let(binding));
+                }
+            } else {
+                let expr = self.expr(col)?;
+                program.items.push(Item::Expr(expr));
+            }
+
+            if !self.is_eof() && !self.on_new_line() {
+                return Err(self.unexpected("a new line"));
+            }
+        }
+
+        Ok(program)
+    }
+
+    fn expect_qualified_name(&mut self) -> ScriptResult<()> {
+        match self.peek().tok {
+            Tok::Ident(_) => {
+                self.advance();
+                Ok(())
+            }
+            _ => Err(self.unexpected("a module name")),
+        }
+    }
+
+    /// Parse a sequence of items aligned on `col`.
+    fn block(&mut self, col: usize) -> ScriptResult<Expr> {
+        self.nested(|p| p.block_items(col))
+    }
+
+    fn block_items(&mut self, col: usize) -> ScriptResult<Expr> {
+        if self.is_kw("let") {
+            let binding = self.let_binding(col)?;
+            let (line, c) = (binding.body.line, binding.body.col);
+
+            let body = if self.is_kw("in") {
+                self.advance();
+                if self.on_new_line() {
+                    let body_col = self.peek().col;
+                    self.block(body_col)?
+                } else {
+                    self.block_or_expr(col)?
+                }
+            } else if self.on_new_line() && self.peek().col == col && !self.is_eof() {
+                self.block(col)?
+            } else {
+                return Err(ScriptError::syntax(
+                    "a `let` binding must be followed by an expression",
+                    line,
+                    c,
+                ));
+            };
+
+            return Ok(Expr::new(
+                ExprKind::Let(Box::new(binding), Box::new(body)),
+                line,
+                c,
+            ));
+        }
+
+        let first = self.expr(col)?;
+        if self.on_new_line() && self.peek().col == col && self.starts_item() {
+            let (line, c) = (first.line, first.col);
+            let rest = self.block(col)?;
+            Ok(Expr::new(
+                ExprKind::Seq(Box::new(first), Box::new(rest)),
+                line,
+                c,
+            ))
+        } else {
+            Ok(first)
+        }
+    }
+
+    /// Parse an expression either inline or as an indented block on the next line.
+    fn block_or_expr(&mut self, offside: usize) -> ScriptResult<Expr> {
+        if self.on_new_line() {
+            if self.peek().col <= offside || self.is_eof() {
+                return Err(self.unexpected("an indented expression"));
+            }
+            let col = self.peek().col;
+            self.block(col)
+        } else if self.is_kw("let") {
+            let col = self.peek().col;
+            self.block(col)
+        } else {
+            self.expr(offside)
+        }
+    }
+
+    /// Whether the current token can begin a block item.
+    fn starts_item(&self) -> bool {
+        match &self.peek().tok {
+            Tok::Keyword(k) => matches!(*k, "let" | "if" | "match" | "fun" | "true" | "false"),
+            Tok::Sym(s) => matches!(*s, "(" | "[" | "{" | "-"),
+            Tok::Eof | Tok::Load(_) => false,
+            _ => true,
+        }
+    }
+
+    fn let_binding(&mut self, col: usize) -> ScriptResult<Binding> {
+        self.expect_kw("let")?;
+        let recursive = if self.is_kw("rec") {
+            self.advance();
+            true
+        } else {
+            false
+        };
+
+        let head = self.peek().clone();
+        let mut pattern = self.pattern_atom()?;
+
+        let mut params = Vec::new();
+        if let Pattern::Var(_) = pattern {
+            while !self.is_sym("=") && !self.is_sym(",") && !self.is_eof() {
+                params.push(self.pattern_atom()?);
+            }
+        }
+
+        if params.is_empty() && self.is_sym(",") {
+            let mut items = vec![pattern];
+            while self.is_sym(",") {
+                self.advance();
+                items.push(self.pattern_atom()?);
+            }
+            pattern = Pattern::Tuple(items);
+        }
+
+        self.expect_sym("=")?;
+        let body = self.block_or_expr(col)?;
+
+        let body = if params.is_empty() {
+            body
+        } else {
+            Expr::new(
+                ExprKind::Lambda(Arc::new(Lambda { params, body })),
+                head.line,
+                head.col,
+            )
+        };
+
+        Ok(Binding {
+            pattern,
+            recursive,
+            body,
+        })
+    }
+
+    // =========================================================================
+    // Expressions
+    // =========================================================================
+
+    /// Parse a full expression, including tuples.
+    fn expr(&mut self, offside: usize) -> ScriptResult<Expr> {
+        let first = self.expr_no_tuple(offside)?;
+        if !self.is_sym(",") || !self.continues_infix(offside) {
+            return Ok(first);
+        }
+
+        let (line, col) = (first.line, first.col);
+        let mut items = vec![first];
+        while self.is_sym(",") && self.continues_infix(offside) {
+            self.advance();
+            items.push(self.expr_no_tuple(offside)?);
+        }
+        Ok(Expr::new(ExprKind::Tuple(items), line, col))
+    }
+
+    fn expr_no_tuple(&mut self, offside: usize) -> ScriptResult<Expr> {
+        self.nested(|p| match p.peek().tok {
+            Tok::Keyword("if") => p.if_expr(offside),
+            Tok::Keyword("match") => p.match_expr(offside),
+            Tok::Keyword("fun") => p.lambda(offside),
+            Tok::Keyword("let") => {
+                let col = p.peek().col;
+                p.block(col)
+            }
+            _ => p.binary(offside, 0),
+        })
+    }
+
+    fn binary(&mut self, offside: usize, min_prec: u8) -> ScriptResult<Expr> {
+        let mut lhs = self.unary(offside)?;
+        // Every operator nests the expression so far one level deeper
+        let mut chain = 0;
+
+        while let Tok::Sym(sym) = self.peek().tok {
+            let Some((op, prec, right_assoc)) = BinOp::from_symbol(sym) else {
+                break;
+            };
+
+            if prec <= min_prec || !self.continues_infix(offside) {
+                break;
+            }
+
+            self.enter()?;
+            chain += 1;
+            self.advance();
+            let next_min = if right_assoc { prec - 1 } else { prec };
+            let rhs = if self.is_kw("fun") || self.is_kw("if") || self.is_kw("match") {
+                self.expr_no_tuple(offside)?
+            } else {
+                self.binary(offside, next_min)?
+            };
+
+            let (line, col) = (lhs.line, lhs.col);
+            lhs = Expr::new(
+                ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+                line,
+                col,
+            );
+        }
+
+        self.depth -= chain;
+        Ok(lhs)
+    }
+
+    fn unary(&mut self, offside: usize) -> ScriptResult<Expr> {
+        if self.is_sym("-") {
+            let tok = self.advance();
+            let operand = self.nested(|p| p.unary(offside))?;
+            return Ok(match operand.kind {
+                ExprKind::Int(i) => Expr::new(ExprKind::Int(-i), tok.line, tok.col),
+                ExprKind::Float(f) => Expr::new(ExprKind::Float(-f), tok.line, tok.col),
+                _ => Expr::new(ExprKind::Neg(Box::new(operand)), tok.line, tok.col),
+            });
+        }
+        self.application(offside)
+    }
+
+    fn application(&mut self, offside: usize) -> ScriptResult<Expr> {
+        let func = self.atom()?;
+        let mut args = Vec::new();
+
+        while self.starts_atom() && self.continues(offside) {
+            args.push(self.atom()?);
+        }
+
+        if args.is_empty() {
+            Ok(func)
+        } else {
+            let (line, col) = (func.line, func.col);
+            Ok(Expr::new(ExprKind::App(Box::new(func), args), line, col))
+        }
+    }
+
+    fn starts_atom(&self) -> bool {
+        match &self.peek().tok {
+            Tok::Int(_) | Tok::Float(_) | Tok::Str(_) | Tok::Ident(_) => true,
+            Tok::Keyword(k) => matches!(*k, "true" | "false"),
+            Tok::Sym(s) => matches!(*s, "(" | "[" | "{"),
+            _ => false,
+        }
+    }
+
+    fn atom(&mut self) -> ScriptResult<Expr> {
+        let tok = self.peek().clone();
+        let (line, col) = (tok.line, tok.col);
+
+        let kind = match tok.tok {
+            Tok::Int(i) => {
+                self.advance();
+                ExprKind::Int(i)
+            }
+            Tok::Float(f) => {
+                self.advance();
+                ExprKind::Float(f)
+            }
+            Tok::Str(s) => {
+                self.advance();
+                ExprKind::Str(Arc::from(s.as_str()))
+            }
+            Tok::Keyword("true") => {
+                self.advance();
+                ExprKind::Bool(true)
+            }
+            Tok::Keyword("false") => {
+                self.advance();
+                ExprKind::Bool(false)
+            }
+            Tok::Ident(name) => {
+                self.advance();
+                ExprKind::Path(name.split('.').map(str::to_string).collect())
+            }
+            Tok::Sym("(") => return self.paren(),
+            Tok::Sym("[") => return self.list(),
+            Tok::Sym("{") => return self.record(),
+            Tok::Keyword("if") | Tok::Keyword("match") | Tok::Keyword("fun") => {
+                return self.expr_no_tuple(NO_OFFSIDE);
+            }
+            _ => return Err(self.unexpected("an expression")),
+        };
+
+        Ok(Expr::new(kind, line, col))
+    }
+
+    fn paren(&mut self) -> ScriptResult<Expr> {
+        let open = self.expect_sym("(")?;
+        let (line, col) = (open.line, open.col);
+
+        if self.is_sym(")") {
+            self.advance();
+            return Ok(Expr::new(ExprKind::Unit, line, col));
+        }
+
+        // Operator section, e.g. `(+)`.
+        if let Tok::Sym(sym) = self.peek().tok {
+            if let Some((op, _, _)) = BinOp::from_symbol(sym) {
+                if matches!(self.peek_at(1).tok, Tok::Sym(")")) {
+                    self.advance();
+                    self.advance();
+                    return Ok(operator_lambda(op, line, col));
+                }
+            }
+        }
+
+        let inner = if self.on_new_line() || self.is_kw("let") {
+            let c = self.peek().col;
+            self.block(c)?
+        } else {
+            self.expr(NO_OFFSIDE)?
+        };
+        self.expect_sym(")")?;
+        Ok(inner)
+    }
+
+    fn list(&mut self) -> ScriptResult<Expr> {
+        let open = self.expect_sym("[")?;
+        let (line, col) = (open.line, open.col);
+
+        if self.is_sym("]") {
+            self.advance();
+            return Ok(Expr::new(ExprKind::List(Vec::new()), line, col));
+        }
+
+        let first_col = self.peek().col;
+        let first = self.expr(first_col)?;
+
+        if self.is_sym("..") {
+            self.advance();
+            let end = self.expr(NO_OFFSIDE)?;
+            self.expect_sym("]")?;
+            return Ok(Expr::new(
+                ExprKind::Range(Box::new(first), Box::new(end)),
+                line,
+                col,
+            ));
+        }
+
+        let mut items = vec![first];
+        loop {
+            if self.is_sym(";") {
+                self.advance();
+                if self.is_sym("]") {
+                    break;
+                }
+                let c = self.peek().col;
+                items.push(self.expr(c.min(first_col))?);
+            } else if self.is_sym("]") {
+                break;
+            } else if self.on_new_line() && self.peek().col == first_col {
+                items.push(self.expr(first_col)?);
+            } else {
+                return Err(self.unexpected("`;` or `]`"));
+            }
+        }
+        self.expect_sym("]")?;
+        Ok(Expr::new(ExprKind::List(items), line, col))
+    }
+
+    fn record(&mut self) -> ScriptResult<Expr> {
+        let open = self.expect_sym("{")?;
+        let (line, col) = (open.line, open.col);
+
+        let base = if matches!(self.peek().tok, Tok::Ident(_))
+            && matches!(self.peek_at(1).tok, Tok::Keyword("with"))
+        {
+            let base = self.atom()?;
+            self.advance();
+            Some(base)
+        } else {
+            None
+        };
+
+        let mut fields = Vec::new();
+        let first_col = self.peek().col;
+        while !self.is_sym("}") {
+            let (name, field_tok) = self.expect_ident()?;
+            self.expect_sym("=")?;
+            let value = self.block_or_expr(field_tok.col)?;
+            fields.push((name, value));
+
+            if self.is_sym(";") {
+                self.advance();
+            } else if self.is_sym("}") {
+                break;
+            } else if !(self.on_new_line() && self.peek().col == first_col) {
+                return Err(self.unexpected("`;` or `}`"));
+            }
+        }
+        self.expect_sym("}")?;
+
+        let kind = match base {
+            Some(base) => ExprKind::RecordUpdate(Box::new(base), fields),
+            None => ExprKind::Record(fields),
+        };
+        Ok(Expr::new(kind, line, col))
+    }
+
+    fn if_expr(&mut self, offside: usize) -> ScriptResult<Expr> {
+        let if_tok = self.advance();
+        let cond = self.expr(NO_OFFSIDE)?;
+        self.expect_kw("then")?;
+        let then_branch = self.block_or_expr(offside)?;
+
+        let else_branch = if self.is_kw("elif") && self.peek().col >= offside {
+            Some(Box::new(self.nested(|p| p.if_expr(offside))?))
+        } else if self.is_kw("else") && self.peek().col >= offside {
+            self.advance();
+            Some(Box::new(self.block_or_expr(offside)?))
+        } else {
+            None
+        };
+
+        Ok(Expr::new(
+            ExprKind::If(Box::new(cond), Box::new(then_branch), else_branch),
+            if_tok.line,
+            if_tok.col,
+        ))
+    }
+
+    fn match_expr(&mut self, offside: usize) -> ScriptResult<Expr> {
+        let match_tok = self.advance();
+        let scrutinee = self.expr(NO_OFFSIDE)?;
+        self.expect_kw("with")?;
+
+        // Arms may be aligned with `match` or with the enclosing block.
+        let arm_col = offside.min(match_tok.col);
+        let mut arms = Vec::new();
+        let mut first = true;
+        loop {
+            if self.is_sym("|") && (self.peek().col >= arm_col || !self.on_new_line()) {
+                self.advance();
+            } else if !first {
+                break;
+            }
+            first = false;
+
+            let bar_col = self.tokens[self.pos.saturating_sub(1)].col;
+            let pattern = self.pattern()?;
+            let guard = if self.is_kw("when") {
+                self.advance();
+                Some(self.expr(NO_OFFSIDE)?)
+            } else {
+                None
+            };
+            self.expect_sym("->")?;
+            let body = self.block_or_expr(bar_col.max(arm_col))?;
+            arms.push(Arm {
+                pattern,
+                guard,
+                body,
+            });
+        }
+
+        Ok(Expr::new(
+            ExprKind::Match(Box::new(scrutinee), arms),
+            match_tok.line,
+            match_tok.col,
+        ))
+    }
+
+    fn lambda(&mut self, offside: usize) -> ScriptResult<Expr> {
+        let fun_tok = self.advance();
+        let mut params = Vec::new();
+        while !self.is_sym("->") {
+            if self.is_eof() {
+                return Err(self.unexpected("`->`"));
+            }
+            params.push(self.pattern_atom()?);
+        }
+        if params.is_empty() {
+            return Err(self.unexpected("a parameter"));
+        }
+        self.advance();
+        let body = self.block_or_expr(offside)?;
+
+        Ok(Expr::new(
+            ExprKind::Lambda(Arc::new(Lambda { params, body })),
+            fun_tok.line,
+            fun_tok.col,
+        ))
+    }
+
+    // =========================================================================
+    // Patterns
+    // =========================================================================
+
+    fn pattern(&mut self) -> ScriptResult<Pattern> {
+        let first = self.pattern_cons()?;
+        if !self.is_sym(",") {
+            return Ok(first);
+        }
+        let mut items = vec![first];
+        while self.is_sym(",") {
+            self.advance();
+            items.push(self.pattern_cons()?);
+        }
+        Ok(Pattern::Tuple(items))
+    }
+
+    fn pattern_cons(&mut self) -> ScriptResult<Pattern> {
+        let head = self.pattern_atom()?;
+        if self.is_sym("::") {
+            self.advance();
+            let tail = self.nested(Self::pattern_cons)?;
+            return Ok(Pattern::Cons(Box::new(head), Box::new(tail)));
+        }
+        Ok(head)
+    }
+
+    fn pattern_atom(&mut self) -> ScriptResult<Pattern> {
+        let tok = self.peek().clone();
+        match tok.tok {
+            Tok::Sym("_") => {
+                self.advance();
+                Ok(Pattern::Wildcard)
+            }
+            Tok::Ident(name) if !name.contains('.') => {
+                self.advance();
+                Ok(Pattern::Var(name))
+            }
+            Tok::Int(i) => {
+                self.advance();
+                Ok(Pattern::Int(i))
+            }
+            Tok::Sym("-") if matches!(self.peek_at(1).tok, Tok::Int(_)) => {
+                self.advance();
+                match self.advance().tok {
+                    Tok::Int(i) => Ok(Pattern::Int(-i)),
+                    _ => unreachable!(),
+                }
+            }
+            Tok::Str(s) => {
+                self.advance();
+                Ok(Pattern::Str(s))
+            }
+            Tok::Keyword("true") => {
+                self.advance();
+                Ok(Pattern::Bool(true))
+            }
+            Tok::Keyword("false") => {
+                self.advance();
+                Ok(Pattern::Bool(false))
+            }
+            Tok::Sym("(") => {
+                self.advance();
+                if self.is_sym(")") {
+                    self.advance();
+                    return Ok(Pattern::Unit);
+                }
+                let inner = self.nested(Self::pattern)?;
+                self.expect_sym(")")?;
+                Ok(inner)
+            }
+            Tok::Sym("[") => {
+                self.advance();
+                let mut items = Vec::new();
+                while !self.is_sym("]") {
+                    items.push(self.pattern()?);
+                    if self.is_sym(";") {
+                        self.advance();
+                    } else if !self.is_sym("]") {
+                        return Err(self.unexpected("`;` or `]`"));
+                    }
+                }
+                self.advance();
+                Ok(Pattern::List(items))
+            }
+            _ => Err(self.unexpected("a pattern")),
+        }
+    }
+}
+
+/// Desugar an operator section such as `(+)` into `fun a b -> a + b`.
+fn operator_lambda(op: BinOp, line: usize, col: usize) -> Expr {
+    let var = |name: &str| Expr::new(ExprKind::Path(vec![name.to_string()]), line, col);
+    let body = Expr::new(
+        ExprKind::Binary(op, Box::new(var("$a")), Box::new(var("$b"))),
+        line,
+        col,
+    );
+    Expr::new(
+        ExprKind::Lambda(Arc::new(Lambda {
+            params: vec![
+                Pattern::Var("$a".to_string()),
+                Pattern::Var("$b".to_string()),
+            ],
+            body,
+        })),
+        line,
+        col,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_ok(source: &str) -> Program {
+        parse(source).unwrap_or_else(|e| panic!("parse failed: {}", e))
+    }
+
+    #[test]
+    fn test_parse_top_level_bindings() {
+        let program = parse_ok("let x = 42\nlet add a b = a + b\n");
+        assert_eq!(program.items.len(), 2);
+        match &program.items[1] {
+            Item::Let(binding) => {
+                assert_eq!(binding.pattern, Pattern::Var("add".to_string()));
+                assert!(matches!(binding.body.kind, ExprKind::Lambda(_)));
+            }
+            other => panic!("unexpected item {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_indented_block_with_pipeline() {
+        let source = r#"
+let render buffer area state =
+    let block =
+        tui.widget.block ()
+        |> tui.widget.blockTitle "Hi"
+    tui.widget.render buffer area block
+"#;
+        let program = parse_ok(source);
+        assert_eq!(program.items.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_match_and_if() {
+        let source = r#"
+let describe n =
+    match n with
+    | 0 -> "zero"
+    | n when n < 0 -> "negative"
+    | _ ->
+        if n > 100 then "big"
+        else "small"
+"#;
+        let program = parse_ok(source);
+        let Item::Let(binding) = &program.items[0] else {
+            panic!("expected let");
+        };
+        let ExprKind::Lambda(lambda) = &binding.body.kind else {
+            panic!("expected lambda");
+        };
+        match &lambda.body.kind {
+            ExprKind::Match(_, arms) => assert_eq!(arms.len(), 3),
+            other => panic!("unexpected body {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_lists_and_records() {
+        let source = r#"
+let items = [1; 2; 3]
+let aligned =
+    [ "a"
+      "b" ]
+let range = [0 .. 10]
+let r = { name = "x"; count = 2 }
+let r2 = { r with count = 3 }
+"#;
+        let program = parse_ok(source);
+        assert_eq!(program.items.len(), 5);
+        let Item::Let(aligned) = &program.items[1] else {
+            panic!("expected let");
+        };
+        match &aligned.body.kind {
+            ExprKind::List(items) => assert_eq!(items.len(), 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_loads() {
+        let program = parse_ok("#load \"a.fsx\"\n#load \"b.fsx\"\nlet x = 1");
+        let loads: Vec<&str> = program.loads.iter().map(|(p, _, _)| p.as_str()).collect();
+        assert_eq!(loads, vec!["a.fsx", "b.fsx"]);
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        let err = parse("let x =\n    (1 + \n").unwrap_err();
+        assert_eq!(err.kind, crate::error::ScriptErrorKind::Syntax);
+        assert_eq!(err.line, 3);
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let nested = |depth: usize| format!("let x = {}1{}", "(".repeat(depth), ")".repeat(depth));
+        parse_ok(&nested(100));
+        let err = parse(&nested(10_000)).unwrap_err();
+        assert_eq!(err.kind, crate::error::ScriptErrorKind::Syntax);
+        assert!(err.message.contains("nested more than 200 levels"));
+
+        let chain = format!("let x = 1{}", " + 1".repeat(100_000));
+        assert!(parse(&chain).unwrap_err().message.contains("nested"));
+        let negations = format!("let x = {}1", "- ".repeat(10_000));
+        assert!(parse(&negations).is_err());
+        let pattern = format!("let f {}x{} = x", "(".repeat(10_000), ")".repeat(10_000));
+        assert!(parse(&pattern).is_err());
+    }
+
+    #[test]
+    fn test_parse_let_without_body_in_block() {
+        let err = parse("let f () =\n    let y = 1\n").unwrap_err();
+        assert!(err.message.contains("must be followed"));
+    }
+}
@@ -0,0 +1,293 @@
+//! Runtime values for Fusabi dashboard scripts.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use fusabi_tui_core::layout::{Constraint, Direction, Rect};
+use fusabi_tui_core::style::{Color, Style};
+use fusabi_tui_widgets::block::Block;
+use fusabi_tui_widgets::gauge::Gauge;
+use fusabi_tui_widgets::list::{List, ListItem};
+use fusabi_tui_widgets::paragraph::Paragraph;
+use fusabi_tui_widgets::sparkline::Sparkline;
+use fusabi_tui_widgets::table::{Row, Table};
+use fusabi_tui_widgets::tabs::Tabs;
+
+use super::ast::Lambda;
+use super::interpreter::Interpreter;
+use crate::error::ScriptResult;
+
+/// Signature of a native function callable from scripts.
+pub(crate) type HostFn = fn(&mut Interpreter, Vec<Value>) -> ScriptResult<Value>;
+
+/// A script value.
+///
+/// Compound values are reference counted so that cloning is cheap and values
+/// can be shared between closures.
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(Arc<str>),
+    List(Arc<Vec<Value>>),
+    Tuple(Arc<Vec<Value>>),
+    Record(Arc<BTreeMap<String, Value>>),
+    Closure(Arc<Closure>),
+    Host(Arc<HostCall>),
+    Color(Color),
+    Style(Style),
+    Rect(Rect),
+    Constraint(Constraint),
+    Direction(Direction),
+    Widget(Arc<ScriptWidget>),
+    /// Handle to the buffer being rendered. The buffer itself is owned by the
+    /// interpreter for the duration of the `render` call.
+    Buffer,
+}
+
+/// A script function together with its captured environment.
+#[derive(Debug)]
+pub(crate) struct Closure {
+    pub lambda: Arc<Lambda>,
+    pub env: Env,
+    /// Name the closure binds to itself when declared with `let rec`.
+    pub rec_name: Option<String>,
+    /// Arguments supplied so far by partial application.
+    pub applied: Vec<Value>,
+}
+
+/// A native function, possibly partially applied.
+#[derive(Debug)]
+pub(crate) struct HostCall {
+    pub name: &'static str,
+    pub arity: usize,
+    pub func: HostFn,
+    pub applied: Vec<Value>,
+}
+
+/// Widgets built by `tui.widget.*` functions.
+#[derive(Debug, Clone)]
+pub(crate) enum ScriptWidget {
+    Block(Block),
+    Paragraph(Paragraph<'static>),
+    List(List<'static>, Option<usize>),
+    ListItem(ListItem<'static>),
+    Table(Table, Option<usize>),
+    Row(Row),
+    Gauge(Gauge),
+    Sparkline(Sparkline),
+    Tabs(Tabs),
+}
+
+impl ScriptWidget {
+    /// Name used in error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScriptWidget::Block(_) => "block",
+            ScriptWidget::Paragraph(_) => "paragraph",
+            ScriptWidget::List(..) => "list",
+            ScriptWidget::ListItem(_) => "list item",
+            ScriptWidget::Table(..) => "table",
+            ScriptWidget::Row(_) => "table row",
+            ScriptWidget::Gauge(_) => "gauge",
+            ScriptWidget::Sparkline(_) => "sparkline",
+            ScriptWidget::Tabs(_) => "tabs",
+        }
+    }
+}
+
+/// Local variable environment: a persistent linked list of bindings.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Env(Option<Arc<EnvNode>>);
+
+#[derive(Debug)]
+struct EnvNode {
+    name: String,
+    value: Value,
+    next: Env,
+}
+
+impl Env {
+    /// Return a new environment with `name` bound to `value`.
+    pub fn bind(&self, name: impl Into<String>, value: Value) -> Env {
+        Env(Some(Arc::new(EnvNode {
+            name: name.into(),
+            value,
+            next: self.clone(),
+        })))
+    }
+
+    /// Look up the innermost binding for `name`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        let mut node = self.0.as_deref();
+        while let Some(n) = node {
+            if n.name == name {
+                return Some(&n.value);
+            }
+            node = n.next.0.as_deref();
+        }
+        None
+    }
+}
+
+impl Value {
+    pub fn str(s: impl AsRef<str>) -> Value {
+        Value::Str(Arc::from(s.as_ref()))
+    }
+
+    pub fn list(items: Vec<Value>) -> Value {
+        Value::List(Arc::new(items))
+    }
+
+    pub fn tuple(items: Vec<Value>) -> Value {
+        Value::Tuple(Arc::new(items))
+    }
+
+    pub fn record(fields: BTreeMap<String, Value>) -> Value {
+        Value::Record(Arc::new(fields))
+    }
+
+    pub fn widget(widget: ScriptWidget) -> Value {
+        Value::Widget(Arc::new(widget))
+    }
+
+    /// Type name used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "unit",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Tuple(_) => "tuple",
+            Value::Record(_) => "record",
+            Value::Closure(_) | Value::Host(_) => "function",
+            Value::Color(_) => "color",
+            Value::Style(_) => "style",
+            Value::Rect(_) => "rect",
+            Value::Constraint(_) => "constraint",
+            Value::Direction(_) => "direction",
+            Value::Widget(w) => w.kind(),
+            Value::Buffer => "buffer",
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self, Value::Closure(_) | Value::Host(_))
+    }
+
+    /// Structural equality. Functions never compare equal.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Unit, Value::Unit) | (Value::Buffer, Value::Buffer) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => {
+                (*a as f64) == *b
+            }
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::List(a), Value::List(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| x.equals(y))
+            }
+            (Value::Record(a), Value::Record(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.equals(vb))
+            }
+            (Value::Color(a), Value::Color(b)) => a == b,
+            (Value::Style(a), Value::Style(b)) => a == b,
+            (Value::Rect(a), Value::Rect(b)) => a == b,
+            (Value::Constraint(a), Value::Constraint(b)) => a == b,
+            (Value::Direction(a), Value::Direction(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Ordering for comparison operators, if the values are comparable.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Unit, Value::Unit) => Some(Ordering::Equal),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+            (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            (Value::List(a), Value::List(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                for (x, y) in a.iter().zip(b.iter()) {
+                    match x.compare(y)? {
+                        Ordering::Equal => {}
+                        other => return Some(other),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats values the way `%A` and `string` print them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => {
+                if x.fract() == 0.0 && x.is_finite() {
+                    write!(f, "{:.1}", x)
+                } else {
+                    write!(f, "{}", x)
+                }
+            }
+            Value::Str(s) => write!(f, "{}", s),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Tuple(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            Value::Record(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{} = {}", name, value)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Closure(_) => write!(f, "<fun>"),
+            Value::Host(h) => write!(f, "<fun {}>", h.name),
+            Value::Color(c) => write!(f, "{}", c),
+            Value::Style(s) => write!(f, "{:?}", s),
+            Value::Rect(r) => write!(f, "{:?}", r),
+            Value::Constraint(c) => write!(f, "{:?}", c),
+            Value::Direction(d) => write!(f, "{:?}", d),
+            Value::Widget(w) => write!(f, "<{}>", w.kind()),
+            Value::Buffer => write!(f, "<buffer>"),
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Mouse hit-testing against the widgets of the last frame.
+//!
+//! Widgets register the areas they render to in the [`HitMap`] of the
+//! [`DashboardState`](crate::state::DashboardState), together with what the
+//! area contains: list rows, tab titles or a scrollbar track. The engine
+//! looks up mouse events in the map and updates the
+//! [`WidgetState`](crate::state::WidgetState) under the cursor.
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_core::layout::Rect;
+//! use fusabi_tui_engine::hit::{HitMap, HitPart};
+//!
+//! let hits = HitMap::new();
+//!
+//! // A bordered list of 20 items scrolled down by 5, registered while rendering
+//! let area = Rect::new(0, 0, 30, 10);
+//! hits.widget("files", area);
+//! hits.rows("files", Rect::new(1, 1, 28, 8), 5, 20);
+//!
+//! let hit = hits.hit(3, 2).unwrap();
+//! assert_eq!(hit.id, "files");
+//! assert_eq!(hit.part, HitPart::Row(6));
+//! ```
+
+use std::cell::RefCell;
+
+use fusabi_tui_core::layout::Rect;
+
+/// What a registered area contains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HitKind {
+    /// The widget as a whole.
+    Widget,
+
+    /// One item per line, as in a list or the body of a table.
+    Rows {
+        /// Index of the item on the first line.
+        offset: usize,
+
+        /// Total number of items.
+        len: usize,
+    },
+
+    /// Tab titles, one area per tab.
+    Tabs(Vec<Rect>),
+
+    /// The track of a scrollbar.
+    Scrollbar {
+        /// Whether the track runs top to bottom.
+        vertical: bool,
+    },
+}
+
+/// A registered area.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitRegion {
+    /// Id of the widget, as used for its [`WidgetState`](crate::state::WidgetState).
+    pub id: String,
+
+    /// Screen area.
+    pub area: Rect,
+
+    /// What the area contains.
+    pub kind: HitKind,
+}
+
+/// The part of a widget at a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitPart {
+    /// Somewhere on the widget, not on a row, tab or track.
+    Widget,
+
+    /// The item with this index.
+    Row(usize),
+
+    /// The tab with this index.
+    Tab(usize),
+
+    /// A cell of a scrollbar track.
+    Scrollbar {
+        /// Cell index from the start of the track.
+        offset: u16,
+
+        /// Number of cells in the track.
+        length: u16,
+    },
+}
+
+/// The widget at a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    /// Id of the widget.
+    pub id: String,
+
+    /// The part of the widget at the position.
+    pub part: HitPart,
+}
+
+/// Areas of the widgets in the last rendered frame.
+///
+/// Like [`FocusRing`](crate::focus::FocusRing), registration takes `&self`
+/// so widgets can register while rendering, and the engine clears the map at
+/// the start of every frame. Areas registered later are on top.
+#[derive(Debug, Default)]
+pub struct HitMap {
+    regions: RefCell<Vec<HitRegion>>,
+}
+
+impl HitMap {
+    /// Create an empty hit map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an area of a widget.
+    pub fn register(&self, id: impl Into<String>, area: Rect, kind: HitKind) {
+        self.regions.borrow_mut().push(HitRegion {
+            id: id.into(),
+            area,
+            kind,
+        });
+    }
+
+    /// Register the whole area of a widget.
+    pub fn widget(&self, id: impl Into<String>, area: Rect) {
+        self.register(id, area, HitKind::Widget);
+    }
+
+    /// Register the lines of a list or table body. The first line shows item
+    /// `offset` of `len` items.
+    pub fn rows(&self, id: impl Into<String>, area: Rect, offset: usize, len: usize) {
+        self.register(id, area, HitKind::Rows { offset, len });
+    }
+
+    /// Register tab titles, as returned by
+    /// [`Tabs::title_areas`](fusabi_tui_widgets::tabs::Tabs::title_areas).
+    pub fn tabs(&self, id: impl Into<String>, title_areas: Vec<Rect>) {
+        let area = title_areas
+            .iter()
+            .copied()
+            .reduce(Rect::union)
+            .unwrap_or_default();
+        self.register(id, area, HitKind::Tabs(title_areas));
+    }
+
+    /// Register a scrollbar track, as returned by
+    /// [`Scrollbar::track_area`](fusabi_tui_widgets::scrollbar::Scrollbar::track_area).
+    pub fn scrollbar(&self, id: impl Into<String>, track: Rect, vertical: bool) {
+        self.register(id, track, HitKind::Scrollbar { vertical });
+    }
+
+    /// Remove all areas.
+    pub fn clear(&self) {
+        self.regions.borrow_mut().clear();
+    }
+
+    /// Get the registered areas, bottom to top.
+    pub fn regions(&self) -> Vec<HitRegion> {
+        self.regions.borrow().clone()
+    }
+
+    /// Get the number of registered areas.
+    pub fn len(&self) -> usize {
+        self.regions.borrow().len()
+    }
+
+    /// Check if no areas are registered.
+    pub fn is_empty(&self) -> bool {
+        self.regions.borrow().is_empty()
+    }
+
+    /// Find the topmost widget at a position.
+    pub fn hit(&self, x: u16, y: u16) -> Option<Hit> {
+        let regions = self.regions.borrow();
+        let region = regions.iter().rev().find(|r| r.area.contains(x, y))?;
+        let part = match &region.kind {
+            HitKind::Widget => HitPart::Widget,
+            HitKind::Rows { offset, len } => {
+                let index = offset + usize::from(y - region.area.y);
+                if index < *len {
+                    HitPart::Row(index)
+                } else {
+                    HitPart::Widget
+                }
+            }
+            HitKind::Tabs(titles) => titles
+                .iter()
+                .position(|t| t.contains(x, y))
+                .map_or(HitPart::Widget, HitPart::Tab),
+            HitKind::Scrollbar { vertical } => track_part(region.area, *vertical, x, y),
+        };
+        Some(Hit {
+            id: region.id.clone(),
+            part,
+        })
+    }
+
+    /// Get the number of items registered with [`rows`](Self::rows) for a
+    /// widget.
+    pub fn row_count(&self, id: &str) -> Option<usize> {
+        self.regions
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|r| match r.kind {
+                HitKind::Rows { len, .. } if r.id == id => Some(len),
+                _ => None,
+            })
+    }
+
+    /// Map a position to the nearest cell of a widget's scrollbar track.
+    ///
+    /// Unlike [`hit`](Self::hit), the position may be outside the track, as
+    /// when dragging the thumb past its end.
+    pub fn scrollbar_part(&self, id: &str, x: u16, y: u16) -> Option<HitPart> {
+        self.regions
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|r| match r.kind {
+                HitKind::Scrollbar { vertical } if r.id == id => {
+                    Some(track_part(r.area, vertical, x, y))
+                }
+                _ => None,
+            })
+    }
+}
+
+impl Clone for HitMap {
+    fn clone(&self) -> Self {
+        Self {
+            regions: RefCell::new(self.regions.borrow().clone()),
+        }
+    }
+}
+
+/// The track cell nearest to a position.
+fn track_part(track: Rect, vertical: bool, x: u16, y: u16) -> HitPart {
+    let (start, length, position) = if vertical {
+        (track.y, track.height, y)
+    } else {
+        (track.x, track.width, x)
+    };
+    let offset = position.saturating_sub(start).min(length.saturating_sub(1));
+    HitPart::Scrollbar { offset, length }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_topmost() {
+        let hits = HitMap::new();
+        hits.widget("back", Rect::new(0, 0, 20, 20));
+        hits.widget("front", Rect::new(5, 5, 5, 5));
+
+        assert_eq!(hits.hit(6, 6).unwrap().id, "front");
+        assert_eq!(hits.hit(1, 1).unwrap().id, "back");
+        assert_eq!(hits.hit(30, 1), None);
+
+        hits.clear();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_hit_rows() {
+        let hits = HitMap::new();
+        hits.rows("list", Rect::new(0, 2, 10, 5), 3, 5);
+
+        assert_eq!(hits.hit(0, 2).unwrap().part, HitPart::Row(3));
+        assert_eq!(hits.hit(0, 3).unwrap().part, HitPart::Row(4));
+        // Past the last item
+        assert_eq!(hits.hit(0, 4).unwrap().part, HitPart::Widget);
+        assert_eq!(hits.row_count("list"), Some(5));
+        assert_eq!(hits.row_count("other"), None);
+    }
+
+    #[test]
+    fn test_hit_tabs() {
+        let hits = HitMap::new();
+        hits.tabs("tabs", vec![Rect::new(0, 0, 3, 1), Rect::new(6, 0, 3, 1)]);
+
+        assert_eq!(hits.hit(1, 0).unwrap().part, HitPart::Tab(0));
+        assert_eq!(hits.hit(7, 0).unwrap().part, HitPart::Tab(1));
+        assert_eq!(hits.hit(4, 0).unwrap().part, HitPart::Widget);
+    }
+
+    #[test]
+    fn test_hit_scrollbar() {
+        let hits = HitMap::new();
+        hits.scrollbar("bar", Rect::new(9, 1, 1, 8), true);
+
+        assert_eq!(
+            hits.hit(9, 4).unwrap().part,
+            HitPart::Scrollbar {
+                offset: 3,
+                length: 8
+            }
+        );
+        assert_eq!(
+            hits.scrollbar_part("bar", 0, 30),
+            Some(HitPart::Scrollbar {
+                offset: 7,
+                length: 8
+            })
+        );
+        assert_eq!(
+            hits.scrollbar_part("bar", 0, 0),
+            Some(HitPart::Scrollbar {
+                offset: 0,
+                length: 8
+            })
+        );
+    }
+}
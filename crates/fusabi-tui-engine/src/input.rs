@@ -0,0 +1,632 @@
+//! Input event sources merged into a single stream of engine [`Event`]s.
+//!
+//! An [`EventReader`] combines:
+//!
+//! - terminal input from an [`EventSource`], such as [`CrosstermEvents`],
+//! - tick timers, which are merged into a single [`Event::Tick`] when several
+//!   are due at once,
+//! - file changes reported by a [`FileWatcher`],
+//! - [`Event::Custom`] messages sent from other threads through a
+//!   [`CustomSender`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "crossterm-backend")]
+//! # fn main() -> fusabi_tui_engine::error::EngineResult<()> {
+//! use fusabi_tui_engine::input::{CrosstermEvents, EventReader};
+//! use fusabi_tui_engine::prelude::*;
+//! use std::time::Duration;
+//!
+//! let mut events = EventReader::new()
+//!     .with_source(CrosstermEvents::new())
+//!     .with_tick_rate(Duration::from_millis(250));
+//!
+//! let sender = events.custom_sender();
+//! std::thread::spawn(move || {
+//!     let _ = sender.send("refresh".to_string());
+//! });
+//!
+//! loop {
+//!     match events.next_event()? {
+//!         Event::Key(key) if key.code == KeyCode::Char('q') => break,
+//!         Event::Tick => { /* advance animations */ }
+//!         Event::Custom(message) => println!("{message}"),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "crossterm-backend"))]
+//! # fn main() {}
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+use crate::error::EngineResult;
+use crate::event::Event;
+use crate::watcher::FileWatcher;
+
+/// How long the reader waits on a source before checking the file watcher and
+/// the custom channel again.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Sends [`Event::Custom`] messages to an [`EventReader`] from any thread.
+pub type CustomSender = Sender<String>;
+
+/// A source of terminal input events.
+pub trait EventSource: Send {
+    /// Wait up to `timeout` for the next event.
+    ///
+    /// Returns `Ok(None)` if nothing arrived in time. Raw input that has no
+    /// engine equivalent is consumed and also reported as `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the input fails.
+    fn poll_event(&mut self, timeout: Duration) -> EngineResult<Option<Event>>;
+}
+
+/// A periodic timer that produces [`Event::Tick`].
+#[derive(Debug, Clone, Copy)]
+struct TickTimer {
+    interval: Duration,
+    next: Instant,
+}
+
+/// Merges terminal input, ticks, file changes and custom messages into one
+/// stream of events.
+///
+/// Events are returned in the order they are noticed. Custom messages and file
+/// changes are checked at least every 20 ms while waiting on the input source.
+pub struct EventReader {
+    source: Option<Box<dyn EventSource>>,
+    timers: Vec<TickTimer>,
+    watcher: Option<FileWatcher>,
+    custom_tx: Sender<String>,
+    custom_rx: Receiver<String>,
+    /// Events noticed but not yet returned.
+    pending: VecDeque<Event>,
+}
+
+impl Default for EventReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventReader {
+    /// Create a reader with no input source, timers or file watcher.
+    ///
+    /// Custom messages can always be sent through [`custom_sender`](Self::custom_sender).
+    pub fn new() -> Self {
+        let (custom_tx, custom_rx) = mpsc::channel();
+        Self {
+            source: None,
+            timers: Vec::new(),
+            watcher: None,
+            custom_tx,
+            custom_rx,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Read terminal input from `source`.
+    pub fn with_source(mut self, source: impl EventSource + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Emit [`Event::Tick`] every `interval`.
+    ///
+    /// Can be called several times. Timers that are due together produce a
+    /// single tick, and ticks missed while the caller was busy are not
+    /// replayed.
+    pub fn with_tick_rate(mut self, interval: Duration) -> Self {
+        self.add_tick_timer(interval);
+        self
+    }
+
+    /// Report file changes from `watcher` as [`Event::FileChange`].
+    pub fn with_file_watcher(mut self, watcher: FileWatcher) -> Self {
+        self.watcher = Some(watcher);
+        self
+    }
+
+    /// Add a tick timer. See [`with_tick_rate`](Self::with_tick_rate).
+    ///
+    /// A zero interval is ignored.
+    pub fn add_tick_timer(&mut self, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+        self.timers.push(TickTimer {
+            interval,
+            next: Instant::now() + interval,
+        });
+    }
+
+    /// Remove all tick timers.
+    pub fn clear_tick_timers(&mut self) {
+        self.timers.clear();
+    }
+
+    /// Get the file watcher, if any.
+    pub fn watcher(&self) -> Option<&FileWatcher> {
+        self.watcher.as_ref()
+    }
+
+    /// Get mutable access to the file watcher, for example to watch more paths.
+    pub fn watcher_mut(&mut self) -> Option<&mut FileWatcher> {
+        self.watcher.as_mut()
+    }
+
+    /// Get a sender for [`Event::Custom`] messages.
+    ///
+    /// The sender can be cloned and moved to other threads.
+    pub fn custom_sender(&self) -> CustomSender {
+        self.custom_tx.clone()
+    }
+
+    /// Block until the next event arrives.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading terminal input fails.
+    pub fn next_event(&mut self) -> EngineResult<Event> {
+        loop {
+            if let Some(event) = self.next_event_timeout(Duration::from_secs(3600))? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for the next event.
+    ///
+    /// Returns `Ok(None)` if no event arrived in time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading terminal input fails.
+    pub fn next_event_timeout(&mut self, timeout: Duration) -> EngineResult<Option<Event>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            self.collect_ready();
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+
+            // Wait no longer than the caller allows, the next tick is due, or
+            // the watcher needs checking.
+            let mut wait = deadline - now;
+            if let Some(next_tick) = self.timers.iter().map(|t| t.next).min() {
+                wait = wait.min(next_tick.saturating_duration_since(now));
+            }
+            if self.watcher.is_some() {
+                wait = wait.min(POLL_INTERVAL);
+            }
+
+            match self.source.as_mut() {
+                Some(source) => {
+                    if let Some(event) = source.poll_event(wait.min(POLL_INTERVAL))? {
+                        return Ok(Some(event));
+                    }
+                }
+                None => match self.custom_rx.recv_timeout(wait) {
+                    Ok(message) => return Ok(Some(Event::Custom(message))),
+                    // The reader holds a sender itself, so the channel cannot
+                    // disconnect
+                    Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {}
+                },
+            }
+        }
+    }
+
+    /// Queue custom messages, file changes and due ticks, in that order.
+    fn collect_ready(&mut self) {
+        while let Ok(message) = self.custom_rx.try_recv() {
+            self.pending.push_back(Event::Custom(message));
+        }
+
+        if let Some(watcher) = self.watcher.as_mut() {
+            let changes = watcher.poll();
+            self.pending
+                .extend(changes.into_iter().map(Event::FileChange));
+        }
+
+        let now = Instant::now();
+        let mut due = false;
+        for timer in &mut self.timers {
+            if timer.next <= now {
+                due = true;
+                // Skip ticks that were missed rather than replaying them
+                while timer.next <= now {
+                    timer.next += timer.interval;
+                }
+            }
+        }
+        if due {
+            self.pending.push_back(Event::Tick);
+        }
+    }
+}
+
+#[cfg(feature = "crossterm-backend")]
+pub use self::crossterm_source::CrosstermEvents;
+
+#[cfg(feature = "crossterm-backend")]
+mod crossterm_source {
+    use std::time::Duration;
+
+    use crossterm::event as ct;
+
+    use super::EventSource;
+    use crate::error::EngineResult;
+    use crate::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+
+    /// Reads terminal input through crossterm.
+    ///
+    /// The terminal should be in raw mode, and mouse capture must be enabled
+    /// to receive mouse events.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct CrosstermEvents;
+
+    impl CrosstermEvents {
+        /// Create a crossterm event source.
+        pub fn new() -> Self {
+            Self
+        }
+    }
+
+    impl EventSource for CrosstermEvents {
+        fn poll_event(&mut self, timeout: Duration) -> EngineResult<Option<Event>> {
+            if ct::poll(timeout)? {
+                Ok(Event::from_crossterm(ct::read()?))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    impl Event {
+        /// Convert a crossterm event.
+        ///
+        /// Returns `None` for events the engine has no equivalent for, such as
+        /// key releases, focus changes and pastes.
+        pub fn from_crossterm(event: ct::Event) -> Option<Self> {
+            match event {
+                ct::Event::Key(key) => KeyEvent::from_crossterm(key).map(Event::Key),
+                ct::Event::Mouse(mouse) => MouseEvent::from_crossterm(mouse).map(Event::Mouse),
+                ct::Event::Resize(width, height) => Some(Event::Resize(width, height)),
+                _ => None,
+            }
+        }
+    }
+
+    impl KeyEvent {
+        /// Convert a crossterm key event.
+        ///
+        /// Returns `None` for key releases and for keys the engine does not
+        /// model. `BackTab` becomes `Tab` with Shift held.
+        pub fn from_crossterm(key: ct::KeyEvent) -> Option<Self> {
+            if key.kind == ct::KeyEventKind::Release {
+                return None;
+            }
+
+            let mut modifiers = KeyModifiers::from(key.modifiers);
+            let code = match key.code {
+                ct::KeyCode::Char(' ') => KeyCode::Space,
+                ct::KeyCode::Char(c) => KeyCode::Char(c),
+                ct::KeyCode::F(n) => KeyCode::F(n),
+                ct::KeyCode::Enter => KeyCode::Enter,
+                ct::KeyCode::Esc => KeyCode::Esc,
+                ct::KeyCode::Backspace => KeyCode::Backspace,
+                ct::KeyCode::Tab => KeyCode::Tab,
+                ct::KeyCode::BackTab => {
+                    modifiers.shift = true;
+                    KeyCode::Tab
+                }
+                ct::KeyCode::Up => KeyCode::Up,
+                ct::KeyCode::Down => KeyCode::Down,
+                ct::KeyCode::Left => KeyCode::Left,
+                ct::KeyCode::Right => KeyCode::Right,
+                ct::KeyCode::Home => KeyCode::Home,
+                ct::KeyCode::End => KeyCode::End,
+                ct::KeyCode::PageUp => KeyCode::PageUp,
+                ct::KeyCode::PageDown => KeyCode::PageDown,
+                ct::KeyCode::Delete => KeyCode::Delete,
+                ct::KeyCode::Insert => KeyCode::Insert,
+                _ => return None,
+            };
+
+            Some(KeyEvent { code, modifiers })
+        }
+    }
+
+    impl From<ct::KeyModifiers> for KeyModifiers {
+        fn from(modifiers: ct::KeyModifiers) -> Self {
+            Self {
+                shift: modifiers.contains(ct::KeyModifiers::SHIFT),
+                ctrl: modifiers.contains(ct::KeyModifiers::CONTROL),
+                alt: modifiers.contains(ct::KeyModifiers::ALT),
+                meta: modifiers.intersects(ct::KeyModifiers::META | ct::KeyModifiers::SUPER),
+            }
+        }
+    }
+
+    impl MouseEvent {
+        /// Convert a crossterm mouse event.
+        ///
+        /// Horizontal scrolling has no equivalent and returns `None`.
+        pub fn from_crossterm(mouse: ct::MouseEvent) -> Option<Self> {
+            let kind = match mouse.kind {
+                ct::MouseEventKind::Down(button) => MouseEventKind::Down(button.into()),
+                ct::MouseEventKind::Up(button) => MouseEventKind::Up(button.into()),
+                ct::MouseEventKind::Drag(button) => MouseEventKind::Drag(button.into()),
+                ct::MouseEventKind::Moved => MouseEventKind::Moved,
+                ct::MouseEventKind::ScrollUp => MouseEventKind::ScrollUp,
+                ct::MouseEventKind::ScrollDown => MouseEventKind::ScrollDown,
+                ct::MouseEventKind::ScrollLeft | ct::MouseEventKind::ScrollRight => return None,
+            };
+
+            Some(MouseEvent {
+                kind,
+                x: mouse.column,
+                y: mouse.row,
+                modifiers: mouse.modifiers.into(),
+            })
+        }
+    }
+
+    impl From<ct::MouseButton> for MouseButton {
+        fn from(button: ct::MouseButton) -> Self {
+            match button {
+                ct::MouseButton::Left => MouseButton::Left,
+                ct::MouseButton::Right => MouseButton::Right,
+                ct::MouseButton::Middle => MouseButton::Middle,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_key_conversion() {
+            let key = ct::KeyEvent::new(ct::KeyCode::Char('c'), ct::KeyModifiers::CONTROL);
+            assert_eq!(
+                Event::from_crossterm(ct::Event::Key(key)),
+                Some(Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::ctrl(),
+                }))
+            );
+
+            let space = ct::KeyEvent::new(ct::KeyCode::Char(' '), ct::KeyModifiers::NONE);
+            assert_eq!(
+                KeyEvent::from_crossterm(space).unwrap().code,
+                KeyCode::Space
+            );
+
+            let back_tab = ct::KeyEvent::new(ct::KeyCode::BackTab, ct::KeyModifiers::SHIFT);
+            assert_eq!(
+                KeyEvent::from_crossterm(back_tab),
+                Some(KeyEvent {
+                    code: KeyCode::Tab,
+                    modifiers: KeyModifiers::shift(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_key_release_ignored() {
+            let release = ct::KeyEvent::new_with_kind(
+                ct::KeyCode::Char('a'),
+                ct::KeyModifiers::NONE,
+                ct::KeyEventKind::Release,
+            );
+            assert_eq!(KeyEvent::from_crossterm(release), None);
+        }
+
+        #[test]
+        fn test_mouse_conversion() {
+            let mouse = ct::MouseEvent {
+                kind: ct::MouseEventKind::Down(ct::MouseButton::Left),
+                column: 4,
+                row: 2,
+                modifiers: ct::KeyModifiers::ALT,
+            };
+            assert_eq!(
+                Event::from_crossterm(ct::Event::Mouse(mouse)),
+                Some(Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    x: 4,
+                    y: 2,
+                    modifiers: KeyModifiers::alt(),
+                }))
+            );
+
+            let drag = ct::MouseEvent {
+                kind: ct::MouseEventKind::Drag(ct::MouseButton::Left),
+                ..mouse
+            };
+            assert_eq!(
+                MouseEvent::from_crossterm(drag).map(|m| m.kind),
+                Some(MouseEventKind::Drag(MouseButton::Left))
+            );
+
+            let scroll = ct::MouseEvent {
+                kind: ct::MouseEventKind::ScrollLeft,
+                ..mouse
+            };
+            assert_eq!(MouseEvent::from_crossterm(scroll), None);
+        }
+
+        #[test]
+        fn test_other_events() {
+            assert_eq!(
+                Event::from_crossterm(ct::Event::Resize(80, 24)),
+                Some(Event::Resize(80, 24))
+            );
+            assert_eq!(Event::from_crossterm(ct::Event::FocusGained), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+    use crate::watcher::FileChangeKind;
+    use std::io::Write;
+
+    /// Source that hands out a fixed list of events.
+    struct Scripted(VecDeque<Event>);
+
+    impl EventSource for Scripted {
+        fn poll_event(&mut self, timeout: Duration) -> EngineResult<Option<Event>> {
+            match self.0.pop_front() {
+                Some(event) => Ok(Some(event)),
+                None => {
+                    std::thread::sleep(timeout);
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::none(),
+        })
+    }
+
+    #[test]
+    fn test_source_events() {
+        let mut reader =
+            EventReader::new().with_source(Scripted(VecDeque::from([key('a'), key('b')])));
+        assert_eq!(reader.next_event().unwrap(), key('a'));
+        assert_eq!(reader.next_event().unwrap(), key('b'));
+        assert_eq!(
+            reader
+                .next_event_timeout(Duration::from_millis(10))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_custom_messages() {
+        let mut reader = EventReader::new();
+        let sender = reader.custom_sender();
+        std::thread::spawn(move || {
+            sender.send("first".to_string()).unwrap();
+            sender.send("second".to_string()).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Event::Custom("first".to_string())
+        );
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Event::Custom("second".to_string())
+        );
+    }
+
+    #[test]
+    fn test_custom_message_wakes_blocking_reader() {
+        let mut reader = EventReader::new();
+        let sender = reader.custom_sender();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            sender.send("wake".to_string()).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Event::Custom("wake".to_string())
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_ticks() {
+        let mut reader = EventReader::new().with_tick_rate(Duration::from_millis(10));
+        let start = Instant::now();
+        assert_eq!(reader.next_event().unwrap(), Event::Tick);
+        assert_eq!(reader.next_event().unwrap(), Event::Tick);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_due_timers_merge_into_one_tick() {
+        let mut reader = EventReader::new()
+            .with_tick_rate(Duration::from_millis(5))
+            .with_tick_rate(Duration::from_millis(5));
+        std::thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(
+            reader.next_event_timeout(Duration::ZERO).unwrap(),
+            Some(Event::Tick)
+        );
+        // Missed ticks are not replayed
+        assert_eq!(reader.next_event_timeout(Duration::ZERO).unwrap(), None);
+    }
+
+    #[test]
+    fn test_ticks_between_input() {
+        let mut reader = EventReader::new()
+            .with_source(Scripted(VecDeque::new()))
+            .with_tick_rate(Duration::from_millis(10));
+        assert_eq!(reader.next_event().unwrap(), Event::Tick);
+    }
+
+    #[test]
+    fn test_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dashboard.fsx");
+        std::fs::write(&path, "let x = 1\n").unwrap();
+
+        let mut watcher = FileWatcher::new(0).unwrap();
+        watcher.watch(dir.path()).unwrap();
+        let mut reader = EventReader::new().with_file_watcher(watcher);
+        assert!(reader.watcher().is_some());
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        writeln!(file, "let y = 2").unwrap();
+        file.sync_all().unwrap();
+
+        let event = reader.next_event_timeout(Duration::from_secs(5)).unwrap();
+        match event {
+            Some(Event::FileChange(changed)) => {
+                assert_eq!(changed.path.file_name(), path.file_name());
+                assert_eq!(changed.kind, FileChangeKind::Modified);
+            }
+            other => panic!("expected a file change, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,1003 @@
+//! Key bindings that map key sequences to [`Action`]s.
+//!
+//! A [`Keymap`] holds [`Binding`]s from a [`KeySequence`] such as `ctrl+r` or
+//! `g g` to an action. Bindings can be scoped to the focused widget and to the
+//! input mode in [`DashboardState`]; when several bindings for the same keys
+//! are active, the most specific one wins.
+//!
+//! # Key syntax
+//!
+//! A sequence is a space-separated list of keys. Each key is an optional list
+//! of modifiers (`ctrl`, `alt`, `shift`, `meta`) followed by a key name, joined
+//! with `+`: `ctrl+shift+tab`, `alt+x`, `F5`, `g g`. Key names are a single
+//! character or one of `enter`, `esc`, `backspace`, `tab`, `space`, `up`,
+//! `down`, `left`, `right`, `home`, `end`, `pageup`, `pagedown`, `delete`,
+//! `insert`, `plus` and `f1` to `f12`.
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_engine::event::Action;
+//! use fusabi_tui_engine::keymap::{Binding, Keymap, KeyScope};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut keymap = Keymap::with_defaults();
+//!
+//! // Rebind quit and disable the reload default
+//! keymap.bind("q", Action::Quit)?;
+//! keymap.bind("ctrl+r", Action::None)?;
+//!
+//! // Jump to the top of the log view with `g g`
+//! keymap.add(
+//!     Binding::new("g g".parse()?, Action::Custom("top".to_string()))
+//!         .scope(KeyScope::focus("logs"))
+//!         .description("Jump to top"),
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::error::{KeymapError, KeymapResult};
+use crate::event::{Action, KeyCode, KeyEvent, KeyModifiers};
+use crate::focus::FocusDirection;
+use crate::state::DashboardState;
+
+/// A sequence of one or more key presses, such as `ctrl+c` or `g g`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequence(Vec<KeyEvent>);
+
+impl KeySequence {
+    /// Create a sequence from key presses.
+    pub fn new(keys: Vec<KeyEvent>) -> Self {
+        Self(keys.iter().map(normalize).collect())
+    }
+
+    /// Get the key presses in the sequence.
+    pub fn keys(&self) -> &[KeyEvent] {
+        &self.0
+    }
+
+    /// Get the number of key presses.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if the sequence has no key presses.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<KeyEvent> for KeySequence {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(vec![key])
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = KeymapError;
+
+    fn from_str(s: &str) -> KeymapResult<Self> {
+        let keys = s
+            .split_whitespace()
+            .map(parse_key)
+            .collect::<KeymapResult<Vec<_>>>()?;
+        if keys.is_empty() {
+            return Err(KeymapError::InvalidKey {
+                key: s.to_string(),
+                reason: "no keys given".to_string(),
+            });
+        }
+        Ok(Self(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write_key(f, key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a binding applies.
+///
+/// The default scope is global. A scope with a focus only applies while that
+/// widget is focused, and a scope with a mode only while the dashboard is in
+/// that mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyScope {
+    /// Widget that must be focused.
+    pub focus: Option<String>,
+
+    /// Input mode that must be active.
+    pub mode: Option<String>,
+}
+
+impl KeyScope {
+    /// A scope that always applies.
+    pub fn global() -> Self {
+        Self::default()
+    }
+
+    /// A scope that applies while `widget_id` is focused.
+    pub fn focus(widget_id: impl Into<String>) -> Self {
+        Self::default().with_focus(widget_id)
+    }
+
+    /// A scope that applies in `mode`.
+    pub fn mode(mode: impl Into<String>) -> Self {
+        Self::default().with_mode(mode)
+    }
+
+    /// Also require `widget_id` to be focused.
+    pub fn with_focus(mut self, widget_id: impl Into<String>) -> Self {
+        self.focus = Some(widget_id.into());
+        self
+    }
+
+    /// Also require `mode` to be active.
+    pub fn with_mode(mut self, mode: impl Into<String>) -> Self {
+        self.mode = Some(mode.into());
+        self
+    }
+
+    /// Check if the scope applies to the given state.
+    pub fn is_active(&self, state: &DashboardState) -> bool {
+        let focus_ok = self.focus.is_none() || self.focus.as_deref() == state.focused_widget();
+        let mode_ok = self.mode.is_none() || self.mode.as_deref() == state.mode();
+        focus_ok && mode_ok
+    }
+
+    /// Rank used to pick between active bindings for the same keys. Focus
+    /// outranks mode.
+    fn specificity(&self) -> u8 {
+        u8::from(self.focus.is_some()) * 2 + u8::from(self.mode.is_some())
+    }
+}
+
+/// A key sequence bound to an action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    /// Keys that trigger the binding.
+    pub keys: KeySequence,
+
+    /// Action to perform. [`Action::None`] disables the keys in this scope.
+    pub action: Action,
+
+    /// Where the binding applies.
+    pub scope: KeyScope,
+
+    /// Text shown in the help overlay.
+    pub description: Option<String>,
+}
+
+impl Binding {
+    /// Create a global binding.
+    pub fn new(keys: KeySequence, action: Action) -> Self {
+        Self {
+            keys,
+            action,
+            scope: KeyScope::global(),
+            description: None,
+        }
+    }
+
+    /// Set the scope.
+    pub fn scope(mut self, scope: KeyScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Set the help text.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Text for the help overlay: the description, or a name for the action.
+    pub fn label(&self) -> String {
+        match &self.description {
+            Some(description) => description.clone(),
+            None => match &self.action {
+                Action::None => "Disabled".to_string(),
+                Action::Render => "Redraw".to_string(),
+                Action::Reload => "Reload dashboard".to_string(),
+                Action::Quit => "Quit".to_string(),
+                Action::DismissError => "Dismiss error".to_string(),
+                Action::ToggleHelp => "Toggle help".to_string(),
+                Action::FocusNext => "Focus next".to_string(),
+                Action::FocusPrevious => "Focus previous".to_string(),
+                Action::Focus(direction) => format!("Focus {:?}", direction).to_lowercase(),
+                Action::Custom(name) => name.clone(),
+                Action::Batch(actions) => format!("{} actions", actions.len()),
+            },
+        }
+    }
+}
+
+/// Result of feeding a key press to a [`Keymap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyResult {
+    /// The keys completed a binding.
+    Action(Action),
+
+    /// The keys so far are the start of a longer binding.
+    Pending,
+
+    /// No binding matched the key.
+    Unbound,
+}
+
+/// Maps key sequences to actions.
+///
+/// Feed key presses to [`handle_key`](Self::handle_key). Multi-key sequences
+/// are collected until they complete a binding, stop matching, or no key
+/// arrives within the [sequence timeout](Self::set_sequence_timeout).
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    pending: Vec<KeyEvent>,
+    last_key: Option<Instant>,
+    sequence_timeout: Duration,
+}
+
+impl Keymap {
+    /// Create an empty keymap.
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+            last_key: None,
+            sequence_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Create a keymap with the engine's default bindings:
+    ///
+    /// | Keys                    | Action                    |
+    /// |-------------------------|---------------------------|
+    /// | `ctrl+c`                | [`Action::Quit`]          |
+    /// | `ctrl+r`                | [`Action::Reload`]        |
+    /// | `ctrl+d`                | [`Action::DismissError`]  |
+    /// | `f1`                    | [`Action::ToggleHelp`]    |
+    /// | `tab`                   | [`Action::FocusNext`]     |
+    /// | `shift+tab`             | [`Action::FocusPrevious`] |
+    /// | `alt+` arrow keys       | [`Action::Focus`]         |
+    pub fn with_defaults() -> Self {
+        let mut keymap = Self::new();
+        let defaults = [
+            (KeyCode::Char('c'), KeyModifiers::ctrl(), Action::Quit),
+            (KeyCode::Char('r'), KeyModifiers::ctrl(), Action::Reload),
+            (
+                KeyCode::Char('d'),
+                KeyModifiers::ctrl(),
+                Action::DismissError,
+            ),
+            (KeyCode::F(1), KeyModifiers::none(), Action::ToggleHelp),
+            (KeyCode::Tab, KeyModifiers::none(), Action::FocusNext),
+            (KeyCode::Tab, KeyModifiers::shift(), Action::FocusPrevious),
+            (KeyCode::Up, KeyModifiers::alt(), Action::Focus(FocusDirection::Up)),
+            (KeyCode::Down, KeyModifiers::alt(), Action::Focus(FocusDirection::Down)),
+            (KeyCode::Left, KeyModifiers::alt(), Action::Focus(FocusDirection::Left)),
+            (KeyCode::Right, KeyModifiers::alt(), Action::Focus(FocusDirection::Right)),
+        ];
+        for (code, modifiers, action) in defaults {
+            keymap.add(Binding::new(KeyEvent { code, modifiers }.into(), action));
+        }
+        keymap
+    }
+
+    /// Bind `keys` to `action` globally, replacing any global binding for
+    /// the same keys. Bind to [`Action::None`] to disable the keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `keys` is not a valid key sequence.
+    pub fn bind(&mut self, keys: &str, action: Action) -> KeymapResult<()> {
+        self.add(Binding::new(keys.parse()?, action));
+        Ok(())
+    }
+
+    /// Bind `keys` to `action` in `scope`, replacing any binding for the same
+    /// keys and scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `keys` is not a valid key sequence.
+    pub fn bind_scoped(&mut self, keys: &str, scope: KeyScope, action: Action) -> KeymapResult<()> {
+        self.add(Binding::new(keys.parse()?, action).scope(scope));
+        Ok(())
+    }
+
+    /// Add a binding, replacing any binding for the same keys and scope.
+    pub fn add(&mut self, binding: Binding) {
+        self.bindings
+            .retain(|b| b.keys != binding.keys || b.scope != binding.scope);
+        self.bindings.push(binding);
+    }
+
+    /// Remove the binding for `keys` in `scope`.
+    ///
+    /// Returns `true` if a binding was removed. Lower-precedence bindings for
+    /// the same keys take effect again; bind to [`Action::None`] instead to
+    /// disable the keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `keys` is not a valid key sequence.
+    pub fn unbind(&mut self, keys: &str, scope: &KeyScope) -> KeymapResult<bool> {
+        let keys: KeySequence = keys.parse()?;
+        let before = self.bindings.len();
+        self.bindings
+            .retain(|b| b.keys != keys || &b.scope != scope);
+        Ok(self.bindings.len() != before)
+    }
+
+    /// Get all bindings, in the order they were added.
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Get the bindings in effect for `state`.
+    ///
+    /// For each key sequence only the most specific active binding is
+    /// returned, and disabled keys are left out.
+    pub fn active_bindings(&self, state: &DashboardState) -> Vec<&Binding> {
+        let mut active: Vec<&Binding> = Vec::new();
+        for binding in self.bindings.iter().filter(|b| b.scope.is_active(state)) {
+            match active.iter_mut().find(|b| b.keys == binding.keys) {
+                Some(existing) => {
+                    if binding.scope.specificity() >= existing.scope.specificity() {
+                        *existing = binding;
+                    }
+                }
+                None => active.push(binding),
+            }
+        }
+        active.retain(|b| b.action != Action::None);
+        active
+    }
+
+    /// Find the active binding for `keys`, if any.
+    ///
+    /// Returns disabled bindings too, so callers can tell that the keys are
+    /// deliberately unbound.
+    pub fn lookup(&self, keys: &KeySequence, state: &DashboardState) -> Option<&Binding> {
+        self.bindings
+            .iter()
+            .filter(|b| b.keys == *keys && b.scope.is_active(state))
+            // max_by_key returns the last of equal elements, so later
+            // bindings win ties
+            .max_by_key(|b| b.scope.specificity())
+    }
+
+    /// Feed a key press and return what it triggered.
+    ///
+    /// When the keys so far match a binding exactly, its action is returned
+    /// even if a longer binding starts with the same keys. When a pending
+    /// sequence stops matching, it is dropped and `key` is tried on its own.
+    pub fn handle_key(&mut self, key: &KeyEvent, state: &DashboardState) -> KeyResult {
+        let now = Instant::now();
+        if self
+            .last_key
+            .is_some_and(|last| now.duration_since(last) > self.sequence_timeout)
+        {
+            self.pending.clear();
+        }
+        self.last_key = Some(now);
+
+        let key = normalize(key);
+        self.pending.push(key.clone());
+
+        let sequence = KeySequence(self.pending.clone());
+        if let Some(binding) = self.lookup(&sequence, state) {
+            let action = binding.action.clone();
+            self.pending.clear();
+            return match action {
+                Action::None => KeyResult::Unbound,
+                action => KeyResult::Action(action),
+            };
+        }
+
+        if self.is_prefix(&self.pending, state) {
+            return KeyResult::Pending;
+        }
+
+        let retry = self.pending.len() > 1;
+        self.pending.clear();
+        if retry {
+            self.handle_key(&key, state)
+        } else {
+            KeyResult::Unbound
+        }
+    }
+
+    /// Get the keys of an unfinished sequence.
+    pub fn pending(&self) -> &[KeyEvent] {
+        &self.pending
+    }
+
+    /// Drop any unfinished sequence.
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Set how long to wait for the next key of a sequence.
+    pub fn set_sequence_timeout(&mut self, timeout: Duration) {
+        self.sequence_timeout = timeout;
+    }
+
+    /// Get how long to wait for the next key of a sequence.
+    pub fn sequence_timeout(&self) -> Duration {
+        self.sequence_timeout
+    }
+
+    /// Check if `keys` start a longer active, enabled binding.
+    fn is_prefix(&self, keys: &[KeyEvent], state: &DashboardState) -> bool {
+        self.active_bindings(state)
+            .iter()
+            .any(|b| b.keys.len() > keys.len() && b.keys.keys().starts_with(keys))
+    }
+}
+
+impl Default for Keymap {
+    /// Same as [`Keymap::with_defaults`].
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod toml_format {
+    use serde::Deserialize;
+
+    use super::{Binding, KeyScope, Keymap};
+    use crate::error::{KeymapError, KeymapResult};
+    use crate::event::Action;
+    use crate::focus::FocusDirection;
+
+    /// On-disk keymap format.
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct KeymapFile {
+        /// Start from the default bindings.
+        #[serde(default = "default_true")]
+        defaults: bool,
+
+        /// Sequence timeout in milliseconds.
+        sequence_timeout_ms: Option<u64>,
+
+        #[serde(default)]
+        bind: Vec<BindingFile>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct BindingFile {
+        keys: String,
+        action: String,
+        focus: Option<String>,
+        mode: Option<String>,
+        description: Option<String>,
+    }
+
+    fn default_true() -> bool {
+        true
+    }
+
+    /// Parse an action name. Unknown names become [`Action::Custom`].
+    fn parse_action(name: &str) -> Action {
+        match name {
+            "none" => Action::None,
+            "render" => Action::Render,
+            "reload" => Action::Reload,
+            "quit" => Action::Quit,
+            "dismiss_error" => Action::DismissError,
+            "help" => Action::ToggleHelp,
+            "focus_next" => Action::FocusNext,
+            "focus_previous" => Action::FocusPrevious,
+            "focus_up" => Action::Focus(FocusDirection::Up),
+            "focus_down" => Action::Focus(FocusDirection::Down),
+            "focus_left" => Action::Focus(FocusDirection::Left),
+            "focus_right" => Action::Focus(FocusDirection::Right),
+            custom => Action::Custom(custom.to_string()),
+        }
+    }
+
+    impl Keymap {
+        /// Load a keymap from a TOML string.
+        ///
+        /// Bindings are added on top of the defaults unless `defaults = false`.
+        /// The actions `none`, `render`, `reload`, `quit`, `dismiss_error`,
+        /// `help`, `focus_next`, `focus_previous` and `focus_up`/`down`/`left`/
+        /// `right` are built in; any other name is a custom action. Binding a
+        /// key to `none` disables it.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the TOML is invalid or a key sequence cannot be
+        /// parsed.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        /// use fusabi_tui_engine::keymap::Keymap;
+        ///
+        /// let keymap = Keymap::from_toml(r#"
+        ///     [[bind]]
+        ///     keys = "q"
+        ///     action = "quit"
+        ///
+        ///     [[bind]]
+        ///     keys = "ctrl+r"
+        ///     action = "none"
+        ///
+        ///     [[bind]]
+        ///     keys = "g g"
+        ///     action = "top"
+        ///     focus = "logs"
+        ///     description = "Jump to top"
+        /// "#).unwrap();
+        /// ```
+        pub fn from_toml(toml_str: &str) -> KeymapResult<Self> {
+            let file: KeymapFile =
+                toml::from_str(toml_str).map_err(|e| KeymapError::ParseFailed(e.to_string()))?;
+
+            let mut keymap = if file.defaults {
+                Keymap::with_defaults()
+            } else {
+                Keymap::new()
+            };
+            if let Some(ms) = file.sequence_timeout_ms {
+                keymap.set_sequence_timeout(std::time::Duration::from_millis(ms));
+            }
+
+            for entry in file.bind {
+                let mut binding = Binding::new(entry.keys.parse()?, parse_action(&entry.action))
+                    .scope(KeyScope {
+                        focus: entry.focus,
+                        mode: entry.mode,
+                    });
+                binding.description = entry.description;
+                keymap.add(binding);
+            }
+
+            Ok(keymap)
+        }
+
+        /// Load a keymap from a TOML file.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the file cannot be read or parsed.
+        pub fn from_toml_file(path: impl AsRef<std::path::Path>) -> KeymapResult<Self> {
+            let path = path.as_ref();
+            let content =
+                std::fs::read_to_string(path).map_err(|source| KeymapError::ReadFailed {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+            Self::from_toml(&content)
+        }
+    }
+}
+
+/// Drop Shift from character keys, whose case already records it.
+fn normalize(key: &KeyEvent) -> KeyEvent {
+    let mut key = key.clone();
+    if let KeyCode::Char(_) = key.code {
+        key.modifiers.shift = false;
+    }
+    key
+}
+
+/// Parse one key such as `ctrl+shift+tab`.
+fn parse_key(s: &str) -> KeymapResult<KeyEvent> {
+    let invalid = |reason: &str| KeymapError::InvalidKey {
+        key: s.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut parts: Vec<&str> = s.split('+').collect();
+    let name = parts
+        .pop()
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| invalid("missing key name"))?;
+
+    let mut modifiers = KeyModifiers::none();
+    for part in parts {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers.ctrl = true,
+            "alt" | "option" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            "meta" | "super" | "cmd" => modifiers.meta = true,
+            _ => return Err(invalid(&format!("unknown modifier `{}`", part))),
+        }
+    }
+
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Space,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "plus" => KeyCode::Char('+'),
+            lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(invalid(&format!("unknown key `{}`", name))),
+            },
+        },
+    };
+
+    Ok(normalize(&KeyEvent { code, modifiers }))
+}
+
+/// Write one key in the syntax [`parse_key`] accepts.
+fn write_key(f: &mut fmt::Formatter<'_>, key: &KeyEvent) -> fmt::Result {
+    let m = &key.modifiers;
+    for (held, name) in [
+        (m.ctrl, "ctrl"),
+        (m.alt, "alt"),
+        (m.shift, "shift"),
+        (m.meta, "meta"),
+    ] {
+        if held {
+            write!(f, "{}+", name)?;
+        }
+    }
+    match key.code {
+        KeyCode::Char('+') => write!(f, "plus"),
+        KeyCode::Char(c) => write!(f, "{}", c),
+        KeyCode::F(n) => write!(f, "f{}", n),
+        KeyCode::Enter => write!(f, "enter"),
+        KeyCode::Esc => write!(f, "esc"),
+        KeyCode::Backspace => write!(f, "backspace"),
+        KeyCode::Tab => write!(f, "tab"),
+        KeyCode::Space => write!(f, "space"),
+        KeyCode::Up => write!(f, "up"),
+        KeyCode::Down => write!(f, "down"),
+        KeyCode::Left => write!(f, "left"),
+        KeyCode::Right => write!(f, "right"),
+        KeyCode::Home => write!(f, "home"),
+        KeyCode::End => write!(f, "end"),
+        KeyCode::PageUp => write!(f, "pageup"),
+        KeyCode::PageDown => write!(f, "pagedown"),
+        KeyCode::Delete => write!(f, "delete"),
+        KeyCode::Insert => write!(f, "insert"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::none(),
+        }
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::ctrl(),
+        }
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let seq: KeySequence = "ctrl+shift+tab g G f5 space plus".parse().unwrap();
+        let mut modifiers = KeyModifiers::ctrl();
+        modifiers.shift = true;
+        assert_eq!(
+            seq.keys(),
+            &[
+                KeyEvent {
+                    code: KeyCode::Tab,
+                    modifiers,
+                },
+                key('g'),
+                key('G'),
+                KeyEvent {
+                    code: KeyCode::F(5),
+                    modifiers: KeyModifiers::none(),
+                },
+                KeyEvent {
+                    code: KeyCode::Space,
+                    modifiers: KeyModifiers::none(),
+                },
+                key('+'),
+            ]
+        );
+        assert_eq!(seq.to_string(), "ctrl+shift+tab g G f5 space plus");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("".parse::<KeySequence>().is_err());
+        assert!("hyper+x".parse::<KeySequence>().is_err());
+        assert!("ctrl+".parse::<KeySequence>().is_err());
+        assert!("f13".parse::<KeySequence>().is_err());
+        assert!("nope".parse::<KeySequence>().is_err());
+    }
+
+    #[test]
+    fn test_defaults() {
+        let mut keymap = Keymap::with_defaults();
+        let state = DashboardState::new();
+
+        assert_eq!(
+            keymap.handle_key(&ctrl('c'), &state),
+            KeyResult::Action(Action::Quit)
+        );
+        assert_eq!(
+            keymap.handle_key(&ctrl('r'), &state),
+            KeyResult::Action(Action::Reload)
+        );
+        assert_eq!(keymap.handle_key(&key('x'), &state), KeyResult::Unbound);
+    }
+
+    #[test]
+    fn test_rebind_and_disable() {
+        let mut keymap = Keymap::with_defaults();
+        let state = DashboardState::new();
+
+        keymap.bind("ctrl+c", Action::None).unwrap();
+        keymap.bind("q", Action::Quit).unwrap();
+
+        assert_eq!(keymap.handle_key(&ctrl('c'), &state), KeyResult::Unbound);
+        assert_eq!(
+            keymap.handle_key(&key('q'), &state),
+            KeyResult::Action(Action::Quit)
+        );
+        assert_eq!(
+            keymap
+                .bindings()
+                .iter()
+                .filter(|b| b.keys == ctrl('c').into())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_shift_is_ignored_for_chars() {
+        let mut keymap = Keymap::new();
+        let state = DashboardState::new();
+        keymap
+            .bind("G", Action::Custom("bottom".to_string()))
+            .unwrap();
+
+        let shifted = KeyEvent {
+            code: KeyCode::Char('G'),
+            modifiers: KeyModifiers::shift(),
+        };
+        assert_eq!(
+            keymap.handle_key(&shifted, &state),
+            KeyResult::Action(Action::Custom("bottom".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sequences() {
+        let mut keymap = Keymap::new();
+        let state = DashboardState::new();
+        keymap
+            .bind("g g", Action::Custom("top".to_string()))
+            .unwrap();
+        keymap
+            .bind("x", Action::Custom("delete".to_string()))
+            .unwrap();
+
+        assert_eq!(keymap.handle_key(&key('g'), &state), KeyResult::Pending);
+        assert_eq!(keymap.pending(), &[key('g')]);
+        assert_eq!(
+            keymap.handle_key(&key('g'), &state),
+            KeyResult::Action(Action::Custom("top".to_string()))
+        );
+        assert!(keymap.pending().is_empty());
+
+        // A broken sequence falls back to the last key on its own
+        assert_eq!(keymap.handle_key(&key('g'), &state), KeyResult::Pending);
+        assert_eq!(
+            keymap.handle_key(&key('x'), &state),
+            KeyResult::Action(Action::Custom("delete".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sequence_timeout() {
+        let mut keymap = Keymap::new();
+        let state = DashboardState::new();
+        keymap
+            .bind("g g", Action::Custom("top".to_string()))
+            .unwrap();
+        keymap.set_sequence_timeout(Duration::from_millis(1));
+
+        assert_eq!(keymap.handle_key(&key('g'), &state), KeyResult::Pending);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(keymap.handle_key(&key('g'), &state), KeyResult::Pending);
+    }
+
+    #[test]
+    fn test_scopes() {
+        let mut keymap = Keymap::new();
+        let mut state = DashboardState::new();
+        keymap
+            .bind("j", Action::Custom("global".to_string()))
+            .unwrap();
+        keymap
+            .bind_scoped(
+                "j",
+                KeyScope::mode("normal"),
+                Action::Custom("mode".to_string()),
+            )
+            .unwrap();
+        keymap
+            .bind_scoped(
+                "j",
+                KeyScope::focus("logs"),
+                Action::Custom("focus".to_string()),
+            )
+            .unwrap();
+        keymap
+            .bind_scoped(
+                "i",
+                KeyScope::mode("insert"),
+                Action::Custom("insert".to_string()),
+            )
+            .unwrap();
+
+        let press =
+            |keymap: &mut Keymap, state: &DashboardState, c| keymap.handle_key(&key(c), state);
+
+        assert_eq!(
+            press(&mut keymap, &state, 'j'),
+            KeyResult::Action(Action::Custom("global".to_string()))
+        );
+        assert_eq!(press(&mut keymap, &state, 'i'), KeyResult::Unbound);
+
+        state.set_mode("normal");
+        assert_eq!(
+            press(&mut keymap, &state, 'j'),
+            KeyResult::Action(Action::Custom("mode".to_string()))
+        );
+
+        // Focus outranks mode
+        state.set_focus("logs");
+        assert_eq!(
+            press(&mut keymap, &state, 'j'),
+            KeyResult::Action(Action::Custom("focus".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_scoped_disable() {
+        let mut keymap = Keymap::with_defaults();
+        let mut state = DashboardState::new();
+        keymap
+            .bind_scoped("ctrl+d", KeyScope::focus("editor"), Action::None)
+            .unwrap();
+
+        state.set_focus("editor");
+        assert_eq!(keymap.handle_key(&ctrl('d'), &state), KeyResult::Unbound);
+        assert!(keymap
+            .active_bindings(&state)
+            .iter()
+            .all(|b| b.action != Action::DismissError));
+
+        state.clear_focus();
+        assert_eq!(
+            keymap.handle_key(&ctrl('d'), &state),
+            KeyResult::Action(Action::DismissError)
+        );
+    }
+
+    #[test]
+    fn test_unbind() {
+        let mut keymap = Keymap::with_defaults();
+        let state = DashboardState::new();
+
+        assert!(keymap.unbind("ctrl+c", &KeyScope::global()).unwrap());
+        assert!(!keymap.unbind("ctrl+c", &KeyScope::global()).unwrap());
+        assert_eq!(keymap.handle_key(&ctrl('c'), &state), KeyResult::Unbound);
+    }
+
+    #[test]
+    fn test_active_bindings() {
+        let mut keymap = Keymap::with_defaults();
+        let mut state = DashboardState::new();
+        keymap
+            .bind_scoped(
+                "ctrl+c",
+                KeyScope::mode("insert"),
+                Action::Custom("copy".to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(keymap.active_bindings(&state).len(), 10);
+
+        state.set_mode("insert");
+        let active = keymap.active_bindings(&state);
+        assert_eq!(active.len(), 10);
+        let copy = active.iter().find(|b| b.keys == ctrl('c').into()).unwrap();
+        assert_eq!(copy.label(), "copy");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_from_toml() {
+        let keymap = Keymap::from_toml(
+            r#"
+            sequence_timeout_ms = 500
+
+            [[bind]]
+            keys = "q"
+            action = "quit"
+
+            [[bind]]
+            keys = "ctrl+r"
+            action = "none"
+
+            [[bind]]
+            keys = "g g"
+            action = "top"
+            focus = "logs"
+            mode = "normal"
+            description = "Jump to top"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(keymap.sequence_timeout(), Duration::from_millis(500));
+        // 10 defaults, with ctrl+r replaced, plus q and g g
+        assert_eq!(keymap.bindings().len(), 12);
+
+        let top = keymap.bindings().last().unwrap();
+        assert_eq!(top.action, Action::Custom("top".to_string()));
+        assert_eq!(top.scope, KeyScope::focus("logs").with_mode("normal"));
+        assert_eq!(top.label(), "Jump to top");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_from_toml_without_defaults() {
+        let keymap = Keymap::from_toml(
+            r#"
+            defaults = false
+
+            [[bind]]
+            keys = "esc"
+            action = "quit"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(keymap.bindings().len(), 1);
+
+        assert!(Keymap::from_toml("[[bind]]\nkeys = \"hyper+x\"\naction = \"quit\"").is_err());
+        assert!(Keymap::from_toml("unknown = 1").is_err());
+    }
+}
@@ -3,11 +3,22 @@
 //! This crate provides the core engine for building hot-reloadable TUI applications
 //! with the Fusabi framework. It includes:
 //!
-//! - **DashboardEngine**: Main orchestration engine for TUI applications
-//! - **FileWatcher**: File system watching with debouncing
+//! - **DashboardEngine**: Main orchestration engine for TUI applications, with a
+//!   built-in app loop in `DashboardEngine::run`
+//! - **FileWatcher**: File and directory watching with debouncing, glob filters and
+//!   `.gitignore` support
 //! - **FileLoader**: Smart file loading with dependency tracking and caching
 //! - **Event handling**: Comprehensive input event types and actions
-//! - **State management**: Dashboard and widget state management
+//! - **Async mode**: `DashboardEngine::run_async` on tokio, driven by event streams and
+//!   state updates pushed from background tasks through a `DashboardHandle`
+//! - **EventReader**: Terminal input, ticks, file changes and custom messages in one stream
+//! - **Keymap**: Rebindable key sequences scoped by focus and mode, with a help overlay
+//! - **Focus and mouse**: Tab and arrow-key focus traversal, and clicks and scrolling
+//!   routed to the widget under the cursor
+//! - **State management**: Dashboard and widget state management, with snapshots that
+//!   survive reloads and can be persisted across restarts
+//! - **Recording**: Sessions recorded to a file and replayed on a `TestRenderer`, for
+//!   exact bug reproductions and regression tests of interactive flows
 //!
 //! # Features
 //!
@@ -97,27 +108,51 @@
 #![warn(clippy::all)]
 #![warn(missing_docs)]
 
+pub mod app;
 pub mod dashboard;
 pub mod error;
 pub mod event;
+pub mod focus;
 pub mod fusabi_runtime;
+pub mod hit;
+pub mod input;
+pub mod keymap;
 pub mod loader;
 pub mod overlay;
+pub mod recording;
+pub mod snapshot;
 pub mod state;
+pub mod stream;
 pub mod watcher;
 
 /// Convenient re-exports for common types and traits.
 pub mod prelude {
     //! Prelude module with commonly used types.
 
-    pub use crate::dashboard::DashboardEngine;
-    pub use crate::error::{EngineError, EngineResult, LoadError, WatchError};
+    #[cfg(feature = "crossterm-backend")]
+    pub use crate::app::TerminalGuard;
+    pub use crate::app::RunOptions;
+    pub use crate::dashboard::{DashboardEngine, DashboardHandle};
+    pub use crate::error::{
+        EngineError, EngineResult, KeymapError, LoadError, RecordingError, ScriptError,
+        ScriptErrorKind, SnapshotError, WatchError,
+    };
     pub use crate::event::{
         Action, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
     };
+    pub use crate::focus::{FocusDirection, FocusRing, FocusStyle};
     pub use crate::fusabi_runtime::{FusabiContext, parse_load_directives};
+    pub use crate::hit::{Hit, HitKind, HitMap, HitPart};
+    #[cfg(feature = "crossterm-backend")]
+    pub use crate::input::CrosstermEvents;
+    pub use crate::input::{CustomSender, EventReader, EventSource};
+    pub use crate::keymap::{Binding, KeyResult, KeyScope, KeySequence, Keymap};
     pub use crate::loader::{FileLoader, LoadedFile};
-    pub use crate::overlay::{ErrorMessage, ErrorOverlay, ErrorSeverity};
-    pub use crate::state::{DashboardState, ListState, TableState, WidgetState};
-    pub use crate::watcher::FileWatcher;
+    pub use crate::overlay::{ErrorMessage, ErrorOverlay, ErrorSeverity, HelpOverlay, SourceSpan};
+    pub use crate::recording::{FrameMismatch, RecordedEvent, Recorder, Recording};
+    pub use crate::snapshot::{StateSnapshot, WidgetSnapshot};
+    pub use crate::state::{
+        DashboardState, ListState, StateValue, TableState, TabsState, WidgetState,
+    };
+    pub use crate::watcher::{FileChange, FileChangeKind, FileWatcher, WatchFilter};
 }
\ No newline at end of file
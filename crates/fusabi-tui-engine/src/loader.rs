@@ -1,6 +1,13 @@
 //! File loading and dependency tracking for the Fusabi TUI engine.
+//!
+//! The [`FileLoader`] keeps the module graph of a dashboard: every file and
+//! the files it pulls in with `#load` directives. When a file changes,
+//! [`FileLoader::invalidate`] returns it together with every file that loads
+//! it, directly or through other files, which are the modules that need to be
+//! recompiled.
 
 use crate::error::{LoadError, LoadResult};
+use crate::fusabi_runtime::{hash_source, parse_load_directives};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -15,10 +22,13 @@ pub struct LoadedFile {
     /// Raw file content as a string.
     pub content: String,
 
+    /// Hash of the content, as computed by [`hash_source`].
+    pub source_hash: u64,
+
     /// Last modification time.
     pub modified: SystemTime,
 
-    /// Files that this file depends on.
+    /// Files that this file loads with `#load` directives.
     pub dependencies: Vec<PathBuf>,
 }
 
@@ -28,6 +38,10 @@ pub struct FileLoader {
     /// Cache of loaded files keyed by canonical path.
     cache: HashMap<PathBuf, LoadedFile>,
 
+    /// Dependency map: file -> files it loads. Kept when a file is
+    /// invalidated, so that its dependents can still be found.
+    dependencies: HashMap<PathBuf, Vec<PathBuf>>,
+
     /// Reverse dependency map: file -> files that depend on it.
     dependents: HashMap<PathBuf, HashSet<PathBuf>>,
 }
@@ -37,17 +51,25 @@ impl FileLoader {
     pub fn new() -> Self {
         Self {
             cache: HashMap::new(),
+            dependencies: HashMap::new(),
             dependents: HashMap::new(),
         }
     }
 
-    /// Load a file from disk, using cache if available and up-to-date.
+    /// Load a file and its dependencies from disk, using the cache for files
+    /// that are up-to-date.
     ///
     /// This method will:
-    /// - Check if the file is already cached and up-to-date
-    /// - Read the file from disk if needed
-    /// - Parse dependencies (can be extended for .fsx files)
+    /// - Check each file in the module graph against its cached version
+    /// - Read the files that are new or were modified
+    /// - Parse their `#load` directives with [`parse_load_directives`]
     /// - Update the cache and dependency graph
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LoadError::CircularDependency`] if the files load each other
+    /// in a cycle, and [`LoadError::DependencyNotFound`] if a loaded file
+    /// does not exist.
     pub fn load(&mut self, path: &Path) -> LoadResult<&LoadedFile> {
         let canonical_path = path
             .canonicalize()
@@ -55,73 +77,110 @@ impl FileLoader {
                 path: path.to_path_buf(),
             })?;
 
-        // Check if we have a valid cached version
-        let needs_reload = if let Some(cached) = self.cache.get(&canonical_path) {
-            if let Ok(metadata) = fs::metadata(&canonical_path) {
-                if let Ok(modified) = metadata.modified() {
-                    modified > cached.modified
-                } else {
-                    true
-                }
-            } else {
-                true
-            }
-        } else {
-            true
-        };
+        let mut stack = Vec::new();
+        let mut done = HashSet::new();
+        self.load_module(&canonical_path, &mut stack, &mut done)?;
+
+        Ok(&self.cache[&canonical_path])
+    }
 
-        if !needs_reload {
-            return Ok(self.cache.get(&canonical_path).unwrap());
+    /// Load a module and, depth first, its dependencies.
+    ///
+    /// `stack` holds the chain of modules being loaded, for cycle detection,
+    /// and `done` the modules whose dependencies are already loaded.
+    fn load_module(
+        &mut self,
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        done: &mut HashSet<PathBuf>,
+    ) -> LoadResult<()> {
+        if let Some(start) = stack.iter().position(|p| p == path) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(path.to_path_buf());
+            return Err(LoadError::CircularDependency(cycle));
+        }
+        if done.contains(path) {
+            return Ok(());
         }
 
-        // Load the file from disk
-        let content = fs::read_to_string(&canonical_path).map_err(|e| {
-            LoadError::ReadFailed {
-                path: canonical_path.clone(),
-                source: e,
-            }
-        })?;
-
-        let metadata = fs::metadata(&canonical_path).map_err(|e| LoadError::ReadFailed {
-            path: canonical_path.clone(),
-            source: e,
-        })?;
-
-        let modified = metadata
-            .modified()
-            .map_err(|e| LoadError::ReadFailed {
-                path: canonical_path.clone(),
-                source: e,
-            })?;
+        if !self.is_up_to_date(path) {
+            self.read(path)?;
+        }
+
+        let dependencies = self.cache[path].dependencies.clone();
+        stack.push(path.to_path_buf());
+        for dependency in dependencies {
+            let canonical = dependency
+                .canonicalize()
+                .map_err(|_| LoadError::DependencyNotFound {
+                    dependency: dependency.clone(),
+                    dependent: path.to_path_buf(),
+                })?;
+            self.load_module(&canonical, stack, done)?;
+        }
+        stack.pop();
+
+        done.insert(path.to_path_buf());
+        Ok(())
+    }
 
-        // Parse dependencies (simplified - can be extended for .fsx imports)
-        let dependencies = self.parse_dependencies(&canonical_path, &content)?;
+    /// Check if a file is cached and was not modified since.
+    fn is_up_to_date(&self, path: &Path) -> bool {
+        let Some(cached) = self.cache.get(path) else {
+            return false;
+        };
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified <= cached.modified)
+    }
 
-        // Update reverse dependency map
+    /// Read a file into the cache and update its edges in the graph.
+    fn read(&mut self, path: &Path) -> LoadResult<()> {
+        let read_failed = |source| LoadError::ReadFailed {
+            path: path.to_path_buf(),
+            source,
+        };
+        let content = fs::read_to_string(path).map_err(read_failed)?;
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(read_failed)?;
+
+        let dependencies = parse_load_directives(&content, path);
+
+        // Replace the edges from the previous version of the file
+        if let Some(old) = self.dependencies.remove(path) {
+            for dep in old {
+                if let Some(dependents) = self.dependents.get_mut(&dep) {
+                    dependents.remove(path);
+                }
+            }
+        }
         for dep in &dependencies {
             self.dependents
                 .entry(dep.clone())
-                .or_insert_with(HashSet::new)
-                .insert(canonical_path.clone());
+                .or_default()
+                .insert(path.to_path_buf());
         }
+        self.dependencies
+            .insert(path.to_path_buf(), dependencies.clone());
 
-        // Create and cache the loaded file
         let loaded = LoadedFile {
-            path: canonical_path.clone(),
+            path: path.to_path_buf(),
+            source_hash: hash_source(&content),
             content,
             modified,
             dependencies,
         };
-
-        self.cache.insert(canonical_path.clone(), loaded);
-
-        Ok(self.cache.get(&canonical_path).unwrap())
+        self.cache.insert(path.to_path_buf(), loaded);
+        Ok(())
     }
 
-    /// Invalidate a file in the cache and return all files that depend on it.
+    /// Invalidate a file in the cache and return it together with all files
+    /// that depend on it.
     ///
     /// This is useful when a file changes - you can invalidate it and reload
-    /// all dependent files.
+    /// all dependent files. The dependency graph is kept, so a later change
+    /// to the same file finds the same dependents.
     pub fn invalidate(&mut self, path: &Path) -> Vec<PathBuf> {
         let canonical_path = match path.canonicalize() {
             Ok(p) => p,
@@ -174,20 +233,48 @@ impl FileLoader {
 
         if let Some(deps) = self.dependents.get(path) {
             for dep in deps {
-                result.push(dep.clone());
+                if !visited.contains(dep) {
+                    result.push(dep.clone());
+                }
                 self.collect_dependents(dep, visited, result);
             }
         }
     }
 
-    /// Parse dependencies from file content.
+    /// Get a file and all files it loads (transitively), dependencies first.
     ///
-    /// This is a simplified implementation. In a real system, this would
-    /// parse .fsx files for import/open statements and resolve relative paths.
-    fn parse_dependencies(&self, _path: &Path, _content: &str) -> LoadResult<Vec<PathBuf>> {
-        // TODO: Implement proper dependency parsing for .fsx files
-        // For now, return empty dependencies
-        Ok(Vec::new())
+    /// Only files known from a previous [`load`](Self::load) are included.
+    pub fn modules(&self, path: &Path) -> Vec<PathBuf> {
+        let canonical_path = match path.canonicalize() {
+            Ok(p) => p,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut visited = HashSet::new();
+        let mut result = Vec::new();
+        self.collect_modules(&canonical_path, &mut visited, &mut result);
+        result
+    }
+
+    /// Recursively collect a module after its dependencies.
+    fn collect_modules(
+        &self,
+        path: &Path,
+        visited: &mut HashSet<PathBuf>,
+        result: &mut Vec<PathBuf>,
+    ) {
+        if !visited.insert(path.to_path_buf()) {
+            return;
+        }
+
+        if let Some(deps) = self.dependencies.get(path) {
+            for dep in deps {
+                self.collect_modules(dep, visited, result);
+            }
+        }
+        if self.dependencies.contains_key(path) {
+            result.push(path.to_path_buf());
+        }
     }
 
     /// Check if a file is loaded in the cache.
@@ -208,6 +295,7 @@ impl FileLoader {
     /// Clear all cached files.
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.dependencies.clear();
         self.dependents.clear();
     }
 
@@ -339,4 +427,103 @@ mod tests {
         assert_eq!(loader.len(), 0);
         assert!(loader.is_empty());
     }
+
+    /// Write `files` into a temporary directory and return its canonical path.
+    fn write_files(files: &[(&str, &str)]) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for (name, content) in files {
+            fs::write(root.join(name), content).unwrap();
+        }
+        (dir, root)
+    }
+
+    #[test]
+    fn test_module_graph() {
+        let (_dir, root) = write_files(&[
+            ("main.fsx", "#load \"lib.fsx\"\n#load \"util.fsx\"\n"),
+            ("lib.fsx", "#load \"util.fsx\"\nlet lib = 1\n"),
+            ("util.fsx", "let util = 2\n"),
+        ]);
+        let mut loader = FileLoader::new();
+
+        let main = loader.load(&root.join("main.fsx")).unwrap();
+        assert_eq!(main.dependencies, [root.join("lib.fsx"), root.join("util.fsx")]);
+        assert_eq!(loader.len(), 3);
+        assert_eq!(
+            loader.modules(&root.join("main.fsx")),
+            [root.join("util.fsx"), root.join("lib.fsx"), root.join("main.fsx")]
+        );
+
+        // A change to util.fsx affects everything, a change to main.fsx only itself
+        let mut invalidated = loader.invalidate(&root.join("util.fsx"));
+        invalidated.sort();
+        assert_eq!(
+            invalidated,
+            [root.join("lib.fsx"), root.join("main.fsx"), root.join("util.fsx")]
+        );
+        loader.load(&root.join("main.fsx")).unwrap();
+        assert_eq!(loader.invalidate(&root.join("main.fsx")), [root.join("main.fsx")]);
+        assert!(loader.is_cached(&root.join("lib.fsx")));
+    }
+
+    #[test]
+    fn test_reload_updates_dependents() {
+        let (_dir, root) = write_files(&[
+            ("main.fsx", "#load \"lib.fsx\"\n"),
+            ("lib.fsx", "let lib = 1\n"),
+        ]);
+        let mut loader = FileLoader::new();
+        loader.load(&root.join("main.fsx")).unwrap();
+        assert_eq!(loader.get_dependents(&root.join("lib.fsx")), [root.join("main.fsx")]);
+
+        fs::write(root.join("main.fsx"), "let standalone = 1\n").unwrap();
+        loader.invalidate(&root.join("main.fsx"));
+        loader.load(&root.join("main.fsx")).unwrap();
+        assert!(loader.get_dependents(&root.join("lib.fsx")).is_empty());
+    }
+
+    #[test]
+    fn test_load_cycle() {
+        let (_dir, root) = write_files(&[
+            ("main.fsx", "#load \"a.fsx\"\n"),
+            ("a.fsx", "#load \"b.fsx\"\n"),
+            ("b.fsx", "#load \"a.fsx\"\n"),
+        ]);
+        let mut loader = FileLoader::new();
+
+        match loader.load(&root.join("main.fsx")) {
+            Err(LoadError::CircularDependency(cycle)) => {
+                assert_eq!(cycle, [root.join("a.fsx"), root.join("b.fsx"), root.join("a.fsx")]);
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+
+        let err = LoadError::CircularDependency(vec![
+            PathBuf::from("a.fsx"),
+            PathBuf::from("b.fsx"),
+            PathBuf::from("a.fsx"),
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "Circular dependency detected: a.fsx -> b.fsx -> a.fsx"
+        );
+    }
+
+    #[test]
+    fn test_load_missing_dependency() {
+        let (_dir, root) = write_files(&[("main.fsx", "#load \"gone.fsx\"\n")]);
+        let mut loader = FileLoader::new();
+
+        match loader.load(&root.join("main.fsx")) {
+            Err(LoadError::DependencyNotFound {
+                dependency,
+                dependent,
+            }) => {
+                assert_eq!(dependency, root.join("gone.fsx"));
+                assert_eq!(dependent, root.join("main.fsx"));
+            }
+            other => panic!("expected a missing dependency, got {:?}", other),
+        }
+    }
 }
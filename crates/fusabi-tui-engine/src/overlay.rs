@@ -1,24 +1,76 @@
 //! Development overlay for displaying diagnostics and errors during hot reload.
 
 use crate::error::EngineError;
+use crate::event::{Action, KeyCode, KeyEvent};
+use crate::keymap::Keymap;
+use crate::loader::FileLoader;
+use crate::state::DashboardState;
 use fusabi_tui_core::buffer::Buffer;
 use fusabi_tui_core::layout::Rect;
 use fusabi_tui_core::style::{Color, Modifier, Style};
 use fusabi_tui_widgets::block::Block;
 use fusabi_tui_widgets::borders::{BorderType, Borders};
 use fusabi_tui_widgets::paragraph::Paragraph;
+use fusabi_tui_widgets::text::{Line, Span, Text};
 use fusabi_tui_widgets::widget::Widget;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
+/// Lines scrolled by Page Up and Page Down in the error overlay.
+const PAGE_LINES: u16 = 10;
+
+/// Smallest height of a docked error overlay.
+const DOCKED_MIN_HEIGHT: u16 = 6;
+
+/// Columns a tab takes up in code snippets.
+const TAB_WIDTH: usize = 4;
+
 /// An overlay widget that displays error messages and diagnostics during development.
 ///
 /// The error overlay appears on top of the regular dashboard when compilation or
 /// runtime errors occur, providing immediate feedback to developers without crashing
 /// the application.
+///
+/// Errors with a source location are shown like compiler diagnostics: the
+/// offending lines with line numbers and the column underlined, followed by
+/// related locations and notes. The overlay can hold several errors; the
+/// left and right arrow keys switch between them and the up and down arrow
+/// keys scroll a long one (see [`handle_key`](Self::handle_key)).
+///
+/// # Example
+///
+/// ```
+/// use fusabi_tui_core::buffer::Buffer;
+/// use fusabi_tui_core::layout::Rect;
+/// use fusabi_tui_engine::overlay::{ErrorMessage, ErrorOverlay, SourceSpan};
+///
+/// let error = ErrorMessage::new("Script Error", "unknown function `twice`")
+///     .with_source("main.fsx")
+///     .with_line(2)
+///     .with_column(9)
+///     .with_source_text("main.fsx", "#load \"lib.fsx\"\nlet x = twice 2\n")
+///     .with_related(SourceSpan::new("main.fsx", 1, 1).with_label("lib.fsx is loaded here"))
+///     .with_note("`lib.fsx` defines `double`, not `twice`");
+///
+/// let area = Rect::new(0, 0, 80, 24);
+/// let mut buffer = Buffer::new(area);
+/// ErrorOverlay::new(error).render(area, &mut buffer);
+/// ```
 #[derive(Debug, Clone)]
 pub struct ErrorOverlay {
-    /// The error to display.
-    error: ErrorMessage,
+    /// The errors to display, at least one.
+    errors: Vec<ErrorMessage>,
+
+    /// Index of the error on screen.
+    selected: usize,
+
+    /// Lines scrolled past at the top of the error on screen.
+    scroll: u16,
+
+    /// Largest useful scroll offset in the last rendered frame.
+    max_scroll: Cell<u16>,
 
     /// When the error was first shown.
     timestamp: Instant,
@@ -26,12 +78,22 @@ pub struct ErrorOverlay {
     /// Whether to show the overlay (can be dismissed).
     visible: bool,
 
+    /// Whether the overlay is a panel along the bottom of the screen
+    /// instead of a centered dialog.
+    docked: bool,
+
     /// Auto-dismiss after this duration (None = manual dismiss only).
     auto_dismiss_after: Option<Duration>,
+
+    /// Keys bound to [`Action::DismissError`], shown in the footer.
+    dismiss_keys: Option<String>,
+
+    /// Keys bound to [`Action::Reload`], shown in the footer.
+    reload_keys: Option<String>,
 }
 
 /// A displayable error message with context.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ErrorMessage {
     /// The main error title/summary.
     pub title: String,
@@ -53,6 +115,36 @@ pub struct ErrorMessage {
 
     /// Additional context or hints for fixing the error.
     pub hints: Vec<String>,
+
+    /// Other locations involved in the error.
+    pub related: Vec<SourceSpan>,
+
+    /// Notes shown below the code snippets.
+    pub notes: Vec<String>,
+
+    /// Text of the source files, by the names used in `source` and
+    /// `related`, for code snippets.
+    pub sources: BTreeMap<String, String>,
+}
+
+/// A location in a source file, underlined in the error overlay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// Source file path.
+    pub source: String,
+
+    /// 1-based line number.
+    pub line: usize,
+
+    /// 1-based column number.
+    pub column: usize,
+
+    /// Number of columns to underline; `None` underlines the word at the
+    /// column.
+    pub width: Option<usize>,
+
+    /// Text shown after the underline.
+    pub label: Option<String>,
 }
 
 /// Error severity levels for visual styling.
@@ -71,11 +163,27 @@ pub enum ErrorSeverity {
 impl ErrorOverlay {
     /// Create a new error overlay from an error.
     pub fn new(error: ErrorMessage) -> Self {
+        Self::from_errors(vec![error])
+    }
+
+    /// Create an error overlay listing several errors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `errors` is empty.
+    pub fn from_errors(errors: Vec<ErrorMessage>) -> Self {
+        assert!(!errors.is_empty(), "an error overlay needs an error");
         Self {
-            error,
+            errors,
+            selected: 0,
+            scroll: 0,
+            max_scroll: Cell::new(u16::MAX),
             timestamp: Instant::now(),
             visible: true,
+            docked: false,
             auto_dismiss_after: None,
+            dismiss_keys: None,
+            reload_keys: None,
         }
     }
 
@@ -107,6 +215,31 @@ impl ErrorOverlay {
         self.timestamp = Instant::now();
     }
 
+    /// Check if the overlay is docked along the bottom of the screen.
+    pub fn is_docked(&self) -> bool {
+        self.docked
+    }
+
+    /// Dock the overlay along the bottom of the screen, or center it.
+    ///
+    /// A docked overlay leaves most of the screen to the dashboard behind
+    /// it, as when a failed reload keeps the last good version running.
+    /// Its footer does not mention the scroll and error switching keys,
+    /// which the dashboard keeps for itself.
+    pub fn set_docked(&mut self, docked: bool) {
+        self.docked = docked;
+    }
+
+    /// Show the keys that dismiss the overlay and reload the dashboard in
+    /// the footer, as bound in `keymap` for `state`.
+    ///
+    /// Without keys, which is the default, the footer leaves them out. The
+    /// engine sets them before every frame, so rebound keys show up at once.
+    pub fn set_keys(&mut self, keymap: &Keymap, state: &DashboardState) {
+        self.dismiss_keys = bound_keys(keymap, state, &Action::DismissError);
+        self.reload_keys = bound_keys(keymap, state, &Action::Reload);
+    }
+
     /// Update the overlay state (handle auto-dismiss).
     pub fn update(&mut self) {
         if let Some(duration) = self.auto_dismiss_after {
@@ -116,9 +249,80 @@ impl ErrorOverlay {
         }
     }
 
-    /// Get the error message.
+    /// Get the error on screen.
     pub fn error(&self) -> &ErrorMessage {
-        &self.error
+        &self.errors[self.selected]
+    }
+
+    /// Get all errors.
+    pub fn errors(&self) -> &[ErrorMessage] {
+        &self.errors
+    }
+
+    /// Add an error, unless the same error is already listed.
+    ///
+    /// Returns `true` if the error was added.
+    pub fn push(&mut self, error: ErrorMessage) -> bool {
+        if self.errors.contains(&error) {
+            return false;
+        }
+        self.errors.push(error);
+        true
+    }
+
+    /// Get the index of the error on screen.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Show the next error, wrapping around.
+    pub fn select_next(&mut self) {
+        self.select((self.selected + 1) % self.errors.len());
+    }
+
+    /// Show the previous error, wrapping around.
+    pub fn select_previous(&mut self) {
+        self.select(self.selected.checked_sub(1).unwrap_or(self.errors.len() - 1));
+    }
+
+    fn select(&mut self, index: usize) {
+        if index != self.selected {
+            self.selected = index;
+            self.scroll = 0;
+            self.max_scroll.set(u16::MAX);
+        }
+    }
+
+    /// Get the number of lines scrolled past.
+    pub fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    /// Scroll the error on screen up by `lines`.
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// Scroll the error on screen down by `lines`, stopping at its end as
+    /// of the last rendered frame.
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_add(lines).min(self.max_scroll.get());
+    }
+
+    /// Scroll or switch errors with the arrow and page keys.
+    ///
+    /// Returns `true` if the key was used.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(PAGE_LINES),
+            KeyCode::PageDown => self.scroll_down(PAGE_LINES),
+            KeyCode::Left if self.errors.len() > 1 => self.select_previous(),
+            KeyCode::Right if self.errors.len() > 1 => self.select_next(),
+            _ => return false,
+        }
+        true
     }
 
     /// Get the time since the error was shown.
@@ -129,14 +333,19 @@ impl ErrorOverlay {
     /// Render the overlay to a buffer.
     ///
     /// This creates a semi-transparent overlay effect by rendering
-    /// a centered error panel on top of the existing buffer content.
+    /// a centered error panel on top of the existing buffer content, or a
+    /// panel along the bottom if the overlay is [docked](Self::set_docked).
     pub fn render(&self, area: Rect, buf: &mut Buffer) {
         if !self.visible {
             return;
         }
 
-        // Create a centered area for the error dialog
-        let overlay_area = Self::centered_rect(80, 60, area);
+        let overlay_area = if self.docked {
+            Self::docked_rect(area)
+        } else {
+            // Create a centered area for the error dialog
+            Self::centered_rect(80, 60, area)
+        };
 
         // Render the error panel
         self.render_error_panel(overlay_area, buf);
@@ -146,7 +355,7 @@ impl ErrorOverlay {
     fn render_error_panel(&self, area: Rect, buf: &mut Buffer) {
         use fusabi_tui_widgets::block::Title;
 
-        let error = &self.error;
+        let error = self.error();
 
         // Determine colors based on severity
         let (border_color, title_color) = match error.severity {
@@ -156,7 +365,17 @@ impl ErrorOverlay {
         };
 
         // Create title string
-        let title_str = format!(" {}: {} ", error.severity.as_str(), error.title);
+        let title_str = if self.errors.len() > 1 {
+            format!(
+                " {} {}/{}: {} ",
+                error.severity.as_str(),
+                self.selected + 1,
+                self.errors.len(),
+                error.title
+            )
+        } else {
+            format!(" {}: {} ", error.severity.as_str(), error.title)
+        };
         let title = Title::new(title_str)
             .style(Style::default().fg(title_color).add_modifier(Modifier::BOLD));
 
@@ -170,40 +389,50 @@ impl ErrorOverlay {
 
         let inner = block.inner(area);
         block.render(area, buf);
+        if inner.height == 0 {
+            return;
+        }
 
-        // Build content as a single string for simplicity
-        let mut content = String::new();
+        // The footer stays on the last line; the rest scrolls
+        let body = Rect::new(inner.x, inner.y, inner.width, inner.height - 1);
+        let lines = error.diagnostic_lines(title_color);
+        let max_scroll = (lines.len() as u16).saturating_sub(body.height);
+        self.max_scroll.set(max_scroll);
+        let scroll = self.scroll.min(max_scroll);
 
-        // Error message
-        content.push_str(&error.message);
-        content.push_str("\n\n");
-
-        // Location info if available
-        if let Some(source) = &error.source {
-            if let (Some(line), Some(col)) = (error.line, error.column) {
-                content.push_str(&format!("Location: {}:{}:{}\n\n", source, line, col));
-            } else if let Some(line) = error.line {
-                content.push_str(&format!("Location: {}:{}\n\n", source, line));
-            } else {
-                content.push_str(&format!("Location: {}\n\n", source));
-            }
+        let mut keys = Vec::new();
+        if let Some(dismiss) = &self.dismiss_keys {
+            keys.push(format!("{} to dismiss", dismiss));
         }
-
-        // Hints
-        if !error.hints.is_empty() {
-            content.push_str("Hints:\n");
-            for hint in &error.hints {
-                content.push_str(&format!("  * {}\n", hint));
-            }
-            content.push('\n');
+        if let Some(reload) = &self.reload_keys {
+            keys.push(format!("{} to reload", reload));
+        }
+        if self.errors.len() > 1 && !self.docked {
+            keys.push("Left/Right for other errors".to_string());
+        }
+        if max_scroll > 0 && !self.docked {
+            keys.push("Up/Down to scroll".to_string());
         }
+        let footer = if keys.is_empty() {
+            String::new()
+        } else {
+            format!("Press {}", keys.join(", "))
+        };
 
-        // Footer
-        content.push_str("Press Ctrl+D to dismiss, Ctrl+R to reload");
+        Paragraph::new(Text::from_lines(lines))
+            .style(Style::default().fg(Color::White))
+            .scroll(0, scroll)
+            .render(body, buf);
+        Paragraph::new(footer)
+            .style(Style::default().fg(Color::DarkGray))
+            .render(Rect::new(inner.x, inner.bottom() - 1, inner.width, 1), buf);
+    }
 
-        let para = Paragraph::new(content)
-            .style(Style::default().fg(Color::White));
-        para.render(inner, buf);
+    /// The bottom two fifths of the area, at least [`DOCKED_MIN_HEIGHT`]
+    /// lines.
+    fn docked_rect(area: Rect) -> Rect {
+        let height = (area.height * 2 / 5).max(DOCKED_MIN_HEIGHT).min(area.height);
+        Rect::new(area.x, area.bottom() - height, area.width, height)
     }
 
     /// Helper function to create a centered rectangle.
@@ -220,6 +449,88 @@ impl ErrorOverlay {
     }
 }
 
+/// An overlay listing the key bindings that are currently active.
+///
+/// Build it from a [`Keymap`] with [`HelpOverlay::from_keymap`]; the engine
+/// shows it when [`Action::ToggleHelp`] is performed.
+#[derive(Debug, Clone, Default)]
+pub struct HelpOverlay {
+    /// Key sequence and description for each binding.
+    entries: Vec<(String, String)>,
+
+    /// Keys that close the overlay, shown in the footer.
+    close_keys: Option<String>,
+}
+
+impl HelpOverlay {
+    /// Create a help overlay for the bindings active in `state`.
+    pub fn from_keymap(keymap: &Keymap, state: &DashboardState) -> Self {
+        let entries = keymap
+            .active_bindings(state)
+            .into_iter()
+            .map(|b| (b.keys.to_string(), b.label()))
+            .collect();
+        Self {
+            entries,
+            close_keys: bound_keys(keymap, state, &Action::ToggleHelp),
+        }
+    }
+
+    /// Get the key sequence and description of each listed binding.
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Render the overlay to a buffer, centered in `area`.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        use fusabi_tui_widgets::block::Title;
+
+        let area = ErrorOverlay::centered_rect(60, 60, area);
+
+        let title = Title::new(" Key Bindings ")
+            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Black));
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let key_width = self
+            .entries
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut content = String::new();
+        if self.entries.is_empty() {
+            content.push_str("No key bindings\n");
+        }
+        for (keys, description) in &self.entries {
+            content.push_str(&format!("{:<width$}  {}\n", keys, description, width = key_width));
+        }
+        if let Some(keys) = &self.close_keys {
+            content.push_str(&format!("\nPress {} to close", keys));
+        }
+
+        let para = Paragraph::new(content).style(Style::default().fg(Color::White));
+        para.render(inner, buf);
+    }
+}
+
+/// The keys of the first binding for `action` that is active in `state`.
+fn bound_keys(keymap: &Keymap, state: &DashboardState, action: &Action) -> Option<String> {
+    keymap
+        .active_bindings(state)
+        .into_iter()
+        .find(|b| b.action == *action)
+        .map(|b| b.keys.to_string())
+}
+
 impl ErrorMessage {
     /// Create a new error message.
     pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
@@ -231,12 +542,15 @@ impl ErrorMessage {
             column: None,
             severity: ErrorSeverity::Error,
             hints: Vec::new(),
+            related: Vec::new(),
+            notes: Vec::new(),
+            sources: BTreeMap::new(),
         }
     }
 
     /// Create an error message from an EngineError.
     pub fn from_engine_error(error: &EngineError) -> Self {
-        use crate::error::LoadError;
+        use crate::error::{LoadError, ScriptErrorKind};
 
         match error {
             EngineError::LoadError(load_err) => match load_err {
@@ -264,6 +578,19 @@ impl ErrorMessage {
                 .with_hint("Check the syntax of your .fsx file")
                 .with_hint("Look for unclosed brackets, quotes, or other syntax errors"),
 
+                LoadError::CircularDependency(cycle) => {
+                    let mut msg = Self::new("Circular Dependency", format!("{}", load_err))
+                        .with_hint("Move the shared definitions into a file that neither loads");
+                    for pair in cycle.windows(2) {
+                        msg = msg.with_note(format!(
+                            "{} loads {}",
+                            pair[0].display(),
+                            pair[1].display()
+                        ));
+                    }
+                    msg
+                }
+
                 _ => Self::new("Load Error", format!("{}", load_err)),
             },
 
@@ -280,6 +607,34 @@ impl ErrorMessage {
                     .with_severity(ErrorSeverity::Warning)
             }
 
+            EngineError::Script(script_err) => {
+                let title = match script_err.kind {
+                    ScriptErrorKind::Syntax => "Syntax Error",
+                    ScriptErrorKind::Runtime => "Script Error",
+                };
+                let mut msg = Self::new(title, script_err.message.clone())
+                    .with_line(script_err.line)
+                    .with_column(script_err.column);
+                if let Some(path) = &script_err.path {
+                    msg = msg.with_source(path.display().to_string());
+                }
+                match script_err.kind {
+                    ScriptErrorKind::Syntax => {
+                        msg.with_hint("Check the indentation and brackets near this position")
+                    }
+                    ScriptErrorKind::Runtime => msg.with_hint("Fix the script and save to reload"),
+                }
+            }
+
+            EngineError::ScriptPanic(msg) => Self::new("Script Panic", msg.clone())
+                .with_hint("The script or a host function it called panicked")
+                .with_hint("Fix the script and save to reload"),
+
+            EngineError::Theme { path, reason } => Self::new("Theme Error", reason.clone())
+                .with_source(path.display().to_string())
+                .with_hint("The previous theme is kept until the file loads")
+                .with_hint("Fix the theme file and save to reload"),
+
             EngineError::InvalidState(msg) => {
                 Self::new("Invalid State", msg.clone()).with_hint("Try reloading the dashboard")
             }
@@ -317,6 +672,246 @@ impl ErrorMessage {
         self.hints.push(hint.into());
         self
     }
+
+    /// Add another location involved in the error.
+    pub fn with_related(mut self, span: SourceSpan) -> Self {
+        self.related.push(span);
+        self
+    }
+
+    /// Add a note.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Set the text of a source file, for code snippets.
+    pub fn with_source_text(mut self, source: impl Into<String>, text: impl Into<String>) -> Self {
+        self.sources.insert(source.into(), text.into());
+        self
+    }
+
+    /// Take the text of the source files the error points into from the
+    /// loader's cache. Files that are not cached are shown without snippets.
+    pub fn with_sources_from(mut self, loader: &FileLoader) -> Self {
+        let names = self.source.iter().chain(self.related.iter().map(|s| &s.source));
+        let missing: Vec<String> = names
+            .filter(|name| !self.sources.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in missing {
+            if let Some(loaded) = loader.get(Path::new(&name)) {
+                let text = loaded.content.clone();
+                self.sources.insert(name, text);
+            }
+        }
+        self
+    }
+
+    /// Get a line of a source file, if its text is known.
+    pub fn source_line(&self, source: &str, line: usize) -> Option<&str> {
+        self.sources.get(source)?.lines().nth(line.checked_sub(1)?)
+    }
+
+    /// Build the overlay text: the message, code snippets, notes and hints.
+    fn diagnostic_lines(&self, accent: Color) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line<'static>> = self
+            .message
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        lines.push(Line::empty());
+
+        let gutter = Gutter::new(self.line.into_iter().chain(self.related.iter().map(|s| s.line)));
+        let mut snippets = false;
+        if let Some(source) = &self.source {
+            let location = match (self.line, self.column) {
+                (Some(line), Some(column)) => format!("{}:{}:{}", source, line, column),
+                (Some(line), None) => format!("{}:{}", source, line),
+                _ => source.clone(),
+            };
+            lines.push(gutter.location("-->", location));
+            if let Some(line) = self.line {
+                let marks = self.column.map(|column| Marks {
+                    column,
+                    width: None,
+                    symbol: '^',
+                    style: Style::default().fg(accent).add_modifier(Modifier::BOLD),
+                    label: None,
+                });
+                snippets |= self.push_snippet(&mut lines, &gutter, source, line, marks);
+            }
+        }
+        for span in &self.related {
+            let location = format!("{}:{}:{}", span.source, span.line, span.column);
+            lines.push(gutter.location(":::", location));
+            let marks = Marks {
+                column: span.column,
+                width: span.width,
+                symbol: '-',
+                style: Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                label: span.label.clone(),
+            };
+            let source = &span.source;
+            snippets |= self.push_snippet(&mut lines, &gutter, source, span.line, Some(marks));
+        }
+        if snippets {
+            lines.push(gutter.line(None, Vec::new()));
+        }
+        for note in &self.notes {
+            lines.push(Line::from_spans(vec![
+                gutter.span(format!("{} = ", gutter.pad())),
+                Span::styled("note: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(note.clone()),
+            ]));
+        }
+        if self.source.is_some() || !self.related.is_empty() || !self.notes.is_empty() {
+            lines.push(Line::empty());
+        }
+
+        // Hints
+        if !self.hints.is_empty() {
+            lines.push(Line::from("Hints:"));
+            for hint in &self.hints {
+                lines.push(Line::from(format!("  * {}", hint)));
+            }
+            lines.push(Line::empty());
+        }
+
+        lines.pop();
+        lines
+    }
+
+    /// Add a numbered source line, underlined with `marks`. Returns `false`
+    /// if the line's text is not known.
+    fn push_snippet(
+        &self,
+        lines: &mut Vec<Line<'static>>,
+        gutter: &Gutter,
+        source: &str,
+        line: usize,
+        marks: Option<Marks>,
+    ) -> bool {
+        let Some(text) = self.source_line(source, line) else {
+            return false;
+        };
+
+        lines.push(gutter.line(None, Vec::new()));
+        lines.push(gutter.line(Some(line), vec![Span::raw(expand_tabs(text))]));
+        if let Some(marks) = marks {
+            lines.push(gutter.line(None, marks.spans(text)));
+        }
+        true
+    }
+}
+
+impl SourceSpan {
+    /// Create a span at a 1-based line and column.
+    pub fn new(source: impl Into<String>, line: usize, column: usize) -> Self {
+        Self {
+            source: source.into(),
+            line,
+            column,
+            width: None,
+            label: None,
+        }
+    }
+
+    /// Set the number of columns to underline.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set the text shown after the underline.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+/// The line number column to the left of code snippets.
+struct Gutter {
+    /// Width of the widest line number.
+    width: usize,
+}
+
+impl Gutter {
+    fn new(line_numbers: impl Iterator<Item = usize>) -> Self {
+        let width = line_numbers.max().map_or(0, |n| n.to_string().len());
+        Self { width }
+    }
+
+    fn pad(&self) -> String {
+        " ".repeat(self.width)
+    }
+
+    fn span(&self, text: String) -> Span<'static> {
+        Span::styled(text, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD))
+    }
+
+    /// A `--> file:line:column` line.
+    fn location(&self, arrow: &str, location: String) -> Line<'static> {
+        Line::from_spans(vec![
+            self.span(format!("{}{} ", self.pad(), arrow)),
+            Span::raw(location),
+        ])
+    }
+
+    /// A line with an optional line number in the gutter.
+    fn line(&self, number: Option<usize>, content: Vec<Span<'static>>) -> Line<'static> {
+        let number = match number {
+            Some(number) => format!("{:>width$} | ", number, width = self.width),
+            None => format!("{} | ", self.pad()),
+        };
+        let mut spans = vec![self.span(number)];
+        spans.extend(content);
+        Line::from_spans(spans)
+    }
+}
+
+/// An underline below a code snippet.
+struct Marks {
+    column: usize,
+    width: Option<usize>,
+    symbol: char,
+    style: Style,
+    label: Option<String>,
+}
+
+impl Marks {
+    /// The underline for `text`, lined up with its tab-expanded form.
+    fn spans(self, text: &str) -> Vec<Span<'static>> {
+        let chars: Vec<char> = text.chars().collect();
+        let start = self.column.saturating_sub(1).min(chars.len());
+        let width = self.width.unwrap_or_else(|| {
+            chars[start..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric() || **c == '_')
+                .count()
+        });
+        let indent: usize = chars[..start].iter().map(|c| char_width(*c)).sum();
+
+        let mut marks = " ".repeat(indent);
+        marks.extend(std::iter::repeat_n(self.symbol, width.max(1)));
+        if let Some(label) = self.label {
+            marks.push(' ');
+            marks.push_str(&label);
+        }
+        vec![Span::styled(marks, self.style)]
+    }
+}
+
+fn char_width(c: char) -> usize {
+    if c == '\t' {
+        TAB_WIDTH
+    } else {
+        1
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
 }
 
 impl ErrorSeverity {
@@ -333,6 +928,7 @@ impl ErrorSeverity {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::KeyModifiers;
 
     #[test]
     fn test_error_message_new() {
@@ -404,4 +1000,176 @@ mod tests {
 
         assert!(!overlay.is_visible());
     }
+
+    #[test]
+    fn test_error_message_from_script_error() {
+        let err = EngineError::Script(
+            crate::error::ScriptError::syntax("expected `)`", 3, 7).with_path("dash.fsx"),
+        );
+        let msg = ErrorMessage::from_engine_error(&err);
+
+        assert_eq!(msg.title, "Syntax Error");
+        assert_eq!(msg.message, "expected `)`");
+        assert_eq!(msg.source.as_deref(), Some("dash.fsx"));
+        assert_eq!(msg.line, Some(3));
+        assert_eq!(msg.column, Some(7));
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::none(),
+        }
+    }
+
+    /// The rows of a buffer, with trailing spaces removed.
+    fn rows(buffer: &Buffer) -> Vec<String> {
+        let area = buffer.area;
+        (area.y..area.bottom())
+            .map(|y| {
+                let row: String = (area.x..area.right())
+                    .map(|x| buffer.get(x, y).unwrap().symbol.as_str())
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_error_overlay_snippet() {
+        let error = ErrorMessage::new("Script Error", "unknown function `twice`")
+            .with_source("main.fsx")
+            .with_line(10)
+            .with_column(9)
+            .with_source_text("main.fsx", "#load \"lib.fsx\"\n\n\n\n\n\n\n\n\nlet x\t= twice 2")
+            .with_related(
+                SourceSpan::new("main.fsx", 1, 1)
+                    .with_width(5)
+                    .with_label("loaded here"),
+            )
+            .with_note("lib.fsx defines double");
+
+        let lines: Vec<String> = error
+            .diagnostic_lines(Color::Red)
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "unknown function `twice`",
+                "",
+                "  --> main.fsx:10:9",
+                "   | ",
+                "10 | let x    = twice 2",
+                "   |            ^^^^^",
+                "  ::: main.fsx:1:1",
+                "   | ",
+                " 1 | #load \"lib.fsx\"",
+                "   | ----- loaded here",
+                "   | ",
+                "   = note: lib.fsx defines double",
+            ]
+        );
+
+        let area = Rect::new(0, 0, 50, 20);
+        let mut buffer = Buffer::new(area);
+        ErrorOverlay::new(error).render(area, &mut buffer);
+        let rows = rows(&buffer);
+        assert!(rows.iter().any(|r| r.contains("│10 | let x    = twice 2")));
+    }
+
+    #[test]
+    fn test_error_overlay_without_source_text() {
+        let error = ErrorMessage::new("Error", "Message")
+            .with_source("gone.fsx")
+            .with_line(3)
+            .with_hint("Hint");
+        let lines = error.diagnostic_lines(Color::Red);
+        assert_eq!(lines.len(), 6);
+        assert_eq!(error.source_line("gone.fsx", 3), None);
+    }
+
+    #[test]
+    fn test_error_overlay_errors() {
+        let first = ErrorMessage::new("First", "Message");
+        let second = ErrorMessage::new("Second", "Message");
+        let mut overlay = ErrorOverlay::new(first.clone());
+        assert!(overlay.push(second));
+        assert!(!overlay.push(first));
+        assert_eq!(overlay.errors().len(), 2);
+
+        let right = key(KeyCode::Right);
+        let left = key(KeyCode::Left);
+        assert!(overlay.handle_key(&right));
+        assert_eq!(overlay.error().title, "Second");
+        assert!(overlay.handle_key(&right));
+        assert_eq!(overlay.selected(), 0);
+        assert!(overlay.handle_key(&left));
+        assert_eq!(overlay.selected(), 1);
+        assert!(!overlay.handle_key(&key(KeyCode::Enter)));
+
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buffer = Buffer::new(area);
+        overlay.render(area, &mut buffer);
+        assert!(rows(&buffer)[2].contains("ERROR 2/2: Second"));
+    }
+
+    #[test]
+    fn test_error_overlay_scroll() {
+        let message: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        let mut overlay = ErrorOverlay::new(ErrorMessage::new("Long", message.join("\n")));
+
+        // 10 rows: 6 for the panel, 4 inside the border, 3 above the footer
+        let area = Rect::new(0, 0, 100, 10);
+        let mut buffer = Buffer::new(area);
+        overlay.render(area, &mut buffer);
+        overlay.scroll_down(100);
+        assert_eq!(overlay.scroll(), 17);
+
+        overlay.render(area, &mut buffer);
+        let rows = rows(&buffer);
+        assert!(rows[3].contains("line 18"));
+        assert!(rows[6].contains("Up/Down to scroll"));
+
+        overlay.handle_key(&key(KeyCode::PageUp));
+        assert_eq!(overlay.scroll(), 7);
+    }
+
+    #[test]
+    fn test_error_overlay_footer_keys() {
+        let mut overlay = ErrorOverlay::new(ErrorMessage::new("Test", "Message"));
+        let area = Rect::new(0, 0, 100, 10);
+        let footer = |overlay: &ErrorOverlay| {
+            let mut buffer = Buffer::new(area);
+            overlay.render(area, &mut buffer);
+            rows(&buffer).into_iter().find(|row| row.contains("Press")).unwrap_or_default()
+        };
+        assert_eq!(footer(&overlay), "");
+
+        let mut keymap = Keymap::with_defaults();
+        keymap.bind("ctrl+r", Action::None).unwrap();
+        keymap.bind("F5", Action::Reload).unwrap();
+        overlay.set_keys(&keymap, &DashboardState::new());
+        assert!(footer(&overlay).contains("Press ctrl+d to dismiss, f5 to reload"));
+
+        keymap.bind("ctrl+d", Action::None).unwrap();
+        overlay.set_keys(&keymap, &DashboardState::new());
+        assert!(footer(&overlay).contains("Press f5 to reload"));
+    }
+
+    #[test]
+    fn test_help_overlay_from_keymap() {
+        let mut keymap = Keymap::with_defaults();
+        keymap.bind("ctrl+r", Action::None).unwrap();
+        let overlay = HelpOverlay::from_keymap(&keymap, &DashboardState::new());
+
+        assert_eq!(overlay.entries().len(), 9);
+        assert!(overlay.entries().contains(&("ctrl+c".to_string(), "Quit".to_string())));
+        assert_eq!(overlay.close_keys.as_deref(), Some("f1"));
+
+        let area = Rect::new(0, 0, 60, 20);
+        let mut buffer = Buffer::new(area);
+        overlay.render(area, &mut buffer);
+    }
 }
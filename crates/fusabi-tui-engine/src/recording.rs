@@ -0,0 +1,455 @@
+//! Recording and replay of dashboard sessions.
+//!
+//! A [`Recording`] holds every [`Event`] fed into
+//! [`DashboardEngine::handle_event`], with the time it arrived and optionally
+//! the screen after the dashboard handled it. Start one with
+//! [`DashboardEngine::start_recording`].
+//!
+//! [`DashboardEngine::replay`] feeds the events of a recording into a
+//! `DashboardEngine<TestRenderer>` and compares the screens, which turns a
+//! recorded session into an exact reproduction of a bug report or a
+//! regression test for an interactive flow.
+//!
+//! With the `serde` feature, recordings are written to and read from TOML
+//! files. The format is versioned by [`RECORDING_VERSION`].
+//!
+//! [`DashboardEngine::handle_event`]: crate::dashboard::DashboardEngine::handle_event
+//! [`DashboardEngine::start_recording`]: crate::dashboard::DashboardEngine::start_recording
+//! [`DashboardEngine::replay`]: crate::dashboard::DashboardEngine::replay
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_core::buffer::Buffer;
+//! use fusabi_tui_core::layout::Rect;
+//! use fusabi_tui_core::style::Style;
+//! use fusabi_tui_engine::dashboard::DashboardEngine;
+//! use fusabi_tui_engine::event::Event;
+//! use fusabi_tui_render::test::TestRenderer;
+//! use std::path::PathBuf;
+//!
+//! fn dashboard() -> DashboardEngine<TestRenderer> {
+//!     let mut engine = DashboardEngine::new(TestRenderer::new(20, 2), PathBuf::from("."));
+//!     engine.set_render_callback(|buffer: &mut Buffer, area: Rect, _state| {
+//!         buffer.set_string(0, 0, &format!("{}x{}", area.width, area.height), Style::default());
+//!     });
+//!     engine
+//! }
+//!
+//! // Record a session, with the screen after every event
+//! let mut engine = dashboard();
+//! engine.start_recording(true).unwrap();
+//! engine.handle_event(Event::Resize(30, 4)).unwrap();
+//! engine.render().unwrap();
+//! let recording = engine.stop_recording().unwrap();
+//! assert_eq!(recording.events[0].frame.as_ref().unwrap()[0].trim_end(), "30x4");
+//!
+//! // Replay it on a fresh engine
+//! let mismatches = dashboard().replay(&recording).unwrap();
+//! assert!(mismatches.is_empty());
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use fusabi_tui_core::buffer::Buffer;
+use fusabi_tui_core::layout::Rect;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RecordingError, RecordingResult};
+use crate::event::Event;
+
+/// Version of the recording format written by this version of the engine.
+pub const RECORDING_VERSION: u32 = 1;
+
+/// A recorded dashboard session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Recording {
+    /// Format version, [`RECORDING_VERSION`] for new recordings.
+    pub version: u32,
+
+    /// Screen width when the recording started.
+    pub width: u16,
+
+    /// Screen height when the recording started.
+    pub height: u16,
+
+    /// The events, oldest first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub events: Vec<RecordedEvent>,
+}
+
+/// An event in a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecordedEvent {
+    /// Time since the recording started.
+    #[cfg_attr(feature = "serde", serde(rename = "at_ms", with = "millis"))]
+    pub at: Duration,
+
+    /// The event.
+    pub event: Event,
+
+    /// Text of the screen rows after the event was handled and the dashboard
+    /// rendered, if frames are recorded.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub frame: Option<Vec<String>>,
+}
+
+impl Recording {
+    /// Create an empty recording for a screen of the given size.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            version: RECORDING_VERSION,
+            width,
+            height,
+            events: Vec::new(),
+        }
+    }
+
+    /// Check that this version of the engine can replay the recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecordingError::UnsupportedVersion`] if the recording was
+    /// made with a newer format.
+    pub fn check_version(&self) -> RecordingResult<()> {
+        if self.version > RECORDING_VERSION {
+            return Err(RecordingError::UnsupportedVersion {
+                found: self.version,
+                supported: RECORDING_VERSION,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Collects a [`Recording`] while a session runs.
+///
+/// The engine keeps one between
+/// [`start_recording`](crate::dashboard::DashboardEngine::start_recording)
+/// and [`stop_recording`](crate::dashboard::DashboardEngine::stop_recording).
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    recording: Recording,
+    started: Instant,
+    frames: bool,
+}
+
+impl Recorder {
+    /// Start recording a session on a screen of the given size.
+    ///
+    /// With `frames`, the screen after each event is recorded as well.
+    pub fn new(area: Rect, frames: bool) -> Self {
+        Self {
+            recording: Recording::new(area.width, area.height),
+            started: Instant::now(),
+            frames,
+        }
+    }
+
+    /// Check if the screen after each event is recorded.
+    pub fn records_frames(&self) -> bool {
+        self.frames
+    }
+
+    /// Record an event.
+    pub fn record(&mut self, event: &Event) {
+        self.recording.events.push(RecordedEvent {
+            at: self.started.elapsed(),
+            event: event.clone(),
+            frame: None,
+        });
+    }
+
+    /// Record a rendered frame as the screen after the last event.
+    ///
+    /// Does nothing if frames are not recorded or no event was recorded
+    /// yet. A later frame for the same event replaces the earlier one.
+    pub fn record_frame(&mut self, buffer: &Buffer) {
+        if !self.frames {
+            return;
+        }
+        if let Some(last) = self.recording.events.last_mut() {
+            last.frame = Some(frame_rows(buffer));
+        }
+    }
+
+    /// Get the recording so far.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Stop recording and get the recording.
+    pub fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+/// A screen that differs from the recorded one during a replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameMismatch {
+    /// Index of the event after which the screens differ.
+    pub index: usize,
+
+    /// The recorded screen rows.
+    pub expected: Vec<String>,
+
+    /// The screen rows during the replay.
+    pub actual: Vec<String>,
+}
+
+impl fmt::Display for FrameMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "screen after event {} differs:", self.index)?;
+        let rows = self.expected.len().max(self.actual.len());
+        for row in 0..rows {
+            let expected = self.expected.get(row).map(String::as_str);
+            let actual = self.actual.get(row).map(String::as_str);
+            if expected != actual {
+                writeln!(f, "{:>4} - {}", row, expected.unwrap_or(""))?;
+                writeln!(f, "{:>4} + {}", row, actual.unwrap_or(""))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Get the text of each row of a buffer, as recorded in frames.
+pub(crate) fn frame_rows(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .filter_map(|x| buffer.get(x, y))
+                .map(|cell| cell.symbol.as_str())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(at: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(at.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use std::path::Path;
+
+    use super::Recording;
+    use crate::error::{RecordingError, RecordingResult};
+
+    impl Recording {
+        /// Parse a recording from a TOML string.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the TOML is not a valid recording, or if it
+        /// was made with a newer format.
+        pub fn from_toml(toml_str: &str) -> RecordingResult<Self> {
+            let recording: Self = toml::from_str(toml_str)
+                .map_err(|e| RecordingError::ParseFailed(e.to_string()))?;
+            recording.check_version()?;
+            Ok(recording)
+        }
+
+        /// Serialize the recording to a TOML string.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the recording cannot be serialized.
+        pub fn to_toml(&self) -> RecordingResult<String> {
+            toml::to_string_pretty(self).map_err(|e| RecordingError::SerializeFailed(e.to_string()))
+        }
+
+        /// Read a recording from a TOML file.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the file cannot be read or parsed.
+        pub fn load(path: &Path) -> RecordingResult<Self> {
+            let content =
+                std::fs::read_to_string(path).map_err(|source| RecordingError::ReadFailed {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+            Self::from_toml(&content)
+        }
+
+        /// Write the recording to a TOML file, creating parent directories.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the recording cannot be serialized or the file
+        /// cannot be written.
+        pub fn save(&self, path: &Path) -> RecordingResult<()> {
+            let content = self.to_toml()?;
+            let write = || -> std::io::Result<()> {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, content)
+            };
+            write().map_err(|source| RecordingError::WriteFailed {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusabi_tui_core::style::Style;
+
+    #[test]
+    fn test_recorder_attaches_frames_to_last_event() {
+        let area = Rect::new(0, 0, 4, 2);
+        let mut buffer = Buffer::new(area);
+        let mut recorder = Recorder::new(area, true);
+
+        // Frames before the first event are not recorded
+        recorder.record_frame(&buffer);
+        recorder.record(&Event::Tick);
+        buffer.set_string(0, 0, "ab", Style::default());
+        recorder.record_frame(&buffer);
+        recorder.record(&Event::Custom("refresh".to_string()));
+
+        let recording = recorder.finish();
+        assert_eq!((recording.width, recording.height), (4, 2));
+        assert_eq!(recording.events.len(), 2);
+        assert_eq!(
+            recording.events[0].frame,
+            Some(vec!["ab  ".to_string(), "    ".to_string()])
+        );
+        assert_eq!(recording.events[1].frame, None);
+        assert!(recording.events[0].at <= recording.events[1].at);
+    }
+
+    #[test]
+    fn test_recorder_without_frames() {
+        let area = Rect::new(0, 0, 4, 2);
+        let mut recorder = Recorder::new(area, false);
+        recorder.record(&Event::Tick);
+        recorder.record_frame(&Buffer::new(area));
+        assert_eq!(recorder.recording().events[0].frame, None);
+    }
+
+    #[test]
+    fn test_frame_mismatch_display() {
+        let mismatch = FrameMismatch {
+            index: 3,
+            expected: vec!["same".to_string(), "old".to_string()],
+            actual: vec!["same".to_string(), "new".to_string()],
+        };
+        assert_eq!(
+            mismatch.to_string(),
+            "screen after event 3 differs:\n   1 - old\n   1 + new\n"
+        );
+    }
+
+    #[test]
+    fn test_check_version() {
+        let mut recording = Recording::new(80, 24);
+        assert!(recording.check_version().is_ok());
+        recording.version = RECORDING_VERSION + 1;
+        assert!(matches!(
+            recording.check_version(),
+            Err(RecordingError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_toml_round_trip() {
+        use crate::event::{
+            KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+        };
+        use crate::watcher::{FileChange, FileChangeKind};
+
+        let at = |ms| Duration::from_millis(ms);
+        let mut recording = Recording::new(80, 24);
+        recording.events = vec![
+            RecordedEvent {
+                at: at(0),
+                event: Event::Key(KeyEvent {
+                    code: KeyCode::Char('r'),
+                    modifiers: KeyModifiers::ctrl(),
+                }),
+                frame: Some(vec!["hello".to_string(), "  world".to_string()]),
+            },
+            RecordedEvent {
+                at: at(120),
+                event: Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    x: 3,
+                    y: 4,
+                    modifiers: KeyModifiers::none(),
+                }),
+                frame: None,
+            },
+            RecordedEvent {
+                at: at(250),
+                event: Event::Resize(100, 30),
+                frame: None,
+            },
+            RecordedEvent {
+                at: at(1000),
+                event: Event::Tick,
+                frame: None,
+            },
+            RecordedEvent {
+                at: at(1500),
+                event: Event::FileChange(FileChange::new(
+                    "main.fsx",
+                    FileChangeKind::Renamed {
+                        from: "old.fsx".into(),
+                    },
+                )),
+                frame: None,
+            },
+            RecordedEvent {
+                at: at(2000),
+                event: Event::Custom("refresh".to_string()),
+                frame: None,
+            },
+        ];
+
+        let toml = recording.to_toml().unwrap();
+        assert!(toml.contains("version = 1"));
+        assert!(toml.contains("at_ms = 120"));
+        assert_eq!(Recording::from_toml(&toml).unwrap(), recording);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions/bug.toml");
+        recording.save(&path).unwrap();
+        assert_eq!(Recording::load(&path).unwrap(), recording);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_toml_rejects_newer_version() {
+        let toml = "version = 99\nwidth = 80\nheight = 24\n";
+        assert!(matches!(
+            Recording::from_toml(toml),
+            Err(RecordingError::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+}
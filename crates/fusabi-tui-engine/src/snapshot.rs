@@ -0,0 +1,380 @@
+//! Snapshots of the dashboard state that survive reloads and restarts.
+//!
+//! A [`StateSnapshot`] holds the parts of a [`DashboardState`] that describe
+//! where the user is: list and table selections, scroll offsets, the selected
+//! tab, scrollbar positions, the focused widget, the input mode and the
+//! custom values set by the script. Per-frame data such as the focus ring and
+//! hit map, and [`WidgetState::Custom`] states, are not included.
+//!
+//! With the `serde` feature, snapshots can be written to and read from TOML
+//! files, which is how [`DashboardEngine::set_state_file`] keeps the state
+//! across restarts.
+//!
+//! [`DashboardEngine::set_state_file`]: crate::dashboard::DashboardEngine::set_state_file
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_engine::state::{DashboardState, ListState, WidgetState};
+//!
+//! let mut state = DashboardState::new();
+//! state.insert_widget("files", WidgetState::List(ListState::with_selected(3)));
+//! state.set_focus("files");
+//! let snapshot = state.snapshot();
+//!
+//! // A new state, for example after a restart
+//! let mut restored = DashboardState::new();
+//! restored.insert_widget("files", WidgetState::List(ListState::new()));
+//! restored.restore(&snapshot);
+//!
+//! assert!(restored.is_focused("files"));
+//! match restored.get_widget("files") {
+//!     Some(WidgetState::List(list)) => assert_eq!(list.selected(), Some(3)),
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::{DashboardState, StateValue, WidgetState};
+
+/// The persistent part of a [`DashboardState`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StateSnapshot {
+    /// The focused widget.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub focus: Option<String>,
+
+    /// The input mode.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mode: Option<String>,
+
+    /// Widget states keyed by widget ID.
+    pub widgets: BTreeMap<String, WidgetSnapshot>,
+
+    /// Custom values set by the script or the application.
+    pub custom: BTreeMap<String, StateValue>,
+}
+
+/// The persistent part of a [`WidgetState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum WidgetSnapshot {
+    /// Selection and scroll offset of a list.
+    List {
+        /// Index of the selected item.
+        selected: Option<usize>,
+
+        /// Index of the first visible item.
+        offset: usize,
+    },
+
+    /// Selection and scroll offset of a table.
+    Table {
+        /// Index of the selected row.
+        selected: Option<usize>,
+
+        /// Index of the selected column.
+        selected_column: Option<usize>,
+
+        /// Index of the first visible row.
+        offset: usize,
+    },
+
+    /// Selected tab.
+    Tabs {
+        /// Index of the selected tab.
+        selected: usize,
+    },
+
+    /// Scrollbar position.
+    Scrollbar {
+        /// Scroll position.
+        position: usize,
+    },
+}
+
+impl WidgetSnapshot {
+    /// Capture the persistent part of a widget state.
+    ///
+    /// Returns `None` for [`WidgetState::Custom`].
+    pub fn capture(widget: &WidgetState) -> Option<Self> {
+        Some(match widget {
+            WidgetState::List(list) => WidgetSnapshot::List {
+                selected: list.selected(),
+                offset: list.offset(),
+            },
+            WidgetState::Table(table) => WidgetSnapshot::Table {
+                selected: table.selected(),
+                selected_column: table.selected_column(),
+                offset: table.offset(),
+            },
+            WidgetState::Tabs(tabs) => WidgetSnapshot::Tabs {
+                selected: tabs.selected(),
+            },
+            WidgetState::Scrollbar(scrollbar) => WidgetSnapshot::Scrollbar {
+                position: scrollbar.get_position(),
+            },
+            WidgetState::Custom(_) => return None,
+        })
+    }
+
+    /// Apply the snapshot to a widget state of the same kind.
+    ///
+    /// Returns `false` and leaves the state alone if the kinds differ.
+    pub fn apply(&self, widget: &mut WidgetState) -> bool {
+        match (*self, widget) {
+            (WidgetSnapshot::List { selected, offset }, WidgetState::List(list)) => {
+                list.select(selected);
+                list.set_offset(offset);
+            }
+            (
+                WidgetSnapshot::Table {
+                    selected,
+                    selected_column,
+                    offset,
+                },
+                WidgetState::Table(table),
+            ) => {
+                table.select(selected);
+                table.select_column(selected_column);
+                table.set_offset(offset);
+            }
+            (WidgetSnapshot::Tabs { selected }, WidgetState::Tabs(tabs)) => tabs.select(selected),
+            (WidgetSnapshot::Scrollbar { position }, WidgetState::Scrollbar(scrollbar)) => {
+                scrollbar.set_position(position);
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl StateSnapshot {
+    /// Capture the persistent part of a dashboard state.
+    pub fn capture(state: &DashboardState) -> Self {
+        Self {
+            focus: state.focus.clone(),
+            mode: state.mode.clone(),
+            widgets: state
+                .widgets
+                .iter()
+                .filter_map(|(id, widget)| {
+                    WidgetSnapshot::capture(widget).map(|snapshot| (id.clone(), snapshot))
+                })
+                .collect(),
+            custom: state.custom.clone(),
+        }
+    }
+
+    /// Apply the snapshot to a dashboard state.
+    ///
+    /// Widgets are matched by ID. Snapshots of widgets that no longer exist,
+    /// or that now have a different kind, are skipped, and widgets without a
+    /// snapshot keep their state. The focus is restored unless the last frame
+    /// had focusable widgets and the focused one was not among them. Custom
+    /// values are merged into the existing ones.
+    pub fn restore(&self, state: &mut DashboardState) {
+        for (id, snapshot) in &self.widgets {
+            if let Some(widget) = state.widgets.get_mut(id) {
+                snapshot.apply(widget);
+            }
+        }
+
+        if let Some(focus) = &self.focus {
+            if state.focus_ring.is_empty() || state.focus_ring.contains(focus) {
+                state.focus = Some(focus.clone());
+            }
+        }
+        state.mode.clone_from(&self.mode);
+        state
+            .custom
+            .extend(self.custom.iter().map(|(k, v)| (k.clone(), v.clone())));
+        state.mark_dirty();
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use std::path::Path;
+
+    use super::StateSnapshot;
+    use crate::error::{SnapshotError, SnapshotResult};
+
+    impl StateSnapshot {
+        /// Parse a snapshot from a TOML string.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the TOML is not a valid snapshot.
+        pub fn from_toml(toml_str: &str) -> SnapshotResult<Self> {
+            toml::from_str(toml_str).map_err(|e| SnapshotError::ParseFailed(e.to_string()))
+        }
+
+        /// Serialize the snapshot to a TOML string.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the snapshot cannot be serialized.
+        pub fn to_toml(&self) -> SnapshotResult<String> {
+            toml::to_string_pretty(self).map_err(|e| SnapshotError::SerializeFailed(e.to_string()))
+        }
+
+        /// Read a snapshot from a TOML file.
+        ///
+        /// Returns `Ok(None)` if the file does not exist.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the file cannot be read or parsed.
+        pub fn load(path: &Path) -> SnapshotResult<Option<Self>> {
+            match std::fs::read_to_string(path) {
+                Ok(content) => Self::from_toml(&content).map(Some),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(source) => Err(SnapshotError::ReadFailed {
+                    path: path.to_path_buf(),
+                    source,
+                }),
+            }
+        }
+
+        /// Write the snapshot to a TOML file, creating parent directories.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the snapshot cannot be serialized or the file
+        /// cannot be written.
+        pub fn save(&self, path: &Path) -> SnapshotResult<()> {
+            let content = self.to_toml()?;
+            let write = || -> std::io::Result<()> {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, content)
+            };
+            write().map_err(|source| SnapshotError::WriteFailed {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ListState, TableState, TabsState};
+    use fusabi_tui_core::layout::Rect;
+    use fusabi_tui_widgets::scrollbar::ScrollbarState;
+
+    fn sample_state() -> DashboardState {
+        let mut state = DashboardState::new();
+        state.insert_widget("files", WidgetState::List(ListState::with_selected(4)));
+        let mut table = TableState::with_selected(2);
+        table.select_column(Some(1));
+        table.set_offset(1);
+        state.insert_widget("procs", WidgetState::Table(table));
+        state.insert_widget("tabs", WidgetState::Tabs(TabsState { selected: 2 }));
+        state.insert_widget(
+            "bar",
+            WidgetState::Scrollbar(ScrollbarState::new(100).position(40)),
+        );
+        state.insert_widget("chart", WidgetState::Custom(Box::new(7_u32)));
+        state.set_focus("procs");
+        state.set_mode("insert");
+        state.set_value("filter", "err");
+        state
+    }
+
+    #[test]
+    fn test_capture_skips_custom_widgets() {
+        let snapshot = sample_state().snapshot();
+        assert_eq!(snapshot.widgets.len(), 4);
+        assert_eq!(
+            snapshot.widgets["procs"],
+            WidgetSnapshot::Table {
+                selected: Some(2),
+                selected_column: Some(1),
+                offset: 1
+            }
+        );
+        assert_eq!(snapshot.focus.as_deref(), Some("procs"));
+        assert_eq!(snapshot.custom["filter"], StateValue::from("err"));
+    }
+
+    #[test]
+    fn test_restore_tolerates_missing_widgets() {
+        let snapshot = sample_state().snapshot();
+
+        // `procs` is gone and `tabs` is now a list
+        let mut state = DashboardState::new();
+        state.insert_widget("files", WidgetState::List(ListState::new()));
+        state.insert_widget("tabs", WidgetState::List(ListState::with_selected(0)));
+        state.focus_ring.register("files", Rect::new(0, 0, 10, 10));
+        state.restore(&snapshot);
+
+        match state.get_widget("files") {
+            Some(WidgetState::List(list)) => assert_eq!(list.selected(), Some(4)),
+            _ => unreachable!(),
+        }
+        match state.get_widget("tabs") {
+            Some(WidgetState::List(list)) => assert_eq!(list.selected(), Some(0)),
+            _ => unreachable!(),
+        }
+        assert!(state.get_widget("procs").is_none());
+        // `procs` was not rendered in the last frame
+        assert_eq!(state.focused_widget(), None);
+        assert_eq!(state.mode(), Some("insert"));
+        assert_eq!(state.value("filter"), Some(&StateValue::from("err")));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_toml_round_trip() {
+        let snapshot = sample_state().snapshot();
+        let toml = snapshot.to_toml().unwrap();
+        assert_eq!(StateSnapshot::from_toml(&toml).unwrap(), snapshot);
+
+        let snapshot = StateSnapshot::from_toml(
+            r#"
+            focus = "files"
+
+            [widgets.files]
+            kind = "list"
+            selected = 2
+            offset = 0
+
+            [custom]
+            count = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            snapshot.widgets["files"],
+            WidgetSnapshot::List {
+                selected: Some(2),
+                offset: 0
+            }
+        );
+        assert_eq!(snapshot.custom["count"], StateValue::Int(3));
+        assert!(StateSnapshot::from_toml("focus = 1").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("dashboard.toml");
+        assert_eq!(StateSnapshot::load(&path).unwrap(), None);
+
+        let snapshot = sample_state().snapshot();
+        snapshot.save(&path).unwrap();
+        assert_eq!(StateSnapshot::load(&path).unwrap(), Some(snapshot));
+    }
+}
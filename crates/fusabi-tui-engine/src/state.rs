@@ -1,7 +1,18 @@
 //! Dashboard state management for the Fusabi TUI engine.
 
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use fusabi_tui_widgets::scrollbar::ScrollbarState;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use fusabi_tui_widgets::list::ListState;
+pub use fusabi_tui_widgets::table::TableState;
+
+use crate::focus::{FocusDirection, FocusRing};
+use crate::hit::{HitMap, HitPart};
+use crate::snapshot::StateSnapshot;
 
 /// Represents the state of the entire dashboard.
 #[derive(Debug)]
@@ -12,6 +23,18 @@ pub struct DashboardState {
     /// The currently focused widget ID, if any.
     pub focus: Option<String>,
 
+    /// Focusable widgets of the last frame, used for focus traversal.
+    pub focus_ring: FocusRing,
+
+    /// Widget areas of the last frame, used for mouse hit-testing.
+    pub hit_map: HitMap,
+
+    /// The current input mode, if any. Key bindings can be scoped to a mode.
+    pub mode: Option<String>,
+
+    /// Custom values set by the script or the application, keyed by name.
+    pub custom: BTreeMap<String, StateValue>,
+
     /// Flag indicating if the dashboard needs re-rendering.
     pub dirty: bool,
 }
@@ -22,6 +45,10 @@ impl DashboardState {
         Self {
             widgets: HashMap::new(),
             focus: None,
+            focus_ring: FocusRing::new(),
+            hit_map: HitMap::new(),
+            mode: None,
+            custom: BTreeMap::new(),
             dirty: false,
         }
     }
@@ -58,6 +85,102 @@ impl DashboardState {
         self.focus.as_deref() == Some(widget_id)
     }
 
+    /// Get the state of the focused widget.
+    pub fn focused_widget_mut(&mut self) -> Option<&mut WidgetState> {
+        let id = self.focus.as_deref()?;
+        self.widgets.get_mut(id)
+    }
+
+    /// Move focus to the next widget in the focus ring, wrapping around.
+    ///
+    /// Returns `false` if the focus ring is empty.
+    pub fn focus_next(&mut self) -> bool {
+        let next = self.focus_ring.next(self.focused_widget());
+        self.move_focus(next)
+    }
+
+    /// Move focus to the previous widget in the focus ring, wrapping around.
+    ///
+    /// Returns `false` if the focus ring is empty.
+    pub fn focus_previous(&mut self) -> bool {
+        let previous = self.focus_ring.previous(self.focused_widget());
+        self.move_focus(previous)
+    }
+
+    /// Move focus to the nearest widget on screen in `direction`.
+    ///
+    /// Focuses the first widget in the ring if nothing in the ring is focused.
+    /// Returns `false` if focus did not move.
+    pub fn focus_direction(&mut self, direction: FocusDirection) -> bool {
+        let target = match self.focused_widget() {
+            Some(id) if self.focus_ring.contains(id) => {
+                self.focus_ring.in_direction(id, direction)
+            }
+            _ => self.focus_ring.next(None),
+        };
+        self.move_focus(target)
+    }
+
+    fn move_focus(&mut self, target: Option<String>) -> bool {
+        match target {
+            Some(id) if !self.is_focused(&id) => {
+                self.set_focus(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set the input mode.
+    pub fn set_mode(&mut self, mode: impl Into<String>) {
+        self.mode = Some(mode.into());
+        self.mark_dirty();
+    }
+
+    /// Clear the input mode.
+    pub fn clear_mode(&mut self) {
+        self.mode = None;
+        self.mark_dirty();
+    }
+
+    /// Get the current input mode.
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
+    }
+
+    /// Set a custom value.
+    pub fn set_value(&mut self, key: impl Into<String>, value: impl Into<StateValue>) {
+        self.custom.insert(key.into(), value.into());
+        self.mark_dirty();
+    }
+
+    /// Get a custom value.
+    pub fn value(&self, key: &str) -> Option<&StateValue> {
+        self.custom.get(key)
+    }
+
+    /// Remove a custom value.
+    pub fn remove_value(&mut self, key: &str) -> Option<StateValue> {
+        let removed = self.custom.remove(key);
+        if removed.is_some() {
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    /// Capture the selections, scroll offsets, focus, mode and custom values.
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot::capture(self)
+    }
+
+    /// Restore a snapshot taken with [`snapshot`](Self::snapshot).
+    ///
+    /// See [`StateSnapshot::restore`] for how widgets that disappeared are
+    /// handled.
+    pub fn restore(&mut self, snapshot: &StateSnapshot) {
+        snapshot.restore(self);
+    }
+
     /// Insert or update a widget state.
     pub fn insert_widget(&mut self, id: impl Into<String>, state: WidgetState) {
         self.widgets.insert(id.into(), state);
@@ -87,6 +210,10 @@ impl DashboardState {
     pub fn clear(&mut self) {
         self.widgets.clear();
         self.focus = None;
+        self.focus_ring.clear();
+        self.hit_map.clear();
+        self.mode = None;
+        self.custom.clear();
         self.mark_dirty();
     }
 }
@@ -97,213 +224,225 @@ impl Default for DashboardState {
     }
 }
 
-/// Represents the state of an individual widget.
-#[derive(Debug)]
-pub enum WidgetState {
-    /// State for a List widget.
-    List(ListState),
-
-    /// State for a Table widget.
-    Table(TableState),
+/// A custom value kept in the [`DashboardState`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum StateValue {
+    /// A boolean.
+    Bool(bool),
 
-    /// State for custom widgets.
-    Custom(Box<dyn Any>),
-}
+    /// An integer.
+    Int(i64),
 
-/// State for a List widget.
-#[derive(Debug, Clone)]
-pub struct ListState {
-    /// Index of the currently selected item.
-    pub selected: Option<usize>,
+    /// A floating point number.
+    Float(f64),
 
-    /// Scroll offset for the list.
-    pub offset: usize,
+    /// A string.
+    Str(String),
 }
 
-impl ListState {
-    /// Create a new list state with no selection.
-    pub fn new() -> Self {
-        Self {
-            selected: None,
-            offset: 0,
-        }
-    }
-
-    /// Create a new list state with the first item selected.
-    pub fn with_selected(index: usize) -> Self {
-        Self {
-            selected: Some(index),
-            offset: 0,
-        }
-    }
-
-    /// Select an item by index.
-    pub fn select(&mut self, index: Option<usize>) {
-        self.selected = index;
-    }
-
-    /// Select the next item.
-    pub fn select_next(&mut self, total_items: usize) {
-        if total_items == 0 {
-            self.selected = None;
-            return;
-        }
-
-        self.selected = Some(match self.selected {
-            Some(i) => (i + 1).min(total_items - 1),
-            None => 0,
-        });
+impl From<bool> for StateValue {
+    fn from(value: bool) -> Self {
+        StateValue::Bool(value)
     }
+}
 
-    /// Select the previous item.
-    pub fn select_previous(&mut self) {
-        self.selected = Some(match self.selected {
-            Some(i) => i.saturating_sub(1),
-            None => 0,
-        });
-    }
-
-    /// Get the selected index.
-    pub fn selected(&self) -> Option<usize> {
-        self.selected
+impl From<i64> for StateValue {
+    fn from(value: i64) -> Self {
+        StateValue::Int(value)
     }
+}
 
-    /// Set the scroll offset.
-    pub fn set_offset(&mut self, offset: usize) {
-        self.offset = offset;
+impl From<f64> for StateValue {
+    fn from(value: f64) -> Self {
+        StateValue::Float(value)
     }
+}
 
-    /// Get the scroll offset.
-    pub fn offset(&self) -> usize {
-        self.offset
+impl From<&str> for StateValue {
+    fn from(value: &str) -> Self {
+        StateValue::Str(value.to_string())
     }
 }
 
-impl Default for ListState {
-    fn default() -> Self {
-        Self::new()
+impl From<String> for StateValue {
+    fn from(value: String) -> Self {
+        StateValue::Str(value)
     }
 }
 
-/// State for a Table widget.
-#[derive(Debug, Clone)]
-pub struct TableState {
-    /// Index of the currently selected row.
-    pub selected_row: Option<usize>,
+/// Represents the state of an individual widget.
+#[derive(Debug)]
+pub enum WidgetState {
+    /// State for a List widget.
+    List(ListState),
+
+    /// State for a Table widget.
+    Table(TableState),
 
-    /// Index of the currently selected column.
-    pub selected_column: Option<usize>,
+    /// State for a Tabs widget.
+    Tabs(TabsState),
 
-    /// Vertical scroll offset.
-    pub row_offset: usize,
+    /// State for a Scrollbar widget.
+    Scrollbar(ScrollbarState),
 
-    /// Horizontal scroll offset.
-    pub column_offset: usize,
+    /// State for custom widgets.
+    Custom(Box<dyn Any>),
 }
 
-impl TableState {
-    /// Create a new table state with no selection.
-    pub fn new() -> Self {
-        Self {
-            selected_row: None,
-            selected_column: None,
-            row_offset: 0,
-            column_offset: 0,
+impl WidgetState {
+    /// Apply a mouse click on `part` of the widget.
+    ///
+    /// A click on a row selects it, a click on a tab selects the tab, and a
+    /// click on a scrollbar track moves the scroll position there. Returns
+    /// `true` if the state changed.
+    pub fn click(&mut self, part: HitPart) -> bool {
+        match (self, part) {
+            (WidgetState::List(list), HitPart::Row(row)) => {
+                reselect(list.selected(), Some(row), |i| list.select(i))
+            }
+            (WidgetState::Table(table), HitPart::Row(row)) => {
+                reselect(table.selected(), Some(row), |i| table.select(i))
+            }
+            (WidgetState::Tabs(tabs), HitPart::Tab(tab)) => replace(&mut tabs.selected, tab),
+            (WidgetState::Scrollbar(scrollbar), HitPart::Scrollbar { offset, length }) => {
+                let scrollable = scrollbar
+                    .get_content_length()
+                    .saturating_sub(scrollbar.get_viewport_content_length());
+                let position = match length {
+                    0 | 1 => 0,
+                    _ => {
+                        let last = usize::from(length - 1);
+                        (usize::from(offset) * scrollable + last / 2) / last
+                    }
+                };
+                let before = scrollbar.get_position();
+                scrollbar.set_position(position);
+                before != position
+            }
+            _ => false,
         }
     }
 
-    /// Create a new table state with a cell selected.
-    pub fn with_selected(row: usize, column: usize) -> Self {
-        Self {
-            selected_row: Some(row),
-            selected_column: Some(column),
-            row_offset: 0,
-            column_offset: 0,
+    /// Scroll up by one step with the mouse wheel.
+    ///
+    /// Moves the selection of lists and tables and the position of
+    /// scrollbars, stopping at the first item rather than wrapping around.
+    /// Returns `true` if the state changed.
+    pub fn scroll_up(&mut self) -> bool {
+        match self {
+            WidgetState::List(list) => {
+                let selected = wheel_up(list.selected());
+                reselect(list.selected(), selected, |i| list.select(i))
+            }
+            WidgetState::Table(table) => {
+                let selected = wheel_up(table.selected());
+                reselect(table.selected(), selected, |i| table.select(i))
+            }
+            WidgetState::Scrollbar(scrollbar) => {
+                let before = scrollbar.get_position();
+                scrollbar.scroll_up();
+                before != scrollbar.get_position()
+            }
+            WidgetState::Tabs(_) | WidgetState::Custom(_) => false,
         }
     }
 
-    /// Select a cell by row and column.
-    pub fn select(&mut self, row: Option<usize>, column: Option<usize>) {
-        self.selected_row = row;
-        self.selected_column = column;
-    }
-
-    /// Select the next row.
-    pub fn select_next_row(&mut self, total_rows: usize) {
-        if total_rows == 0 {
-            self.selected_row = None;
-            return;
+    /// Scroll down by one step with the mouse wheel.
+    ///
+    /// `len` is the number of items in a list or table; without it their
+    /// selection does not move. The selection stops at the last item rather
+    /// than wrapping around. Returns `true` if the state changed.
+    pub fn scroll_down(&mut self, len: Option<usize>) -> bool {
+        match (self, len) {
+            (WidgetState::List(list), Some(len)) => {
+                let selected = wheel_down(list.selected(), len);
+                reselect(list.selected(), selected, |i| list.select(i))
+            }
+            (WidgetState::Table(table), Some(len)) => {
+                let selected = wheel_down(table.selected(), len);
+                reselect(table.selected(), selected, |i| table.select(i))
+            }
+            (WidgetState::Scrollbar(scrollbar), _) => {
+                let before = scrollbar.get_position();
+                scrollbar.scroll_down();
+                before != scrollbar.get_position()
+            }
+            _ => false,
         }
-
-        self.selected_row = Some(match self.selected_row {
-            Some(i) => (i + 1).min(total_rows - 1),
-            None => 0,
-        });
-    }
-
-    /// Select the previous row.
-    pub fn select_previous_row(&mut self) {
-        self.selected_row = Some(match self.selected_row {
-            Some(i) => i.saturating_sub(1),
-            None => 0,
-        });
     }
+}
 
-    /// Select the next column.
-    pub fn select_next_column(&mut self, total_columns: usize) {
-        if total_columns == 0 {
-            self.selected_column = None;
-            return;
-        }
+/// Apply a new selection with `select` and report whether it changed.
+fn reselect(
+    before: Option<usize>,
+    after: Option<usize>,
+    select: impl FnOnce(Option<usize>),
+) -> bool {
+    select(after);
+    before != after
+}
 
-        self.selected_column = Some(match self.selected_column {
-            Some(i) => (i + 1).min(total_columns - 1),
-            None => 0,
-        });
-    }
+/// The selection one wheel step up, stopping at the first item.
+fn wheel_up(selected: Option<usize>) -> Option<usize> {
+    Some(selected.map_or(0, |i| i.saturating_sub(1)))
+}
 
-    /// Select the previous column.
-    pub fn select_previous_column(&mut self) {
-        self.selected_column = Some(match self.selected_column {
-            Some(i) => i.saturating_sub(1),
-            None => 0,
-        });
+/// The selection one wheel step down among `len` items, stopping at the last.
+fn wheel_down(selected: Option<usize>, len: usize) -> Option<usize> {
+    match selected {
+        _ if len == 0 => None,
+        Some(i) => Some((i + 1).min(len - 1)),
+        None => Some(0),
     }
+}
 
-    /// Get the selected row index.
-    pub fn selected_row(&self) -> Option<usize> {
-        self.selected_row
+/// Set `slot` to `value` and report whether it changed.
+fn replace<T: PartialEq>(slot: &mut T, value: T) -> bool {
+    if *slot == value {
+        false
+    } else {
+        *slot = value;
+        true
     }
+}
 
-    /// Get the selected column index.
-    pub fn selected_column(&self) -> Option<usize> {
-        self.selected_column
-    }
+/// State for a Tabs widget.
+#[derive(Debug, Clone, Default)]
+pub struct TabsState {
+    /// Index of the selected tab.
+    pub selected: usize,
+}
 
-    /// Set the row offset.
-    pub fn set_row_offset(&mut self, offset: usize) {
-        self.row_offset = offset;
+impl TabsState {
+    /// Create a new tabs state with the first tab selected.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Set the column offset.
-    pub fn set_column_offset(&mut self, offset: usize) {
-        self.column_offset = offset;
+    /// Select a tab by index.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index;
     }
 
-    /// Get the row offset.
-    pub fn row_offset(&self) -> usize {
-        self.row_offset
+    /// Select the next tab, wrapping around.
+    pub fn select_next(&mut self, total_tabs: usize) {
+        if total_tabs > 0 {
+            self.selected = (self.selected + 1) % total_tabs;
+        }
     }
 
-    /// Get the column offset.
-    pub fn column_offset(&self) -> usize {
-        self.column_offset
+    /// Select the previous tab, wrapping around.
+    pub fn select_previous(&mut self, total_tabs: usize) {
+        if total_tabs > 0 {
+            self.selected = self.selected.checked_sub(1).unwrap_or(total_tabs - 1);
+        }
     }
-}
 
-impl Default for TableState {
-    fn default() -> Self {
-        Self::new()
+    /// Get the selected index.
+    pub fn selected(&self) -> usize {
+        self.selected
     }
 }
 
@@ -346,6 +485,31 @@ mod tests {
         assert!(state.dirty);
     }
 
+    #[test]
+    fn test_dashboard_state_focus_traversal() {
+        use fusabi_tui_core::layout::Rect;
+
+        let mut state = DashboardState::new();
+        assert!(!state.focus_next());
+
+        state.focus_ring.register("left", Rect::new(0, 0, 10, 10));
+        state.focus_ring.register("right", Rect::new(10, 0, 10, 10));
+
+        assert!(state.focus_previous());
+        assert_eq!(state.focused_widget(), Some("right"));
+        assert!(state.focus_next());
+        assert_eq!(state.focused_widget(), Some("left"));
+        assert!(!state.focus_direction(FocusDirection::Left));
+        assert!(state.focus_direction(FocusDirection::Right));
+        assert_eq!(state.focused_widget(), Some("right"));
+
+        state.insert_widget("right", WidgetState::List(ListState::new()));
+        assert!(matches!(
+            state.focused_widget_mut(),
+            Some(WidgetState::List(_))
+        ));
+    }
+
     #[test]
     fn test_dashboard_state_widgets() {
         let mut state = DashboardState::new();
@@ -361,68 +525,76 @@ mod tests {
     }
 
     #[test]
-    fn test_list_state_selection() {
-        let mut state = ListState::new();
-        assert_eq!(state.selected(), None);
-
-        state.select(Some(5));
-        assert_eq!(state.selected(), Some(5));
-
-        state.select_next(10);
-        assert_eq!(state.selected(), Some(6));
-
-        state.select_next(10);
-        assert_eq!(state.selected(), Some(7));
+    fn test_widget_state_shares_widget_state() {
+        use fusabi_tui_core::buffer::Buffer;
+        use fusabi_tui_core::layout::Rect;
+        use fusabi_tui_widgets::list::{List, ListItem};
+        use fusabi_tui_widgets::widget::StatefulWidget;
 
-        state.select_previous();
-        assert_eq!(state.selected(), Some(6));
-
-        state.select_next(7);
-        assert_eq!(state.selected(), Some(6)); // Clamped to max
+        let mut state = DashboardState::new();
+        state.insert_widget("list", WidgetState::List(ListState::with_selected(7)));
+
+        let items: Vec<_> = (0..10).map(|i| ListItem::new(format!("Item {i}"))).collect();
+        let list = List::new(items);
+        let area = Rect::new(0, 0, 10, 3);
+        let mut buffer = Buffer::new(area);
+        if let Some(WidgetState::List(list_state)) = state.get_widget_mut("list") {
+            StatefulWidget::render(&list, area, &mut buffer, list_state);
+        }
 
-        state.select_previous();
-        state.select_previous();
-        state.select_previous();
-        state.select_previous();
-        state.select_previous();
-        state.select_previous();
-        state.select_previous();
-        assert_eq!(state.selected(), Some(0)); // Saturating sub
+        // The widget scrolled the engine's state to keep the selection visible
+        match state.get_widget("list") {
+            Some(WidgetState::List(list_state)) => assert_eq!(list_state.offset(), 5),
+            _ => unreachable!(),
+        }
     }
 
     #[test]
-    fn test_table_state_selection() {
-        let mut state = TableState::new();
-        assert_eq!(state.selected_row(), None);
-        assert_eq!(state.selected_column(), None);
-
-        state.select(Some(2), Some(3));
-        assert_eq!(state.selected_row(), Some(2));
-        assert_eq!(state.selected_column(), Some(3));
-
-        state.select_next_row(10);
-        assert_eq!(state.selected_row(), Some(3));
-
-        state.select_previous_row();
-        assert_eq!(state.selected_row(), Some(2));
-
-        state.select_next_column(5);
-        assert_eq!(state.selected_column(), Some(4));
-
-        state.select_previous_column();
-        assert_eq!(state.selected_column(), Some(3));
+    fn test_widget_state_click() {
+        let mut list = WidgetState::List(ListState::new());
+        assert!(list.click(HitPart::Row(3)));
+        assert!(!list.click(HitPart::Row(3)));
+        assert!(!list.click(HitPart::Tab(1)));
+
+        let mut tabs = WidgetState::Tabs(TabsState::new());
+        assert!(tabs.click(HitPart::Tab(2)));
+        assert!(matches!(tabs, WidgetState::Tabs(TabsState { selected: 2 })));
+
+        let mut scrollbar =
+            WidgetState::Scrollbar(ScrollbarState::new(110).viewport_content_length(10));
+        let end = HitPart::Scrollbar {
+            offset: 9,
+            length: 10,
+        };
+        assert!(scrollbar.click(end));
+        match &scrollbar {
+            WidgetState::Scrollbar(state) => assert_eq!(state.get_position(), 100),
+            _ => unreachable!(),
+        }
     }
 
     #[test]
-    fn test_table_state_offsets() {
-        let mut state = TableState::new();
-        assert_eq!(state.row_offset(), 0);
-        assert_eq!(state.column_offset(), 0);
+    fn test_widget_state_scroll() {
+        let mut table = WidgetState::Table(TableState::new());
+        assert!(!table.scroll_down(None));
+        assert!(table.scroll_down(Some(2)));
+        assert!(table.scroll_down(Some(2)));
+        assert!(!table.scroll_down(Some(2)));
+        assert!(table.scroll_up());
+        assert!(!table.scroll_up());
 
-        state.set_row_offset(10);
-        state.set_column_offset(5);
+        let mut scrollbar = WidgetState::Scrollbar(ScrollbarState::new(5));
+        assert!(scrollbar.scroll_down(None));
+        assert!(scrollbar.scroll_up());
+        assert!(!scrollbar.scroll_up());
+    }
 
-        assert_eq!(state.row_offset(), 10);
-        assert_eq!(state.column_offset(), 5);
+    #[test]
+    fn test_tabs_state() {
+        let mut tabs = TabsState::new();
+        tabs.select_previous(3);
+        assert_eq!(tabs.selected(), 2);
+        tabs.select_next(3);
+        assert_eq!(tabs.selected(), 0);
     }
 }
@@ -0,0 +1,134 @@
+//! Event streams for the async app loop.
+//!
+//! These streams feed
+//! [`DashboardEngine::run_async_with`](crate::dashboard::DashboardEngine::run_async_with).
+//! Streams from different sources can be combined with
+//! [`StreamExt::merge`](tokio_stream::StreamExt::merge).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fusabi_tui_engine::prelude::*;
+//! use fusabi_tui_engine::stream;
+//! use fusabi_tui_render::test::TestRenderer;
+//! use std::path::PathBuf;
+//! use std::time::Duration;
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn example() -> EngineResult<()> {
+//! let mut watcher = FileWatcher::new(100)?;
+//! watcher.watch(std::path::Path::new("data"))?;
+//!
+//! let events = stream::file_changes(watcher)
+//!     .merge(stream::ticks(Duration::from_secs(1)));
+//!
+//! let mut engine = DashboardEngine::new(TestRenderer::new(80, 24), PathBuf::from("."));
+//! engine.run_async_with(events, RunOptions::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use tokio::time::MissedTickBehavior;
+use tokio_stream::wrappers::{IntervalStream, UnboundedReceiverStream};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::error::EngineResult;
+use crate::event::Event;
+use crate::watcher::FileWatcher;
+
+/// A boxed stream of engine events.
+pub type EventStream = Pin<Box<dyn Stream<Item = EngineResult<Event>> + Send>>;
+
+/// Emit [`Event::Tick`] every `interval`, starting one interval from now.
+///
+/// Ticks missed while the loop was busy are skipped rather than replayed.
+///
+/// # Panics
+///
+/// Panics if `interval` is zero, or if called outside a tokio runtime.
+pub fn ticks(interval: Duration) -> impl Stream<Item = EngineResult<Event>> + Send + Unpin {
+    let mut timer = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    IntervalStream::new(timer).map(|_| Ok(Event::Tick))
+}
+
+/// Report file changes from `watcher` as [`Event::FileChange`].
+///
+/// The watcher runs on a background task that ends when the stream is
+/// dropped.
+///
+/// # Panics
+///
+/// Panics if called outside a tokio runtime.
+pub fn file_changes(
+    mut watcher: FileWatcher,
+) -> impl Stream<Item = EngineResult<Event>> + Send + Unpin {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                changes = watcher.changed() => {
+                    for change in changes {
+                        if tx.send(Ok(Event::FileChange(change))).is_err() {
+                            return;
+                        }
+                    }
+                }
+                () = tx.closed() => return,
+            }
+        }
+    });
+    UnboundedReceiverStream::new(rx)
+}
+
+/// Read terminal input through crossterm as a stream.
+///
+/// The terminal should be in raw mode. Input the engine has no equivalent
+/// for is skipped.
+#[cfg(feature = "crossterm-backend")]
+pub fn terminal() -> impl Stream<Item = EngineResult<Event>> + Send + Unpin {
+    crossterm::event::EventStream::new().filter_map(|result| match result {
+        Ok(event) => Event::from_crossterm(event).map(Ok),
+        Err(err) => Some(Err(err.into())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[tokio::test]
+    async fn test_ticks() {
+        let start = tokio::time::Instant::now();
+        let mut ticks = ticks(Duration::from_millis(10));
+
+        assert_eq!(ticks.next().await.unwrap().unwrap(), Event::Tick);
+        assert_eq!(ticks.next().await.unwrap().unwrap(), Event::Tick);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_file_changes() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "let x = 1").unwrap();
+
+        let mut watcher = FileWatcher::new(10).unwrap();
+        watcher.watch(temp_file.path()).unwrap();
+        let mut changes = file_changes(watcher);
+
+        writeln!(temp_file, "let y = 2").unwrap();
+        temp_file.as_file().sync_all().unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), changes.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, Event::FileChange(_)));
+    }
+}
@@ -1,28 +1,365 @@
 //! File watching for hot reload functionality.
+//!
+//! A [`FileWatcher`] watches individual files and whole directory trees and
+//! reports debounced [`FileChange`]s. Files are watched through their parent
+//! directory, so editors that save by writing a temporary file and renaming
+//! it over the original keep being picked up.
+//!
+//! Files found in watched directories pass through a [`WatchFilter`] first:
+//! include and exclude globs, `.gitignore` files, and a default list of
+//! editor swap and backup files.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fusabi_tui_engine::watcher::{FileChangeKind, FileWatcher, WatchFilter};
+//! use std::path::Path;
+//!
+//! let filter = WatchFilter::new().include("*.fsx")?.exclude("vendor/**")?;
+//! let mut watcher = FileWatcher::new(100)?.with_filter(filter);
+//! watcher.watch(Path::new("dashboards"))?;
+//!
+//! for change in watcher.poll() {
+//!     match change.kind {
+//!         FileChangeKind::Removed => println!("removed {}", change.path.display()),
+//!         _ => println!("changed {}", change.path.display()),
+//!     }
+//! }
+//! # Ok::<(), fusabi_tui_engine::error::WatchError>(())
+//! ```
 
 use crate::error::{WatchError, WatchResult};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Editor swap, backup and temporary files excluded by [`WatchFilter::new`].
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    ".git",
+    "*.swp",
+    "*.swo",
+    "*.swx",
+    "*~",
+    "4913",
+    ".#*",
+    "#*#",
+    "*.tmp",
+    "*___jb_tmp___",
+    "*___jb_old___",
+];
+
+/// How a file changed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FileChangeKind {
+    /// The file was created.
+    Created,
+
+    /// The file's content or metadata changed, or it was replaced by a file
+    /// moved over it.
+    Modified,
+
+    /// The file was deleted or moved away.
+    Removed,
+
+    /// The file was moved here from another watched path.
+    Renamed {
+        /// The previous path.
+        from: PathBuf,
+    },
+}
+
+/// A changed file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FileChange {
+    /// Path of the file.
+    pub path: PathBuf,
+
+    /// How the file changed.
+    pub kind: FileChangeKind,
+}
+
+impl FileChange {
+    /// Create a file change.
+    pub fn new(path: impl Into<PathBuf>, kind: FileChangeKind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+        }
+    }
+
+    /// Get the paths affected by the change: the file, and for a rename
+    /// also its previous path.
+    pub fn paths(&self) -> Vec<&Path> {
+        match &self.kind {
+            FileChangeKind::Renamed { from } => vec![&self.path, from],
+            _ => vec![&self.path],
+        }
+    }
+}
+
+/// Decides which files in watched directories are reported.
+///
+/// Patterns are globs. A pattern without a `/` is matched against file
+/// names; in excludes it also matches any directory on the way to the file.
+/// A pattern with a `/` is matched against the path relative to the watched
+/// directory, where `*` does not cross directories and `**` does.
+///
+/// Files passed to [`FileWatcher::watch`] directly are always reported.
+#[derive(Debug, Clone)]
+pub struct WatchFilter {
+    /// Files must match one of these, if there are any.
+    include: Patterns,
+
+    /// Files must match none of these.
+    exclude: Patterns,
+
+    /// Whether files ignored by `.gitignore` files are skipped.
+    gitignore: bool,
+}
+
+impl WatchFilter {
+    /// Create a filter that excludes [`DEFAULT_EXCLUDES`] and files ignored
+    /// by `.gitignore`.
+    pub fn new() -> Self {
+        let mut filter = Self::empty().gitignore(true);
+        for pattern in DEFAULT_EXCLUDES {
+            filter
+                .exclude
+                .add(pattern)
+                .expect("default excludes are valid globs");
+        }
+        filter
+    }
+
+    /// Create a filter that reports every file.
+    pub fn empty() -> Self {
+        Self {
+            include: Patterns::default(),
+            exclude: Patterns::default(),
+            gitignore: false,
+        }
+    }
+
+    /// Only report files matching `pattern`, or one of the other includes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WatchError::InvalidPattern`] if `pattern` is not a valid glob.
+    pub fn include(mut self, pattern: &str) -> WatchResult<Self> {
+        self.include.add(pattern)?;
+        Ok(self)
+    }
+
+    /// Never report files matching `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WatchError::InvalidPattern`] if `pattern` is not a valid glob.
+    pub fn exclude(mut self, pattern: &str) -> WatchResult<Self> {
+        self.exclude.add(pattern)?;
+        Ok(self)
+    }
+
+    /// Set whether files ignored by `.gitignore` files are skipped.
+    ///
+    /// The `.gitignore` files of the file's directory and its parents up to
+    /// the repository root are read, and read again when they change.
+    pub fn gitignore(mut self, enabled: bool) -> Self {
+        self.gitignore = enabled;
+        self
+    }
+
+    /// Check if `.gitignore` files are respected.
+    pub fn respects_gitignore(&self) -> bool {
+        self.gitignore
+    }
+
+    /// Get the include patterns.
+    pub fn includes(&self) -> &[String] {
+        &self.include.patterns
+    }
+
+    /// Get the exclude patterns.
+    pub fn excludes(&self) -> &[String] {
+        &self.exclude.patterns
+    }
+
+    /// Check a path relative to the watched directory against the include
+    /// and exclude patterns. `.gitignore` files are not consulted.
+    pub fn is_match(&self, relative: &Path) -> bool {
+        (self.include.is_empty() || self.include.is_match(relative, false))
+            && !self.exclude.is_match(relative, true)
+    }
+}
+
+impl Default for WatchFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A compiled list of glob patterns.
+#[derive(Debug, Clone, Default)]
+struct Patterns {
+    /// The patterns as given.
+    patterns: Vec<String>,
+
+    /// Patterns without a `/`, matched against names.
+    name_globs: Vec<Glob>,
+
+    /// Patterns with a `/`, matched against relative paths.
+    path_globs: Vec<Glob>,
+
+    names: GlobSet,
+    paths: GlobSet,
+}
+
+impl Patterns {
+    /// Compile and add a pattern.
+    fn add(&mut self, pattern: &str) -> WatchResult<()> {
+        let invalid = |reason: String| WatchError::InvalidPattern {
+            pattern: pattern.to_string(),
+            reason,
+        };
+        let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+        let glob = GlobBuilder::new(trimmed)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| invalid(e.to_string()))?;
+
+        if trimmed.contains('/') {
+            self.path_globs.push(glob);
+            self.paths = build_set(&self.path_globs).map_err(invalid)?;
+        } else {
+            self.name_globs.push(glob);
+            self.names = build_set(&self.name_globs).map_err(invalid)?;
+        }
+        self.patterns.push(pattern.to_string());
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Match the relative path, and its file name or, with `any_component`,
+    /// any of its components.
+    fn is_match(&self, relative: &Path, any_component: bool) -> bool {
+        if self.paths.is_match(relative) {
+            return true;
+        }
+        if any_component {
+            relative.components().any(|c| match c {
+                Component::Normal(name) => self.names.is_match(name),
+                _ => false,
+            })
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.names.is_match(name))
+        }
+    }
+}
+
+fn build_set(globs: &[Glob]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(glob.clone());
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Parsed `.gitignore` files by directory.
+#[derive(Debug, Default)]
+struct Gitignores {
+    /// `None` for directories without a `.gitignore`.
+    files: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl Gitignores {
+    /// Check the `.gitignore` files from the directory of `path` up to the
+    /// repository root. The deepest file with a matching rule decides.
+    fn is_ignored(&mut self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        for dir in path.ancestors().skip(1) {
+            let gitignore = self
+                .files
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| read_gitignore(dir));
+            if let Some(gitignore) = gitignore {
+                match gitignore.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        false
+    }
+
+    /// Forget the `.gitignore` of `dir`, so it is read again.
+    fn invalidate(&mut self, dir: &Path) {
+        self.files.remove(dir);
+    }
+
+    fn clear(&mut self) {
+        self.files.clear();
+    }
+}
+
+fn read_gitignore(dir: &Path) -> Option<Gitignore> {
+    let path = dir.join(".gitignore");
+    // Invalid lines are skipped; the rest of the file still applies
+    path.is_file().then(|| Gitignore::new(&path).0)
+}
 
 /// Watches files for changes and provides debounced change notifications.
 pub struct FileWatcher {
     /// Receiver for file system events.
     rx: Receiver<notify::Result<Event>>,
 
+    /// Signalled whenever an event is sent, to wake [`FileWatcher::changed`].
+    wakeup: Arc<Notify>,
+
     /// The underlying file system watcher.
     _watcher: RecommendedWatcher,
 
-    /// Set of paths currently being watched.
-    watched_paths: HashSet<PathBuf>,
+    /// Files being watched.
+    files: HashSet<PathBuf>,
+
+    /// Directories being watched recursively.
+    dirs: HashSet<PathBuf>,
+
+    /// Directories registered with the underlying watcher.
+    active: HashMap<PathBuf, RecursiveMode>,
+
+    /// Which files in watched directories are reported.
+    filter: WatchFilter,
+
+    /// Parsed `.gitignore` files.
+    gitignores: Gitignores,
 
     /// Debounce duration in milliseconds.
     debounce_ms: u64,
 
     /// Accumulator for recent changes (for debouncing).
-    pending_changes: HashSet<PathBuf>,
+    pending_changes: HashMap<PathBuf, FileChangeKind>,
 
     /// Last time we processed changes.
     last_process_time: std::time::Instant,
@@ -34,14 +371,20 @@ impl FileWatcher {
     /// The debounce time determines how long to wait after the last change
     /// before reporting changes. This prevents rapid successive notifications
     /// when files are being saved.
+    ///
+    /// The watcher uses [`WatchFilter::new`]; see
+    /// [`with_filter`](Self::with_filter).
     pub fn new(debounce_ms: u64) -> WatchResult<Self> {
         let (tx, rx) = channel();
+        let wakeup = Arc::new(Notify::new());
+        let notify_wakeup = wakeup.clone();
 
         let watcher = RecommendedWatcher::new(
             move |res| {
                 if let Err(_) = tx.send(res) {
                     // Channel closed, watcher is shutting down
                 }
+                notify_wakeup.notify_one();
             },
             Config::default(),
         )
@@ -49,17 +392,42 @@ impl FileWatcher {
 
         Ok(Self {
             rx,
+            wakeup,
             _watcher: watcher,
-            watched_paths: HashSet::new(),
+            files: HashSet::new(),
+            dirs: HashSet::new(),
+            active: HashMap::new(),
+            filter: WatchFilter::new(),
+            gitignores: Gitignores::default(),
             debounce_ms,
-            pending_changes: HashSet::new(),
+            pending_changes: HashMap::new(),
             last_process_time: std::time::Instant::now(),
         })
     }
 
+    /// Use `filter` for files in watched directories.
+    pub fn with_filter(mut self, filter: WatchFilter) -> Self {
+        self.set_filter(filter);
+        self
+    }
+
+    /// Get the filter for files in watched directories.
+    pub fn filter(&self) -> &WatchFilter {
+        &self.filter
+    }
+
+    /// Set the filter for files in watched directories.
+    pub fn set_filter(&mut self, filter: WatchFilter) {
+        self.filter = filter;
+        self.gitignores.clear();
+    }
+
     /// Start watching a file or directory.
     ///
-    /// For directories, this will watch all files in the directory recursively.
+    /// Directories are watched recursively, and files in them are reported
+    /// if they pass the [filter](Self::with_filter). A file is watched
+    /// through its parent directory, so it is still watched after being
+    /// deleted and created again or replaced by a rename.
     pub fn watch(&mut self, path: &Path) -> WatchResult<()> {
         let canonical_path = path
             .canonicalize()
@@ -69,52 +437,112 @@ impl FileWatcher {
             })?;
 
         // Don't watch if already watching
-        if self.watched_paths.contains(&canonical_path) {
+        if self.files.contains(&canonical_path) || self.dirs.contains(&canonical_path) {
             return Ok(());
         }
 
-        self._watcher
-            .watch(&canonical_path, RecursiveMode::Recursive)
-            .map_err(|e| WatchError::WatchFailed {
-                path: canonical_path.clone(),
-                reason: e.to_string(),
-            })?;
+        let is_dir = canonical_path.is_dir();
+        if is_dir {
+            self.dirs.insert(canonical_path.clone());
+        } else {
+            self.files.insert(canonical_path.clone());
+        }
 
-        self.watched_paths.insert(canonical_path);
+        if let Err(reason) = self.sync_watches() {
+            self.files.remove(&canonical_path);
+            self.dirs.remove(&canonical_path);
+            let _ = self.sync_watches();
+            return Err(WatchError::WatchFailed {
+                path: canonical_path,
+                reason,
+            });
+        }
 
         Ok(())
     }
 
     /// Stop watching a file or directory.
+    ///
+    /// A file that no longer exists can be unwatched by the path it was
+    /// watched with.
     pub fn unwatch(&mut self, path: &Path) -> WatchResult<()> {
-        let canonical_path = path
-            .canonicalize()
-            .map_err(|e| WatchError::UnwatchFailed {
-                path: path.to_path_buf(),
-                reason: e.to_string(),
-            })?;
+        let canonical_path = match path.canonicalize() {
+            Ok(canonical_path) => canonical_path,
+            Err(_) if self.files.contains(path) => path.to_path_buf(),
+            Err(e) => {
+                return Err(WatchError::UnwatchFailed {
+                    path: path.to_path_buf(),
+                    reason: e.to_string(),
+                })
+            }
+        };
 
-        if !self.watched_paths.contains(&canonical_path) {
+        if !self.files.remove(&canonical_path) && !self.dirs.remove(&canonical_path) {
             return Ok(());
         }
 
-        self._watcher
-            .unwatch(&canonical_path)
-            .map_err(|e| WatchError::UnwatchFailed {
-                path: canonical_path.clone(),
-                reason: e.to_string(),
-            })?;
+        self.sync_watches()
+            .map_err(|reason| WatchError::UnwatchFailed {
+                path: canonical_path,
+                reason,
+            })
+    }
 
-        self.watched_paths.remove(&canonical_path);
+    /// Bring the directories registered with the underlying watcher in line
+    /// with the watched files and directories.
+    ///
+    /// Directories inside a recursively watched one are not registered
+    /// again; some backends would drop the outer watch when the inner one is
+    /// removed.
+    fn sync_watches(&mut self) -> Result<(), String> {
+        let covered = |dir: &Path| self.dirs.iter().any(|d| dir.starts_with(d) && dir != d);
+        let mut wanted: HashMap<PathBuf, RecursiveMode> = HashMap::new();
+        for dir in &self.dirs {
+            if !covered(dir) {
+                wanted.insert(dir.clone(), RecursiveMode::Recursive);
+            }
+        }
+        for file in &self.files {
+            if let Some(parent) = file.parent() {
+                if !self.dirs.contains(parent) && !covered(parent) {
+                    wanted.insert(parent.to_path_buf(), RecursiveMode::NonRecursive);
+                }
+            }
+        }
+
+        // Remove stale watches first, so nested ones are gone before a
+        // parent is watched recursively
+        let stale: Vec<PathBuf> = self
+            .active
+            .iter()
+            .filter(|(dir, mode)| wanted.get(*dir) != Some(*mode))
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        for dir in stale {
+            self.active.remove(&dir);
+            self._watcher.unwatch(&dir).map_err(|e| e.to_string())?;
+        }
+
+        for (dir, mode) in wanted {
+            if !self.active.contains_key(&dir) {
+                self._watcher.watch(&dir, mode).map_err(|e| e.to_string())?;
+                self.active.insert(dir, mode);
+            }
+        }
 
         Ok(())
     }
 
-    /// Poll for file changes and return paths that have changed.
+    /// Poll for file changes and return the files that have changed, sorted
+    /// by path.
     ///
     /// This method implements debouncing - it will only return changes after
-    /// the debounce period has elapsed since the last change.
-    pub fn poll(&mut self) -> Vec<PathBuf> {
+    /// the debounce period has elapsed since the last change. Events for the
+    /// same file within that period are merged into one change: a file that
+    /// is deleted and created again, as some editors save, is reported as
+    /// [`Modified`](FileChangeKind::Modified), and a temporary file that is
+    /// created and removed again is not reported at all.
+    pub fn poll(&mut self) -> Vec<FileChange> {
         // Process all pending events
         loop {
             match self.rx.try_recv() {
@@ -141,7 +569,12 @@ impl FileWatcher {
 
         if elapsed >= Duration::from_millis(self.debounce_ms) && !self.pending_changes.is_empty()
         {
-            let changes: Vec<PathBuf> = self.pending_changes.drain().collect();
+            let mut changes: Vec<FileChange> = self
+                .pending_changes
+                .drain()
+                .map(|(path, kind)| FileChange { path, kind })
+                .collect();
+            changes.sort();
             self.last_process_time = now;
             changes
         } else {
@@ -149,37 +582,179 @@ impl FileWatcher {
         }
     }
 
+    /// Wait asynchronously until changed files are reported.
+    ///
+    /// This is the async counterpart of [`poll`](Self::poll), with the same
+    /// debouncing. It sleeps until the file system reports an event instead
+    /// of polling, and never returns an empty list.
+    pub async fn changed(&mut self) -> Vec<FileChange> {
+        loop {
+            let changes = self.poll();
+            if !changes.is_empty() {
+                return changes;
+            }
+
+            if self.pending_changes.is_empty() {
+                self.wakeup.notified().await;
+            } else {
+                // Changes are waiting for the debounce period to end
+                let ready = self.last_process_time + Duration::from_millis(self.debounce_ms);
+                tokio::time::sleep_until(ready.into()).await;
+            }
+        }
+    }
+
     /// Process a file system event.
     fn process_event(&mut self, event: Event) {
-        // Filter out events we don't care about
         match event.kind {
-            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+            // Only track files, not directories
+            EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder) => {}
+            EventKind::Create(_) => {
                 for path in event.paths {
-                    // Only track files, not directories
-                    if path.is_file() {
-                        self.pending_changes.insert(path);
+                    self.record(path, FileChangeKind::Created);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(mode)) => self.process_rename(mode, event.paths),
+            EventKind::Modify(_) => {
+                for path in event.paths {
+                    if !path.is_dir() {
+                        self.record(path, FileChangeKind::Modified);
                     }
                 }
             }
+            EventKind::Remove(_) => {
+                for path in event.paths {
+                    self.record(path, FileChangeKind::Removed);
+                }
+            }
             _ => {
-                // Ignore other event types (access, metadata, etc.)
+                // Ignore other event types (access, etc.)
             }
         }
     }
 
+    /// Process a rename.
+    ///
+    /// A file moved over a watched file from a path that is not reported,
+    /// such as an editor's temporary file, counts as a modification of the
+    /// watched file. A file moved to a path that is not reported counts as
+    /// removed.
+    fn process_rename(&mut self, mode: RenameMode, paths: Vec<PathBuf>) {
+        match (mode, paths.as_slice()) {
+            (RenameMode::Both, [from, to]) => {
+                if to.is_dir() {
+                    return;
+                }
+                match (self.is_reported(from), self.is_reported(to)) {
+                    (true, true) => {
+                        let kind = match self.pending_changes.remove(from) {
+                            // Created and moved within the debounce period
+                            Some(FileChangeKind::Created) => FileChangeKind::Created,
+                            Some(FileChangeKind::Renamed { from }) => {
+                                FileChangeKind::Renamed { from }
+                            }
+                            _ => FileChangeKind::Renamed { from: from.clone() },
+                        };
+                        self.record(to.clone(), kind);
+                    }
+                    (true, false) => self.record(from.clone(), FileChangeKind::Removed),
+                    (false, true) => self.record(to.clone(), FileChangeKind::Modified),
+                    (false, false) => {}
+                }
+            }
+            (RenameMode::From, _) => {
+                for path in paths {
+                    self.record(path, FileChangeKind::Removed);
+                }
+            }
+            (RenameMode::To, _) => {
+                for path in paths {
+                    if !path.is_dir() {
+                        self.record(path, FileChangeKind::Modified);
+                    }
+                }
+            }
+            _ => {
+                // Only one side is known; tell them apart by what exists now
+                for path in paths {
+                    if path.is_file() {
+                        self.record(path, FileChangeKind::Modified);
+                    } else if !path.exists() {
+                        self.record(path, FileChangeKind::Removed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a change to the pending changes, merged with an earlier change to
+    /// the same file.
+    fn record(&mut self, path: PathBuf, kind: FileChangeKind) {
+        if path.file_name().is_some_and(|name| name == ".gitignore") {
+            if let Some(dir) = path.parent() {
+                self.gitignores.invalidate(dir);
+            }
+        }
+        if !self.is_reported(&path) {
+            return;
+        }
+
+        let merged = match (self.pending_changes.remove(&path), kind) {
+            (None, kind) => Some(kind),
+            // Saved by deleting and recreating the file
+            (Some(FileChangeKind::Removed), FileChangeKind::Created) => {
+                Some(FileChangeKind::Modified)
+            }
+            // A temporary file
+            (Some(FileChangeKind::Created), FileChangeKind::Removed) => None,
+            // Still new, or still moved here
+            (Some(earlier @ FileChangeKind::Created), FileChangeKind::Modified)
+            | (Some(earlier @ FileChangeKind::Renamed { .. }), FileChangeKind::Modified) => {
+                Some(earlier)
+            }
+            (Some(FileChangeKind::Modified), FileChangeKind::Created) => {
+                Some(FileChangeKind::Modified)
+            }
+            (Some(_), kind) => Some(kind),
+        };
+        if let Some(kind) = merged {
+            self.pending_changes.insert(path, kind);
+        }
+    }
+
+    /// Check if changes to `path` are reported: it is a watched file, or it
+    /// is in a watched directory and passes the filter.
+    fn is_reported(&mut self, path: &Path) -> bool {
+        if self.files.contains(path) {
+            return true;
+        }
+        let Some(root) = self
+            .dirs
+            .iter()
+            .filter(|dir| path.starts_with(dir) && path != dir.as_path())
+            .max_by_key(|dir| dir.as_os_str().len())
+        else {
+            return false;
+        };
+        let relative = path.strip_prefix(root).unwrap_or(path);
+
+        self.filter.is_match(relative)
+            && !(self.filter.gitignore && self.gitignores.is_ignored(path))
+    }
+
     /// Get the list of watched paths.
     pub fn watched_paths(&self) -> Vec<PathBuf> {
-        self.watched_paths.iter().cloned().collect()
+        self.files.iter().chain(&self.dirs).cloned().collect()
     }
 
     /// Get the number of watched paths.
     pub fn watch_count(&self) -> usize {
-        self.watched_paths.len()
+        self.files.len() + self.dirs.len()
     }
 
     /// Clear all watches.
     pub fn clear(&mut self) -> WatchResult<()> {
-        let paths: Vec<PathBuf> = self.watched_paths.iter().cloned().collect();
+        let paths = self.watched_paths();
         for path in paths {
             self.unwatch(&path)?;
         }
@@ -203,6 +778,205 @@ mod tests {
     use std::io::Write;
     use tempfile::{tempdir, NamedTempFile};
 
+    fn event(kind: EventKind, paths: &[&Path]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(path.to_path_buf())
+        })
+    }
+
+    fn rename(mode: RenameMode) -> EventKind {
+        EventKind::Modify(ModifyKind::Name(mode))
+    }
+
+    /// A watcher without debouncing for a temporary directory, and the
+    /// directory's canonical path.
+    ///
+    /// The directory is not registered with the file system, so tests can
+    /// feed it events without real ones getting in between.
+    fn watch_dir(filter: WatchFilter) -> (FileWatcher, tempfile::TempDir, PathBuf) {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let mut watcher = FileWatcher::new(0).unwrap().with_filter(filter);
+        watcher.dirs.insert(root.clone());
+        (watcher, dir, root)
+    }
+
+    #[test]
+    fn test_filter_patterns() {
+        let filter = WatchFilter::new()
+            .include("*.fsx")
+            .unwrap()
+            .include("data/**/*.toml")
+            .unwrap()
+            .exclude("vendor")
+            .unwrap();
+        assert!(filter.is_match(Path::new("main.fsx")));
+        assert!(filter.is_match(Path::new("widgets/list.fsx")));
+        assert!(filter.is_match(Path::new("data/a/b/config.toml")));
+        assert!(!filter.is_match(Path::new("config.toml")));
+        assert!(!filter.is_match(Path::new("vendor/lib.fsx")));
+        assert!(!filter.is_match(Path::new("main.fsx.swp")));
+        assert!(!filter.is_match(Path::new(".git/HEAD")));
+        assert_eq!(filter.includes(), ["*.fsx", "data/**/*.toml"]);
+
+        assert!(WatchFilter::empty().is_match(Path::new("4913")));
+        assert!(!WatchFilter::new().is_match(Path::new("4913")));
+        assert!(matches!(
+            WatchFilter::new().include("[*.fsx"),
+            Err(WatchError::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_removed_file_reported() {
+        let (mut watcher, _dir, root) = watch_dir(WatchFilter::new());
+        let path = root.join("main.fsx");
+
+        watcher.process_event(event(EventKind::Remove(RemoveKind::File), &[&path]));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&path, FileChangeKind::Removed)]
+        );
+    }
+
+    #[test]
+    fn test_editor_saves_merged() {
+        let (mut watcher, _dir, root) = watch_dir(WatchFilter::new());
+        let path = root.join("main.fsx");
+        let backup = root.join("main.fsx~");
+        let probe = root.join("4913");
+        let temp = root.join("main.fsx.tmp");
+
+        // Vim: probe file, move the original to a backup, write a new file
+        watcher.process_event(event(EventKind::Create(CreateKind::File), &[&probe]));
+        watcher.process_event(event(EventKind::Remove(RemoveKind::File), &[&probe]));
+        watcher.process_event(event(rename(RenameMode::Both), &[&path, &backup]));
+        watcher.process_event(event(EventKind::Create(CreateKind::File), &[&path]));
+        watcher.process_event(event(EventKind::Modify(ModifyKind::Any), &[&path]));
+        watcher.process_event(event(EventKind::Remove(RemoveKind::File), &[&backup]));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&path, FileChangeKind::Modified)]
+        );
+
+        // Write a temporary file and move it over the original
+        watcher.process_event(event(EventKind::Create(CreateKind::File), &[&temp]));
+        watcher.process_event(event(rename(RenameMode::Both), &[&temp, &path]));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&path, FileChangeKind::Modified)]
+        );
+    }
+
+    #[test]
+    fn test_temporary_file_not_reported() {
+        let (mut watcher, _dir, root) = watch_dir(WatchFilter::empty());
+        let path = root.join("scratch");
+
+        watcher.process_event(event(EventKind::Create(CreateKind::File), &[&path]));
+        watcher.process_event(event(EventKind::Modify(ModifyKind::Any), &[&path]));
+        watcher.process_event(event(EventKind::Remove(RemoveKind::File), &[&path]));
+        assert!(watcher.poll().is_empty());
+    }
+
+    #[test]
+    fn test_rename_reported() {
+        let (mut watcher, _dir, root) = watch_dir(WatchFilter::new());
+        let from = root.join("old.fsx");
+        let to = root.join("new.fsx");
+
+        watcher.process_event(event(rename(RenameMode::Both), &[&from, &to]));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(
+                &to,
+                FileChangeKind::Renamed { from: from.clone() }
+            )]
+        );
+
+        // Unpaired halves of a rename
+        watcher.process_event(event(rename(RenameMode::From), &[&to]));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&to, FileChangeKind::Removed)]
+        );
+        watcher.process_event(event(rename(RenameMode::To), &[&from]));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&from, FileChangeKind::Modified)]
+        );
+    }
+
+    #[test]
+    fn test_gitignore() {
+        let (mut watcher, _dir, root) = watch_dir(WatchFilter::new());
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        let kept = root.join("src/main.fsx");
+        let modified = |path: &Path| event(EventKind::Modify(ModifyKind::Any), &[path]);
+
+        watcher.process_event(modified(&root.join("target/debug/out")));
+        watcher.process_event(modified(&root.join("server.log")));
+        watcher.process_event(modified(&root.join(".git/index")));
+        watcher.process_event(modified(&kept));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&kept, FileChangeKind::Modified)]
+        );
+
+        // Changes to .gitignore take effect
+        let gitignore = root.join(".gitignore");
+        std::fs::write(&gitignore, "target/\n").unwrap();
+        watcher.process_event(modified(&gitignore));
+        watcher.process_event(modified(&root.join("server.log")));
+        assert_eq!(watcher.poll().len(), 2);
+
+        watcher.set_filter(WatchFilter::new().gitignore(false));
+        watcher.process_event(modified(&root.join("target/debug/out")));
+        assert_eq!(watcher.poll().len(), 1);
+    }
+
+    #[test]
+    fn test_watched_file_ignores_siblings() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let path = root.join("main.fsx");
+
+        let mut watcher = FileWatcher::new(0).unwrap();
+        watcher.files.insert(path.clone());
+        let modified = |path: &Path| event(EventKind::Modify(ModifyKind::Any), &[path]);
+        watcher.process_event(modified(&root.join("other.fsx")));
+        watcher.process_event(modified(&path));
+        assert_eq!(
+            watcher.poll(),
+            [FileChange::new(&path, FileChangeKind::Modified)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_atomic_save_keeps_watch() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.fsx");
+        std::fs::write(&path, "let x = 1\n").unwrap();
+
+        let mut watcher = FileWatcher::new(10).unwrap();
+        watcher.watch(&path).unwrap();
+
+        // The second save only arrives if the first did not end the watch
+        for content in ["let x = 2\n", "let x = 3\n"] {
+            let temp = dir.path().join("main.fsx.tmp");
+            std::fs::write(&temp, content).unwrap();
+            std::fs::rename(&temp, &path).unwrap();
+
+            let changes = tokio::time::timeout(Duration::from_secs(5), watcher.changed())
+                .await
+                .unwrap();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].path.file_name(), path.file_name());
+            assert_eq!(changes[0].kind, FileChangeKind::Modified);
+        }
+    }
+
     #[test]
     fn test_file_watcher_new() {
         let watcher = FileWatcher::new(100);
@@ -299,6 +1073,23 @@ mod tests {
         assert!(changes.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_changed() {
+        let mut watcher = FileWatcher::new(10).unwrap();
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "test content").unwrap();
+        watcher.watch(temp_file.path()).unwrap();
+
+        writeln!(temp_file, "more content").unwrap();
+        temp_file.as_file().sync_all().unwrap();
+
+        let changes = tokio::time::timeout(Duration::from_secs(5), watcher.changed())
+            .await
+            .unwrap();
+        assert!(!changes.is_empty());
+    }
+
     #[test]
     fn test_watched_paths() {
         let mut watcher = FileWatcher::new(100).unwrap();
@@ -0,0 +1,649 @@
+//! Terminal capability detection.
+//!
+//! Terminals differ in the colors they can show. [`Capabilities::detect`]
+//! probes the environment the way most terminal programs do:
+//!
+//! - `NO_COLOR` set to a non-empty value turns colors off
+//! - `COLORTERM=truecolor` or `COLORTERM=24bit` means 24-bit color
+//! - `TERM` names the terminal, whose terminfo entry gives the number of
+//!   colors and whether it understands RGB (the `RGB` and `Tc` extensions)
+//! - Without a terminfo entry, a `TERM` containing `256color` means 256
+//!   colors, and anything else means the 16 ANSI colors
+//!
+//! Styled underlines (curly, dotted and the other shapes, and underline
+//! colors) are detected from the `Smulx`, `Su` and `Setulc` terminfo
+//! extensions, terminals known to support them by `TERM` or `TERM_PROGRAM`,
+//! and `VTE_VERSION` for VTE-based terminals.
+//!
+//! Renderers use [`ColorSupport::adapt`] to downsample colors the terminal
+//! cannot show to the nearest color it can, compared in the perceptual
+//! OKLab color space.
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_core::style::Color;
+//! use fusabi_tui_render::capabilities::{Capabilities, ColorSupport};
+//!
+//! let caps = Capabilities::from_env(|name| match name {
+//!     "TERM" => Some("xterm-256color".to_string()),
+//!     _ => None,
+//! });
+//! assert_eq!(caps.color, ColorSupport::Ansi256);
+//! assert_eq!(caps.color.adapt(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use fusabi_tui_core::color::Oklab;
+use fusabi_tui_core::style::Color;
+
+/// What the terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    /// The colors the terminal can show
+    pub color: ColorSupport,
+    /// Whether the terminal draws underline shapes and underline colors
+    pub styled_underlines: bool,
+}
+
+impl Default for Capabilities {
+    /// Everything a renderer can emit, so nothing is downsampled.
+    fn default() -> Self {
+        Self {
+            color: ColorSupport::TrueColor,
+            styled_underlines: true,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Probe the capabilities of the terminal from the process environment.
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Probe the capabilities of the terminal from the environment variables
+    /// returned by `var`.
+    ///
+    /// The terminfo entry for `TERM` is looked up in `TERMINFO`,
+    /// `~/.terminfo`, `TERMINFO_DIRS` and the system terminfo directories.
+    pub fn from_env<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let terminfo = var("TERM")
+            .filter(|term| !term.is_empty())
+            .and_then(|term| Terminfo::find(&term, &var));
+        Self {
+            color: color_support(&var, terminfo.as_ref()),
+            styled_underlines: styled_underlines(&var, terminfo.as_ref()),
+        }
+    }
+}
+
+/// The colors a terminal can show, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorSupport {
+    /// No colors, only the terminal's default colors
+    NoColor,
+    /// The 16 ANSI colors
+    Ansi16,
+    /// The 256-color palette
+    Ansi256,
+    /// 24-bit RGB colors
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Get the color to show in place of `color`.
+    ///
+    /// Colors the terminal can show are returned unchanged. RGB colors are
+    /// replaced by the nearest 256-color index or ANSI color, and indexed
+    /// colors above 15 by the nearest ANSI color. Without color support,
+    /// every color becomes [`Color::Reset`].
+    pub fn adapt(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::NoColor, _) => Color::Reset,
+            (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Ansi256, Color::Rgb(..)) => {
+                Color::Indexed(nearest(lab(color), 16..=255))
+            }
+            (ColorSupport::Ansi16, Color::Rgb(..)) => ansi(nearest(lab(color), 0..=15)),
+            (ColorSupport::Ansi16, Color::Indexed(i)) if i < 16 => ansi(i),
+            (ColorSupport::Ansi16, Color::Indexed(i)) => {
+                ansi(nearest(palette_lab()[usize::from(i)], 0..=15))
+            }
+            _ => color,
+        }
+    }
+}
+
+/// Get the color support described by the environment and terminfo entry.
+fn color_support<F>(var: F, terminfo: Option<&Terminfo>) -> ColorSupport
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return ColorSupport::NoColor;
+    }
+    if let Some("truecolor" | "24bit") = var("COLORTERM").as_deref() {
+        return ColorSupport::TrueColor;
+    }
+
+    let term = match var("TERM").filter(|term| !term.is_empty()) {
+        Some(term) => term,
+        // The Windows console understands RGB and does not set TERM
+        None if cfg!(windows) => return ColorSupport::TrueColor,
+        None => return ColorSupport::Ansi16,
+    };
+    if term == "dumb" {
+        return ColorSupport::NoColor;
+    }
+    if term.ends_with("-direct") || term.contains("truecolor") || term.contains("24bit") {
+        return ColorSupport::TrueColor;
+    }
+
+    match terminfo {
+        Some(info) if info.has_extension("RGB") || info.has_extension("Tc") => {
+            ColorSupport::TrueColor
+        }
+        Some(info) => match info.max_colors {
+            Some(colors) if colors >= 1 << 24 => ColorSupport::TrueColor,
+            Some(colors) if colors >= 256 => ColorSupport::Ansi256,
+            Some(colors) if colors >= 8 => ColorSupport::Ansi16,
+            _ => ColorSupport::NoColor,
+        },
+        None if term.contains("256color") => ColorSupport::Ansi256,
+        None => ColorSupport::Ansi16,
+    }
+}
+
+/// Get whether the terminal described by the environment and terminfo
+/// entry draws styled underlines.
+fn styled_underlines<F>(var: F, terminfo: Option<&Terminfo>) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    /// Terminals that support styled underlines without advertising them
+    const TERMINALS: &[&str] = &["kitty", "wezterm", "foot", "contour", "ghostty"];
+
+    let term = match var("TERM").filter(|term| !term.is_empty() && term != "dumb") {
+        Some(term) => term,
+        None => return false,
+    };
+    if let Some(info) = terminfo {
+        if ["Smulx", "Su", "Setulc"].iter().any(|name| info.has_extension(name)) {
+            return true;
+        }
+    }
+    if TERMINALS.iter().any(|name| term.contains(name)) {
+        return true;
+    }
+    if var("TERM_PROGRAM").is_some_and(|program| program.eq_ignore_ascii_case("wezterm")) {
+        return true;
+    }
+    // VTE 0.51.2 added curly and colored underlines
+    var("VTE_VERSION")
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5102)
+}
+
+/// Get the named color for an ANSI color index.
+fn ansi(index: u8) -> Color {
+    const ANSI: [Color; 16] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::LightWhite,
+    ];
+    ANSI[usize::from(index & 15)]
+}
+
+/// Get the index of the palette color in `range` nearest to `color`.
+fn nearest(color: Oklab, range: std::ops::RangeInclusive<u8>) -> u8 {
+    let palette = palette_lab();
+    range
+        .min_by(|&a, &b| {
+            let da = color.distance(palette[usize::from(a)]);
+            let db = color.distance(palette[usize::from(b)]);
+            da.total_cmp(&db)
+        })
+        .unwrap_or(0)
+}
+
+/// Get the xterm palette in OKLab, computed once.
+fn palette_lab() -> &'static [Oklab; 256] {
+    static PALETTE: OnceLock<[Oklab; 256]> = OnceLock::new();
+    PALETTE.get_or_init(|| std::array::from_fn(|i| lab(Color::Indexed(i as u8))))
+}
+
+/// Get a color with an RGB value in OKLab.
+fn lab(color: Color) -> Oklab {
+    color.to_oklab().unwrap_or(Oklab {
+        l: 0.0,
+        a: 0.0,
+        b: 0.0,
+    })
+}
+
+/// The parts of a compiled terminfo entry used for capability detection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Terminfo {
+    /// The `colors` capability
+    max_colors: Option<u32>,
+    /// Names of the extended capabilities that are set
+    extensions: Vec<String>,
+}
+
+impl Terminfo {
+    /// Index of `colors` among the numeric capabilities
+    const MAX_COLORS: usize = 13;
+
+    /// Find and read the terminfo entry for `term`.
+    fn find<F>(term: &str, var: F) -> Option<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let first = term.chars().next()?;
+        Self::dirs(var).into_iter().find_map(|dir| {
+            [
+                dir.join(first.to_string()).join(term),
+                dir.join(format!("{:x}", u32::from(first))).join(term),
+            ]
+            .iter()
+            .find_map(|path| Self::read(path))
+        })
+    }
+
+    /// Get the directories to look for terminfo entries in, in order.
+    fn dirs<F>(var: F) -> Vec<PathBuf>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        const SYSTEM: [&str; 4] = [
+            "/etc/terminfo",
+            "/lib/terminfo",
+            "/usr/share/terminfo",
+            "/usr/lib/terminfo",
+        ];
+
+        let mut dirs = Vec::new();
+        dirs.extend(var("TERMINFO").map(PathBuf::from));
+        dirs.extend(var("HOME").map(|home| Path::new(&home).join(".terminfo")));
+        if let Some(list) = var("TERMINFO_DIRS") {
+            for dir in list.split(':') {
+                // An empty entry stands for the system directories
+                if dir.is_empty() {
+                    dirs.extend(SYSTEM.iter().map(PathBuf::from));
+                } else {
+                    dirs.push(PathBuf::from(dir));
+                }
+            }
+        }
+        dirs.extend(SYSTEM.iter().map(PathBuf::from));
+        dirs
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        Self::parse(&std::fs::read(path).ok()?)
+    }
+
+    /// Parse a compiled terminfo entry, in the legacy or the 32-bit format.
+    fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data, pos: 0 };
+        let number_size = match reader.i16()? {
+            0o432 => 2,
+            0o1036 => 4,
+            _ => return None,
+        };
+        let names_size = reader.count()?;
+        let bools = reader.count()?;
+        let numbers = reader.count()?;
+        let strings = reader.count()?;
+        let table_size = reader.count()?;
+
+        reader.skip(names_size + bools)?;
+        reader.align();
+        let mut max_colors = None;
+        for index in 0..numbers {
+            let value = reader.number(number_size)?;
+            if index == Self::MAX_COLORS && value >= 0 {
+                max_colors = Some(value as u32);
+            }
+        }
+        reader.skip(strings * 2 + table_size)?;
+
+        let extensions = if reader.at_end() {
+            Vec::new()
+        } else {
+            reader.align();
+            Self::parse_extended(&mut reader, number_size).unwrap_or_default()
+        };
+
+        Some(Self {
+            max_colors,
+            extensions,
+        })
+    }
+
+    /// Parse the extended capabilities that follow the standard ones, and
+    /// get the names of those that are set.
+    fn parse_extended(reader: &mut Reader<'_>, number_size: usize) -> Option<Vec<String>> {
+        let bools = reader.count()?;
+        let numbers = reader.count()?;
+        let strings = reader.count()?;
+        let _items = reader.count()?;
+        let table_size = reader.count()?;
+
+        let mut set = Vec::with_capacity(bools + numbers + strings);
+        for _ in 0..bools {
+            set.push(reader.u8()? == 1);
+        }
+        reader.align();
+        for _ in 0..numbers {
+            set.push(reader.number(number_size)? >= 0);
+        }
+        for _ in 0..strings {
+            set.push(reader.i16()? >= 0);
+        }
+        reader.skip(set.len() * 2)?;
+        let table = reader.bytes(table_size)?;
+
+        // The table holds the string values, then the capability names, in
+        // the order of the capabilities
+        let entries: Vec<&[u8]> = table
+            .strip_suffix(&[0])
+            .unwrap_or(table)
+            .split(|&c| c == 0)
+            .collect();
+        let names = entries.get(entries.len().checked_sub(set.len())?..)?;
+        Some(
+            names
+                .iter()
+                .zip(set)
+                .filter(|(_, set)| *set)
+                .map(|(name, _)| String::from_utf8_lossy(name).into_owned())
+                .collect(),
+        )
+    }
+
+    fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|extension| extension == name)
+    }
+}
+
+/// Reads little-endian values from a compiled terminfo entry.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        self.bytes(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Read a count from a header, where -1 means none.
+    fn count(&mut self) -> Option<usize> {
+        self.i16().map(|count| usize::try_from(count).unwrap_or(0))
+    }
+
+    fn number(&mut self, size: usize) -> Option<i32> {
+        if size == 4 {
+            self.bytes(4)
+                .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        } else {
+            self.i16().map(i32::from)
+        }
+    }
+
+    /// Skip the padding byte that aligns sections to even offsets.
+    fn align(&mut self) {
+        if self.pos % 2 == 1 && self.pos < self.data.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn terminfo(max_colors: Option<u32>, extensions: &[&str]) -> Terminfo {
+        Terminfo {
+            max_colors,
+            extensions: extensions.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Build a compiled terminfo entry with the given numbers and extended
+    /// booleans.
+    fn compiled(numbers: &[i16], extended: &[(&str, bool)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let push = |data: &mut Vec<u8>, value: i16| data.extend(value.to_le_bytes());
+        let names = b"test|test terminal\0";
+        for value in [0o432, names.len() as i16, 1, numbers.len() as i16, 0, 0] {
+            push(&mut data, value);
+        }
+        data.extend(names);
+        data.push(1);
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        for &number in numbers {
+            push(&mut data, number);
+        }
+
+        if !extended.is_empty() {
+            let table: Vec<u8> = extended
+                .iter()
+                .flat_map(|(name, _)| name.bytes().chain([0]))
+                .collect();
+            let count = extended.len() as i16;
+            for value in [count, 0, 0, count, table.len() as i16] {
+                push(&mut data, value);
+            }
+            data.extend(extended.iter().map(|(_, set)| u8::from(*set)));
+            if data.len() % 2 == 1 {
+                data.push(0);
+            }
+            let mut offset = 0;
+            for (name, _) in extended {
+                push(&mut data, offset);
+                offset += name.len() as i16 + 1;
+            }
+            data.extend(table);
+        }
+        data
+    }
+
+    #[test]
+    fn test_no_color() {
+        let info = terminfo(Some(256), &[]);
+        let vars = env(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]);
+        assert_eq!(color_support(vars, Some(&info)), ColorSupport::NoColor);
+
+        // An empty NO_COLOR is ignored
+        let vars = env(&[("NO_COLOR", ""), ("TERM", "xterm-256color")]);
+        assert_eq!(color_support(vars, Some(&info)), ColorSupport::Ansi256);
+    }
+
+    #[test]
+    fn test_colorterm() {
+        for value in ["truecolor", "24bit"] {
+            let vars = env(&[("COLORTERM", value), ("TERM", "xterm")]);
+            assert_eq!(color_support(vars, None), ColorSupport::TrueColor);
+        }
+        let vars = env(&[("COLORTERM", "yes"), ("TERM", "xterm")]);
+        assert_eq!(color_support(vars, None), ColorSupport::Ansi16);
+    }
+
+    #[test]
+    fn test_term_names() {
+        let support = |term| color_support(env(&[("TERM", term)]), None);
+        assert_eq!(support("dumb"), ColorSupport::NoColor);
+        assert_eq!(support("xterm-direct"), ColorSupport::TrueColor);
+        assert_eq!(support("xterm-256color"), ColorSupport::Ansi256);
+        assert_eq!(support("screen"), ColorSupport::Ansi16);
+    }
+
+    #[test]
+    fn test_terminfo_colors() {
+        let support = |info: Terminfo| color_support(env(&[("TERM", "xterm")]), Some(&info));
+        assert_eq!(support(terminfo(Some(8), &[])), ColorSupport::Ansi16);
+        assert_eq!(support(terminfo(Some(256), &[])), ColorSupport::Ansi256);
+        assert_eq!(
+            support(terminfo(Some(1 << 24), &[])),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            support(terminfo(Some(256), &["Tc"])),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            support(terminfo(Some(256), &["RGB"])),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(support(terminfo(None, &[])), ColorSupport::NoColor);
+
+        // The terminfo entry wins over the name
+        let vars = env(&[("TERM", "weird-256color")]);
+        assert_eq!(
+            color_support(vars, Some(&terminfo(Some(8), &[]))),
+            ColorSupport::Ansi16
+        );
+    }
+
+    #[test]
+    fn test_styled_underlines() {
+        let styled = |vars: &[(&str, &str)], info: Option<Terminfo>| {
+            styled_underlines(env(vars), info.as_ref())
+        };
+        assert!(styled(&[("TERM", "xterm-kitty")], None));
+        assert!(styled(&[("TERM", "foot")], None));
+        assert!(styled(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")], None));
+        assert!(styled(&[("TERM", "xterm-256color"), ("VTE_VERSION", "6800")], None));
+        assert!(styled(&[("TERM", "tmux-256color")], Some(terminfo(Some(256), &["Smulx"]))));
+        assert!(styled(&[("TERM", "xterm")], Some(terminfo(Some(8), &["Su"]))));
+
+        assert!(!styled(&[("TERM", "xterm-256color"), ("VTE_VERSION", "4601")], None));
+        assert!(!styled(&[("TERM", "screen")], Some(terminfo(Some(8), &[]))));
+        assert!(!styled(&[("TERM", "dumb")], None));
+        assert!(!styled(&[], None));
+        assert!(Capabilities::default().styled_underlines);
+    }
+
+    #[test]
+    fn test_parse_terminfo() {
+        let mut numbers = [-1; 15];
+        numbers[Terminfo::MAX_COLORS] = 256;
+        let data = compiled(&numbers, &[("AX", true), ("Tc", true), ("XT", false)]);
+        let info = Terminfo::parse(&data).unwrap();
+        assert_eq!(info.max_colors, Some(256));
+        assert_eq!(info.extensions, vec!["AX".to_string(), "Tc".to_string()]);
+        assert!(info.has_extension("Tc"));
+        assert!(!info.has_extension("XT"));
+
+        let info = Terminfo::parse(&compiled(&[-1; 4], &[])).unwrap();
+        assert_eq!(info, Terminfo::default());
+
+        assert_eq!(Terminfo::parse(b"not terminfo"), None);
+        assert_eq!(Terminfo::parse(&data[..20]), None);
+    }
+
+    #[test]
+    fn test_find_terminfo() {
+        let dir = std::env::temp_dir().join(format!("fusabi-terminfo-{}", std::process::id()));
+        let mut numbers = [-1; 14];
+        numbers[Terminfo::MAX_COLORS] = 88;
+        std::fs::create_dir_all(dir.join("f")).unwrap();
+        std::fs::write(dir.join("f/fusabi-test"), compiled(&numbers, &[])).unwrap();
+
+        let caps = Capabilities::from_env(env(&[
+            ("TERM", "fusabi-test"),
+            ("TERMINFO", dir.to_str().unwrap()),
+        ]));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(caps.color, ColorSupport::Ansi16);
+    }
+
+    #[test]
+    fn test_adapt_to_256_colors() {
+        let adapt = |color| ColorSupport::Ansi256.adapt(color);
+        assert_eq!(adapt(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(adapt(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(adapt(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(adapt(Color::Rgb(95, 135, 175)), Color::Indexed(67));
+        assert_eq!(adapt(Color::Red), Color::Red);
+        assert_eq!(adapt(Color::Indexed(42)), Color::Indexed(42));
+        assert_eq!(adapt(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn test_adapt_to_16_colors() {
+        let adapt = |color| ColorSupport::Ansi16.adapt(color);
+        assert_eq!(adapt(Color::Rgb(255, 0, 0)), Color::LightRed);
+        assert_eq!(adapt(Color::Rgb(180, 0, 0)), Color::Red);
+        assert_eq!(adapt(Color::Rgb(250, 250, 250)), Color::LightWhite);
+        assert_eq!(adapt(Color::Rgb(20, 20, 20)), Color::Black);
+        assert_eq!(adapt(Color::Indexed(1)), Color::Red);
+        assert_eq!(adapt(Color::Indexed(196)), Color::LightRed);
+        assert_eq!(adapt(Color::Indexed(232)), Color::Black);
+        assert_eq!(adapt(Color::Cyan), Color::Cyan);
+    }
+
+    #[test]
+    fn test_adapt_without_colors() {
+        assert_eq!(ColorSupport::NoColor.adapt(Color::Red), Color::Reset);
+        assert_eq!(
+            ColorSupport::NoColor.adapt(Color::Rgb(1, 2, 3)),
+            Color::Reset
+        );
+        assert_eq!(
+            ColorSupport::TrueColor.adapt(Color::Rgb(1, 2, 3)),
+            Color::Rgb(1, 2, 3)
+        );
+    }
+}
@@ -2,14 +2,23 @@
 //!
 //! This module provides a renderer that uses crossterm to draw to the terminal.
 //! It implements differential rendering to minimize terminal updates.
-
-use std::io::Write;
-
-use crossterm::ExecutableCommand;
-use fusabi_tui_core::buffer::{Buffer, Cell};
+//!
+//! Colors the terminal cannot show are downsampled to the nearest color it
+//! can, as described by its [`Capabilities`]. Underline shapes and colors
+//! fall back to a plain underline on terminals without styled underlines.
+//! Cells with a hyperlink are wrapped in OSC 8 sequences.
+
+use std::io::{self, Write};
+
+use crossterm::style::{
+    Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor, SetUnderlineColor,
+};
+use crossterm::{ExecutableCommand, QueueableCommand};
+use fusabi_tui_core::buffer::{Buffer, Cell, Hyperlink};
 use fusabi_tui_core::layout::Rect;
-use fusabi_tui_core::style::{Color, Modifier};
+use fusabi_tui_core::style::{Color, Modifier, UnderlineStyle};
 
+use crate::capabilities::{Capabilities, ColorSupport};
 use crate::error::{RenderError, Result};
 use crate::renderer::Renderer;
 
@@ -17,19 +26,64 @@ use crate::renderer::Renderer;
 ///
 /// This renderer implements differential rendering by comparing the current buffer
 /// with the last rendered buffer and only updating cells that have changed.
+///
+/// A frame is assembled in memory and handed to the writer in a single write.
+/// Buffers are placed on screen at the origin of their `area`, so a buffer may
+/// cover only part of the screen, such as an inline viewport.
+///
+/// Within a frame, the cursor is only moved when the next changed cell is not
+/// directly after the previous one, and SGR sequences are only emitted when
+/// the colors or modifiers differ from those of the previous cell. Call
+/// [`Renderer::flush`] once per frame to push it to the terminal.
+///
+/// Colors are adapted to the renderer's [`Capabilities`] as they are
+/// written, so RGB colors show up as the nearest 256-color or ANSI color on
+/// terminals without 24-bit color, and not at all with `NO_COLOR`. Underline
+/// shapes and underline colors are only written when the terminal supports
+/// styled underlines; elsewhere underlined cells get a plain underline.
+///
+/// Linked cells are written inside OSC 8 hyperlink sequences that carry the
+/// link's id, so a link stays one link when only some of its cells are
+/// redrawn. Terminals without hyperlinks ignore the sequences.
 pub struct CrosstermRenderer<W: Write + Send> {
     /// The output writer (typically stdout)
     writer: W,
-    /// The current buffer being rendered
-    buffer: Buffer,
+    /// The screen area that buffers are drawn into
+    area: Rect,
     /// The last buffer that was rendered (for differential rendering)
     last_buffer: Option<Buffer>,
+    /// Scratch space the next frame is assembled in
+    frame: Vec<u8>,
+    /// Whether frames are wrapped in synchronized-update sequences
+    synchronized: bool,
+    /// Whether a synchronized update was begun and not yet ended
+    in_synchronized_update: bool,
+    /// What the terminal can display
+    capabilities: Capabilities,
+}
+
+/// Terminal state already set up during the current frame.
+///
+/// `None` means unknown, which forces the next cell to set it.
+#[derive(Debug, Default)]
+struct Pen {
+    /// Position of the last cell written
+    position: Option<(u16, u16)>,
+    fg: Option<Color>,
+    bg: Option<Color>,
+    modifier: Option<Modifier>,
+    /// Shape of the underline, set while the text is underlined
+    underline_style: Option<UnderlineStyle>,
+    underline_color: Option<Color>,
+    /// The hyperlink currently open, if any
+    hyperlink: Option<Hyperlink>,
 }
 
 impl<W: Write + Send> CrosstermRenderer<W> {
     /// Creates a new crossterm renderer with the given writer.
     ///
-    /// The renderer will query the terminal size on initialization.
+    /// The renderer will query the terminal size on initialization, and
+    /// detect the terminal's capabilities with [`Capabilities::detect`].
     ///
     /// # Errors
     ///
@@ -38,21 +92,48 @@ impl<W: Write + Send> CrosstermRenderer<W> {
         let (width, height) = crossterm::terminal::size()
             .map_err(|e| RenderError::Backend(format!("Failed to get terminal size: {}", e)))?;
 
-        Ok(Self::with_size(writer, width, height))
+        Ok(Self::with_size(writer, width, height).with_capabilities(Capabilities::detect()))
     }
 
     /// Creates a new crossterm renderer with a specific size.
     ///
     /// This is useful for testing or when the terminal size is already known.
+    /// Colors are written as they are; use [`with_capabilities`] to adapt
+    /// them to the terminal.
+    ///
+    /// [`with_capabilities`]: CrosstermRenderer::with_capabilities
     pub fn with_size(writer: W, width: u16, height: u16) -> Self {
         let area = Rect::new(0, 0, width, height);
         Self {
             writer,
-            buffer: Buffer::new(area),
+            area,
             last_buffer: None,
+            frame: Vec::new(),
+            synchronized: false,
+            in_synchronized_update: false,
+            capabilities: Capabilities::default(),
         }
     }
 
+    /// Sets the capabilities of the terminal that colors are adapted to.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.set_capabilities(capabilities);
+        self
+    }
+
+    /// Sets the capabilities of the terminal that colors are adapted to.
+    ///
+    /// The next frame is drawn in full.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+        self.last_buffer = None;
+    }
+
+    /// Gets the capabilities of the terminal that colors are adapted to.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
     /// Gets a mutable reference to the underlying writer.
     ///
     /// This is useful for executing crossterm commands directly.
@@ -65,11 +146,12 @@ impl<W: Write + Send> CrosstermRenderer<W> {
         &self.writer
     }
 
-    /// Converts a fusabi-tui Color to a crossterm Color.
-    fn convert_color(color: Color) -> crossterm::style::Color {
+    /// Converts a fusabi-tui Color to a crossterm Color, downsampling it to
+    /// the colors the terminal supports.
+    fn convert_color(color: Color, support: ColorSupport) -> crossterm::style::Color {
         use crossterm::style::Color as CColor;
 
-        match color {
+        match support.adapt(color) {
             Color::Black => CColor::Black,
             Color::Red => CColor::DarkRed,
             Color::Green => CColor::DarkGreen,
@@ -92,101 +174,187 @@ impl<W: Write + Send> CrosstermRenderer<W> {
         }
     }
 
-    /// Applies text modifiers to the output.
-    fn apply_modifiers(&mut self, modifier: Modifier) -> Result<()> {
-        use crossterm::style::Attribute;
+    /// Gets the attribute that draws an underline of the given shape.
+    fn underline_attribute(style: UnderlineStyle) -> Attribute {
+        match style {
+            UnderlineStyle::Single => Attribute::Underlined,
+            UnderlineStyle::Double => Attribute::DoubleUnderlined,
+            UnderlineStyle::Curly => Attribute::Undercurled,
+            UnderlineStyle::Dotted => Attribute::Underdotted,
+            UnderlineStyle::Dashed => Attribute::Underdashed,
+        }
+    }
 
-        if modifier.contains(Modifier::BOLD) {
-            crossterm::execute!(self.writer, crossterm::style::SetAttribute(Attribute::Bold))?;
+    /// Queues the OSC 8 sequence that opens `hyperlink`, or closes the open
+    /// link with `None`.
+    fn queue_hyperlink(out: &mut Vec<u8>, hyperlink: Option<&Hyperlink>) -> io::Result<()> {
+        match hyperlink {
+            Some(link) => write!(out, "\x1b]8;id={};{}\x1b\\", link.id(), link.uri()),
+            None => write!(out, "\x1b]8;;\x1b\\"),
         }
-        if modifier.contains(Modifier::DIM) {
-            crossterm::execute!(self.writer, crossterm::style::SetAttribute(Attribute::Dim))?;
+    }
+
+    /// Queues the SGR sequences that turn the `from` modifiers into `to`,
+    /// adding an underline with the `underline` attribute.
+    fn queue_modifier_change(
+        out: &mut Vec<u8>,
+        from: Modifier,
+        to: Modifier,
+        underline: Attribute,
+    ) -> io::Result<()> {
+        let removed = from & !to;
+        let mut added = to & !from;
+
+        if removed.contains(Modifier::REVERSED) {
+            out.queue(SetAttribute(Attribute::NoReverse))?;
         }
-        if modifier.contains(Modifier::ITALIC) {
-            crossterm::execute!(self.writer, crossterm::style::SetAttribute(Attribute::Italic))?;
+        if removed.contains(Modifier::BOLD) || removed.contains(Modifier::DIM) {
+            // Bold and dim share a single reset, so restore whichever remains
+            out.queue(SetAttribute(Attribute::NormalIntensity))?;
+            added |= to & (Modifier::BOLD | Modifier::DIM);
         }
-        if modifier.contains(Modifier::UNDERLINED) {
-            crossterm::execute!(
-                self.writer,
-                crossterm::style::SetAttribute(Attribute::Underlined)
-            )?;
+        if removed.contains(Modifier::ITALIC) {
+            out.queue(SetAttribute(Attribute::NoItalic))?;
         }
-        if modifier.contains(Modifier::SLOW_BLINK) {
-            crossterm::execute!(
-                self.writer,
-                crossterm::style::SetAttribute(Attribute::SlowBlink)
-            )?;
+        if removed.contains(Modifier::UNDERLINED) {
+            out.queue(SetAttribute(Attribute::NoUnderline))?;
         }
-        if modifier.contains(Modifier::RAPID_BLINK) {
-            crossterm::execute!(
-                self.writer,
-                crossterm::style::SetAttribute(Attribute::RapidBlink)
-            )?;
+        if removed.contains(Modifier::SLOW_BLINK) || removed.contains(Modifier::RAPID_BLINK) {
+            out.queue(SetAttribute(Attribute::NoBlink))?;
+            added |= to & (Modifier::SLOW_BLINK | Modifier::RAPID_BLINK);
         }
-        if modifier.contains(Modifier::REVERSED) {
-            crossterm::execute!(
-                self.writer,
-                crossterm::style::SetAttribute(Attribute::Reverse)
-            )?;
+        if removed.contains(Modifier::HIDDEN) {
+            out.queue(SetAttribute(Attribute::NoHidden))?;
         }
-        if modifier.contains(Modifier::HIDDEN) {
-            crossterm::execute!(self.writer, crossterm::style::SetAttribute(Attribute::Hidden))?;
+        if removed.contains(Modifier::CROSSED_OUT) {
+            out.queue(SetAttribute(Attribute::NotCrossedOut))?;
         }
-        if modifier.contains(Modifier::CROSSED_OUT) {
-            crossterm::execute!(
-                self.writer,
-                crossterm::style::SetAttribute(Attribute::CrossedOut)
-            )?;
+
+        const ATTRIBUTES: [(Modifier, Attribute); 9] = [
+            (Modifier::BOLD, Attribute::Bold),
+            (Modifier::DIM, Attribute::Dim),
+            (Modifier::ITALIC, Attribute::Italic),
+            (Modifier::UNDERLINED, Attribute::Underlined),
+            (Modifier::SLOW_BLINK, Attribute::SlowBlink),
+            (Modifier::RAPID_BLINK, Attribute::RapidBlink),
+            (Modifier::REVERSED, Attribute::Reverse),
+            (Modifier::HIDDEN, Attribute::Hidden),
+            (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+        ];
+        for (modifier, attribute) in ATTRIBUTES {
+            if added.contains(modifier) {
+                let attribute = if modifier == Modifier::UNDERLINED {
+                    underline
+                } else {
+                    attribute
+                };
+                out.queue(SetAttribute(attribute))?;
+            }
         }
 
         Ok(())
     }
 
-    /// Renders a single cell at the specified position.
-    fn render_cell(&mut self, x: u16, y: u16, cell: &Cell) -> Result<()> {
-        use crossterm::{cursor::MoveTo, style::*};
+    /// Queues a single cell at the specified position, emitting only the
+    /// cursor movement and style changes that `pen` says are needed.
+    fn queue_cell(
+        out: &mut Vec<u8>,
+        pen: &mut Pen,
+        capabilities: Capabilities,
+        x: u16,
+        y: u16,
+        cell: &Cell,
+    ) -> io::Result<()> {
+        use crossterm::cursor::MoveTo;
 
-        // Move cursor to position
-        self.writer.execute(MoveTo(x, y))?;
+        let adjacent = matches!(
+            pen.position,
+            Some((px, py)) if py == y && px.checked_add(1) == Some(x)
+        );
+        if !adjacent {
+            out.queue(MoveTo(x, y))?;
+        }
+        pen.position = Some((x, y));
 
-        // Reset attributes
-        crossterm::execute!(self.writer, SetAttribute(Attribute::Reset))?;
+        let support = capabilities.color;
+        let styled = capabilities.styled_underlines;
+        let underline_style = if styled {
+            cell.underline_style
+        } else {
+            UnderlineStyle::Single
+        };
+        let underline = Self::underline_attribute(underline_style);
+        let was_underlined = pen.modifier.is_some_and(|m| m.contains(Modifier::UNDERLINED));
+
+        match pen.modifier {
+            Some(current) if current == cell.modifier => {}
+            Some(current) => Self::queue_modifier_change(out, current, cell.modifier, underline)?,
+            None => {
+                // A full reset also resets the colors, including the
+                // underline color
+                out.queue(SetAttribute(Attribute::Reset))?;
+                Self::queue_modifier_change(out, Modifier::EMPTY, cell.modifier, underline)?;
+                pen.fg = None;
+                pen.bg = None;
+                pen.underline_color = Some(Color::Reset);
+            }
+        }
+        pen.modifier = Some(cell.modifier);
 
-        // Set foreground color
-        self.writer.execute(SetForegroundColor(Self::convert_color(cell.fg)))?;
+        if pen.fg != Some(cell.fg) {
+            out.queue(SetForegroundColor(Self::convert_color(cell.fg, support)))?;
+            pen.fg = Some(cell.fg);
+        }
+        if pen.bg != Some(cell.bg) {
+            out.queue(SetBackgroundColor(Self::convert_color(cell.bg, support)))?;
+            pen.bg = Some(cell.bg);
+        }
 
-        // Set background color
-        self.writer.execute(SetBackgroundColor(Self::convert_color(cell.bg)))?;
+        if cell.modifier.contains(Modifier::UNDERLINED) {
+            // A newly added underline already has its shape
+            if was_underlined && pen.underline_style != Some(underline_style) {
+                out.queue(SetAttribute(underline))?;
+            }
+            pen.underline_style = Some(underline_style);
 
-        // Apply modifiers
-        self.apply_modifiers(cell.modifier)?;
+            if styled && pen.underline_color != Some(cell.underline_color) {
+                let color = Self::convert_color(cell.underline_color, support);
+                out.queue(SetUnderlineColor(color))?;
+                pen.underline_color = Some(cell.underline_color);
+            }
+        }
 
-        // Write the symbol
-        write!(self.writer, "{}", cell.symbol)?;
+        if pen.hyperlink != cell.hyperlink {
+            Self::queue_hyperlink(out, cell.hyperlink.as_ref())?;
+            pen.hyperlink = cell.hyperlink.clone();
+        }
 
+        out.queue(Print(&cell.symbol))?;
         Ok(())
     }
 }
 
 impl<W: Write + Send> Renderer for CrosstermRenderer<W> {
     fn draw(&mut self, buffer: &Buffer) -> Result<()> {
-        // Check if buffer size matches our expected size
-        if buffer.area != self.buffer.area {
+        // Check that the buffer lies on screen
+        if self.area.union(buffer.area) != self.area {
             return Err(RenderError::SizeMismatch {
-                expected: self.buffer.area,
+                expected: self.area,
                 actual: buffer.area,
             });
         }
 
-        // Compute the diff between the last buffer and the new buffer
+        // Compute the diff between the last buffer and the new buffer. A
+        // buffer at a different position is drawn in full.
         let updates = if let Some(ref last) = self.last_buffer {
             last.diff(buffer)
         } else {
-            // First render - draw everything
+            // First render - draw everything except the continuation cells
+            // of wide glyphs, which the glyphs themselves cover
             let mut all_cells = Vec::new();
             for y in 0..buffer.area.height {
                 for x in 0..buffer.area.width {
-                    if let Some(cell) = buffer.get(x, y) {
+                    if let Some(cell) = buffer.get(x, y).filter(|cell| !cell.continuation) {
                         all_cells.push((x, y, cell));
                     }
                 }
@@ -194,19 +362,42 @@ impl<W: Write + Send> Renderer for CrosstermRenderer<W> {
             all_cells
         };
 
-        // Render only the changed cells
+        // Assemble the changed cells into a single write. The pen starts out
+        // unknown because the writer may have been used directly since the
+        // last frame.
+        let mut out = std::mem::take(&mut self.frame);
+        out.clear();
+        if self.synchronized && !self.in_synchronized_update {
+            out.queue(crossterm::terminal::BeginSynchronizedUpdate)?;
+            self.in_synchronized_update = true;
+        }
+        let mut pen = Pen::default();
+        let capabilities = self.capabilities;
+        let (origin_x, origin_y) = (buffer.area.x, buffer.area.y);
         for (x, y, cell) in updates {
-            self.render_cell(x, y, cell)?;
+            let (x, y) = (origin_x + x, origin_y + y);
+            Self::queue_cell(&mut out, &mut pen, capabilities, x, y, cell)?;
         }
+        if pen.hyperlink.is_some() {
+            Self::queue_hyperlink(&mut out, None)?;
+        }
+        if pen.position.is_some() {
+            out.queue(SetAttribute(Attribute::Reset))?;
+        }
+        let written = self.writer.write_all(&out);
+        self.frame = out;
+        written?;
 
-        // Update our internal buffer and last_buffer
-        self.buffer = buffer.clone();
         self.last_buffer = Some(buffer.clone());
 
         Ok(())
     }
 
     fn flush(&mut self) -> Result<()> {
+        if self.in_synchronized_update {
+            self.writer.queue(crossterm::terminal::EndSynchronizedUpdate)?;
+            self.in_synchronized_update = false;
+        }
         self.writer.flush()?;
         Ok(())
     }
@@ -239,18 +430,74 @@ impl<W: Write + Send> Renderer for CrosstermRenderer<W> {
         self.writer.execute(MoveTo(x, y))?;
         Ok(())
     }
+
+    fn resize(&mut self, area: Rect) -> Result<()> {
+        self.area = area;
+        self.last_buffer = None;
+        Ok(())
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        crossterm::cursor::position()
+            .map_err(|e| RenderError::Backend(format!("Failed to get cursor position: {}", e)))
+    }
+
+    fn append_lines(&mut self, count: u16) -> Result<()> {
+        for _ in 0..count {
+            self.writer.queue(Print("\n"))?;
+        }
+        // Whatever was on screen may have scrolled
+        self.last_buffer = None;
+        Ok(())
+    }
+
+    fn set_synchronized_output(&mut self, enabled: bool) {
+        self.synchronized = enabled;
+    }
+
+    fn synchronized_output(&self) -> bool {
+        self.synchronized
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fusabi_tui_core::style::Style;
+
+    /// Writer that records how often it was flushed.
+    #[derive(Default)]
+    struct FlushCounter {
+        data: Vec<u8>,
+        flushes: usize,
+    }
+
+    impl Write for FlushCounter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    fn draw_output(buffer: &Buffer) -> String {
+        let mut output = Vec::new();
+        let mut renderer =
+            CrosstermRenderer::with_size(&mut output, buffer.area.width, buffer.area.height);
+        renderer.draw(buffer).unwrap();
+        String::from_utf8(output).unwrap()
+    }
 
     #[test]
     fn test_crossterm_renderer_new() {
         let mut buffer = Vec::new();
         let result = CrosstermRenderer::with_size(&mut buffer, 80, 24);
-        assert_eq!(result.buffer.area.width, 80);
-        assert_eq!(result.buffer.area.height, 24);
+        assert_eq!(result.area.width, 80);
+        assert_eq!(result.area.height, 24);
     }
 
     #[test]
@@ -258,19 +505,22 @@ mod tests {
         use crossterm::style::Color as CColor;
 
         assert_eq!(
-            CrosstermRenderer::<Vec<u8>>::convert_color(Color::Black),
+            CrosstermRenderer::<Vec<u8>>::convert_color(Color::Black, ColorSupport::TrueColor),
             CColor::Black
         );
         assert_eq!(
-            CrosstermRenderer::<Vec<u8>>::convert_color(Color::Red),
+            CrosstermRenderer::<Vec<u8>>::convert_color(Color::Red, ColorSupport::TrueColor),
             CColor::DarkRed
         );
         assert_eq!(
-            CrosstermRenderer::<Vec<u8>>::convert_color(Color::LightRed),
+            CrosstermRenderer::<Vec<u8>>::convert_color(Color::LightRed, ColorSupport::TrueColor),
             CColor::Red
         );
         assert_eq!(
-            CrosstermRenderer::<Vec<u8>>::convert_color(Color::Rgb(255, 128, 0)),
+            CrosstermRenderer::<Vec<u8>>::convert_color(
+                Color::Rgb(255, 128, 0),
+                ColorSupport::TrueColor
+            ),
             CColor::Rgb {
                 r: 255,
                 g: 128,
@@ -279,6 +529,244 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_color_downsampling() {
+        use crossterm::style::Color as CColor;
+
+        let convert = CrosstermRenderer::<Vec<u8>>::convert_color;
+        let orange = Color::Rgb(255, 135, 0);
+        assert_eq!(convert(orange, ColorSupport::Ansi256), CColor::AnsiValue(208));
+        assert_eq!(convert(orange, ColorSupport::Ansi16), CColor::Red);
+        assert_eq!(convert(orange, ColorSupport::NoColor), CColor::Reset);
+        assert_eq!(convert(Color::Red, ColorSupport::NoColor), CColor::Reset);
+    }
+
+    #[test]
+    fn test_draw_with_capabilities() {
+        let mut output = Vec::new();
+        let capabilities = Capabilities {
+            color: ColorSupport::Ansi256,
+            ..Capabilities::default()
+        };
+        let mut renderer =
+            CrosstermRenderer::with_size(&mut output, 2, 1).with_capabilities(capabilities);
+        assert_eq!(renderer.capabilities(), capabilities);
+
+        let mut buffer = Buffer::new(Rect::new(0, 0, 2, 1));
+        buffer.set_string(0, 0, "x", Style::new().fg(Color::Rgb(255, 0, 0)));
+        renderer.draw(&buffer).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("\x1b[38;5;196m"));
+        assert!(!text.contains("38;2;"));
+    }
+
+    #[test]
+    fn test_draw_flushes_once_per_frame() {
+        let mut renderer = CrosstermRenderer::with_size(FlushCounter::default(), 10, 5);
+        let mut buffer = Buffer::new(Rect::new(0, 0, 10, 5));
+        buffer.set_string(0, 0, "Hello", Style::new().fg(Color::Red));
+
+        renderer.draw(&buffer).unwrap();
+        assert_eq!(renderer.writer().flushes, 0);
+        assert!(!renderer.writer().data.is_empty());
+
+        renderer.flush().unwrap();
+        assert_eq!(renderer.writer().flushes, 1);
+    }
+
+    #[test]
+    fn test_adjacent_cells_skip_cursor_moves() {
+        let buffer = Buffer::new(Rect::new(0, 0, 10, 3));
+        let text = draw_output(&buffer);
+
+        // One move per row, one reset at the start and one at the end
+        assert_eq!(text.matches("\x1b[1;1H").count(), 1);
+        assert_eq!(text.matches('H').count(), 3);
+        assert_eq!(text.matches("\x1b[0m").count(), 2);
+        assert!(text.contains("          "));
+    }
+
+    #[test]
+    fn test_style_emitted_only_on_change() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "abc", Style::new().fg(Color::Red));
+
+        let text = draw_output(&buffer);
+        let red = "\x1b[38;5;1m";
+        assert_eq!(text.matches(red).count(), 1);
+        assert!(text.contains(&format!("{red}\x1b[49mabc\x1b[39m   ")));
+    }
+
+    #[test]
+    fn test_modifier_removal() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "a", Style::new().add_modifier(Modifier::BOLD | Modifier::DIM));
+        buffer.set_string(1, 0, "b", Style::new().add_modifier(Modifier::DIM));
+
+        let text = draw_output(&buffer);
+        // Dropping bold resets intensity and restores dim; dropping dim resets it
+        assert!(text.contains("\x1b[1m\x1b[2m\x1b[39m\x1b[49ma\x1b[22m\x1b[2mb\x1b[22m "));
+    }
+
+    #[test]
+    fn test_styled_underlines() {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 4, 1));
+        let curly = Style::new()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Rgb(255, 0, 0));
+        buffer.set_string(0, 0, "ab", curly);
+        buffer.set_string(2, 0, "c", Style::new().underline_style(UnderlineStyle::Dotted));
+
+        let text = draw_output(&buffer);
+        // The shape and color are set once, then the shape changes in place
+        assert_eq!(text.matches("\x1b[4:3m").count(), 1);
+        assert_eq!(text.matches("\x1b[58;2;255;0;0m").count(), 1);
+        assert!(text.contains("ab\x1b[4:4m\x1b[59mc"));
+        assert!(!text.contains("\x1b[4m"));
+        assert!(text.contains("c\x1b[24m "));
+    }
+
+    #[test]
+    fn test_styled_underlines_fallback() {
+        let mut output = Vec::new();
+        let capabilities = Capabilities {
+            styled_underlines: false,
+            ..Capabilities::default()
+        };
+        let mut renderer =
+            CrosstermRenderer::with_size(&mut output, 3, 1).with_capabilities(capabilities);
+
+        let mut buffer = Buffer::new(Rect::new(0, 0, 3, 1));
+        let curly = Style::new()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Red);
+        buffer.set_string(0, 0, "a", curly);
+        buffer.set_string(1, 0, "b", Style::new().underline_style(UnderlineStyle::Double));
+        renderer.draw(&buffer).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        assert_eq!(text.matches("\x1b[4m").count(), 1);
+        assert!(!text.contains("4:"));
+        assert!(!text.contains("58;"));
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        let link = Hyperlink::new("https://example.com");
+        let mut buffer = Buffer::new(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "link", Style::new());
+        buffer.set_hyperlink(Rect::new(1, 0, 2, 1), Some(&link));
+
+        let open = format!("\x1b]8;id={};https://example.com\x1b\\", link.id());
+        let close = "\x1b]8;;\x1b\\";
+        let text = draw_output(&buffer);
+        assert!(text.contains(&format!("l{open}in{close}k  ")));
+        assert_eq!(text.matches(close).count(), 1);
+    }
+
+    #[test]
+    fn test_hyperlink_id_survives_diff() {
+        let link = Hyperlink::new("https://example.com").with_id("docs");
+        let mut output = Vec::new();
+        let mut renderer = CrosstermRenderer::with_size(&mut output, 4, 1);
+
+        let mut buffer = Buffer::new(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "docs", Style::new());
+        buffer.set_hyperlink(Rect::new(0, 0, 4, 1), Some(&link));
+        renderer.draw(&buffer).unwrap();
+        let first_frame = renderer.writer().len();
+
+        // Only the changed cell is drawn, inside a link with the same id
+        buffer.set_string(3, 0, "S", Style::new());
+        buffer.set_hyperlink(Rect::new(3, 0, 1, 1), Some(&link));
+        renderer.draw(&buffer).unwrap();
+        let text = String::from_utf8(output[first_frame..].to_vec()).unwrap();
+        assert!(text.contains("\x1b]8;id=docs;https://example.com\x1b\\S\x1b]8;;\x1b\\"));
+        assert_eq!(text.matches("\x1b]8;id=").count(), 1);
+    }
+
+    #[test]
+    fn test_synchronized_output_wraps_frame() {
+        let mut renderer = CrosstermRenderer::with_size(FlushCounter::default(), 4, 1);
+        renderer.set_synchronized_output(true);
+        let buffer = Buffer::new(Rect::new(0, 0, 4, 1));
+
+        renderer.draw(&buffer).unwrap();
+        renderer.flush().unwrap();
+        renderer.draw(&buffer).unwrap();
+        renderer.flush().unwrap();
+
+        let text = String::from_utf8(renderer.writer().data.clone()).unwrap();
+        let begin = "\x1b[?2026h";
+        let end = "\x1b[?2026l";
+        assert_eq!(text.matches(begin).count(), 2);
+        assert_eq!(text.matches(end).count(), 2);
+        assert!(text.starts_with(begin));
+        assert!(text.ends_with(end));
+        assert_eq!(renderer.writer().flushes, 2);
+    }
+
+    #[test]
+    fn test_synchronized_output_disabled_by_default() {
+        let mut output = Vec::new();
+        let mut renderer = CrosstermRenderer::with_size(&mut output, 4, 1);
+        assert!(!renderer.synchronized_output());
+        renderer.draw(&Buffer::new(Rect::new(0, 0, 4, 1))).unwrap();
+        renderer.flush().unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains("2026"));
+    }
+
+    #[test]
+    fn test_wide_glyph_drawn_once() {
+        let mut output = Vec::new();
+        let mut renderer = CrosstermRenderer::with_size(&mut output, 4, 1);
+        let mut buffer = Buffer::new(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "世a", Style::default());
+        renderer.draw(&buffer).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        assert_eq!(text.matches('世').count(), 1);
+        // "a" is two columns after the glyph, so it needs a second move
+        assert_eq!(text.matches('H').count(), 2);
+    }
+
+    #[test]
+    fn test_draw_at_offset() {
+        let mut output = Vec::new();
+        let mut renderer = CrosstermRenderer::with_size(&mut output, 10, 5);
+        let mut buffer = Buffer::new(Rect::new(0, 3, 10, 2));
+        buffer.set_string(0, 1, "x", Style::default());
+        renderer.draw(&buffer).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        // Rows 3 and 4 of the screen, one-based in the escape sequence
+        assert!(text.starts_with("\x1b[4;1H"));
+        assert!(text.contains("\x1b[5;1H"));
+        assert!(!text.contains("\x1b[1;1H"));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut output = Vec::new();
+        let mut renderer = CrosstermRenderer::with_size(&mut output, 10, 5);
+        let buffer = Buffer::new(Rect::new(0, 0, 20, 10));
+        assert!(renderer.draw(&buffer).is_err());
+
+        renderer.resize(Rect::new(0, 0, 20, 10)).unwrap();
+        assert!(renderer.draw(&buffer).is_ok());
+    }
+
+    #[test]
+    fn test_append_lines_forces_full_redraw() {
+        let mut output = Vec::new();
+        let mut renderer = CrosstermRenderer::with_size(&mut output, 4, 2);
+        let buffer = Buffer::new(Rect::new(0, 0, 4, 2));
+        renderer.draw(&buffer).unwrap();
+        renderer.append_lines(2).unwrap();
+        assert!(renderer.last_buffer.is_none());
+    }
+
     #[test]
     fn test_clear() {
         let mut output = Vec::new();
@@ -23,5 +23,15 @@ pub enum RenderError {
     },
 }
 
+/// Error reading a buffer snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid snapshot at line {line}: {message}")]
+pub struct SnapshotError {
+    /// 1-based line of the snapshot text
+    pub line: usize,
+    /// What is wrong with the line
+    pub message: String,
+}
+
 /// Type alias for Results in the rendering system.
 pub type Result<T> = std::result::Result<T, RenderError>;
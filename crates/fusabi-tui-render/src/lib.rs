@@ -47,6 +47,9 @@
 //!
 //! assert_eq!(renderer.buffer().get(0, 0).unwrap().symbol, "T");
 //! ```
+//!
+//! For whole-screen checks, [`assert_snapshot!`] compares a buffer with a
+//! golden file; see the [`snapshot`] module.
 
 #![warn(clippy::all)]
 
@@ -54,8 +57,10 @@
 pub use fusabi_tui_core;
 
 // Core module exports
+pub mod capabilities;
 pub mod error;
 pub mod renderer;
+pub mod snapshot;
 pub mod terminal;
 pub mod test;
 
@@ -64,16 +69,17 @@ pub mod test;
 pub mod crossterm;
 
 // Re-export terminal types for convenience
-pub use terminal::{CompletedFrame, Frame, Terminal};
+pub use terminal::{CompletedFrame, Frame, Terminal, TerminalOptions, Viewport};
 
 // Prelude for convenient imports
 pub mod prelude {
     //! Convenient re-exports for common types and traits.
 
-    pub use crate::error::{RenderError, Result};
+    pub use crate::capabilities::{Capabilities, ColorSupport};
+    pub use crate::error::{RenderError, Result, SnapshotError};
     pub use crate::renderer::Renderer;
-    pub use crate::terminal::{CompletedFrame, Frame, Terminal};
-    pub use crate::test::TestRenderer;
+    pub use crate::terminal::{CompletedFrame, Frame, Terminal, TerminalOptions, Viewport};
+    pub use crate::test::{FrameEvent, TestRenderer};
 
     #[cfg(feature = "crossterm-backend")]
     pub use crate::crossterm::CrosstermRenderer;
@@ -3,7 +3,7 @@
 use fusabi_tui_core::buffer::Buffer;
 use fusabi_tui_core::layout::Rect;
 
-use crate::error::Result;
+use crate::error::{RenderError, Result};
 
 /// A renderer that can draw buffers to a terminal or other output device.
 ///
@@ -74,4 +74,57 @@ pub trait Renderer: Send {
     ///
     /// Returns an error if the cursor cannot be moved to the specified position.
     fn set_cursor(&mut self, x: u16, y: u16) -> Result<()>;
+
+    /// Tell the renderer that the screen now has the given size.
+    ///
+    /// Buffers drawn afterwards must fit within `area`. The next draw repaints
+    /// every cell, since the terminal may have reflowed its contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the renderer cannot adapt to the new size.
+    fn resize(&mut self, _area: Rect) -> Result<()> {
+        Ok(())
+    }
+
+    /// Get the current cursor position as `(column, row)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the position cannot be determined. The default
+    /// implementation always fails.
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        Err(RenderError::Backend(
+            "cursor position is not supported by this renderer".to_string(),
+        ))
+    }
+
+    /// Move the cursor down `count` lines, scrolling the screen up whenever the
+    /// cursor is already on the last line. Lines scrolled off the top end up
+    /// in the terminal's scrollback.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output fails. The default implementation
+    /// always fails.
+    fn append_lines(&mut self, _count: u16) -> Result<()> {
+        Err(RenderError::Backend(
+            "appending lines is not supported by this renderer".to_string(),
+        ))
+    }
+
+    /// Enable or disable synchronized output (DEC private mode 2026).
+    ///
+    /// While enabled, every frame (a `draw` followed by a `flush`) is wrapped in
+    /// begin/end synchronized-update sequences, so terminals that support the
+    /// mode present it atomically instead of showing it half drawn. Terminals
+    /// without support ignore the sequences. Disabled by default.
+    ///
+    /// Backends that have no notion of synchronized output ignore this call.
+    fn set_synchronized_output(&mut self, _enabled: bool) {}
+
+    /// Whether synchronized output is enabled.
+    fn synchronized_output(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,723 @@
+//! Snapshot (golden-file) testing for buffers.
+//!
+//! A buffer is written as readable text: the symbols of each row, then a
+//! grid with one style letter per cell and a legend describing each letter.
+//! [`assert_snapshot!`](crate::assert_snapshot) compares a buffer with the
+//! snapshot stored under `tests/snapshots` in the crate being tested, and on
+//! a mismatch panics with the cells that differ.
+//!
+//! Run the tests with the [`UPDATE_ENV`] environment variable set to accept
+//! new and changed snapshots:
+//!
+//! ```text
+//! FUSABI_UPDATE_SNAPSHOTS=1 cargo test
+//! ```
+//!
+//! Without it, the buffer of a failed or missing snapshot is written next to
+//! it with a `.snap.new` extension for review.
+//!
+//! # Format
+//!
+//! ```text
+//! size: 12x3
+//! text:
+//! |┌ Title ───┐|
+//! |│Hello     │|
+//! |└──────────┘|
+//! styles:
+//! |aabbbbbaaaaa|
+//! |a.cccc.....a|
+//! |aaaaaaaaaaaa|
+//! legend:
+//! a: fg=Blue
+//! b: fg=Blue bold
+//! c: fg=Red bg=Rgb(30, 30, 30) italic underlined
+//! ```
+//!
+//! Rows are wrapped in `|` so trailing spaces stay visible. The continuation
+//! cells of wide glyphs are part of the glyph in the text rows but have their
+//! own letter in the style rows. Cells with the default style are `.`, and
+//! the style rows and legend are left out when every cell has the default
+//! style. Underline shapes other than single and underline colors are
+//! written as `underline_style=curly` and `underline_color=Red`.
+//!
+//! Hyperlinks follow in an optional `links:` section, one run of linked
+//! cells per line with its position, width and URI. The link id is only
+//! written when it is not the one derived from the URI:
+//!
+//! ```text
+//! links:
+//! 4,1 3 https://tracker.example/42
+//! 0,2 5 https://docs.example id=docs
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use fusabi_tui_core::buffer::Buffer;
+//! use fusabi_tui_core::layout::Rect;
+//! use fusabi_tui_core::style::{Color, Style};
+//! use fusabi_tui_render::snapshot;
+//!
+//! let mut buffer = Buffer::new(Rect::new(0, 0, 6, 1));
+//! buffer.set_string(0, 0, "OK", Style::default().fg(Color::Green));
+//!
+//! let text = snapshot::to_snapshot(&buffer);
+//! assert_eq!(
+//!     text,
+//!     "size: 6x1\ntext:\n|OK    |\nstyles:\n|aa....|\nlegend:\na: fg=Green\n"
+//! );
+//! assert_eq!(snapshot::from_snapshot(&text).unwrap(), buffer);
+//! ```
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use fusabi_tui_core::buffer::{Buffer, Cell, Hyperlink};
+use fusabi_tui_core::layout::Rect;
+use fusabi_tui_core::style::{Color, Modifier, Style, UnderlineStyle};
+
+use crate::error::SnapshotError;
+
+/// Environment variable that makes [`assert_snapshot`] write the buffer as
+/// the new snapshot instead of comparing with it.
+pub const UPDATE_ENV: &str = "FUSABI_UPDATE_SNAPSHOTS";
+
+/// Most cell differences listed when a snapshot does not match.
+const MAX_LISTED_CELLS: usize = 20;
+
+/// Modifier names used in the legend.
+const MODIFIERS: &[(Modifier, &str)] = &[
+    (Modifier::BOLD, "bold"),
+    (Modifier::DIM, "dim"),
+    (Modifier::ITALIC, "italic"),
+    (Modifier::UNDERLINED, "underlined"),
+    (Modifier::SLOW_BLINK, "slow_blink"),
+    (Modifier::RAPID_BLINK, "rapid_blink"),
+    (Modifier::REVERSED, "reversed"),
+    (Modifier::HIDDEN, "hidden"),
+    (Modifier::CROSSED_OUT, "crossed_out"),
+];
+
+/// Letters for styles in the order they first appear.
+const STYLE_LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Assert that a buffer matches the snapshot `name` in the crate's
+/// `tests/snapshots` directory.
+///
+/// # Example
+///
+/// ```no_run
+/// use fusabi_tui_core::buffer::Buffer;
+/// use fusabi_tui_core::layout::Rect;
+/// use fusabi_tui_render::assert_snapshot;
+///
+/// let buffer = Buffer::new(Rect::new(0, 0, 10, 2));
+/// assert_snapshot!("empty", &buffer);
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $buffer:expr $(,)?) => {
+        $crate::snapshot::assert_snapshot(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("snapshots"),
+            $name,
+            $buffer,
+        )
+    };
+}
+
+/// Assert that a buffer matches the snapshot `<dir>/<name>.snap`.
+///
+/// With [`UPDATE_ENV`] set, the snapshot is written instead. Otherwise a
+/// missing or different snapshot is written to `<name>.snap.new`.
+///
+/// # Panics
+///
+/// Panics if the snapshot is missing, cannot be parsed or differs from the
+/// buffer, listing the cells that differ, or if a file cannot be written.
+pub fn assert_snapshot(dir: impl AsRef<Path>, name: &str, buffer: &Buffer) {
+    let dir = dir.as_ref();
+    let path = dir.join(format!("{}.snap", name));
+    let actual = to_snapshot(buffer);
+
+    let update = std::env::var_os(UPDATE_ENV).is_some_and(|v| !v.is_empty() && v != "0");
+    if update {
+        write_file(&path, &actual);
+        let _ = std::fs::remove_file(dir.join(format!("{}.snap.new", name)));
+        return;
+    }
+
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let new_path = dir.join(format!("{}.snap.new", name));
+            write_file(&new_path, &actual);
+            panic!(
+                "Snapshot `{}` does not exist. The buffer was written to {}; \
+                 set {}=1 to accept it.\n{}",
+                name,
+                new_path.display(),
+                UPDATE_ENV,
+                actual
+            );
+        }
+        Err(e) => panic!("Failed to read snapshot {}: {}", path.display(), e),
+    };
+    // Snapshots checked out on Windows may have CRLF line endings
+    if expected.replace("\r\n", "\n") == actual {
+        return;
+    }
+
+    let new_path = dir.join(format!("{}.snap.new", name));
+    write_file(&new_path, &actual);
+    let differences = match from_snapshot(&expected) {
+        Ok(expected) => diff(&expected, buffer),
+        Err(err) => format!("{}\n", err),
+    };
+    panic!(
+        "Snapshot `{}` does not match.\n{}\nexpected:\n{}\nactual:\n{}\n\
+         The buffer was written to {}; set {}=1 to accept it.",
+        name,
+        differences,
+        expected,
+        actual,
+        new_path.display(),
+        UPDATE_ENV
+    );
+}
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .unwrap_or_else(|e| panic!("Failed to create {}: {}", parent.display(), e));
+    }
+    std::fs::write(path, content)
+        .unwrap_or_else(|e| panic!("Failed to write snapshot {}: {}", path.display(), e));
+}
+
+/// Write a buffer in the snapshot format.
+pub fn to_snapshot(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut out = format!("size: {}x{}\ntext:\n", area.width, area.height);
+    for y in 0..area.height {
+        out.push('|');
+        for x in 0..area.width {
+            if let Some(cell) = buffer.get(x, y).filter(|c| !c.continuation) {
+                out.push_str(&cell.symbol);
+            }
+        }
+        out.push_str("|\n");
+    }
+
+    let mut styles: Vec<Style> = Vec::new();
+    let mut grid = String::new();
+    for y in 0..area.height {
+        grid.push('|');
+        for x in 0..area.width {
+            let style = buffer.get(x, y).map(cell_style).unwrap_or_default();
+            if style == Style::default() {
+                grid.push('.');
+                continue;
+            }
+            let index = match styles.iter().position(|s| *s == style) {
+                Some(index) => index,
+                None => {
+                    styles.push(style);
+                    styles.len() - 1
+                }
+            };
+            grid.push(style_letter(index));
+        }
+        grid.push_str("|\n");
+    }
+
+    if !styles.is_empty() {
+        out.push_str("styles:\n");
+        out.push_str(&grid);
+        out.push_str("legend:\n");
+        for (index, style) in styles.iter().enumerate() {
+            let _ = writeln!(out, "{}: {}", style_letter(index), describe_style(style));
+        }
+    }
+
+    let links = buffer.hyperlinks();
+    if !links.is_empty() {
+        out.push_str("links:\n");
+        for (area, link) in links {
+            let _ = writeln!(out, "{},{} {} {}", area.x, area.y, area.width, describe_link(link));
+        }
+    }
+    out
+}
+
+/// Read a buffer from the snapshot format.
+///
+/// # Errors
+///
+/// Returns an error if the text is not a valid snapshot.
+pub fn from_snapshot(text: &str) -> Result<Buffer, SnapshotError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .peekable();
+
+    let (line, size) = lines.next().ok_or_else(|| error(1, "empty snapshot".into()))?;
+    let size = size
+        .strip_prefix("size: ")
+        .ok_or_else(|| error(line, "expected `size: <width>x<height>`".into()))?;
+    let (width, height) = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u16>().ok()?, h.parse::<u16>().ok()?)))
+        .ok_or_else(|| error(line, format!("invalid size `{}`", size)))?;
+    let mut buffer = Buffer::new(Rect::new(0, 0, width, height));
+
+    expect_header(&mut lines, "text:", true)?;
+    for y in 0..height {
+        let (_, row) = next_row(&mut lines, "text")?;
+        buffer.set_string(0, y, row, Style::default());
+    }
+
+    if lines.next_if(|(_, header)| *header == "styles:").is_some() {
+        read_styles(&mut lines, &mut buffer)?;
+    }
+
+    if !expect_header(&mut lines, "links:", false)? {
+        return Ok(buffer);
+    }
+    for (line, entry) in lines {
+        let (area, link) = parse_link(entry).map_err(|message| error(line, message))?;
+        buffer.set_hyperlink(area, Some(&link));
+    }
+    Ok(buffer)
+}
+
+/// Read the style rows and legend of a snapshot into `buffer`.
+fn read_styles<'a, I>(
+    lines: &mut std::iter::Peekable<I>,
+    buffer: &mut Buffer,
+) -> Result<(), SnapshotError>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    let (width, height) = (buffer.area.width, buffer.area.height);
+    let mut grid = Vec::new();
+    for _ in 0..height {
+        let (line, row) = next_row(lines, "style")?;
+        grid.push((line, row.chars().collect::<Vec<_>>()));
+    }
+
+    expect_header(lines, "legend:", true)?;
+    let mut legend = Vec::new();
+    while let Some((line, entry)) = lines.next_if(|(_, entry)| *entry != "links:") {
+        let (letter, description) = entry
+            .split_once(": ")
+            .filter(|(letter, _)| letter.chars().count() == 1)
+            .ok_or_else(|| error(line, format!("invalid legend entry `{}`", entry)))?;
+        let style = parse_style(description).map_err(|message| error(line, message))?;
+        legend.push((letter.chars().next().unwrap_or('.'), style));
+    }
+
+    for (y, (line, letters)) in grid.iter().enumerate() {
+        if letters.len() != usize::from(width) {
+            return Err(error(*line, format!("expected {} style letters", width)));
+        }
+        for (x, letter) in letters.iter().enumerate() {
+            if *letter == '.' {
+                continue;
+            }
+            let style = legend
+                .iter()
+                .find(|(l, _)| l == letter)
+                .map(|(_, style)| *style)
+                .ok_or_else(|| error(*line, format!("style `{}` is not in the legend", letter)))?;
+            if let Some(cell) = buffer.get_mut(x as u16, y as u16) {
+                cell.set_style(style);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Describe the cells that differ between two buffers, one per line.
+pub fn diff(expected: &Buffer, actual: &Buffer) -> String {
+    if expected.area.width != actual.area.width || expected.area.height != actual.area.height {
+        return format!(
+            "size differs: expected {}x{}, got {}x{}\n",
+            expected.area.width, expected.area.height, actual.area.width, actual.area.height
+        );
+    }
+
+    let mut differences = Vec::new();
+    for y in 0..expected.area.height {
+        for x in 0..expected.area.width {
+            let (Some(e), Some(a)) = (expected.get(x, y), actual.get(x, y)) else {
+                continue;
+            };
+            if e != a {
+                differences.push(format!(
+                    "  ({}, {}): expected {}, got {}",
+                    x,
+                    y,
+                    describe_cell(e),
+                    describe_cell(a)
+                ));
+            }
+        }
+    }
+
+    let cells = usize::from(expected.area.width) * usize::from(expected.area.height);
+    let mut out = format!("{} of {} cells differ:\n", differences.len(), cells);
+    for difference in differences.iter().take(MAX_LISTED_CELLS) {
+        out.push_str(difference);
+        out.push('\n');
+    }
+    if differences.len() > MAX_LISTED_CELLS {
+        let _ = writeln!(out, "  ... and {} more", differences.len() - MAX_LISTED_CELLS);
+    }
+    out
+}
+
+fn error(line: usize, message: String) -> SnapshotError {
+    SnapshotError { line, message }
+}
+
+/// Consume a section header, which is optional at the end of the text unless
+/// `required` is set.
+fn expect_header<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    name: &str,
+    required: bool,
+) -> Result<bool, SnapshotError> {
+    match lines.next() {
+        Some((_, header)) if header == name => Ok(true),
+        Some((line, header)) => Err(error(
+            line,
+            format!("expected `{}`, found `{}`", name, header),
+        )),
+        None if required => Err(error(0, format!("missing `{}`", name))),
+        None => Ok(false),
+    }
+}
+
+/// Consume a row wrapped in `|` and return its content.
+fn next_row<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    section: &str,
+) -> Result<(usize, &'a str), SnapshotError> {
+    let (line, row) = lines
+        .next()
+        .ok_or_else(|| error(0, format!("missing {} rows", section)))?;
+    let content = row
+        .strip_prefix('|')
+        .and_then(|row| row.strip_suffix('|'))
+        .ok_or_else(|| error(line, "row not wrapped in `|`".into()))?;
+    Ok((line, content))
+}
+
+fn style_letter(index: usize) -> char {
+    STYLE_LETTERS.chars().nth(index).unwrap_or_else(|| {
+        // Past the ASCII letters and digits, continue in Latin-1
+        char::from_u32(0xC0 + (index - STYLE_LETTERS.len()) as u32).unwrap_or('?')
+    })
+}
+
+/// The style of a cell, with `Reset` colors and single underlines left out.
+fn cell_style(cell: &Cell) -> Style {
+    Style {
+        fg: Some(cell.fg).filter(|c| *c != Color::Reset),
+        bg: Some(cell.bg).filter(|c| *c != Color::Reset),
+        modifiers: cell.modifier,
+        underline_style: Some(cell.underline_style).filter(|u| *u != UnderlineStyle::Single),
+        underline_color: Some(cell.underline_color).filter(|c| *c != Color::Reset),
+    }
+}
+
+fn describe_cell(cell: &Cell) -> String {
+    let symbol = if cell.continuation {
+        "continuation".to_string()
+    } else {
+        format!("{:?}", cell.symbol)
+    };
+    let mut description = symbol;
+    let style = cell_style(cell);
+    if style != Style::default() {
+        description = format!("{} {}", description, describe_style(&style));
+    }
+    if let Some(link) = &cell.hyperlink {
+        description = format!("{} link={}", description, describe_link(link));
+    }
+    description
+}
+
+/// Describe a link as its URI, followed by its id if it is not the one
+/// derived from the URI.
+fn describe_link(link: &Hyperlink) -> String {
+    if link.id() == Hyperlink::new(link.uri()).id() {
+        link.uri().to_string()
+    } else {
+        format!("{} id={}", link.uri(), link.id())
+    }
+}
+
+/// Parse a `links:` entry: `<x>,<y> <width> <uri>`, optionally followed by
+/// `id=<id>`.
+fn parse_link(entry: &str) -> Result<(Rect, Hyperlink), String> {
+    let invalid = || format!("invalid link `{}`", entry);
+    let mut parts = entry.split(' ');
+    let (x, y) = parts
+        .next()
+        .and_then(|position| position.split_once(','))
+        .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+        .ok_or_else(invalid)?;
+    let width = parts.next().and_then(|w| w.parse().ok()).ok_or_else(invalid)?;
+    let mut link = Hyperlink::new(parts.next().ok_or_else(invalid)?);
+    if let Some(id) = parts.next() {
+        link = link.with_id(id.strip_prefix("id=").ok_or_else(invalid)?);
+    }
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok((Rect::new(x, y, width, 1), link))
+}
+
+fn describe_style(style: &Style) -> String {
+    let mut parts = Vec::new();
+    if let Some(fg) = style.fg {
+        parts.push(format!("fg={}", fg));
+    }
+    if let Some(bg) = style.bg {
+        parts.push(format!("bg={}", bg));
+    }
+    for (modifier, name) in MODIFIERS {
+        if style.modifiers.contains(*modifier) {
+            parts.push(name.to_string());
+        }
+    }
+    if let Some(underline_style) = style.underline_style {
+        parts.push(format!("underline_style={}", underline_style));
+    }
+    if let Some(underline_color) = style.underline_color {
+        parts.push(format!("underline_color={}", underline_color));
+    }
+    parts.join(" ")
+}
+
+fn parse_style(description: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    // Colors may contain spaces, as in `Rgb(1, 2, 3)`
+    let mut rest = description.trim();
+    while !rest.is_empty() {
+        let end = if ["fg=", "bg=", "underline_color="]
+            .iter()
+            .any(|prefix| rest.starts_with(prefix))
+        {
+            rest.find(')').map_or_else(
+                || rest.find(' ').unwrap_or(rest.len()),
+                |close| close + 1,
+            )
+        } else {
+            rest.find(' ').unwrap_or(rest.len())
+        };
+        let (part, tail) = rest.split_at(end);
+        rest = tail.trim_start();
+
+        if let Some(color) = part.strip_prefix("fg=") {
+            style.fg = Some(parse_color(color)?);
+        } else if let Some(color) = part.strip_prefix("bg=") {
+            style.bg = Some(parse_color(color)?);
+        } else if let Some(color) = part.strip_prefix("underline_color=") {
+            style.underline_color = Some(parse_color(color)?);
+        } else if let Some(name) = part.strip_prefix("underline_style=") {
+            let underline_style = UnderlineStyle::from_name(name)
+                .ok_or_else(|| format!("unknown underline style `{}`", name))?;
+            style.underline_style = Some(underline_style);
+        } else {
+            let modifier = MODIFIERS
+                .iter()
+                .find(|(_, name)| *name == part)
+                .ok_or_else(|| format!("unknown modifier `{}`", part))?;
+            style.modifiers = style.modifiers.insert(modifier.0);
+        }
+    }
+    Ok(style)
+}
+
+/// Parse a color as written by its `Display` implementation.
+fn parse_color(text: &str) -> Result<Color, String> {
+    let invalid = || format!("invalid color `{}`", text);
+    let args = |prefix: &str| -> Option<Vec<u8>> {
+        let inner = text.strip_prefix(prefix)?.strip_suffix(')')?;
+        inner.split(',').map(|n| n.trim().parse().ok()).collect()
+    };
+
+    if let Some(args) = args("Rgb(") {
+        return match args[..] {
+            [r, g, b] => Ok(Color::Rgb(r, g, b)),
+            _ => Err(invalid()),
+        };
+    }
+    if let Some(args) = args("Indexed(") {
+        return match args[..] {
+            [i] => Ok(Color::Indexed(i)),
+            _ => Err(invalid()),
+        };
+    }
+    let named = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::LightWhite,
+        Color::Reset,
+    ];
+    named
+        .into_iter()
+        .find(|color| color.to_string() == text)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styled_buffer() -> Buffer {
+        let mut buffer = Buffer::new(Rect::new(0, 0, 8, 2));
+        let bold_red = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        buffer.set_string(0, 0, "Hi", bold_red);
+        buffer.set_string(3, 0, "界!", Style::default().bg(Color::Rgb(1, 2, 3)));
+        buffer.set_string(
+            0,
+            1,
+            "a|b",
+            Style::default()
+                .fg(Color::Indexed(42))
+                .add_modifier(Modifier::ITALIC | Modifier::UNDERLINED),
+        );
+        buffer
+    }
+
+    #[test]
+    fn test_to_snapshot() {
+        assert_eq!(
+            to_snapshot(&styled_buffer()),
+            "size: 8x2\n\
+             text:\n\
+             |Hi 界!  |\n\
+             |a|b     |\n\
+             styles:\n\
+             |aa.bbb..|\n\
+             |ccc.....|\n\
+             legend:\n\
+             a: fg=Red bold\n\
+             b: bg=Rgb(1, 2, 3)\n\
+             c: fg=Indexed(42) italic underlined\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let buffer = styled_buffer();
+        assert_eq!(from_snapshot(&to_snapshot(&buffer)).unwrap(), buffer);
+
+        let mut underlined = Buffer::new(Rect::new(0, 0, 3, 1));
+        let curly = Style::default()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Rgb(255, 0, 0));
+        underlined.set_string(0, 0, "e", curly);
+        let text = to_snapshot(&underlined);
+        assert!(text.ends_with(
+            "a: underlined underline_style=curly underline_color=Rgb(255, 0, 0)\n"
+        ));
+        assert_eq!(from_snapshot(&text).unwrap(), underlined);
+
+        let mut linked = Buffer::new(Rect::new(0, 0, 6, 2));
+        linked.set_string(0, 0, "docs", Style::default().fg(Color::Blue));
+        let docs = Hyperlink::new("https://docs.example");
+        linked.set_hyperlink(Rect::new(0, 0, 4, 1), Some(&docs));
+        linked.set_hyperlink(Rect::new(1, 1, 2, 1), Some(&docs.clone().with_id("two")));
+        let text = to_snapshot(&linked);
+        assert!(text.ends_with(
+            "a: fg=Blue\nlinks:\n0,0 4 https://docs.example\n1,1 2 https://docs.example id=two\n"
+        ));
+        assert_eq!(from_snapshot(&text).unwrap(), linked);
+        linked.set_hyperlink(Rect::new(0, 0, 4, 1), None);
+        assert_eq!(from_snapshot(&to_snapshot(&linked)).unwrap(), linked);
+
+        let plain = Buffer::new(Rect::new(0, 0, 3, 1));
+        assert_eq!(to_snapshot(&plain), "size: 3x1\ntext:\n|   |\n");
+        assert_eq!(from_snapshot(&to_snapshot(&plain)).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_from_snapshot_errors() {
+        let err = from_snapshot("size: 2x1\ntext:\n|ab|\nstyles:\n|ax|\nlegend:\na: fg=Red\n")
+            .unwrap_err();
+        assert_eq!(err.line, 5);
+        assert!(err.message.contains("`x`"));
+
+        let err = from_snapshot("size: 2x1\ntext:\n|ab|\nstyles:\n|a.|\nlegend:\na: fg=Pink\n")
+            .unwrap_err();
+        assert_eq!((err.line, err.message.as_str()), (7, "invalid color `Pink`"));
+
+        assert!(from_snapshot("size: 2\n").is_err());
+
+        let err = from_snapshot("size: 2x1\ntext:\n|ab|\nlinks:\n0,0 x https://a\n").unwrap_err();
+        assert_eq!((err.line, err.message.as_str()), (5, "invalid link `0,0 x https://a`"));
+        assert!(from_snapshot("size: 2x1\ntext:\nab\n").is_err());
+    }
+
+    #[test]
+    fn test_diff_lists_cells() {
+        let expected = styled_buffer();
+        let mut actual = expected.clone();
+        actual.set_string(1, 1, "x", Style::default().fg(Color::Indexed(42)));
+        actual.get_mut(2, 1).unwrap().modifier = Modifier::EMPTY;
+
+        assert_eq!(
+            diff(&expected, &actual),
+            "2 of 16 cells differ:\n  \
+             (1, 1): expected \"|\" fg=Indexed(42) italic underlined, \
+             got \"x\" fg=Indexed(42) italic underlined\n  \
+             (2, 1): expected \"b\" fg=Indexed(42) italic underlined, got \"b\" fg=Indexed(42)\n"
+        );
+    }
+
+    #[test]
+    fn test_assert_snapshot_files() {
+        let dir = std::env::temp_dir().join(format!("fusabi-snapshot-{}", std::process::id()));
+        let buffer = styled_buffer();
+
+        // A missing snapshot fails and leaves the buffer for review
+        let missing = std::panic::catch_unwind(|| assert_snapshot(&dir, "styled", &buffer));
+        assert!(missing.is_err());
+        let new_path = dir.join("styled.snap.new");
+        assert_eq!(std::fs::read_to_string(&new_path).unwrap(), to_snapshot(&buffer));
+
+        std::fs::rename(&new_path, dir.join("styled.snap")).unwrap();
+        assert_snapshot(&dir, "styled", &buffer);
+
+        let mut changed = buffer.clone();
+        changed.set_string(7, 1, "!", Style::default());
+        let message = std::panic::catch_unwind(|| assert_snapshot(&dir, "styled", &changed))
+            .unwrap_err()
+            .downcast::<String>()
+            .unwrap();
+        assert!(message.contains("1 of 16 cells differ:\n  (7, 1): expected \" \", got \"!\""));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
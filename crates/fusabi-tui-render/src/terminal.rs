@@ -35,36 +35,236 @@ use crate::renderer::Renderer;
 /// ```
 pub struct Terminal<R: Renderer> {
     renderer: R,
+    viewport: Viewport,
+    /// Screen size seen by the last draw
+    screen: Rect,
+    /// Where on screen frames are drawn
+    viewport_area: Rect,
+    /// The last frame drawn, kept so an inline viewport can be repainted after
+    /// lines are inserted above it
+    last_frame: Option<Buffer>,
+}
+
+/// The part of the screen a [`Terminal`] draws to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Viewport {
+    /// The whole screen, typically the alternate screen
+    #[default]
+    Fullscreen,
+    /// A fixed number of lines starting at the cursor row, below whatever the
+    /// shell printed before. Scrollback above the viewport is left alone, and
+    /// [`Terminal::insert_before`] prints lines that scroll into it.
+    Inline(u16),
+}
+
+/// Options for [`Terminal::with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TerminalOptions {
+    /// The part of the screen to draw to
+    pub viewport: Viewport,
 }
 
 impl<R: Renderer> Terminal<R> {
     /// Creates a new terminal with the given renderer.
+    ///
+    /// The terminal draws to the whole screen.
     pub fn new(renderer: R) -> Result<Self> {
-        Ok(Self { renderer })
+        Self::with_options(renderer, TerminalOptions::default())
+    }
+
+    /// Creates a new terminal with the given renderer and options.
+    ///
+    /// For an inline viewport, the lines are reserved starting at the cursor
+    /// row. If there are not enough lines below the cursor, the screen is
+    /// scrolled up to make room.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fusabi_tui_render::prelude::*;
+    /// use fusabi_tui_render::terminal::{TerminalOptions, Viewport};
+    /// use std::io::stdout;
+    ///
+    /// # fn main() -> Result<()> {
+    /// let renderer = CrosstermRenderer::new(stdout())?;
+    /// let mut terminal = Terminal::with_options(
+    ///     renderer,
+    ///     TerminalOptions {
+    ///         viewport: Viewport::Inline(4),
+    ///     },
+    /// )?;
+    ///
+    /// terminal.insert_before(1, |buf| {
+    ///     buf.set_string(0, 0, "step 1 done", Default::default());
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_options(mut renderer: R, options: TerminalOptions) -> Result<Self> {
+        let screen = renderer.size()?;
+        let viewport_area = match options.viewport {
+            Viewport::Fullscreen => screen,
+            Viewport::Inline(height) => {
+                let (_, row) = renderer.cursor_position()?;
+                Self::reserve_inline(&mut renderer, screen, row, height)?
+            }
+        };
+
+        Ok(Self {
+            renderer,
+            viewport: options.viewport,
+            screen,
+            viewport_area,
+            last_frame: None,
+        })
+    }
+
+    /// Reserves `height` lines starting at `row`, scrolling the screen if they
+    /// do not fit below it, and returns the reserved area.
+    fn reserve_inline(renderer: &mut R, screen: Rect, row: u16, height: u16) -> Result<Rect> {
+        let height = height.min(screen.height);
+        let row = row.min(screen.height.saturating_sub(1));
+        if height > 0 {
+            renderer.set_cursor(0, row)?;
+            renderer.append_lines(height - 1)?;
+        }
+        let top = row.min(screen.height - height);
+
+        Ok(Rect::new(screen.x, screen.y + top, screen.width, height))
+    }
+
+    /// Re-anchors the viewport if the screen size changed since the last draw.
+    ///
+    /// An inline viewport keeps its top row if it still fits, and otherwise
+    /// moves up just enough to fit.
+    fn autoresize(&mut self) -> Result<()> {
+        let screen = self.renderer.size()?;
+        if screen == self.screen {
+            return Ok(());
+        }
+
+        self.renderer.resize(screen)?;
+        self.viewport_area = match self.viewport {
+            Viewport::Fullscreen => screen,
+            Viewport::Inline(height) => {
+                let height = height.min(screen.height);
+                let top = (self.viewport_area.y - self.screen.y).min(screen.height - height);
+                Rect::new(screen.x, screen.y + top, screen.width, height)
+            }
+        };
+        self.screen = screen;
+        self.last_frame = None;
+        Ok(())
     }
 
     /// Draws a frame using the provided render function.
     ///
     /// The render function receives a `Frame` which can be used to render widgets.
     /// After rendering, the frame's buffer is automatically drawn to the terminal.
+    ///
+    /// The frame area always starts at (0, 0), also for an inline viewport; the
+    /// viewport's position on screen is applied when the frame is drawn.
     pub fn draw<F>(&mut self, render_fn: F) -> Result<CompletedFrame>
     where
         F: FnOnce(&mut Frame),
     {
-        let size = self.renderer.size()?;
-        let mut buffer = Buffer::new(size);
-        let mut frame = Frame::new(&mut buffer, size);
+        self.autoresize()?;
+
+        let area = Rect::new(0, 0, self.viewport_area.width, self.viewport_area.height);
+        let mut buffer = Buffer::new(area);
+        let mut frame = Frame::new(&mut buffer, area);
 
         render_fn(&mut frame);
 
+        buffer.area = self.viewport_area;
         self.renderer.draw(&buffer)?;
         self.renderer.flush()?;
+        self.last_frame = Some(buffer);
 
         Ok(CompletedFrame {
-            area: size,
+            area: self.viewport_area,
         })
     }
 
+    /// Prints `height` lines above an inline viewport.
+    ///
+    /// `draw_fn` fills a buffer that is `height` lines tall and as wide as the
+    /// screen. The lines are drawn where the viewport was, and the viewport
+    /// moves down below them, scrolling the screen once it reaches the bottom,
+    /// so the lines end up in the terminal's scrollback. The last frame is then
+    /// drawn again in the new position.
+    ///
+    /// Does nothing for a fullscreen viewport.
+    pub fn insert_before<F>(&mut self, height: u16, draw_fn: F) -> Result<()>
+    where
+        F: FnOnce(&mut Buffer),
+    {
+        if self.viewport == Viewport::Fullscreen || height == 0 {
+            return Ok(());
+        }
+        self.autoresize()?;
+
+        let width = self.viewport_area.width;
+        let mut lines = Buffer::new(Rect::new(0, 0, width, height));
+        draw_fn(&mut lines);
+
+        // Draw the lines in chunks that fit on screen together with the
+        // viewport, scrolling before each chunk to make room for it.
+        let screen_bottom = self.screen.bottom();
+        let viewport_height = self.viewport_area.height;
+        let chunk_height = (self.screen.height - viewport_height).max(1);
+        let mut next = 0;
+        while next < height {
+            let rows = chunk_height.min(height - next);
+
+            let needed =
+                (self.viewport_area.y + rows + viewport_height).saturating_sub(screen_bottom);
+            let scroll = needed.min(self.viewport_area.y - self.screen.y);
+            self.scroll_up(scroll)?;
+
+            let area = Rect::new(self.viewport_area.x, self.viewport_area.y, width, rows);
+            let mut chunk = Buffer::new(area);
+            for y in 0..rows {
+                for x in 0..width {
+                    if let (Some(cell), Some(target)) = (lines.get(x, next + y), chunk.get_mut(x, y))
+                    {
+                        *target = cell.clone();
+                    }
+                }
+            }
+            self.renderer.draw(&chunk)?;
+            self.viewport_area.y += rows;
+            next += rows;
+
+            // Only happens when the viewport fills the whole screen
+            let overflow =
+                (self.viewport_area.y + viewport_height).saturating_sub(screen_bottom);
+            self.scroll_up(overflow)?;
+        }
+
+        if let Some(mut frame) = self.last_frame.take() {
+            frame.area = self.viewport_area;
+            self.renderer.draw(&frame)?;
+            self.last_frame = Some(frame);
+        }
+        self.renderer.flush()
+    }
+
+    /// Scrolls the screen up by `lines`, moving the viewport along with it.
+    fn scroll_up(&mut self, lines: u16) -> Result<()> {
+        if lines > 0 {
+            self.renderer.set_cursor(0, self.screen.bottom() - 1)?;
+            self.renderer.append_lines(lines)?;
+            self.viewport_area.y -= lines;
+        }
+        Ok(())
+    }
+
+    /// Returns the area of the screen that frames are drawn to.
+    pub fn viewport_area(&self) -> Rect {
+        self.viewport_area
+    }
+
     /// Gets the current terminal size.
     pub fn size(&self) -> Result<Rect> {
         self.renderer.size()
@@ -168,6 +368,157 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_terminal_draw_synchronized() {
+        let mut renderer = TestRenderer::new(20, 5);
+        renderer.set_synchronized_output(true);
+        let mut terminal = Terminal::new(renderer).unwrap();
+
+        for _ in 0..3 {
+            terminal
+                .draw(|f| f.render_widget(Paragraph::new("Hello"), f.area()))
+                .unwrap();
+        }
+
+        terminal.backend().assert_synchronized_frames();
+    }
+
+    fn inline(renderer: TestRenderer, height: u16) -> Terminal<TestRenderer> {
+        let options = TerminalOptions {
+            viewport: Viewport::Inline(height),
+        };
+        Terminal::with_options(renderer, options).unwrap()
+    }
+
+    fn lines(terminal: &Terminal<TestRenderer>) -> Vec<String> {
+        terminal
+            .backend()
+            .debug_output()
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_viewport_at_cursor() {
+        let mut renderer = TestRenderer::new(10, 6);
+        renderer.set_cursor(0, 1).unwrap();
+        let mut terminal = inline(renderer, 3);
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 1, 10, 3));
+
+        let completed = terminal
+            .draw(|f| {
+                assert_eq!(f.area(), Rect::new(0, 0, 10, 3));
+                f.render_widget(Paragraph::new("ui"), f.area());
+            })
+            .unwrap();
+        assert_eq!(completed.area, Rect::new(0, 1, 10, 3));
+        assert_eq!(lines(&terminal), vec!["", "ui", "", "", "", ""]);
+    }
+
+    #[test]
+    fn test_inline_viewport_scrolls_to_fit() {
+        let mut renderer = TestRenderer::new(10, 4);
+        let mut screen = Buffer::new(Rect::new(0, 0, 10, 4));
+        screen.set_string(0, 0, "$ one", Style::default());
+        screen.set_string(0, 2, "$ two", Style::default());
+        renderer.draw(&screen).unwrap();
+        renderer.set_cursor(0, 3).unwrap();
+
+        let terminal = inline(renderer, 2);
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 2, 10, 2));
+        assert_eq!(terminal.backend().scrollback(), &["$ one     ".to_string()]);
+        assert_eq!(lines(&terminal)[1], "$ two");
+    }
+
+    #[test]
+    fn test_insert_before_scrolls_into_history() {
+        let mut renderer = TestRenderer::new(10, 4);
+        renderer.set_cursor(0, 2).unwrap();
+        let mut terminal = inline(renderer, 2);
+        terminal
+            .draw(|f| f.render_widget(Paragraph::new("ui"), f.area()))
+            .unwrap();
+
+        for i in 0..3 {
+            terminal
+                .insert_before(1, |buf| {
+                    buf.set_string(0, 0, &format!("log {i}"), Style::default());
+                })
+                .unwrap();
+        }
+
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 2, 10, 2));
+        let scrollback: Vec<_> = terminal
+            .backend()
+            .scrollback()
+            .iter()
+            .map(|line| line.trim_end())
+            .collect();
+        assert_eq!(scrollback, vec!["", "", "log 0"]);
+        assert_eq!(lines(&terminal), vec!["log 1", "log 2", "ui", ""]);
+    }
+
+    #[test]
+    fn test_insert_before_more_lines_than_screen() {
+        let mut renderer = TestRenderer::new(10, 4);
+        renderer.set_cursor(0, 3).unwrap();
+        let mut terminal = inline(renderer, 2);
+        terminal
+            .insert_before(5, |buf| {
+                for y in 0..5 {
+                    buf.set_string(0, y, &format!("log {y}"), Style::default());
+                }
+            })
+            .unwrap();
+
+        let history: Vec<_> = terminal
+            .backend()
+            .scrollback()
+            .iter()
+            .map(|line| line.trim_end())
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(history, vec!["log 0", "log 1", "log 2"]);
+        assert_eq!(lines(&terminal), vec!["log 3", "log 4", "", ""]);
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 2, 10, 2));
+    }
+
+    #[test]
+    fn test_insert_before_fullscreen_is_noop() {
+        let mut terminal = Terminal::new(TestRenderer::new(10, 4)).unwrap();
+        terminal
+            .insert_before(1, |buf| {
+                buf.set_string(0, 0, "log", Style::default());
+            })
+            .unwrap();
+        assert!(terminal.backend().scrollback().is_empty());
+    }
+
+    #[test]
+    fn test_inline_viewport_reanchors_on_resize() {
+        let mut renderer = TestRenderer::new(10, 8);
+        renderer.set_cursor(0, 5).unwrap();
+        let mut terminal = inline(renderer, 3);
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 5, 10, 3));
+
+        terminal.backend_mut().resize(Rect::new(0, 0, 6, 6)).unwrap();
+        terminal.draw(|_| {}).unwrap();
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 3, 6, 3));
+
+        terminal.backend_mut().resize(Rect::new(0, 0, 6, 2)).unwrap();
+        terminal.draw(|_| {}).unwrap();
+        assert_eq!(terminal.viewport_area(), Rect::new(0, 0, 6, 2));
+    }
+
+    #[test]
+    fn test_fullscreen_follows_resize() {
+        let mut terminal = Terminal::new(TestRenderer::new(10, 4)).unwrap();
+        terminal.backend_mut().resize(Rect::new(0, 0, 20, 5)).unwrap();
+        let completed = terminal.draw(|_| {}).unwrap();
+        assert_eq!(completed.area, Rect::new(0, 0, 20, 5));
+    }
+
     #[test]
     fn test_frame_area() {
         let mut buffer = Buffer::new(Rect::new(0, 0, 80, 24));
@@ -3,17 +3,52 @@
 //! This module provides a renderer that stores output in memory,
 //! making it easy to test TUI applications without a real terminal.
 
-use fusabi_tui_core::buffer::Buffer;
+use fusabi_tui_core::buffer::{Buffer, Hyperlink};
 use fusabi_tui_core::layout::Rect;
 
 use crate::error::Result;
 use crate::renderer::Renderer;
 
+/// A rendering step recorded by [`TestRenderer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameEvent {
+    /// A synchronized update was begun
+    BeginSynchronizedUpdate,
+    /// A buffer was drawn
+    Draw,
+    /// A synchronized update was ended
+    EndSynchronizedUpdate,
+    /// Output was flushed
+    Flush,
+}
+
+/// A run of adjacent linked cells on one row of a [`TestRenderer`] screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The cells of the run, one row high
+    pub area: Rect,
+    /// The text of the cells
+    pub text: String,
+    /// The link target
+    pub hyperlink: Hyperlink,
+}
+
 /// A test renderer that stores output in memory.
 ///
 /// This renderer is useful for unit testing TUI applications without
 /// needing a real terminal. It maintains an internal buffer and cursor state
 /// that can be inspected after rendering operations.
+///
+/// Buffers smaller than the screen are drawn at the origin of their area, and
+/// lines scrolled off the top by [`Renderer::append_lines`] are kept as
+/// scrollback text.
+///
+/// It also records draws, flushes and synchronized-update boundaries as
+/// [`FrameEvent`]s, using the same rules as the crossterm backend: with
+/// synchronized output enabled, a `draw` begins a synchronized update and the
+/// next `flush` ends it.
+///
+/// Hyperlinks drawn on the screen are listed by [`TestRenderer::links`].
 #[derive(Debug, Clone)]
 pub struct TestRenderer {
     /// The internal buffer
@@ -22,6 +57,14 @@ pub struct TestRenderer {
     cursor: (u16, u16),
     /// Whether the cursor is visible
     cursor_visible: bool,
+    /// Whether synchronized output is enabled
+    synchronized: bool,
+    /// Whether a synchronized update is open
+    in_synchronized_update: bool,
+    /// Recorded frame events
+    events: Vec<FrameEvent>,
+    /// Lines scrolled off the top of the screen, oldest first
+    scrollback: Vec<String>,
 }
 
 impl TestRenderer {
@@ -31,6 +74,10 @@ impl TestRenderer {
             buffer: Buffer::new(Rect::new(0, 0, width, height)),
             cursor: (0, 0),
             cursor_visible: true,
+            synchronized: false,
+            in_synchronized_update: false,
+            events: Vec::new(),
+            scrollback: Vec::new(),
         }
     }
 
@@ -51,6 +98,102 @@ impl TestRenderer {
         self.cursor_visible
     }
 
+    /// Returns the lines scrolled off the top of the screen, oldest first.
+    pub fn scrollback(&self) -> &[String] {
+        &self.scrollback
+    }
+
+    /// Returns the frame events recorded so far.
+    pub fn events(&self) -> &[FrameEvent] {
+        &self.events
+    }
+
+    /// Forgets the recorded frame events.
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// Asserts that every draw happened inside exactly one synchronized update,
+    /// and that each synchronized update contains exactly one draw.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the recorded events if a draw happened outside a
+    /// synchronized update, updates were nested or left open, or an update
+    /// contained no draw or several draws.
+    pub fn assert_synchronized_frames(&self) {
+        let mut draws_in_update = None;
+        for (i, event) in self.events.iter().enumerate() {
+            let ok = match (event, draws_in_update) {
+                (FrameEvent::BeginSynchronizedUpdate, None) => {
+                    draws_in_update = Some(0);
+                    true
+                }
+                (FrameEvent::Draw, Some(0)) => {
+                    draws_in_update = Some(1);
+                    true
+                }
+                (FrameEvent::EndSynchronizedUpdate, Some(1)) => {
+                    draws_in_update = None;
+                    true
+                }
+                (FrameEvent::Flush, _) => true,
+                _ => false,
+            };
+            assert!(
+                ok,
+                "Unexpected {:?} at event {} in {:?}",
+                event, i, self.events
+            );
+        }
+        assert!(
+            draws_in_update.is_none(),
+            "Synchronized update left open: {:?}",
+            self.events
+        );
+    }
+
+    /// Returns the hyperlinks on the screen, as runs of linked cells in
+    /// reading order.
+    pub fn links(&self) -> Vec<Link> {
+        self.buffer
+            .hyperlinks()
+            .into_iter()
+            .map(|(area, hyperlink)| Link {
+                area,
+                text: (area.left()..area.right())
+                    .filter_map(|x| self.buffer.get(x, area.y))
+                    .map(|cell| cell.symbol.as_str())
+                    .collect(),
+                hyperlink: hyperlink.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the hyperlink of the cell at the given position.
+    pub fn link_at(&self, x: u16, y: u16) -> Option<&Hyperlink> {
+        self.buffer.get(x, y).and_then(|cell| cell.hyperlink.as_ref())
+    }
+
+    /// Asserts that the text `text` is on the screen as a link to `uri`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the links on the screen if no run of linked cells has
+    /// this text and URI.
+    pub fn assert_link(&self, text: &str, uri: &str) {
+        let links = self.links();
+        assert!(
+            links
+                .iter()
+                .any(|link| link.text == text && link.hyperlink.uri() == uri),
+            "No link `{}` to {} in {:#?}",
+            text,
+            uri,
+            links
+        );
+    }
+
     /// Asserts that the internal buffer matches the expected buffer.
     ///
     /// This is a convenience method for tests that panics with a helpful
@@ -76,28 +219,67 @@ impl TestRenderer {
     pub fn debug_output(&self) -> String {
         let mut output = String::new();
         for y in 0..self.buffer.area.height {
-            for x in 0..self.buffer.area.width {
-                if let Some(cell) = self.buffer.get(x, y) {
-                    output.push_str(&cell.symbol);
-                }
-            }
+            output.push_str(&self.row_text(y));
             if y < self.buffer.area.height - 1 {
                 output.push('\n');
             }
         }
         output
     }
+
+    /// Returns the symbols of one screen row joined together.
+    fn row_text(&self, y: u16) -> String {
+        (0..self.buffer.area.width)
+            .filter_map(|x| self.buffer.get(x, y))
+            .map(|cell| cell.symbol.as_str())
+            .collect()
+    }
+
+    /// Moves every row up by one, saving the top row as scrollback.
+    fn scroll_up(&mut self) {
+        self.scrollback.push(self.row_text(0));
+        let area = self.buffer.area;
+        for y in 1..area.height {
+            for x in 0..area.width {
+                if let Some(cell) = self.buffer.get(x, y).cloned() {
+                    if let Some(above) = self.buffer.get_mut(x, y - 1) {
+                        *above = cell;
+                    }
+                }
+            }
+        }
+        for x in 0..area.width {
+            if let Some(cell) = self.buffer.get_mut(x, area.height - 1) {
+                cell.reset();
+            }
+        }
+    }
 }
 
 impl Renderer for TestRenderer {
     fn draw(&mut self, buffer: &Buffer) -> Result<()> {
-        // Simply copy the buffer
-        self.buffer = buffer.clone();
+        if self.synchronized && !self.in_synchronized_update {
+            self.events.push(FrameEvent::BeginSynchronizedUpdate);
+            self.in_synchronized_update = true;
+        }
+        let screen = self.buffer.area;
+        if buffer.area != screen && screen.union(buffer.area) == screen {
+            // Partial buffers are placed at their origin
+            self.buffer.merge(buffer);
+        } else {
+            // Simply copy the buffer
+            self.buffer = buffer.clone();
+        }
+        self.events.push(FrameEvent::Draw);
         Ok(())
     }
 
     fn flush(&mut self) -> Result<()> {
-        // No-op for test renderer
+        if self.in_synchronized_update {
+            self.events.push(FrameEvent::EndSynchronizedUpdate);
+            self.in_synchronized_update = false;
+        }
+        self.events.push(FrameEvent::Flush);
         Ok(())
     }
 
@@ -119,6 +301,34 @@ impl Renderer for TestRenderer {
         self.cursor = (x, y);
         Ok(())
     }
+
+    fn resize(&mut self, area: Rect) -> Result<()> {
+        self.buffer.resize(area);
+        Ok(())
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        Ok(self.cursor)
+    }
+
+    fn append_lines(&mut self, count: u16) -> Result<()> {
+        for _ in 0..count {
+            if self.cursor.1 + 1 < self.buffer.area.height {
+                self.cursor.1 += 1;
+            } else {
+                self.scroll_up();
+            }
+        }
+        Ok(())
+    }
+
+    fn set_synchronized_output(&mut self, enabled: bool) {
+        self.synchronized = enabled;
+    }
+
+    fn synchronized_output(&self) -> bool {
+        self.synchronized
+    }
 }
 
 #[cfg(test)]
@@ -217,6 +427,79 @@ mod tests {
         renderer.assert_buffer(&buffer2);
     }
 
+    #[test]
+    fn test_synchronized_frames() {
+        let mut renderer = TestRenderer::new(5, 1);
+        renderer.set_synchronized_output(true);
+        let buffer = Buffer::new(Rect::new(0, 0, 5, 1));
+
+        for _ in 0..2 {
+            renderer.draw(&buffer).unwrap();
+            renderer.flush().unwrap();
+        }
+
+        assert_eq!(
+            renderer.events(),
+            &[
+                FrameEvent::BeginSynchronizedUpdate,
+                FrameEvent::Draw,
+                FrameEvent::EndSynchronizedUpdate,
+                FrameEvent::Flush,
+                FrameEvent::BeginSynchronizedUpdate,
+                FrameEvent::Draw,
+                FrameEvent::EndSynchronizedUpdate,
+                FrameEvent::Flush,
+            ]
+        );
+        renderer.assert_synchronized_frames();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected Draw")]
+    fn test_unsynchronized_draw_detected() {
+        let mut renderer = TestRenderer::new(5, 1);
+        renderer.draw(&Buffer::new(Rect::new(0, 0, 5, 1))).unwrap();
+        renderer.assert_synchronized_frames();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected Draw")]
+    fn test_two_draws_in_one_update_detected() {
+        let mut renderer = TestRenderer::new(5, 1);
+        renderer.set_synchronized_output(true);
+        let buffer = Buffer::new(Rect::new(0, 0, 5, 1));
+        renderer.draw(&buffer).unwrap();
+        renderer.draw(&buffer).unwrap();
+        renderer.flush().unwrap();
+        renderer.assert_synchronized_frames();
+    }
+
+    #[test]
+    fn test_draw_partial_buffer() {
+        let mut renderer = TestRenderer::new(5, 3);
+        let mut buffer = Buffer::new(Rect::new(0, 2, 5, 1));
+        buffer.set_string(0, 0, "Hi", Style::default());
+        renderer.draw(&buffer).unwrap();
+
+        assert_eq!(renderer.debug_output(), "     \n     \nHi   ");
+    }
+
+    #[test]
+    fn test_append_lines_scrolls() {
+        let mut renderer = TestRenderer::new(3, 2);
+        let mut buffer = Buffer::new(Rect::new(0, 0, 3, 2));
+        buffer.set_string(0, 0, "ab", Style::default());
+        buffer.set_string(0, 1, "cd", Style::default());
+        renderer.draw(&buffer).unwrap();
+
+        renderer.set_cursor(0, 0).unwrap();
+        renderer.append_lines(2).unwrap();
+
+        assert_eq!(renderer.cursor_position().unwrap(), (0, 1));
+        assert_eq!(renderer.scrollback(), &["ab ".to_string()]);
+        assert_eq!(renderer.debug_output(), "cd \n   ");
+    }
+
     #[test]
     fn test_debug_output() {
         let mut renderer = TestRenderer::new(5, 3);
@@ -233,4 +516,32 @@ mod tests {
         assert!(lines[0].starts_with("Hello"));
         assert!(lines[1].starts_with("World"));
     }
+
+    #[test]
+    fn test_links() {
+        let mut renderer = TestRenderer::new(12, 2);
+        let mut buffer = Buffer::new(Rect::new(0, 0, 12, 2));
+        let ticket = Hyperlink::new("https://tracker.example/42");
+        buffer.set_string(0, 1, "see #42 now", Style::default());
+        buffer.set_hyperlink(Rect::new(4, 1, 3, 1), Some(&ticket));
+        renderer.draw(&buffer).unwrap();
+
+        assert_eq!(
+            renderer.links(),
+            vec![Link {
+                area: Rect::new(4, 1, 3, 1),
+                text: "#42".to_string(),
+                hyperlink: ticket.clone(),
+            }]
+        );
+        assert_eq!(renderer.link_at(5, 1), Some(&ticket));
+        assert_eq!(renderer.link_at(3, 1), None);
+        renderer.assert_link("#42", "https://tracker.example/42");
+    }
+
+    #[test]
+    #[should_panic(expected = "No link `#43`")]
+    fn test_assert_link_failure() {
+        TestRenderer::new(4, 1).assert_link("#43", "https://tracker.example/43");
+    }
 }
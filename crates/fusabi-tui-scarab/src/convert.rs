@@ -186,6 +186,7 @@ pub fn shared_to_tui_cell(cell: &SharedCell) -> TuiCell {
         fg,
         bg,
         modifier,
+        ..TuiCell::default()
     }
 }
 
@@ -253,6 +254,7 @@ mod tests {
             fg: Color::Red,
             bg: Color::Black,
             modifier: Modifier::BOLD,
+            ..TuiCell::default()
         };
 
         let shared_cell = SharedCell::from(&tui_cell);
@@ -284,6 +286,7 @@ mod tests {
             fg: Color::Cyan,
             bg: Color::DarkGray,
             modifier: Modifier::BOLD | Modifier::UNDERLINED,
+            ..TuiCell::default()
         };
 
         let shared = SharedCell::from(&original);
@@ -251,6 +251,7 @@ mod tests {
             fg: Color::Green,
             bg: Color::Black,
             modifier: Modifier::ITALIC,
+            ..Default::default()
         };
 
         let shared_cell = SharedCell::from(&tui_cell);
@@ -11,6 +11,7 @@ use fusabi_tui_core::{
 };
 use unicode_width::UnicodeWidthStr;
 
+use crate::text::set_truncated;
 use crate::widget::Widget;
 
 /// Direction for rendering bars.
@@ -387,16 +388,8 @@ impl BarChart {
                     self.value_style
                 };
 
-                for (i, ch) in text.chars().enumerate() {
-                    let cell_x = text_x.saturating_add(i as u16);
-                    if cell_x >= area.right() {
-                        break;
-                    }
-                    if let Some(cell) = buf.get_mut(cell_x, text_y) {
-                        cell.symbol = ch.to_string();
-                        cell.set_style(value_style);
-                    }
-                }
+                let max_width = area.right().saturating_sub(text_x);
+                set_truncated(buf, text_x, text_y, text, max_width, value_style);
             }
         }
 
@@ -407,16 +400,8 @@ impl BarChart {
                 let label_x = x + (self.bar_width.saturating_sub(label_width as u16)) / 2;
                 let label_y = area.y + area.height - 1;
 
-                for (i, ch) in label.chars().enumerate() {
-                    let cell_x = label_x.saturating_add(i as u16);
-                    if cell_x >= area.right() {
-                        break;
-                    }
-                    if let Some(cell) = buf.get_mut(cell_x, label_y) {
-                        cell.symbol = ch.to_string();
-                        cell.set_style(Style::default());
-                    }
-                }
+                let max_width = area.right().saturating_sub(label_x);
+                set_truncated(buf, label_x, label_y, label, max_width, Style::default());
             }
         }
     }
@@ -481,16 +466,9 @@ impl Widget for BarChart {
                                 + (group_width.saturating_sub(label_width as u16)) / 2;
                             let label_y = area.y + area.height - 1;
 
-                            for (i, ch) in label.chars().enumerate() {
-                                let cell_x = label_x.saturating_add(i as u16);
-                                if cell_x >= area.right() {
-                                    break;
-                                }
-                                if let Some(cell) = buf.get_mut(cell_x, label_y) {
-                                    cell.symbol = ch.to_string();
-                                    cell.set_style(Style::default());
-                                }
-                            }
+                            let max_width = area.right().saturating_sub(label_x);
+                            let style = Style::default();
+                            set_truncated(buf, label_x, label_y, label, max_width, style);
                         }
                     }
                 }
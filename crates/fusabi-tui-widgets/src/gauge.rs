@@ -9,9 +9,11 @@ use fusabi_tui_core::{
     style::Style,
     symbols::block,
 };
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::block::Block;
+use crate::text::set_truncated;
 use crate::widget::Widget;
 
 /// Set of characters used to render a gauge.
@@ -216,31 +218,32 @@ impl Widget for Gauge {
                     (gauge_area.width.saturating_sub(label_width as u16)) / 2,
                 );
 
-                for (i, ch) in label.chars().enumerate() {
-                    let x = label_x.saturating_add(i as u16);
-                    if x >= gauge_area.right() {
-                        break;
-                    }
-                    if let Some(cell) = buf.get_mut(x, gauge_area.y) {
-                        cell.symbol = ch.to_string();
-                        // Keep the background style but use label foreground
-                        // Determine if this position is in the filled or unfilled area
-                        if x < gauge_area.x.saturating_add(filled_width) {
-                            // In filled area - use gauge style background with inverted foreground
-                            let mut label_style = self.gauge_style;
-                            if let Some(fg) = self.style.fg {
-                                label_style.fg = Some(fg);
-                            }
-                            cell.set_style(label_style);
-                        } else {
-                            // In unfilled area - use normal style
-                            let mut label_style = self.style;
-                            if let Some(fg) = self.gauge_style.fg {
-                                label_style.fg = Some(fg);
-                            }
-                            cell.set_style(label_style);
+                let mut x = label_x;
+                for grapheme in label.graphemes(true) {
+                    let max_width = gauge_area.right().saturating_sub(x);
+                    // Keep the background style but use label foreground
+                    // Determine if this position is in the filled or unfilled area
+                    let label_style = if x < gauge_area.x.saturating_add(filled_width) {
+                        // In filled area - use gauge style background with inverted foreground
+                        let mut label_style = self.gauge_style;
+                        if let Some(fg) = self.style.fg {
+                            label_style.fg = Some(fg);
+                        }
+                        label_style
+                    } else {
+                        // In unfilled area - use normal style
+                        let mut label_style = self.style;
+                        if let Some(fg) = self.gauge_style.fg {
+                            label_style.fg = Some(fg);
                         }
+                        label_style
+                    };
+                    let written =
+                        set_truncated(buf, x, gauge_area.y, grapheme, max_width, label_style);
+                    if written == 0 && grapheme.width() > 0 {
+                        break;
                     }
+                    x += written;
                 }
             }
         }
@@ -369,6 +372,20 @@ mod tests {
         assert_eq!(buffer.get(label_start + 2, 0).unwrap().symbol, "%");
     }
 
+    #[test]
+    fn test_gauge_render_wide_label() {
+        let gauge = Gauge::new().ratio(0.0).label("界e\u{301}");
+
+        let area = Rect::new(0, 0, 5, 1);
+        let mut buffer = Buffer::new(area);
+        gauge.render(area, &mut buffer);
+
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, "界");
+        assert!(buffer.get(2, 0).unwrap().continuation);
+        assert_eq!(buffer.get(3, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(4, 0).unwrap().symbol, " ");
+    }
+
     #[test]
     fn test_gauge_char_set() {
         let gauge1 = Gauge::new().char_set(GaugeCharSet::Full);
@@ -76,7 +76,9 @@ impl<'a> From<Text<'a>> for ListItem<'a> {
 
 /// State for a stateful list widget.
 ///
-/// Tracks the currently selected item and scroll offset.
+/// Tracks the currently selected item and scroll offset. This is also the
+/// list state the dashboard engine keeps per widget, so it can be passed
+/// straight to [`StatefulWidget::render`].
 ///
 /// # Examples
 ///
@@ -86,7 +88,7 @@ impl<'a> From<Text<'a>> for ListItem<'a> {
 /// let mut state = ListState::default();
 /// state.select(Some(0));
 /// ```
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ListState {
     /// The index of the selected item
     selected: Option<usize>,
@@ -100,6 +102,15 @@ impl ListState {
         Self::default()
     }
 
+    /// Creates a list state with an item selected.
+    #[must_use]
+    pub fn with_selected(index: usize) -> Self {
+        Self {
+            selected: Some(index),
+            offset: 0,
+        }
+    }
+
     /// Returns the currently selected index.
     pub fn selected(&self) -> Option<usize> {
         self.selected
@@ -125,15 +136,7 @@ impl ListState {
     /// If no item is selected, selects the first item.
     /// If the last item is selected, wraps around to the first item.
     pub fn select_next(&mut self, len: usize) {
-        if len == 0 {
-            self.selected = None;
-            return;
-        }
-
-        self.selected = Some(match self.selected {
-            Some(i) => (i + 1) % len,
-            None => 0,
-        });
+        self.selected = next_index(self.selected, len);
     }
 
     /// Selects the previous item in the list.
@@ -141,16 +144,7 @@ impl ListState {
     /// If no item is selected, selects the last item.
     /// If the first item is selected, wraps around to the last item.
     pub fn select_previous(&mut self, len: usize) {
-        if len == 0 {
-            self.selected = None;
-            return;
-        }
-
-        self.selected = Some(match self.selected {
-            Some(0) => len - 1,
-            Some(i) => i - 1,
-            None => len - 1,
-        });
+        self.selected = previous_index(self.selected, len);
     }
 
     /// Selects the first item in the list.
@@ -167,6 +161,28 @@ impl ListState {
     }
 }
 
+/// The index after `current` among `len` items, wrapping around.
+///
+/// Starts at the first item if nothing is selected.
+pub(crate) fn next_index(current: Option<usize>, len: usize) -> Option<usize> {
+    match current {
+        _ if len == 0 => None,
+        Some(i) => Some((i + 1) % len),
+        None => Some(0),
+    }
+}
+
+/// The index before `current` among `len` items, wrapping around.
+///
+/// Starts at the last item if nothing is selected.
+pub(crate) fn previous_index(current: Option<usize>, len: usize) -> Option<usize> {
+    match current {
+        _ if len == 0 => None,
+        Some(0) | None => Some(len - 1),
+        Some(i) => Some(i - 1),
+    }
+}
+
 /// A scrollable list widget with selection support.
 ///
 /// # Examples
@@ -258,6 +274,12 @@ impl<'a> List<'a> {
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
+
+    /// Returns the area the items are drawn in when the list is rendered
+    /// into `area`: the inner area of the block, if any.
+    pub fn rows_area(&self, area: Rect) -> Rect {
+        self.block.as_ref().map_or(area, |block| block.inner(area))
+    }
 }
 
 impl Widget for List<'_> {
@@ -348,13 +370,8 @@ impl StatefulWidget for List<'_> {
                     };
 
                     // Render span content
-                    for ch in span.content.chars() {
-                        if line_x >= x + content_width {
-                            break;
-                        }
-                        buf.set_string(line_x, current_y, &ch.to_string(), span_style);
-                        line_x += unicode_width::UnicodeWidthStr::width(ch.to_string().as_str()) as u16;
-                    }
+                    let max_width = (x + content_width).saturating_sub(line_x);
+                    line_x += span.render(buf, line_x, current_y, max_width, span_style);
                 }
 
                 // Fill remaining space with highlight style if selected
@@ -379,6 +396,7 @@ impl StatefulWidget for List<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::borders::Borders;
     use fusabi_tui_core::style::Color;
 
     #[test]
@@ -387,6 +405,15 @@ mod tests {
         assert_eq!(item.content.lines.len(), 1);
     }
 
+    #[test]
+    fn test_list_rows_area() {
+        let area = Rect::new(0, 0, 10, 5);
+        let list = List::new(vec![ListItem::new("a"), ListItem::new("b")]);
+        assert_eq!(list.rows_area(area), area);
+        let list = list.block(Block::default().borders(Borders::ALL));
+        assert_eq!(list.rows_area(area), Rect::new(1, 1, 8, 3));
+    }
+
     #[test]
     fn test_list_item_from_str() {
         let item: ListItem = "test".into();
@@ -495,4 +522,20 @@ mod tests {
         state.select_last(5);
         assert_eq!(state.selected(), Some(4));
     }
+
+    #[test]
+    fn test_list_render_graphemes() {
+        let coder = "\u{1F469}\u{200D}\u{1F4BB}";
+        let list = List::new(vec![ListItem::new(format!("e\u{301}{coder}{coder}"))]);
+
+        // The second emoji does not fit and is left out whole
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buffer = Buffer::new(area);
+        Widget::render(&list, area, &mut buffer);
+
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, coder);
+        assert!(buffer.get(2, 0).unwrap().continuation);
+        assert_eq!(buffer.get(3, 0).unwrap().symbol, " ");
+    }
 }
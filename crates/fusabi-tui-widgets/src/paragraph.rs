@@ -5,6 +5,7 @@ use fusabi_tui_core::{
     layout::Rect,
     style::Style,
 };
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
@@ -154,28 +155,31 @@ impl<'a> Paragraph<'a> {
 
         for span in &line.spans {
             let content = span.content.as_ref();
-            let mut chars = content.chars().peekable();
+            let mut graphemes = content.graphemes(true).peekable();
 
-            while chars.peek().is_some() {
+            while graphemes.peek().is_some() {
                 let mut segment = String::new();
                 let mut segment_width = 0;
 
-                while let Some(&ch) = chars.peek() {
-                    let ch_width = ch.to_string().width();
-                    if current_width + segment_width + ch_width > width {
+                while let Some(&grapheme) = graphemes.peek() {
+                    let grapheme_width = grapheme.width();
+                    // A cluster wider than the whole line still gets a line of
+                    // its own, so wrapping always makes progress.
+                    let used = current_width + segment_width;
+                    if used + grapheme_width > width && used > 0 {
                         break;
                     }
-                    segment.push(ch);
-                    segment_width += ch_width;
-                    chars.next();
+                    segment.push_str(grapheme);
+                    segment_width += grapheme_width;
+                    graphemes.next();
                 }
 
                 if !segment.is_empty() {
-                    current_line.push(Span::styled(segment, span.style));
+                    current_line.push(span.with_content(segment));
                     current_width += segment_width;
                 }
 
-                if current_width >= width || chars.peek().is_some() {
+                if current_width >= width || graphemes.peek().is_some() {
                     if !current_line.is_empty() {
                         wrapped.push(Line::from_spans(current_line));
                         current_line = Vec::new();
@@ -219,7 +223,7 @@ impl<'a> Paragraph<'a> {
                 if word_width > width {
                     // Word is too long, wrap at character boundary
                     let char_wrapped = self.wrap_line_char(
-                        &Line::from(Span::styled(word, span.style)),
+                        &Line::from(span.with_content(word)),
                         width,
                     );
                     for (i, char_line) in char_wrapped.into_iter().enumerate() {
@@ -232,7 +236,7 @@ impl<'a> Paragraph<'a> {
                         current_width = current_line.iter().map(Span::width).sum();
                     }
                 } else {
-                    current_line.push(Span::styled(word, span.style));
+                    current_line.push(span.with_content(word));
                     current_width += word_width;
                 }
             }
@@ -307,33 +311,24 @@ impl Widget for Paragraph<'_> {
                     continue;
                 }
 
-                let content = if skip_width > 0 {
-                    // Skip some characters from this span
-                    let mut skipped = 0;
-                    let mut chars_to_skip = 0;
-                    for ch in span.content.chars() {
-                        let ch_width = ch.to_string().width();
-                        if skipped + ch_width > skip_width {
-                            break;
-                        }
-                        skipped += ch_width;
-                        chars_to_skip += 1;
+                // Skip the grapheme clusters scrolled out of view
+                let mut skipped = 0;
+                let mut start = 0;
+                for (i, grapheme) in span.content.grapheme_indices(true) {
+                    let grapheme_width = grapheme.width();
+                    if skipped + grapheme_width > skip_width {
+                        start = i;
+                        break;
                     }
-                    span.content.chars().skip(chars_to_skip).collect::<String>()
-                } else {
-                    span.content.to_string()
-                };
+                    skipped += grapheme_width;
+                }
+                let visible = span.with_content(&span.content[start..]);
 
                 skip_width = 0;
 
                 // Render the span
-                for ch in content.chars() {
-                    if x >= text_area.x + text_area.width {
-                        break;
-                    }
-                    buf.set_string(x, y, &ch.to_string(), span.style);
-                    x += ch.to_string().width() as u16;
-                }
+                let max_width = (text_area.x + text_area.width).saturating_sub(x);
+                x += visible.render(buf, x, y, max_width, span.style);
             }
         }
     }
@@ -386,6 +381,28 @@ mod tests {
         assert_eq!(buffer.get(0, 1).unwrap().symbol, "W");
     }
 
+    #[test]
+    fn test_paragraph_render_hyperlink() {
+        use fusabi_tui_core::buffer::Hyperlink;
+
+        let link = Hyperlink::new("https://docs.example/guide");
+        let line = Line::from(vec![
+            Span::raw("See "),
+            Span::raw("the guide").hyperlink(link.clone()),
+        ]);
+        let p = Paragraph::new(line).wrap(Wrap::WordWrap);
+
+        let area = Rect::new(0, 0, 8, 2);
+        let mut buffer = Buffer::new(area);
+        p.render(area, &mut buffer);
+
+        // The link is kept on both lines of the wrapped span
+        assert_eq!(
+            buffer.hyperlinks(),
+            vec![(Rect::new(4, 0, 4, 1), &link), (Rect::new(0, 1, 5, 1), &link)]
+        );
+    }
+
     #[test]
     fn test_wrap_line_char() {
         let p = Paragraph::new("").wrap(Wrap::Wrap);
@@ -435,4 +452,41 @@ mod tests {
         assert_eq!(buffer.get(0, 0).unwrap().symbol, "L");
         assert_eq!(buffer.get(4, 0).unwrap().symbol, "2");
     }
+
+    #[test]
+    fn test_paragraph_render_graphemes() {
+        let coder = "\u{1F469}\u{200D}\u{1F4BB}";
+        let p = Paragraph::new(format!("e\u{301}{coder}!"));
+
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buffer = Buffer::new(area);
+        p.render(area, &mut buffer);
+
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, coder);
+        assert!(buffer.get(2, 0).unwrap().continuation);
+        assert_eq!(buffer.get(3, 0).unwrap().symbol, "!");
+    }
+
+    #[test]
+    fn test_paragraph_wrap_and_scroll_graphemes() {
+        let coder = "\u{1F469}\u{200D}\u{1F4BB}";
+        let text = format!("ae\u{301}{coder}");
+
+        // Wrapping never splits a cluster, even when it overflows the line
+        let p = Paragraph::new(text.as_str()).wrap(Wrap::Wrap);
+        let area = Rect::new(0, 0, 3, 2);
+        let mut buffer = Buffer::new(area);
+        p.render(area, &mut buffer);
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(0, 1).unwrap().symbol, coder);
+
+        // Scrolling skips whole clusters
+        let p = Paragraph::new(text.as_str()).scroll(1, 0);
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buffer = Buffer::new(area);
+        p.render(area, &mut buffer);
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, coder);
+    }
 }
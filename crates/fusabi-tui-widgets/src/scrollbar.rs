@@ -228,6 +228,40 @@ impl Scrollbar {
         self
     }
 
+    /// Returns the cells the track and thumb occupy when rendered to `area`,
+    /// leaving out the begin and end symbols.
+    ///
+    /// Useful for mapping mouse positions to scroll positions.
+    pub fn track_area(&self, area: Rect) -> Rect {
+        if area.width == 0 || area.height == 0 {
+            return Rect::new(area.x, area.y, 0, 0);
+        }
+
+        let vertical = matches!(
+            self.orientation,
+            ScrollbarOrientation::VerticalRight | ScrollbarOrientation::VerticalLeft
+        );
+        let length = if vertical { area.height } else { area.width };
+        let begin = u16::from(self.begin_symbol.is_some());
+        let end = u16::from(self.end_symbol.is_some() && length > begin);
+        let track_length = length - begin - end;
+
+        match self.orientation {
+            ScrollbarOrientation::VerticalRight => {
+                Rect::new(area.right() - 1, area.y + begin, 1, track_length)
+            }
+            ScrollbarOrientation::VerticalLeft => {
+                Rect::new(area.x, area.y + begin, 1, track_length)
+            }
+            ScrollbarOrientation::HorizontalBottom => {
+                Rect::new(area.x + begin, area.bottom() - 1, track_length, 1)
+            }
+            ScrollbarOrientation::HorizontalTop => {
+                Rect::new(area.x + begin, area.y, track_length, 1)
+            }
+        }
+    }
+
     /// Sets the symbol displayed at the beginning of the scrollbar.
     pub fn begin_symbol<T>(mut self, symbol: Option<T>) -> Self
     where
@@ -603,4 +637,16 @@ mod tests {
         // Last cell should be end symbol (right arrow)
         assert_eq!(buffer.get(9, 0).unwrap().symbol, arrow::RIGHT);
     }
+
+    #[test]
+    fn test_scrollbar_track_area() {
+        let area = Rect::new(0, 0, 10, 20);
+        assert_eq!(Scrollbar::default().track_area(area), Rect::new(9, 1, 1, 18));
+
+        let bare = Scrollbar::default()
+            .orientation(ScrollbarOrientation::HorizontalBottom)
+            .begin_symbol(None::<&str>)
+            .end_symbol(None::<&str>);
+        assert_eq!(bare.track_area(area), Rect::new(0, 19, 10, 1));
+    }
 }
@@ -10,6 +10,8 @@ use fusabi_tui_core::{
 };
 use unicode_width::UnicodeWidthStr;
 
+use crate::list::{next_index, previous_index};
+use crate::text::truncate_to_width;
 use crate::widget::{StatefulWidget, Widget};
 
 /// A cell within a table row.
@@ -114,10 +116,16 @@ impl Row {
 
 /// State for a stateful table widget.
 ///
-/// Tracks the selected row and scroll offset.
-#[derive(Debug, Clone, Default)]
+/// Tracks the selected row and column and the scroll offset. This is also
+/// the table state the dashboard engine keeps per widget, so it can be
+/// passed straight to [`StatefulWidget::render`].
+///
+/// Like [`ListState`](crate::list::ListState), moving the selection past the
+/// last row or column wraps around to the first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct TableState {
     selected: Option<usize>,
+    selected_column: Option<usize>,
     offset: usize,
 }
 
@@ -127,6 +135,15 @@ impl TableState {
         Self::default()
     }
 
+    /// Creates a table state with a row selected.
+    #[must_use]
+    pub fn with_selected(row: usize) -> Self {
+        Self {
+            selected: Some(row),
+            ..Self::default()
+        }
+    }
+
     /// Returns the index of the selected row.
     pub fn selected(&self) -> Option<usize> {
         self.selected
@@ -137,6 +154,17 @@ impl TableState {
         self.selected = index;
     }
 
+    /// Returns the index of the selected column.
+    #[must_use]
+    pub fn selected_column(&self) -> Option<usize> {
+        self.selected_column
+    }
+
+    /// Selects a column by index.
+    pub fn select_column(&mut self, index: Option<usize>) {
+        self.selected_column = index;
+    }
+
     /// Returns the scroll offset.
     pub fn offset(&self) -> usize {
         self.offset
@@ -146,6 +174,27 @@ impl TableState {
     pub fn set_offset(&mut self, offset: usize) {
         self.offset = offset;
     }
+
+    /// Selects the next of `len` rows, or the first if none is selected.
+    pub fn select_next(&mut self, len: usize) {
+        self.selected = next_index(self.selected, len);
+    }
+
+    /// Selects the previous of `len` rows, or the last if none is selected.
+    pub fn select_previous(&mut self, len: usize) {
+        self.selected = previous_index(self.selected, len);
+    }
+
+    /// Selects the next of `len` columns, or the first if none is selected.
+    pub fn select_next_column(&mut self, len: usize) {
+        self.selected_column = next_index(self.selected_column, len);
+    }
+
+    /// Selects the previous of `len` columns, or the last if none is
+    /// selected.
+    pub fn select_previous_column(&mut self, len: usize) {
+        self.selected_column = previous_index(self.selected_column, len);
+    }
 }
 
 /// A table widget for displaying tabular data.
@@ -229,6 +278,24 @@ impl Table {
         self
     }
 
+    /// Returns the number of rows, not counting the header.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns whether the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the area the rows are drawn in when the table is rendered
+    /// into `area`: the area below the header, if any.
+    pub fn rows_area(&self, area: Rect) -> Rect {
+        let header = self.header.as_ref().map_or(0, Row::total_height);
+        let header = header.min(area.height);
+        Rect::new(area.x, area.y + header, area.width, area.height - header)
+    }
+
     /// Calculates column widths based on constraints and available space.
     fn calculate_widths(&self, max_width: u16) -> Vec<u16> {
         if self.widths.is_empty() {
@@ -337,18 +404,8 @@ impl Table {
             let display_width = width as usize;
 
             // Truncate content if it's too wide
-            let mut truncated = String::new();
-            let mut current_width = 0;
-            for ch in content.chars() {
-                let ch_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0);
-                if current_width + ch_width > display_width {
-                    break;
-                }
-                truncated.push(ch);
-                current_width += ch_width;
-            }
-
-            buf.set_string(x, y, &truncated, cell_style);
+            let truncated = truncate_to_width(content, display_width);
+            buf.set_string(x, y, truncated, cell_style);
 
             x = x.saturating_add(width).saturating_add(self.column_spacing);
         }
@@ -454,6 +511,16 @@ mod tests {
         assert_eq!(cell.style, Style::default());
     }
 
+    #[test]
+    fn test_table_rows_area() {
+        let area = Rect::new(0, 0, 10, 5);
+        let table = Table::new(vec![Row::new(vec!["a"]), Row::new(vec!["b"])]);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.rows_area(area), area);
+        let table = table.header(Row::new(vec!["h"]).bottom_margin(1));
+        assert_eq!(table.rows_area(area), Rect::new(0, 2, 10, 3));
+    }
+
     #[test]
     fn test_table_cell_width() {
         let cell = TableCell::new("hello");
@@ -494,6 +561,23 @@ mod tests {
         assert_eq!(state.offset(), 10);
     }
 
+    #[test]
+    fn test_table_state_navigation() {
+        let mut state = TableState::with_selected(2);
+        state.select_next(3);
+        assert_eq!(state.selected(), Some(0)); // Wraps around
+        state.select_previous(3);
+        assert_eq!(state.selected(), Some(2));
+
+        state.select_previous_column(4);
+        assert_eq!(state.selected_column(), Some(3)); // Starts at last
+        state.select_next_column(4);
+        assert_eq!(state.selected_column(), Some(0));
+
+        state.select_next(0);
+        assert_eq!(state.selected(), None);
+    }
+
     #[test]
     fn test_table_new() {
         let table = Table::new(vec![
@@ -560,6 +644,23 @@ mod tests {
         assert_eq!(buffer.get(0, 1).unwrap().symbol, "c");
     }
 
+    #[test]
+    fn test_table_render_truncates_graphemes() {
+        let table = Table::new(vec![Row::new(vec!["e\u{301}世界", "b"])])
+            .widths(&[Constraint::Length(4), Constraint::Length(1)]);
+
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buffer = Buffer::new(area);
+        StatefulWidget::render(&table, area, &mut buffer, &mut TableState::default());
+
+        // "界" does not fit in the column and is left out whole
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(1, 0).unwrap().symbol, "世");
+        assert!(buffer.get(2, 0).unwrap().continuation);
+        assert_eq!(buffer.get(3, 0).unwrap().symbol, " ");
+        assert!(!buffer.get(4, 0).unwrap().continuation);
+    }
+
     #[test]
     fn test_table_render_with_selection() {
         let highlight_style = Style::default().fg(Color::Green);
@@ -8,8 +8,10 @@ use fusabi_tui_core::{
     layout::Rect,
     style::Style,
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::block::Block;
+use crate::text::{set_truncated, truncate_to_width};
 use crate::widget::Widget;
 
 /// A tabs widget for displaying tab navigation.
@@ -101,6 +103,44 @@ impl Tabs {
     pub fn selected(&self) -> usize {
         self.selected
     }
+
+    /// Returns the area each title occupies when rendered to `area`.
+    ///
+    /// Titles that do not fit are left out, so the result may be shorter than
+    /// [`titles`](Self::titles). Useful for mapping mouse clicks to tabs.
+    #[must_use]
+    pub fn title_areas(&self, area: Rect) -> Vec<Rect> {
+        let inner_area = match self.block {
+            Some(ref block) => block.inner(area),
+            None => area,
+        };
+        if inner_area.area() == 0 {
+            return Vec::new();
+        }
+
+        let right = inner_area.right();
+        // Mirrors `render`: whole grapheme clusters, stopping at the edge
+        let advance = |x: u16, text: &str| {
+            let remaining_width = usize::from(right.saturating_sub(x));
+            let width = truncate_to_width(text, remaining_width).width();
+            u16::try_from(width).unwrap_or(u16::MAX)
+        };
+
+        let mut areas = Vec::new();
+        let mut x = inner_area.x;
+        for (i, title) in self.titles.iter().enumerate() {
+            if x >= right {
+                break;
+            }
+            let width = advance(x, title);
+            areas.push(Rect::new(x, inner_area.y, width, 1));
+            x += width;
+            if i < self.titles.len() - 1 {
+                x += advance(x, &self.divider);
+            }
+        }
+        areas
+    }
 }
 
 impl Widget for Tabs {
@@ -150,51 +190,14 @@ impl Widget for Tabs {
                 self.style
             };
 
-            // Calculate how much space we have left
-            let remaining_width = tabs_area.right().saturating_sub(x) as usize;
-
             // Render the tab title (truncate if necessary)
-            let mut current_width = 0;
-            for ch in title.chars() {
-                if x >= tabs_area.right() {
-                    break;
-                }
-                let ch_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0);
-                if current_width + ch_width > remaining_width {
-                    break;
-                }
-
-                if let Some(cell) = buf.get_mut(x, tabs_area.y) {
-                    cell.symbol = ch.to_string();
-                    cell.set_style(tab_style);
-                }
-
-                x = x.saturating_add(1);
-                current_width += ch_width;
-            }
+            let remaining_width = tabs_area.right().saturating_sub(x);
+            x += set_truncated(buf, x, tabs_area.y, title, remaining_width, tab_style);
 
             // Add divider after tab (except for the last tab)
             if i < self.titles.len() - 1 {
-                let divider_remaining = tabs_area.right().saturating_sub(x) as usize;
-                let mut divider_current = 0;
-
-                for ch in self.divider.chars() {
-                    if x >= tabs_area.right() {
-                        break;
-                    }
-                    let ch_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0);
-                    if divider_current + ch_width > divider_remaining {
-                        break;
-                    }
-
-                    if let Some(cell) = buf.get_mut(x, tabs_area.y) {
-                        cell.symbol = ch.to_string();
-                        cell.set_style(self.style);
-                    }
-
-                    x = x.saturating_add(1);
-                    divider_current += ch_width;
-                }
+                let remaining_width = tabs_area.right().saturating_sub(x);
+                x += set_truncated(buf, x, tabs_area.y, &self.divider, remaining_width, self.style);
             }
         }
 
@@ -384,4 +387,43 @@ mod tests {
         assert_eq!(buffer.get(0, 0).unwrap().symbol, "T");
         assert_eq!(buffer.get(5, 0).unwrap().symbol, "│");
     }
+
+    #[test]
+    fn test_tabs_render_graphemes() {
+        let coder = "\u{1F469}\u{200D}\u{1F4BB}";
+        let tabs = Tabs::new(vec![format!("{coder}x"), "e\u{301}".to_string()]).divider("|");
+
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buffer = Buffer::new(area);
+        tabs.render(area, &mut buffer);
+
+        assert_eq!(buffer.get(0, 0).unwrap().symbol, coder);
+        assert!(buffer.get(1, 0).unwrap().continuation);
+        assert_eq!(buffer.get(2, 0).unwrap().symbol, "x");
+        assert_eq!(buffer.get(3, 0).unwrap().symbol, "|");
+        assert_eq!(buffer.get(4, 0).unwrap().symbol, "e\u{301}");
+        assert_eq!(buffer.get(5, 0).unwrap().symbol, " ");
+    }
+
+    #[test]
+    fn test_tabs_title_areas() {
+        let tabs = Tabs::new(vec!["One", "Two", "Three"]).divider(" | ");
+        let areas = tabs.title_areas(Rect::new(2, 1, 12, 3));
+
+        // "Three" starts at the right edge and does not fit
+        assert_eq!(areas, vec![Rect::new(2, 1, 3, 1), Rect::new(8, 1, 3, 1)]);
+    }
+
+    #[test]
+    fn test_tabs_title_areas_wide() {
+        let tabs = Tabs::new(vec!["日本", "e\u{301}x", "世界"]).divider("|");
+        let areas = tabs.title_areas(Rect::new(0, 0, 10, 1));
+
+        // The areas cover the columns the titles are drawn in; "世界" is cut
+        // to "世" at the edge
+        assert_eq!(
+            areas,
+            vec![Rect::new(0, 0, 4, 1), Rect::new(5, 0, 2, 1), Rect::new(8, 0, 2, 1)]
+        );
+    }
 }
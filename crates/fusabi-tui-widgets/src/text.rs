@@ -5,7 +5,10 @@
 //! - [`Line`] - A sequence of spans forming a single line
 //! - [`Text`] - A sequence of lines forming multi-line text
 
+use fusabi_tui_core::buffer::{Buffer, Hyperlink};
+use fusabi_tui_core::layout::Rect;
 use fusabi_tui_core::style::Style;
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 /// A styled text segment.
@@ -20,12 +23,25 @@ use unicode_width::UnicodeWidthStr;
 ///
 /// let span = Span::styled("Hello", Style::default().fg(Color::Green));
 /// ```
+///
+/// A span can link to a URI, which terminals that support hyperlinks make
+/// clickable:
+///
+/// ```rust
+/// use fusabi_tui_core::buffer::Hyperlink;
+/// use fusabi_tui_widgets::Span;
+///
+/// let link = Hyperlink::new("https://tracker.example/PROJ-42");
+/// let span = Span::raw("PROJ-42").hyperlink(link);
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Span<'a> {
     /// The text content
     pub content: std::borrow::Cow<'a, str>,
     /// The style to apply
     pub style: Style,
+    /// The link target of the text
+    pub hyperlink: Option<Hyperlink>,
 }
 
 impl<'a> Span<'a> {
@@ -37,6 +53,7 @@ impl<'a> Span<'a> {
         Self {
             content: content.into(),
             style: Style::default(),
+            hyperlink: None,
         }
     }
 
@@ -48,6 +65,7 @@ impl<'a> Span<'a> {
         Self {
             content: content.into(),
             style,
+            hyperlink: None,
         }
     }
 
@@ -62,6 +80,45 @@ impl<'a> Span<'a> {
         self.style = style;
         self
     }
+
+    /// Links the span to a hyperlink target.
+    #[must_use]
+    pub fn hyperlink(mut self, hyperlink: Hyperlink) -> Self {
+        self.hyperlink = Some(hyperlink);
+        self
+    }
+
+    /// Creates a span with other content and the same style and hyperlink.
+    pub(crate) fn with_content<'b, T>(&self, content: T) -> Span<'b>
+    where
+        T: Into<std::borrow::Cow<'b, str>>,
+    {
+        Span {
+            content: content.into(),
+            style: self.style,
+            hyperlink: self.hyperlink.clone(),
+        }
+    }
+
+    /// Draws the span at the given position with `style`, keeping to at most
+    /// `max_width` cells, and links the written cells.
+    ///
+    /// Whole grapheme clusters are drawn; a cluster that does not fit in the
+    /// remaining width is left out. Returns the number of cells written.
+    pub(crate) fn render(
+        &self,
+        buf: &mut Buffer,
+        x: u16,
+        y: u16,
+        max_width: u16,
+        style: Style,
+    ) -> u16 {
+        let written = set_truncated(buf, x, y, &self.content, max_width, style);
+        if self.hyperlink.is_some() {
+            buf.set_hyperlink(Rect::new(x, y, written, 1), self.hyperlink.as_ref());
+        }
+        written
+    }
 }
 
 impl<'a> From<&'a str> for Span<'a> {
@@ -274,6 +331,36 @@ impl<'a> From<Span<'a>> for Text<'a> {
     }
 }
 
+/// Returns the longest prefix of `text` made of whole grapheme clusters that
+/// fits in `max_width` cells.
+pub(crate) fn truncate_to_width(text: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > max_width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Writes `text` at the given position with `style`, keeping to at most
+/// `max_width` cells.
+///
+/// Whole grapheme clusters are written; a cluster that does not fit in the
+/// remaining width is left out. Returns the number of cells written.
+pub(crate) fn set_truncated(
+    buf: &mut Buffer,
+    x: u16,
+    y: u16,
+    text: &str,
+    max_width: u16,
+    style: Style,
+) -> u16 {
+    let text = truncate_to_width(text, usize::from(max_width));
+    u16::try_from(buf.set_string(x, y, text, style)).unwrap_or(u16::MAX)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
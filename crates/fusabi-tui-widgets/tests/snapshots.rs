@@ -0,0 +1,138 @@
+//! Snapshot tests for the widgets.
+//!
+//! Each test renders a widget to a buffer and compares it with the snapshot
+//! in `tests/snapshots`. Run with `FUSABI_UPDATE_SNAPSHOTS=1` to accept
+//! changes after reviewing the failure output.
+
+use fusabi_tui_core::buffer::Buffer;
+use fusabi_tui_core::layout::{Constraint, Rect};
+use fusabi_tui_core::style::{Color, Modifier, Style};
+use fusabi_tui_render::assert_snapshot;
+use fusabi_tui_widgets::{
+    Alignment, Bar, BarChart, Block, BorderType, Borders, Gauge, Line, List, ListItem, ListState,
+    Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Span, Sparkline,
+    StatefulWidget, Table, TableState, Tabs, TitleAlignment, Widget, Wrap,
+};
+
+fn render(widget: impl Widget, width: u16, height: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buffer = Buffer::new(area);
+    widget.render(area, &mut buffer);
+    buffer
+}
+
+fn render_stateful<W: StatefulWidget>(
+    widget: W,
+    state: &mut W::State,
+    width: u16,
+    height: u16,
+) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buffer = Buffer::new(area);
+    widget.render(area, &mut buffer, state);
+    buffer
+}
+
+fn bordered(title: &str) -> Block {
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(Color::Blue))
+}
+
+#[test]
+fn block() {
+    let block = Block::default()
+        .title("Panel")
+        .title_alignment(TitleAlignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::new().fg(Color::Cyan));
+    assert_snapshot!("block", &render(block, 16, 4));
+}
+
+#[test]
+fn paragraph() {
+    let text = vec![
+        Line::from(vec![
+            Span::styled("Status: ", Style::new().add_modifier(Modifier::BOLD)),
+            Span::styled("running", Style::new().fg(Color::Green)),
+        ]),
+        Line::from("A longer line that wraps onto the next rows"),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(bordered("Info"))
+        .alignment(Alignment::Left)
+        .wrap(Wrap::WordWrap);
+    assert_snapshot!("paragraph", &render(paragraph, 20, 6));
+}
+
+#[test]
+fn list() {
+    let list = List::new(vec![
+        ListItem::new("alpha"),
+        ListItem::new("beta"),
+        ListItem::new("gamma"),
+    ])
+    .block(bordered("Items"))
+    .highlight_style(Style::new().bg(Color::Blue).add_modifier(Modifier::BOLD))
+    .highlight_symbol("> ");
+    let mut state = ListState::with_selected(1);
+    assert_snapshot!("list", &render_stateful(list, &mut state, 14, 5));
+}
+
+#[test]
+fn table() {
+    let table = Table::new(vec![Row::new(vec!["cpu", "42%"]), Row::new(vec!["mem", "7%"])])
+        .header(Row::new(vec!["Name", "Use"]).style(Style::new().add_modifier(Modifier::BOLD)))
+        .widths(&[Constraint::Length(6), Constraint::Length(4)])
+        .highlight_style(Style::new().fg(Color::Yellow));
+    let mut state = TableState::with_selected(0);
+    assert_snapshot!("table", &render_stateful(table, &mut state, 12, 3));
+}
+
+#[test]
+fn tabs() {
+    let tabs = Tabs::new(vec!["One", "Two", "Three"])
+        .select(1)
+        .highlight_style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    assert_snapshot!("tabs", &render(tabs, 20, 1));
+}
+
+#[test]
+fn gauge() {
+    let gauge = Gauge::new()
+        .block(bordered("Load"))
+        .percent(40)
+        .gauge_style(Style::new().fg(Color::Green));
+    assert_snapshot!("gauge", &render(gauge, 12, 3));
+}
+
+#[test]
+fn sparkline() {
+    let sparkline = Sparkline::new()
+        .data(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 4, 2])
+        .style(Style::new().fg(Color::Magenta));
+    assert_snapshot!("sparkline", &render(sparkline, 11, 2));
+}
+
+#[test]
+fn barchart() {
+    let chart = BarChart::new()
+        .data(&[
+            Bar::new().label("a").value(2),
+            Bar::new().label("b").value(5),
+            Bar::new().label("c").value(3),
+        ])
+        .bar_width(2)
+        .bar_gap(1)
+        .bar_style(Style::new().fg(Color::Cyan));
+    assert_snapshot!("barchart", &render(chart, 10, 6));
+}
+
+#[test]
+fn scrollbar() {
+    let scrollbar = Scrollbar::new().orientation(ScrollbarOrientation::VerticalRight);
+    let mut state = ScrollbarState::new(30).position(10).viewport_content_length(6);
+    assert_snapshot!("scrollbar", &render_stateful(scrollbar, &mut state, 1, 8));
+}